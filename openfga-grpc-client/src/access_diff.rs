@@ -0,0 +1,166 @@
+use crate::{CheckRequest, CheckRequestTupleKey, ConsistencyPreference, OpenFGAClient};
+use std::future::Future;
+
+/// A sampled check whose result differs between two models
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessChange {
+    pub object: String,
+    pub relation: String,
+    pub user: String,
+    /// Result of the check against the current model
+    pub allowed_before: bool,
+    /// Result of the check against the candidate model
+    pub allowed_after: bool,
+}
+
+/// Result of [`OpenFGAClient::compare_effective_access`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessDiff {
+    /// Number of sampled checks
+    pub checked: usize,
+    /// Checks whose result flipped, in sample order
+    pub changes: Vec<AccessChange>,
+}
+
+impl AccessDiff {
+    /// Checks denied by the current model and allowed by the candidate
+    pub fn granted(&self) -> impl Iterator<Item = &AccessChange> {
+        self.changes.iter().filter(|c| c.allowed_after)
+    }
+
+    /// Checks allowed by the current model and denied by the candidate
+    pub fn revoked(&self) -> impl Iterator<Item = &AccessChange> {
+        self.changes.iter().filter(|c| c.allowed_before)
+    }
+}
+
+// Comparison of two models of a store before a rollout
+impl OpenFGAClient {
+    /// Run each sampled `(object, relation, user)` check against `model_a`, the
+    /// current model, and `model_b`, the candidate, and report the checks whose
+    /// result flipped. Both models must belong to the store.
+    pub async fn compare_effective_access(
+        &mut self,
+        store_id: String,
+        model_a: String,
+        model_b: String,
+        sample: Vec<(String, String, String)>,
+    ) -> Result<AccessDiff, tonic::Status> {
+        let client = self.clone();
+        Self::compare_effective_access_with(
+            model_a,
+            model_b,
+            sample,
+            |model_id, object, relation, user| {
+                let mut client = client.clone();
+                let request = CheckRequest {
+                    store_id: store_id.clone(),
+                    tuple_key: Some(CheckRequestTupleKey {
+                        user,
+                        relation,
+                        object,
+                    }),
+                    contextual_tuples: None,
+                    authorization_model_id: model_id,
+                    trace: false,
+                    consistency: ConsistencyPreference::HigherConsistency as i32,
+                    context: None,
+                };
+                async move { Ok(client.check(request).await?.into_inner().allowed) }
+            },
+        )
+        .await
+    }
+
+    /// Compare the models using `check(model_id, object, relation, user)`
+    pub async fn compare_effective_access_with<F, Fut>(
+        model_a: String,
+        model_b: String,
+        sample: Vec<(String, String, String)>,
+        mut check: F,
+    ) -> Result<AccessDiff, tonic::Status>
+    where
+        F: FnMut(String, String, String, String) -> Fut,
+        Fut: Future<Output = Result<bool, tonic::Status>>,
+    {
+        let mut diff = AccessDiff {
+            checked: sample.len(),
+            changes: Vec::new(),
+        };
+
+        for (object, relation, user) in sample {
+            let allowed_before = check(
+                model_a.clone(),
+                object.clone(),
+                relation.clone(),
+                user.clone(),
+            )
+            .await?;
+            let allowed_after = check(
+                model_b.clone(),
+                object.clone(),
+                relation.clone(),
+                user.clone(),
+            )
+            .await?;
+
+            if allowed_before != allowed_after {
+                diff.changes.push(AccessChange {
+                    object,
+                    relation,
+                    user,
+                    allowed_before,
+                    allowed_after,
+                });
+            }
+        }
+
+        Ok(diff)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(object: &str, relation: &str, user: &str) -> (String, String, String) {
+        (object.to_string(), relation.to_string(), user.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_compare_reports_flipped_check() {
+        // The candidate model drops `viewer` from editors; owners keep their access
+        let diff = OpenFGAClient::compare_effective_access_with(
+            "model-a".to_string(),
+            "model-b".to_string(),
+            vec![
+                sample("document:readme", "viewer", "user:anne"),
+                sample("document:readme", "viewer", "user:bob"),
+                sample("document:readme", "owner", "user:carl"),
+            ],
+            |model_id, _object, _relation, user| async move {
+                Ok(match user.as_str() {
+                    "user:anne" => true,
+                    "user:bob" => model_id == "model-a",
+                    _ => false,
+                })
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(diff.checked, 3);
+        assert_eq!(
+            diff.changes,
+            vec![AccessChange {
+                object: "document:readme".to_string(),
+                relation: "viewer".to_string(),
+                user: "user:bob".to_string(),
+                allowed_before: true,
+                allowed_after: false,
+            }]
+        );
+        assert_eq!(diff.revoked().count(), 1);
+        assert_eq!(diff.granted().count(), 0);
+    }
+}
@@ -0,0 +1,236 @@
+use crate::bundle::StoreBackend;
+use crate::{
+    AuthorizationModel, BatchCheckRequest, BatchCheckResponse, ConsistencyPreference,
+    OpenFGAClient, ReadAuthorizationModelRequest, ReadRequest, ReadRequestTupleKey,
+};
+use std::collections::HashSet;
+use std::future::Future;
+
+/// Whether a user has one relation of an object, see [`user_access_summary`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelationAccess {
+    pub relation: String,
+    pub allowed: bool,
+    /// Whether the relation is granted by a tuple naming the user, or their type's
+    /// wildcard, rather than inherited through the model or a userset
+    pub direct: bool,
+}
+
+/// Batch checks, on top of the [`StoreBackend`] reads of [`user_access_summary`]
+pub trait CheckBackend {
+    fn batch_check(
+        &mut self,
+        request: BatchCheckRequest,
+    ) -> impl Future<Output = Result<tonic::Response<BatchCheckResponse>, tonic::Status>> + Send;
+}
+
+impl CheckBackend for OpenFGAClient {
+    async fn batch_check(
+        &mut self,
+        request: BatchCheckRequest,
+    ) -> Result<tonic::Response<BatchCheckResponse>, tonic::Status> {
+        OpenFGAClient::batch_check(self, request).await
+    }
+}
+
+impl OpenFGAClient {
+    /// Every relation of `object`'s type and whether `user` has it, for access panels.
+    /// Reads `model_id`; see [`user_access_summary_with_model`] to reuse a cached model.
+    pub async fn user_access_summary(
+        &mut self,
+        store_id: &str,
+        model_id: &str,
+        object: &str,
+        user: &str,
+    ) -> Result<Vec<RelationAccess>, tonic::Status> {
+        user_access_summary(self, store_id, model_id, object, user).await
+    }
+}
+
+/// Read `model_id` and summarize `user`'s access to `object` with
+/// [`user_access_summary_with_model`]
+pub async fn user_access_summary<B: StoreBackend + CheckBackend>(
+    client: &mut B,
+    store_id: &str,
+    model_id: &str,
+    object: &str,
+    user: &str,
+) -> Result<Vec<RelationAccess>, tonic::Status> {
+    let model = client
+        .read_authorization_model(ReadAuthorizationModelRequest {
+            store_id: store_id.to_string(),
+            id: model_id.to_string(),
+        })
+        .await?
+        .into_inner()
+        .authorization_model
+        .ok_or_else(|| tonic::Status::not_found("authorization model not found"))?;
+
+    user_access_summary_with_model(client, store_id, &model, object, user).await
+}
+
+/// Check each relation `model` defines on `object`'s type for `user` in one batch
+/// check, and read the user's tuples on the object to tell direct grants from
+/// inherited ones. Relations are sorted by name; a relation whose check errored is
+/// reported as not allowed.
+pub async fn user_access_summary_with_model<B: StoreBackend + CheckBackend>(
+    client: &mut B,
+    store_id: &str,
+    model: &AuthorizationModel,
+    object: &str,
+    user: &str,
+) -> Result<Vec<RelationAccess>, tonic::Status> {
+    let object_type = object.split_once(':').map_or(object, |(t, _)| t);
+    let definition = model
+        .type_definitions
+        .iter()
+        .find(|definition| definition.r#type == object_type)
+        .ok_or_else(|| {
+            tonic::Status::invalid_argument(format!(
+                "type {} is not defined in the model",
+                object_type
+            ))
+        })?;
+    let mut relations: Vec<String> = definition.relations.keys().cloned().collect();
+    relations.sort();
+    if relations.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let request = OpenFGAClient::create_check_relations_request(
+        store_id.to_string(),
+        model.id.clone(),
+        object.to_string(),
+        user.to_string(),
+        &relations,
+    );
+    let response = client.batch_check(request).await?.into_inner();
+    let allowed = OpenFGAClient::relations_from_batch_check_response(&relations, response);
+
+    let direct = direct_relations(client, store_id, object, user).await?;
+
+    Ok(relations
+        .into_iter()
+        .map(|relation| {
+            let allowed = allowed.get(&relation).copied().unwrap_or(false);
+            RelationAccess {
+                direct: allowed && direct.contains(&relation),
+                allowed,
+                relation,
+            }
+        })
+        .collect())
+}
+
+/// Relations of the tuples of `object` naming `user` or its type's wildcard
+async fn direct_relations<B: StoreBackend>(
+    client: &mut B,
+    store_id: &str,
+    object: &str,
+    user: &str,
+) -> Result<HashSet<String>, tonic::Status> {
+    let wildcard = user.split_once(':').map(|(t, _)| format!("{}:*", t));
+    let mut relations = HashSet::new();
+
+    for user in std::iter::once(user.to_string()).chain(wildcard) {
+        let mut request = ReadRequest {
+            store_id: store_id.to_string(),
+            tuple_key: Some(ReadRequestTupleKey {
+                user,
+                relation: String::new(),
+                object: object.to_string(),
+            }),
+            page_size: None,
+            continuation_token: String::new(),
+            consistency: ConsistencyPreference::Unspecified as i32,
+        };
+        loop {
+            let response = client.read(request.clone()).await?.into_inner();
+            relations.extend(
+                response
+                    .tuples
+                    .into_iter()
+                    .filter_map(|tuple| tuple.key)
+                    .map(|key| key.relation),
+            );
+
+            if response.continuation_token.is_empty() {
+                break;
+            }
+            request.continuation_token = response.continuation_token;
+        }
+    }
+    Ok(relations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MemoryState, MemoryStore, tuple};
+    use crate::{TypeDefinition, Userset};
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_user_access_summary() {
+        let direct = || Userset {
+            userset: Some(crate::userset::Userset::This(Default::default())),
+        };
+        // alice is a direct reader
+        let mut store = MemoryStore::from(MemoryState {
+            models: vec![AuthorizationModel {
+                id: "model".to_string(),
+                schema_version: "1.1".to_string(),
+                type_definitions: vec![TypeDefinition {
+                    r#type: "document".to_string(),
+                    relations: HashMap::from([
+                        ("writer".to_string(), direct()),
+                        ("reader".to_string(), direct()),
+                    ]),
+                    metadata: None,
+                }],
+                conditions: HashMap::new(),
+            }],
+            tuples: vec![tuple("user:alice", "reader", "document:readme")],
+            ..Default::default()
+        });
+
+        let summary = user_access_summary(
+            &mut store,
+            "store",
+            "model",
+            "document:readme",
+            "user:alice",
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            summary,
+            vec![
+                RelationAccess {
+                    relation: "reader".to_string(),
+                    allowed: true,
+                    direct: true,
+                },
+                RelationAccess {
+                    relation: "writer".to_string(),
+                    allowed: false,
+                    direct: false,
+                },
+            ]
+        );
+
+        // The user's own tuples and its type's wildcard are read
+        let users: Vec<String> = store
+            .state()
+            .reads
+            .iter()
+            .map(|r| r.tuple_key.clone().unwrap().user)
+            .collect();
+        assert_eq!(users, vec!["user:alice", "user:*"]);
+
+        let error = user_access_summary(&mut store, "store", "model", "folder:x", "user:alice")
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{
+    Assertion, CheckRequest, CheckRequestTupleKey, ConsistencyPreference, ContextualTupleKeys,
+    OpenFGAClient, ReadAssertionsRequest,
+};
+use std::future::Future;
+
+/// An assertion whose check didn't return the expected result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssertionFailure {
+    pub object: String,
+    pub relation: String,
+    pub user: String,
+    pub expected: bool,
+    pub actual: bool,
+}
+
+/// Result of [`OpenFGAClient::run_assertions`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AssertionReport {
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    /// Failed assertions, in the order they're stored
+    pub failures: Vec<AssertionFailure>,
+}
+
+impl AssertionReport {
+    /// Whether every assertion passed
+    pub fn is_success(&self) -> bool {
+        self.failed == 0
+    }
+}
+
+// Assertions of a model run as a test suite, e.g. from CI
+impl OpenFGAClient {
+    /// Read the assertions stored for `model_id` and run each as a check against
+    /// that model, with its contextual tuples and context.
+    pub async fn run_assertions(
+        &mut self,
+        store_id: String,
+        model_id: String,
+    ) -> Result<AssertionReport, tonic::Status> {
+        let assertions = self
+            .read_assertions(ReadAssertionsRequest {
+                store_id: store_id.clone(),
+                authorization_model_id: model_id.clone(),
+            })
+            .await?
+            .into_inner()
+            .assertions;
+
+        let client = self.clone();
+        Self::run_assertions_with(assertions, |assertion| {
+            let mut client = client.clone();
+            let tuple_key = assertion.tuple_key.unwrap_or_default();
+            let request = CheckRequest {
+                store_id: store_id.clone(),
+                tuple_key: Some(CheckRequestTupleKey {
+                    user: tuple_key.user,
+                    relation: tuple_key.relation,
+                    object: tuple_key.object,
+                }),
+                contextual_tuples: (!assertion.contextual_tuples.is_empty()).then_some(
+                    ContextualTupleKeys {
+                        tuple_keys: assertion.contextual_tuples,
+                    },
+                ),
+                authorization_model_id: model_id.clone(),
+                trace: false,
+                consistency: ConsistencyPreference::HigherConsistency as i32,
+                context: assertion.context,
+            };
+            async move { Ok(client.check(request).await?.into_inner().allowed) }
+        })
+        .await
+    }
+
+    /// Run `assertions` using `check` for the actual result of each
+    pub async fn run_assertions_with<F, Fut>(
+        assertions: Vec<Assertion>,
+        mut check: F,
+    ) -> Result<AssertionReport, tonic::Status>
+    where
+        F: FnMut(Assertion) -> Fut,
+        Fut: Future<Output = Result<bool, tonic::Status>>,
+    {
+        let mut report = AssertionReport {
+            total: assertions.len(),
+            ..Default::default()
+        };
+
+        for assertion in assertions {
+            let expected = assertion.expectation;
+            let tuple_key = assertion.tuple_key.clone().unwrap_or_default();
+            let actual = check(assertion).await?;
+
+            if actual == expected {
+                report.passed += 1;
+            } else {
+                report.failed += 1;
+                report.failures.push(AssertionFailure {
+                    object: tuple_key.object,
+                    relation: tuple_key.relation,
+                    user: tuple_key.user,
+                    expected,
+                    actual,
+                });
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AssertionTupleKey;
+
+    fn assertion(object: &str, relation: &str, user: &str, expectation: bool) -> Assertion {
+        Assertion {
+            tuple_key: Some(AssertionTupleKey {
+                object: object.to_string(),
+                relation: relation.to_string(),
+                user: user.to_string(),
+            }),
+            expectation,
+            contextual_tuples: Vec::new(),
+            context: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_assertions_reports_failures() {
+        // Only anne can view the readme
+        let report = OpenFGAClient::run_assertions_with(
+            vec![
+                assertion("document:readme", "viewer", "user:anne", true),
+                assertion("document:readme", "viewer", "user:bob", true),
+            ],
+            |assertion| async move { Ok(assertion.tuple_key.unwrap().user == "user:anne") },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report.total, 2);
+        assert_eq!(report.passed, 1);
+        assert_eq!(report.failed, 1);
+        assert!(!report.is_success());
+        assert_eq!(
+            report.failures,
+            vec![AssertionFailure {
+                object: "document:readme".to_string(),
+                relation: "viewer".to_string(),
+                user: "user:bob".to_string(),
+                expected: true,
+                actual: false,
+            }]
+        );
+    }
+}
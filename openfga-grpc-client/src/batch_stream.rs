@@ -0,0 +1,168 @@
+use crate::access_summary::CheckBackend;
+use crate::batch_check_single_result::CheckResult;
+use crate::{BatchCheckRequest, BatchCheckResponse, OpenFGAClient};
+use futures_util::{Stream, StreamExt, stream};
+
+/// Most checks OpenFGA accepts in one batch check, its default `maxChecksPerBatchCheck`
+pub const MAX_BATCH_CHECK_ITEMS: usize = 50;
+
+impl OpenFGAClient {
+    /// Stream the results of a batch check of any size as its sub-batches complete.
+    /// See [`batch_check_stream`].
+    pub fn batch_check_stream(
+        &self,
+        request: BatchCheckRequest,
+        chunk_size: usize,
+        concurrency: usize,
+    ) -> impl Stream<Item = Result<(String, bool), tonic::Status>> + use<> {
+        batch_check_stream(self.clone(), request, chunk_size, concurrency)
+    }
+}
+
+/// Split `request`'s checks into sub-batches of at most `chunk_size`, send up to
+/// `concurrency` of them at a time and yield `(correlation_id, allowed)` for each check
+/// as its sub-batch completes, for sweeps too large to wait on as one batch check.
+///
+/// Results come in completion order, not request order. Every sub-batch shares the
+/// request's store, model and consistency. A check that errored, or got no result, is
+/// yielded as not allowed; a sub-batch that failed as a whole yields its error once,
+/// and the other sub-batches carry on.
+pub fn batch_check_stream<B: CheckBackend + Clone + Send + 'static>(
+    client: B,
+    request: BatchCheckRequest,
+    chunk_size: usize,
+    concurrency: usize,
+) -> impl Stream<Item = Result<(String, bool), tonic::Status>> {
+    let BatchCheckRequest {
+        store_id,
+        checks,
+        authorization_model_id,
+        consistency,
+    } = request;
+
+    stream::iter(checks)
+        .chunks(chunk_size.max(1))
+        .map(move |checks| {
+            let mut client = client.clone();
+            let request = BatchCheckRequest {
+                store_id: store_id.clone(),
+                checks,
+                authorization_model_id: authorization_model_id.clone(),
+                consistency,
+            };
+            async move {
+                let ids: Vec<String> = request
+                    .checks
+                    .iter()
+                    .map(|check| check.correlation_id.clone())
+                    .collect();
+                match client.batch_check(request).await {
+                    Ok(response) => chunk_results(ids, response.into_inner())
+                        .into_iter()
+                        .map(Ok)
+                        .collect(),
+                    Err(status) => vec![Err(status)],
+                }
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .flat_map(stream::iter)
+}
+
+/// Results of one sub-batch, in the order of its correlation IDs
+pub(crate) fn chunk_results(
+    ids: Vec<String>,
+    mut response: BatchCheckResponse,
+) -> Vec<(String, bool)> {
+    ids.into_iter()
+        .map(|id| {
+            let allowed = match response.result.remove(&id).and_then(|r| r.check_result) {
+                Some(CheckResult::Allowed(allowed)) => allowed,
+                Some(CheckResult::Error(e)) => {
+                    tracing::warn!("Check {} failed: {}", id, e.message);
+                    false
+                }
+                None => {
+                    tracing::warn!("No check result returned for check {}", id);
+                    false
+                }
+            };
+            (id, allowed)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BatchCheckItem, BatchCheckSingleResult, CheckRequestTupleKey};
+    use std::collections::HashSet;
+    use std::sync::{Arc, Mutex};
+
+    /// Allows the checks of even objects, recording the size of each batch
+    #[derive(Clone, Default)]
+    struct MockChecks {
+        batch_sizes: Arc<Mutex<Vec<usize>>>,
+    }
+
+    impl CheckBackend for MockChecks {
+        async fn batch_check(
+            &mut self,
+            request: BatchCheckRequest,
+        ) -> Result<tonic::Response<BatchCheckResponse>, tonic::Status> {
+            self.batch_sizes.lock().unwrap().push(request.checks.len());
+            let result = request
+                .checks
+                .into_iter()
+                .map(|check| {
+                    let object = check.tuple_key.unwrap().object;
+                    let n: usize = object.trim_start_matches("document:").parse().unwrap();
+                    let result = BatchCheckSingleResult {
+                        check_result: Some(CheckResult::Allowed(n.is_multiple_of(2))),
+                    };
+                    (check.correlation_id, result)
+                })
+                .collect();
+            Ok(tonic::Response::new(BatchCheckResponse { result }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_check_stream_yields_every_check_once() {
+        let checks = (0..150)
+            .map(|i| BatchCheckItem {
+                tuple_key: Some(CheckRequestTupleKey {
+                    user: "user:anne".to_string(),
+                    relation: "viewer".to_string(),
+                    object: format!("document:{}", i),
+                }),
+                contextual_tuples: None,
+                context: None,
+                correlation_id: i.to_string(),
+            })
+            .collect();
+        let request = BatchCheckRequest {
+            store_id: "store".to_string(),
+            checks,
+            authorization_model_id: String::new(),
+            consistency: 0,
+        };
+        let client = MockChecks::default();
+
+        let results: Vec<(String, bool)> = batch_check_stream(client.clone(), request, 100, 2)
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        assert_eq!(results.len(), 150);
+        let ids: HashSet<&str> = results.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids.len(), 150);
+        for (id, allowed) in &results {
+            assert_eq!(*allowed, id.parse::<usize>().unwrap().is_multiple_of(2));
+        }
+
+        let mut batch_sizes = client.batch_sizes.lock().unwrap().clone();
+        batch_sizes.sort();
+        assert_eq!(batch_sizes, vec![50, 100]);
+    }
+}
@@ -0,0 +1,298 @@
+use crate::{
+    AuthorizationModel, ConsistencyPreference, OpenFGAClient, ReadAuthorizationModelRequest,
+    ReadAuthorizationModelResponse, ReadAuthorizationModelsRequest,
+    ReadAuthorizationModelsResponse, ReadRequest, ReadResponse, TupleKey,
+    WriteAuthorizationModelRequest, WriteAuthorizationModelResponse, WriteRequest,
+    WriteRequestWrites, WriteResponse,
+};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+
+/// Version of the bundle format written by [`export_store`]
+pub const STORE_BUNDLE_VERSION: u32 = 1;
+
+/// Most tuples OpenFGA accepts in a single write
+pub const IMPORT_CHUNK_SIZE: usize = 100;
+
+/// Page size used when reading tuples for export
+const EXPORT_PAGE_SIZE: i32 = 100;
+
+/// Authorization model and tuples of a store, for backups and migrations.
+///
+/// Only tuple keys are kept: OpenFGA assigns new write timestamps on import, and the
+/// model gets a new ID in the target store, so IDs aren't preserved across stores.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoreBundle {
+    pub version: u32,
+    pub authorization_model: AuthorizationModel,
+    pub tuples: Vec<TupleKey>,
+}
+
+impl StoreBundle {
+    /// Serialize the bundle to pretty-printed JSON
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Parse a bundle from JSON
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Result of [`import_store`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportReport {
+    /// ID of the model created in the target store
+    pub authorization_model_id: String,
+    pub tuples_written: usize,
+}
+
+/// The store operations export and import are built on
+pub trait StoreBackend {
+    fn read_authorization_models(
+        &mut self,
+        request: ReadAuthorizationModelsRequest,
+    ) -> impl Future<
+        Output = Result<tonic::Response<ReadAuthorizationModelsResponse>, tonic::Status>,
+    > + Send;
+
+    fn read_authorization_model(
+        &mut self,
+        request: ReadAuthorizationModelRequest,
+    ) -> impl Future<Output = Result<tonic::Response<ReadAuthorizationModelResponse>, tonic::Status>>
+    + Send;
+
+    fn read(
+        &mut self,
+        request: ReadRequest,
+    ) -> impl Future<Output = Result<tonic::Response<ReadResponse>, tonic::Status>> + Send;
+
+    fn write_authorization_model(
+        &mut self,
+        request: WriteAuthorizationModelRequest,
+    ) -> impl Future<
+        Output = Result<tonic::Response<WriteAuthorizationModelResponse>, tonic::Status>,
+    > + Send;
+
+    fn write(
+        &mut self,
+        request: WriteRequest,
+    ) -> impl Future<Output = Result<tonic::Response<WriteResponse>, tonic::Status>> + Send;
+}
+
+impl StoreBackend for OpenFGAClient {
+    async fn read_authorization_models(
+        &mut self,
+        request: ReadAuthorizationModelsRequest,
+    ) -> Result<tonic::Response<ReadAuthorizationModelsResponse>, tonic::Status> {
+        OpenFGAClient::read_authorization_models(self, request).await
+    }
+
+    async fn read_authorization_model(
+        &mut self,
+        request: ReadAuthorizationModelRequest,
+    ) -> Result<tonic::Response<ReadAuthorizationModelResponse>, tonic::Status> {
+        OpenFGAClient::read_authorization_model(self, request)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn read(
+        &mut self,
+        request: ReadRequest,
+    ) -> Result<tonic::Response<ReadResponse>, tonic::Status> {
+        OpenFGAClient::read(self, request).await
+    }
+
+    async fn write_authorization_model(
+        &mut self,
+        request: WriteAuthorizationModelRequest,
+    ) -> Result<tonic::Response<WriteAuthorizationModelResponse>, tonic::Status> {
+        OpenFGAClient::write_authorization_model(self, request)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn write(
+        &mut self,
+        request: WriteRequest,
+    ) -> Result<tonic::Response<WriteResponse>, tonic::Status> {
+        OpenFGAClient::write(self, request).await
+    }
+}
+
+/// Export the latest authorization model and all tuples of a store
+pub async fn export_store<B: StoreBackend>(
+    client: &mut B,
+    store_id: &str,
+) -> Result<StoreBundle, tonic::Status> {
+    let latest = client
+        .read_authorization_models(ReadAuthorizationModelsRequest {
+            store_id: store_id.to_string(),
+            page_size: Some(1),
+            continuation_token: String::new(),
+        })
+        .await?
+        .into_inner()
+        .authorization_models
+        .into_iter()
+        .next()
+        .ok_or_else(|| {
+            tonic::Status::not_found(format!("store {} has no authorization model", store_id))
+        })?;
+
+    let authorization_model = client
+        .read_authorization_model(ReadAuthorizationModelRequest {
+            store_id: store_id.to_string(),
+            id: latest.id,
+        })
+        .await?
+        .into_inner()
+        .authorization_model
+        .ok_or_else(|| tonic::Status::not_found("authorization model not found"))?;
+
+    let mut tuples = Vec::new();
+    let mut request = ReadRequest {
+        store_id: store_id.to_string(),
+        tuple_key: None,
+        page_size: Some(EXPORT_PAGE_SIZE),
+        continuation_token: String::new(),
+        consistency: ConsistencyPreference::HigherConsistency as i32,
+    };
+    loop {
+        let response = client.read(request.clone()).await?.into_inner();
+        tuples.extend(response.tuples.into_iter().filter_map(|tuple| tuple.key));
+
+        if response.continuation_token.is_empty() {
+            break;
+        }
+        request.continuation_token = response.continuation_token;
+    }
+
+    Ok(StoreBundle {
+        version: STORE_BUNDLE_VERSION,
+        authorization_model,
+        tuples,
+    })
+}
+
+/// Create the bundle's model in `store_id` and write its tuples in chunks of
+/// [`IMPORT_CHUNK_SIZE`]. The target store should be empty; existing tuples cause
+/// the write to fail.
+pub async fn import_store<B: StoreBackend>(
+    client: &mut B,
+    store_id: &str,
+    bundle: StoreBundle,
+) -> Result<ImportReport, tonic::Status> {
+    if bundle.version != STORE_BUNDLE_VERSION {
+        return Err(tonic::Status::invalid_argument(format!(
+            "unsupported bundle version {}, expected {}",
+            bundle.version, STORE_BUNDLE_VERSION
+        )));
+    }
+
+    let model = bundle.authorization_model;
+    let authorization_model_id = client
+        .write_authorization_model(WriteAuthorizationModelRequest {
+            store_id: store_id.to_string(),
+            type_definitions: model.type_definitions,
+            schema_version: model.schema_version,
+            conditions: model.conditions,
+        })
+        .await?
+        .into_inner()
+        .authorization_model_id;
+
+    let mut tuples_written = 0;
+    for chunk in bundle.tuples.chunks(IMPORT_CHUNK_SIZE) {
+        client
+            .write(WriteRequest {
+                store_id: store_id.to_string(),
+                authorization_model_id: authorization_model_id.clone(),
+                writes: Some(WriteRequestWrites {
+                    tuple_keys: chunk.to_vec(),
+                    on_duplicate: String::new(),
+                }),
+                deletes: None,
+            })
+            .await?;
+        tuples_written += chunk.len();
+    }
+
+    Ok(ImportReport {
+        authorization_model_id,
+        tuples_written,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TypeDefinition;
+    use crate::test_support::{MemoryState, MemoryStore, tuple};
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let model = AuthorizationModel {
+            id: "source-model".to_string(),
+            schema_version: "1.1".to_string(),
+            type_definitions: vec![TypeDefinition {
+                r#type: "user".to_string(),
+                relations: HashMap::new(),
+                metadata: None,
+            }],
+            conditions: HashMap::new(),
+        };
+        let tuples: Vec<_> = (0..5)
+            .map(|i| tuple(&format!("user:{}", i), "viewer", "document:readme"))
+            .collect();
+        let mut source = MemoryStore::from(MemoryState {
+            models: vec![model],
+            tuples: tuples.clone(),
+            page_size: 2,
+            ..Default::default()
+        });
+
+        let bundle = export_store(&mut source, "source").await.unwrap();
+        assert_eq!(bundle.tuples, tuples);
+        // Tuples are read across pages
+        assert_eq!(source.state().reads.len(), 3);
+
+        let bundle = StoreBundle::from_json(&bundle.to_json().unwrap()).unwrap();
+
+        let mut target = MemoryStore::default();
+        let report = import_store(&mut target, "target", bundle.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(report.tuples_written, 5);
+        let target = target.state();
+        assert_eq!(target.tuples, tuples);
+        // The model gets a new ID in the target store
+        assert_eq!(report.authorization_model_id, "model-1");
+        assert_eq!(
+            target.models[0].type_definitions,
+            bundle.authorization_model.type_definitions
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import_writes_in_chunks() {
+        let bundle = StoreBundle {
+            version: STORE_BUNDLE_VERSION,
+            authorization_model: AuthorizationModel::default(),
+            tuples: (0..IMPORT_CHUNK_SIZE + 1)
+                .map(|i| tuple(&format!("user:{}", i), "viewer", "document:readme"))
+                .collect(),
+        };
+
+        let mut target = MemoryStore::default();
+        import_store(&mut target, "target", bundle).await.unwrap();
+
+        let target = target.state();
+        assert_eq!(target.writes.len(), 2);
+        assert_eq!(target.tuples.len(), IMPORT_CHUNK_SIZE + 1);
+    }
+}
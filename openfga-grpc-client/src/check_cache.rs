@@ -0,0 +1,392 @@
+use crate::{
+    CheckRequest, ConsistencyPreference, OpenFGAClient, TupleKey, TupleKeyWithoutCondition,
+    WriteRequest, WriteResponse,
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Entries a [`CheckCache`] holds unless [`with_capacity`](CheckCache::with_capacity)
+/// says otherwise
+pub const DEFAULT_CHECK_CACHE_CAPACITY: usize = 10_000;
+
+/// How cached checks are invalidated when tuples change
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InvalidationMode {
+    /// Evict entries whose object or user appears in the changed tuples.
+    /// Relations derived through other objects (e.g. a parent folder) may stay stale
+    /// until the entry expires.
+    #[default]
+    Targeted,
+    /// Evict every entry of the store on any write
+    FlushStore,
+}
+
+/// Key of a cached check result
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CheckCacheKey {
+    pub store_id: String,
+    pub authorization_model_id: String,
+    pub user: String,
+    pub relation: String,
+    pub object: String,
+}
+
+impl CheckCacheKey {
+    /// Build the key for a check request, or `None` if the request can't be cached
+    /// because its result depends on contextual tuples or context, or it asks for
+    /// higher consistency than minimizing latency (the default when unspecified)
+    pub fn from_request(request: &CheckRequest) -> Option<Self> {
+        let minimizes_latency = matches!(
+            ConsistencyPreference::try_from(request.consistency),
+            Ok(ConsistencyPreference::Unspecified | ConsistencyPreference::MinimizeLatency)
+        );
+        if !minimizes_latency {
+            return None;
+        }
+
+        let has_contextual_tuples = request
+            .contextual_tuples
+            .as_ref()
+            .is_some_and(|t| !t.tuple_keys.is_empty());
+        if has_contextual_tuples || request.context.is_some() {
+            return None;
+        }
+
+        let tuple_key = request.tuple_key.as_ref()?;
+        Some(Self {
+            store_id: request.store_id.clone(),
+            authorization_model_id: request.authorization_model_id.clone(),
+            user: tuple_key.user.clone(),
+            relation: tuple_key.relation.clone(),
+            object: tuple_key.object.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<CheckCacheKey, (bool, Instant)>,
+    /// Bumped on every write to a store, see [`CheckCache::generation`]
+    generations: HashMap<String, u64>,
+}
+
+/// In-memory cache of check results with expiry and write invalidation.
+///
+/// Holds at most its capacity of entries: inserting into a full cache drops the
+/// expired entries, or else the oldest one.
+#[derive(Debug)]
+pub struct CheckCache {
+    state: Mutex<CacheState>,
+    ttl: Duration,
+    mode: InvalidationMode,
+    capacity: usize,
+}
+
+impl CheckCache {
+    /// Create a cache whose entries expire after `ttl`, holding up to
+    /// [`DEFAULT_CHECK_CACHE_CAPACITY`] entries
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            state: Mutex::new(CacheState::default()),
+            ttl,
+            mode: InvalidationMode::default(),
+            capacity: DEFAULT_CHECK_CACHE_CAPACITY,
+        }
+    }
+
+    /// Set how writes invalidate entries
+    pub fn with_mode(mut self, mode: InvalidationMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set the most entries the cache holds
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Get a cached result if present and not expired
+    pub fn get(&self, key: &CheckCacheKey) -> Option<bool> {
+        let entries = &mut self.state.lock().unwrap().entries;
+        match entries.get(key) {
+            Some((allowed, inserted_at)) if inserted_at.elapsed() < self.ttl => Some(*allowed),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Write generation of a store, to be taken before sending a check whose result
+    /// is then passed to [`insert`](Self::insert)
+    pub fn generation(&self, store_id: &str) -> u64 {
+        let state = self.state.lock().unwrap();
+        state.generations.get(store_id).copied().unwrap_or(0)
+    }
+
+    /// Cache a check result obtained at `generation` of its store. The result is
+    /// dropped if the store was written to since, as it may predate the write.
+    pub fn insert(&self, key: CheckCacheKey, allowed: bool, generation: u64) {
+        let mut state = self.state.lock().unwrap();
+        let current = state.generations.get(&key.store_id).copied().unwrap_or(0);
+        if current != generation {
+            return;
+        }
+
+        let entries = &mut state.entries;
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            entries.retain(|_, (_, inserted_at)| inserted_at.elapsed() < self.ttl);
+            if entries.len() >= self.capacity {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, (_, inserted_at))| *inserted_at)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(key, (allowed, Instant::now()));
+    }
+
+    /// Number of cached entries, including expired ones not yet evicted
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    /// Whether the cache has no entries
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Evict entries affected by the given tuple changes, returning how many were evicted
+    pub fn on_write(
+        &self,
+        store_id: &str,
+        writes: &[TupleKey],
+        deletes: &[TupleKeyWithoutCondition],
+    ) -> usize {
+        if writes.is_empty() && deletes.is_empty() {
+            return 0;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        *state.generations.entry(store_id.to_string()).or_default() += 1;
+        let entries = &mut state.entries;
+        let before = entries.len();
+
+        match self.mode {
+            InvalidationMode::FlushStore => {
+                entries.retain(|key, _| key.store_id != store_id);
+            }
+            InvalidationMode::Targeted => {
+                let changed = writes
+                    .iter()
+                    .map(|t| (t.user.as_str(), t.object.as_str()))
+                    .chain(deletes.iter().map(|t| (t.user.as_str(), t.object.as_str())));
+
+                let mut identifiers = HashSet::new();
+                for (user, object) in changed {
+                    identifiers.insert(object);
+                    identifiers.insert(user);
+                    // A userset like group:eng#member also affects checks on group:eng
+                    if let Some((user_object, _)) = user.split_once('#') {
+                        identifiers.insert(user_object);
+                    }
+                }
+
+                entries.retain(|key, _| {
+                    key.store_id != store_id
+                        || !(identifiers.contains(key.object.as_str())
+                            || identifiers.contains(key.user.as_str()))
+                });
+            }
+        }
+
+        before - entries.len()
+    }
+}
+
+/// Check client that caches results and invalidates them on writes made through it
+pub struct CachingCheckClient {
+    client: OpenFGAClient,
+    cache: Arc<CheckCache>,
+}
+
+impl CachingCheckClient {
+    /// Wrap a client with a (possibly shared) cache
+    pub fn new(client: OpenFGAClient, cache: Arc<CheckCache>) -> Self {
+        Self { client, cache }
+    }
+
+    /// Get the cache
+    pub fn cache(&self) -> &Arc<CheckCache> {
+        &self.cache
+    }
+
+    /// Check a relation, answering from the cache when possible
+    pub async fn check(&mut self, request: CheckRequest) -> Result<bool, tonic::Status> {
+        let key = CheckCacheKey::from_request(&request);
+        if let Some(allowed) = key.as_ref().and_then(|k| self.cache.get(k)) {
+            return Ok(allowed);
+        }
+
+        let generation = self.cache.generation(&request.store_id);
+        let allowed = self.client.check(request).await?.into_inner().allowed;
+        if let Some(key) = key {
+            self.cache.insert(key, allowed, generation);
+        }
+
+        Ok(allowed)
+    }
+
+    /// Write tuples and evict the cache entries they affect
+    pub async fn write(
+        &mut self,
+        request: WriteRequest,
+    ) -> Result<tonic::Response<WriteResponse>, tonic::Status> {
+        let store_id = request.store_id.clone();
+        let writes = request
+            .writes
+            .as_ref()
+            .map(|w| w.tuple_keys.clone())
+            .unwrap_or_default();
+        let deletes = request
+            .deletes
+            .as_ref()
+            .map(|d| d.tuple_keys.clone())
+            .unwrap_or_default();
+
+        let response = self.client.write(request).await?;
+        self.cache.on_write(&store_id, &writes, &deletes);
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::tuple;
+
+    fn key(store_id: &str, user: &str, relation: &str, object: &str) -> CheckCacheKey {
+        CheckCacheKey {
+            store_id: store_id.to_string(),
+            authorization_model_id: "model".to_string(),
+            user: user.to_string(),
+            relation: relation.to_string(),
+            object: object.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_write_evicts_checks_on_object() {
+        let cache = CheckCache::new(Duration::from_secs(60));
+        let on_x = key("store", "user:alice", "viewer", "document:x");
+        let on_y = key("store", "user:carol", "viewer", "document:y");
+        let other_store = key("other", "user:alice", "viewer", "document:x");
+        cache.insert(on_x.clone(), true, 0);
+        cache.insert(on_y.clone(), false, 0);
+        cache.insert(other_store.clone(), true, 0);
+
+        let evicted = cache.on_write("store", &[tuple("user:bob", "viewer", "document:x")], &[]);
+
+        assert_eq!(evicted, 1);
+        assert_eq!(cache.get(&on_x), None);
+        assert_eq!(cache.get(&on_y), Some(false));
+        assert_eq!(cache.get(&other_store), Some(true));
+    }
+
+    #[test]
+    fn test_flush_store_mode_evicts_all_store_entries() {
+        let cache =
+            CheckCache::new(Duration::from_secs(60)).with_mode(InvalidationMode::FlushStore);
+        cache.insert(key("store", "user:alice", "viewer", "document:x"), true, 0);
+        cache.insert(key("store", "user:carol", "viewer", "document:y"), true, 0);
+        cache.insert(key("other", "user:alice", "viewer", "document:x"), true, 0);
+
+        let deletes = [TupleKeyWithoutCondition {
+            user: "user:bob".to_string(),
+            relation: "member".to_string(),
+            object: "group:eng".to_string(),
+        }];
+        let evicted = cache.on_write("store", &[], &deletes);
+
+        assert_eq!(evicted, 2);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_only_minimize_latency_checks_are_cached() {
+        let request = |consistency: ConsistencyPreference| CheckRequest {
+            store_id: "store".to_string(),
+            tuple_key: Some(crate::CheckRequestTupleKey {
+                user: "user:alice".to_string(),
+                relation: "viewer".to_string(),
+                object: "document:x".to_string(),
+            }),
+            consistency: consistency as i32,
+            ..Default::default()
+        };
+
+        assert!(
+            CheckCacheKey::from_request(&request(ConsistencyPreference::Unspecified)).is_some()
+        );
+        assert!(
+            CheckCacheKey::from_request(&request(ConsistencyPreference::MinimizeLatency)).is_some()
+        );
+        assert!(
+            CheckCacheKey::from_request(&request(ConsistencyPreference::HigherConsistency))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_full_cache_drops_expired_then_oldest_entries() {
+        let cache = CheckCache::new(Duration::from_secs(60)).with_capacity(2);
+        let first = key("store", "user:alice", "viewer", "document:x");
+        let second = key("store", "user:bob", "viewer", "document:x");
+        let third = key("store", "user:carol", "viewer", "document:x");
+        cache.insert(first.clone(), true, 0);
+        cache.insert(second.clone(), true, 0);
+        // Updating a cached check doesn't evict another
+        cache.insert(second.clone(), false, 0);
+        assert_eq!(cache.len(), 2);
+
+        cache.insert(third.clone(), true, 0);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&first), None);
+        assert_eq!(cache.get(&second), Some(false));
+        assert_eq!(cache.get(&third), Some(true));
+
+        // Entries never read again are swept once expired
+        let cache = CheckCache::new(Duration::ZERO).with_capacity(2);
+        cache.insert(first, true, 0);
+        cache.insert(second, true, 0);
+        cache.insert(third, true, 0);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_check_started_before_write_is_not_cached() {
+        let cache = CheckCache::new(Duration::from_secs(60));
+        let on_x = key("store", "user:bob", "viewer", "document:x");
+
+        // A check is sent, then a write lands and invalidates before it returns
+        let generation = cache.generation("store");
+        cache.on_write("store", &[tuple("user:bob", "viewer", "document:x")], &[]);
+        cache.insert(on_x.clone(), false, generation);
+        assert_eq!(cache.get(&on_x), None);
+
+        // Other stores' checks are unaffected
+        let other = key("other", "user:bob", "viewer", "document:x");
+        cache.insert(other.clone(), true, generation);
+        assert_eq!(cache.get(&other), Some(true));
+
+        cache.insert(on_x.clone(), true, cache.generation("store"));
+        assert_eq!(cache.get(&on_x), Some(true));
+    }
+}
@@ -0,0 +1,184 @@
+use crate::{
+    CheckRequest, CheckRequestTupleKey, ConsistencyPreference, ContextualTupleKeys, OpenFGAClient,
+    TupleKey,
+};
+
+/// Builds a [`CheckRequest`], leaving out what isn't set:
+///
+/// ```ignore
+/// let request = CheckRequestBuilder::new()
+///     .store(store_id)
+///     .model(model_id)
+///     .tuple("document:readme", "viewer", "user:anne")
+///     .context_json(json!({ "ip_address": "10.0.0.1" }))?
+///     .consistency(ConsistencyPreference::HigherConsistency)
+///     .build();
+/// ```
+///
+/// [`OpenFGAClient::create_check_request`] is a shortcut for the store and tuple only.
+#[derive(Debug, Clone, Default)]
+pub struct CheckRequestBuilder {
+    store_id: String,
+    authorization_model_id: String,
+    tuple_key: Option<CheckRequestTupleKey>,
+    context: Option<prost_wkt_types::Struct>,
+    contextual_tuples: Vec<TupleKey>,
+    consistency: ConsistencyPreference,
+    trace: bool,
+}
+
+impl CheckRequestBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn store(mut self, store_id: impl Into<String>) -> Self {
+        self.store_id = store_id.into();
+        self
+    }
+
+    /// Authorization model to check against; the store's latest model when not set
+    pub fn model(mut self, authorization_model_id: impl Into<String>) -> Self {
+        self.authorization_model_id = authorization_model_id.into();
+        self
+    }
+
+    /// Whether `user` has `relation` with `object`
+    pub fn tuple(
+        mut self,
+        object: impl Into<String>,
+        relation: impl Into<String>,
+        user: impl Into<String>,
+    ) -> Self {
+        self.tuple_key = Some(CheckRequestTupleKey {
+            object: object.into(),
+            relation: relation.into(),
+            user: user.into(),
+        });
+        self
+    }
+
+    /// Condition context for ABAC models
+    pub fn context(mut self, context: prost_wkt_types::Struct) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Condition context for ABAC models, from a JSON object
+    pub fn context_json(self, context: serde_json::Value) -> Result<Self, serde_json::Error> {
+        Ok(self.context(OpenFGAClient::context_from_json(context)?))
+    }
+
+    /// Tuples considered for this check only, on top of the stored ones
+    pub fn contextual_tuples(mut self, tuples: Vec<TupleKey>) -> Self {
+        self.contextual_tuples = tuples;
+        self
+    }
+
+    pub fn consistency(mut self, consistency: ConsistencyPreference) -> Self {
+        self.consistency = consistency;
+        self
+    }
+
+    /// Ask OpenFGA to trace the resolution; slows the check down
+    pub fn trace(mut self, trace: bool) -> Self {
+        self.trace = trace;
+        self
+    }
+
+    pub fn build(self) -> CheckRequest {
+        CheckRequest {
+            store_id: self.store_id,
+            tuple_key: self.tuple_key,
+            contextual_tuples: (!self.contextual_tuples.is_empty()).then_some(
+                ContextualTupleKeys {
+                    tuple_keys: self.contextual_tuples,
+                },
+            ),
+            authorization_model_id: self.authorization_model_id,
+            trace: self.trace,
+            context: self.context,
+            consistency: self.consistency as i32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_minimal_check_request() {
+        let request = CheckRequestBuilder::new()
+            .store("store")
+            .tuple("document:readme", "viewer", "user:anne")
+            .build();
+
+        assert_eq!(
+            request,
+            OpenFGAClient::create_check_request(
+                "store".to_string(),
+                "document:readme".to_string(),
+                "viewer".to_string(),
+                "user:anne".to_string(),
+            )
+        );
+        assert_eq!(request.store_id, "store");
+        assert_eq!(
+            request.tuple_key,
+            Some(CheckRequestTupleKey {
+                object: "document:readme".to_string(),
+                relation: "viewer".to_string(),
+                user: "user:anne".to_string(),
+            })
+        );
+        assert_eq!(request.authorization_model_id, "");
+        assert_eq!(request.contextual_tuples, None);
+        assert_eq!(request.context, None);
+        assert_eq!(
+            request.consistency,
+            ConsistencyPreference::Unspecified as i32
+        );
+        assert!(!request.trace);
+    }
+
+    #[test]
+    fn test_full_check_request() {
+        let membership = TupleKey {
+            user: "user:anne".to_string(),
+            relation: "member".to_string(),
+            object: "group:eng".to_string(),
+            condition: None,
+        };
+        let request = CheckRequestBuilder::new()
+            .store("store")
+            .model("model")
+            .tuple("document:readme", "viewer", "user:anne")
+            .context_json(json!({ "ip_address": "10.0.0.1" }))
+            .unwrap()
+            .contextual_tuples(vec![membership.clone()])
+            .consistency(ConsistencyPreference::HigherConsistency)
+            .trace(true)
+            .build();
+
+        assert_eq!(request.store_id, "store");
+        assert_eq!(request.authorization_model_id, "model");
+        assert_eq!(request.tuple_key.unwrap().object, "document:readme");
+        assert_eq!(
+            serde_json::to_value(request.context.unwrap()).unwrap(),
+            json!({ "ip_address": "10.0.0.1" })
+        );
+        assert_eq!(
+            request.contextual_tuples,
+            Some(ContextualTupleKeys {
+                tuple_keys: vec![membership],
+            })
+        );
+        assert_eq!(
+            request.consistency,
+            ConsistencyPreference::HigherConsistency as i32
+        );
+        assert!(request.trace);
+    }
+}
@@ -0,0 +1,174 @@
+//! Single-string form of a tuple, for logs, CLI tools and diffing tuple sets:
+//!
+//! ```text
+//! tuple     = object "#" relation "@" user [ "[" condition "]" ]
+//! object    = type ":" id
+//! user      = type ":" id         ; user:anne
+//!           | type ":*"           ; user:*, every user of the type
+//!           | object "#" relation ; group:eng#member, a userset
+//! condition = condition name
+//! ```
+//!
+//! e.g. `document:readme#reader@user:anne` or
+//! `document:readme#reader@group:eng#member[in_office_hours]`. Types, IDs and relations
+//! can't contain `#`, `@` or `:`, except the user's ID, which may contain `@` as in
+//! `user:anne@example.com`. Only the condition's name is kept, not its context.
+
+use crate::{RelationshipCondition, TupleKey};
+
+/// A string that isn't a tuple in the compact form, see [the grammar](self)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidCompactTuple {
+    pub tuple: String,
+}
+
+impl std::fmt::Display for InvalidCompactTuple {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} is not a tuple of the form object#relation@user[condition]",
+            self.tuple
+        )
+    }
+}
+
+impl std::error::Error for InvalidCompactTuple {}
+
+impl TupleKey {
+    /// The tuple as `object#relation@user`, followed by `[condition]` if it has one
+    pub fn to_compact_string(&self) -> String {
+        let mut compact = format!("{}#{}@{}", self.object, self.relation, self.user);
+        if let Some(condition) = &self.condition {
+            compact.push_str(&format!("[{}]", condition.name));
+        }
+        compact
+    }
+
+    /// Parse a tuple written by [`TupleKey::to_compact_string`]; its condition, if any,
+    /// has no context
+    pub fn from_compact_str(compact: &str) -> Result<TupleKey, InvalidCompactTuple> {
+        let invalid = || InvalidCompactTuple {
+            tuple: compact.to_string(),
+        };
+
+        let (tuple, condition) = match compact.strip_suffix(']') {
+            Some(rest) => {
+                let (tuple, name) = rest.rsplit_once('[').ok_or_else(invalid)?;
+                if !is_name(name) {
+                    return Err(invalid());
+                }
+                (tuple, Some(name))
+            }
+            None => (compact, None),
+        };
+
+        let (object, rest) = tuple.split_once('#').ok_or_else(invalid)?;
+        let (relation, user) = rest.split_once('@').ok_or_else(invalid)?;
+        let user_object = match user.split_once('#') {
+            Some((user_object, user_relation)) if is_name(user_relation) => user_object,
+            Some(_) => return Err(invalid()),
+            None => user,
+        };
+        if !is_object(object) || !is_name(relation) || !is_user_object(user_object) {
+            return Err(invalid());
+        }
+
+        Ok(TupleKey {
+            object: object.to_string(),
+            relation: relation.to_string(),
+            user: user.to_string(),
+            condition: condition.map(|name| RelationshipCondition {
+                name: name.to_string(),
+                context: None,
+            }),
+        })
+    }
+}
+
+/// A type, relation or condition name
+fn is_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains([':', '#', '@', '[', ']'])
+}
+
+/// `type:id`
+fn is_object(object: &str) -> bool {
+    object
+        .split_once(':')
+        .is_some_and(|(object_type, id)| is_name(object_type) && is_name(id))
+}
+
+/// `type:id` or `type:*`, where the ID may contain `@`
+fn is_user_object(user: &str) -> bool {
+    user.split_once(':').is_some_and(|(user_type, id)| {
+        is_name(user_type) && !id.is_empty() && !id.contains([':', '#', '[', ']'])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(compact: &str) -> TupleKey {
+        let tuple = TupleKey::from_compact_str(compact).unwrap();
+        assert_eq!(tuple.to_compact_string(), compact);
+        tuple
+    }
+
+    #[test]
+    fn test_plain_tuple_round_trip() {
+        let tuple = round_trip("document:readme#reader@user:alice");
+        assert_eq!(tuple.object, "document:readme");
+        assert_eq!(tuple.relation, "reader");
+        assert_eq!(tuple.user, "user:alice");
+        assert_eq!(tuple.condition, None);
+
+        let tuple = round_trip("document:readme#reader@user:alice@example.com");
+        assert_eq!(tuple.user, "user:alice@example.com");
+        assert_eq!(round_trip("document:readme#reader@user:*").user, "user:*");
+    }
+
+    #[test]
+    fn test_userset_tuple_round_trip() {
+        let tuple = round_trip("document:readme#reader@group:eng#member");
+        assert_eq!(tuple.object, "document:readme");
+        assert_eq!(tuple.relation, "reader");
+        assert_eq!(tuple.user, "group:eng#member");
+    }
+
+    #[test]
+    fn test_conditioned_tuple_round_trip() {
+        let tuple = round_trip("document:readme#reader@group:eng#member[in_office_hours]");
+        assert_eq!(tuple.user, "group:eng#member");
+        assert_eq!(
+            tuple.condition,
+            Some(RelationshipCondition {
+                name: "in_office_hours".to_string(),
+                context: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_compact_tuples() {
+        for compact in [
+            "",
+            "document:readme",
+            "document:readme#reader",
+            "readme#reader@user:alice",
+            "document:readme#@user:alice",
+            "document:readme#reader@alice",
+            "document:readme#reader@group:eng#",
+            "document:readme#reader@user:alice[]",
+            "document:readme#reader@user:alice]",
+        ] {
+            assert_eq!(
+                TupleKey::from_compact_str(compact),
+                Err(InvalidCompactTuple {
+                    tuple: compact.to_string()
+                }),
+                "{}",
+                compact
+            );
+        }
+    }
+}
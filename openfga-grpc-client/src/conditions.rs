@@ -0,0 +1,120 @@
+use crate::bundle::StoreBackend;
+use crate::{
+    Condition, OpenFGAClient, ReadAuthorizationModelRequest, WriteAuthorizationModelRequest,
+};
+use std::collections::HashMap;
+
+impl OpenFGAClient {
+    /// Write a new model version with `new_conditions` merged into the conditions of
+    /// `base_model_id`, and return its ID. See [`update_conditions`].
+    pub async fn update_conditions(
+        &mut self,
+        store_id: &str,
+        base_model_id: &str,
+        new_conditions: HashMap<String, Condition>,
+    ) -> Result<String, tonic::Status> {
+        update_conditions(self, store_id, base_model_id, new_conditions).await
+    }
+}
+
+/// Read `base_model_id`, replace or add the conditions of `new_conditions` by name,
+/// and write the result as a new model version with the same schema version and type
+/// definitions. Conditions of the base model not in `new_conditions` are kept.
+///
+/// Returns the ID of the new model; the base model is left as is.
+pub async fn update_conditions<B: StoreBackend>(
+    client: &mut B,
+    store_id: &str,
+    base_model_id: &str,
+    new_conditions: HashMap<String, Condition>,
+) -> Result<String, tonic::Status> {
+    let base = client
+        .read_authorization_model(ReadAuthorizationModelRequest {
+            store_id: store_id.to_string(),
+            id: base_model_id.to_string(),
+        })
+        .await?
+        .into_inner()
+        .authorization_model
+        .ok_or_else(|| tonic::Status::not_found("authorization model not found"))?;
+
+    let mut conditions = base.conditions;
+    conditions.extend(new_conditions);
+
+    let response = client
+        .write_authorization_model(WriteAuthorizationModelRequest {
+            store_id: store_id.to_string(),
+            type_definitions: base.type_definitions,
+            schema_version: base.schema_version,
+            conditions,
+        })
+        .await?;
+    Ok(response.into_inner().authorization_model_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::MemoryStore;
+    use crate::{AuthorizationModel, TypeDefinition};
+
+    fn condition(name: &str, expression: &str) -> Condition {
+        Condition {
+            name: name.to_string(),
+            expression: expression.to_string(),
+            parameters: HashMap::new(),
+            metadata: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_conditions_keeps_type_definitions() {
+        let type_definition = |name: &str| TypeDefinition {
+            r#type: name.to_string(),
+            relations: HashMap::new(),
+            metadata: None,
+        };
+        let mut store = MemoryStore::with_models(vec![AuthorizationModel {
+            id: "model-1".to_string(),
+            schema_version: "1.1".to_string(),
+            type_definitions: vec![type_definition("user"), type_definition("document")],
+            conditions: HashMap::from([
+                (
+                    "in_office".to_string(),
+                    condition("in_office", "ip == \"10.0.0.1\""),
+                ),
+                (
+                    "non_expired".to_string(),
+                    condition("non_expired", "now < expires_at"),
+                ),
+            ]),
+        }]);
+
+        let updated = condition("in_office", "ip.startsWith(\"10.0.\")");
+        let model_id = update_conditions(
+            &mut store,
+            "store",
+            "model-1",
+            HashMap::from([("in_office".to_string(), updated.clone())]),
+        )
+        .await
+        .unwrap();
+        assert_eq!(model_id, "model-2");
+
+        let written = store.state().model_writes.pop().unwrap();
+        assert_eq!(written.store_id, "store");
+        assert_eq!(written.schema_version, "1.1");
+        let types: Vec<_> = written
+            .type_definitions
+            .iter()
+            .map(|t| t.r#type.as_str())
+            .collect();
+        assert_eq!(types, vec!["user", "document"]);
+        assert_eq!(written.conditions.len(), 2);
+        assert_eq!(written.conditions["in_office"], updated);
+        assert_eq!(
+            written.conditions["non_expired"].expression,
+            "now < expires_at"
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use crate::bundle::StoreBackend;
+use crate::{
+    ConsistencyPreference, OpenFGAClient, ReadRequest, ReadRequestTupleKey, TupleKey,
+    TupleKeyWithoutCondition, WriteRequest, WriteRequestWrites, WriteResponse,
+};
+
+/// How [`OpenFGAClient::write_with_resolution`] handles writes of tuples that are
+/// already stored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictResolution {
+    /// Fail the whole write, OpenFGA's default
+    #[default]
+    Error,
+    /// Skip the tuple and keep its stored condition
+    Ignore,
+    /// Replace the stored tuple when its condition differs, by deleting and writing
+    /// it in the same request. Tuples stored with the same condition are skipped.
+    UpsertCondition,
+}
+
+impl ConflictResolution {
+    /// `on_duplicate` value sent with the writes
+    pub fn on_duplicate(&self) -> &'static str {
+        match self {
+            ConflictResolution::Error => "error",
+            ConflictResolution::Ignore | ConflictResolution::UpsertCondition => "ignore",
+        }
+    }
+}
+
+impl OpenFGAClient {
+    /// Write `request`, resolving writes of stored tuples with `resolution`.
+    ///
+    /// [`ConflictResolution::UpsertCondition`] reads each written tuple first, so the
+    /// result is only as current as those reads.
+    pub async fn write_with_resolution(
+        &mut self,
+        request: WriteRequest,
+        resolution: ConflictResolution,
+    ) -> Result<tonic::Response<WriteResponse>, tonic::Status> {
+        write_with_resolution(self, request, resolution).await
+    }
+}
+
+/// Write `request` through `client`, resolving writes of stored tuples with
+/// `resolution`. Any deletes in `request` are kept.
+pub async fn write_with_resolution<B: StoreBackend>(
+    client: &mut B,
+    mut request: WriteRequest,
+    resolution: ConflictResolution,
+) -> Result<tonic::Response<WriteResponse>, tonic::Status> {
+    let Some(writes) = request.writes.take() else {
+        return client.write(request).await;
+    };
+
+    let mut tuple_keys = writes.tuple_keys;
+    if resolution == ConflictResolution::UpsertCondition {
+        let mut deletes = request.deletes.take().unwrap_or_default();
+        let mut upserts = Vec::with_capacity(tuple_keys.len());
+
+        for key in tuple_keys {
+            match stored_tuple(client, &request.store_id, &key).await? {
+                Some(stored) if stored.condition == key.condition => {}
+                Some(stored) => {
+                    deletes.tuple_keys.push(TupleKeyWithoutCondition {
+                        user: stored.user,
+                        relation: stored.relation,
+                        object: stored.object,
+                    });
+                    upserts.push(key);
+                }
+                None => upserts.push(key),
+            }
+        }
+
+        tuple_keys = upserts;
+        request.deletes = (!deletes.tuple_keys.is_empty()).then_some(deletes);
+    }
+
+    request.writes = (!tuple_keys.is_empty()).then(|| WriteRequestWrites {
+        tuple_keys,
+        on_duplicate: resolution.on_duplicate().to_string(),
+    });
+    if request.writes.is_none() && request.deletes.is_none() {
+        // Every tuple is already stored with its condition
+        return Ok(tonic::Response::new(WriteResponse {}));
+    }
+
+    client.write(request).await
+}
+
+/// The stored tuple with the user, relation and object of `key`
+async fn stored_tuple<B: StoreBackend>(
+    client: &mut B,
+    store_id: &str,
+    key: &TupleKey,
+) -> Result<Option<TupleKey>, tonic::Status> {
+    let response = client
+        .read(ReadRequest {
+            store_id: store_id.to_string(),
+            tuple_key: Some(ReadRequestTupleKey {
+                user: key.user.clone(),
+                relation: key.relation.clone(),
+                object: key.object.clone(),
+            }),
+            page_size: Some(1),
+            continuation_token: String::new(),
+            consistency: ConsistencyPreference::HigherConsistency as i32,
+        })
+        .await?
+        .into_inner();
+
+    Ok(response.tuples.into_iter().next().and_then(|t| t.key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RelationshipCondition;
+    use crate::test_support::{MemoryStore, tuple};
+
+    fn conditioned(user: &str, relation: &str, object: &str, condition: &str) -> TupleKey {
+        TupleKey {
+            condition: Some(RelationshipCondition {
+                name: condition.to_string(),
+                context: None,
+            }),
+            ..tuple(user, relation, object)
+        }
+    }
+
+    fn write_request(tuple_keys: Vec<TupleKey>) -> WriteRequest {
+        WriteRequest {
+            store_id: "store".to_string(),
+            authorization_model_id: String::new(),
+            writes: Some(WriteRequestWrites {
+                tuple_keys,
+                on_duplicate: String::new(),
+            }),
+            deletes: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upsert_condition_replaces_stored_condition() {
+        let anne = conditioned("user:anne", "viewer", "document:readme", "in_office");
+        let bob = conditioned("user:bob", "viewer", "document:readme", "in_office");
+        let mut store = MemoryStore::with_tuples(vec![
+            conditioned("user:anne", "viewer", "document:readme", "weekday"),
+            bob.clone(),
+        ]);
+        let carl = tuple("user:carl", "viewer", "document:readme");
+
+        write_with_resolution(
+            &mut store,
+            write_request(vec![anne.clone(), bob.clone(), carl.clone()]),
+            ConflictResolution::UpsertCondition,
+        )
+        .await
+        .unwrap();
+
+        // One request deleting anne's stored tuple and writing it with the new
+        // condition; bob's tuple is unchanged and skipped
+        let state = store.state();
+        assert_eq!(state.writes.len(), 1);
+        assert_eq!(state.tuples, vec![bob, anne.clone(), carl.clone()]);
+        let request = &state.writes[0];
+        assert_eq!(
+            request.deletes.as_ref().unwrap().tuple_keys,
+            vec![TupleKeyWithoutCondition {
+                user: "user:anne".to_string(),
+                relation: "viewer".to_string(),
+                object: "document:readme".to_string(),
+            }]
+        );
+        let writes = request.writes.as_ref().unwrap();
+        assert_eq!(writes.tuple_keys, vec![anne, carl]);
+        assert_eq!(writes.on_duplicate, "ignore");
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_tuples_skip_the_write() {
+        let anne = conditioned("user:anne", "viewer", "document:readme", "in_office");
+        let mut store = MemoryStore::with_tuples(vec![anne.clone()]);
+
+        write_with_resolution(
+            &mut store,
+            write_request(vec![anne.clone()]),
+            ConflictResolution::UpsertCondition,
+        )
+        .await
+        .unwrap();
+        assert!(store.state().writes.is_empty());
+
+        write_with_resolution(
+            &mut store,
+            write_request(vec![anne]),
+            ConflictResolution::Error,
+        )
+        .await
+        .unwrap();
+        let writes = store.state().writes[0].writes.clone().unwrap();
+        assert_eq!(writes.on_duplicate, "error");
+    }
+}
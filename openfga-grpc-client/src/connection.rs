@@ -0,0 +1,51 @@
+use std::time::Duration;
+use tonic::transport::Endpoint;
+
+/// Default interval of keepalive pings, under the 60 second idle timeout common to
+/// load balancers and proxies
+pub const DEFAULT_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Default time to wait for a keepalive ping's acknowledgement before closing the
+/// connection
+pub const DEFAULT_KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// HTTP/2 settings of the connection to OpenFGA, see
+/// [`OpenFGAClientBuilder`](crate::OpenFGAClientBuilder)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Http2Settings {
+    /// Interval of the HTTP/2 pings keeping the connection alive; `None` disables them
+    pub keep_alive_interval: Option<Duration>,
+    /// How long to wait for a ping's acknowledgement before closing the connection
+    pub keep_alive_timeout: Duration,
+    /// Ping idle connections too, so they aren't dropped between bursts of calls
+    pub keep_alive_while_idle: bool,
+    /// Size the flow control windows from the measured bandwidth-delay product,
+    /// instead of HTTP/2's fixed default, for large responses over high-latency links
+    pub adaptive_window: bool,
+}
+
+impl Default for Http2Settings {
+    /// Keepalive pings every [`DEFAULT_KEEP_ALIVE_INTERVAL`], idle or not, and fixed
+    /// windows
+    fn default() -> Self {
+        Self {
+            keep_alive_interval: Some(DEFAULT_KEEP_ALIVE_INTERVAL),
+            keep_alive_timeout: DEFAULT_KEEP_ALIVE_TIMEOUT,
+            keep_alive_while_idle: true,
+            adaptive_window: false,
+        }
+    }
+}
+
+impl Http2Settings {
+    /// Configure `endpoint` with the settings
+    pub fn apply(&self, mut endpoint: Endpoint) -> Endpoint {
+        if let Some(interval) = self.keep_alive_interval {
+            endpoint = endpoint
+                .http2_keep_alive_interval(interval)
+                .keep_alive_timeout(self.keep_alive_timeout)
+                .keep_alive_while_idle(self.keep_alive_while_idle);
+        }
+        endpoint.http2_adaptive_window(self.adaptive_window)
+    }
+}
@@ -0,0 +1,92 @@
+//! Per-call deadlines.
+//!
+//! The [`crate::OpenFGAClient`] call methods take either a message or a
+//! [`tonic::Request`], so a deadline is attached by converting the message:
+//!
+//! ```ignore
+//! let response = client.check(request.with_deadline(Some(Duration::from_secs(2)))).await?;
+//! ```
+
+use std::time::Duration;
+
+/// Attach a deadline to a request message
+pub trait WithDeadline: Sized {
+    /// Wrap the message in a [`tonic::Request`] that times out after `deadline`.
+    ///
+    /// The deadline is sent to OpenFGA as `grpc-timeout` metadata, so the server
+    /// can abandon work the caller won't wait for. `None` leaves the call unbounded.
+    fn with_deadline(self, deadline: Option<Duration>) -> tonic::Request<Self>;
+}
+
+impl<T: prost::Message> WithDeadline for T {
+    fn with_deadline(self, deadline: Option<Duration>) -> tonic::Request<Self> {
+        let mut request = tonic::Request::new(self);
+        if let Some(deadline) = deadline {
+            request.set_timeout(deadline);
+        }
+        request
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CheckRequest, OpenFgaServiceClient};
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Poll};
+    use tonic::body::{BoxBody, empty_body};
+    use tonic::codegen::{Service, http};
+
+    /// Mock transport recording the `grpc-timeout` header of each call and
+    /// answering `UNIMPLEMENTED`
+    #[derive(Clone, Default)]
+    struct RecordTimeout {
+        timeouts: Arc<Mutex<Vec<Option<String>>>>,
+    }
+
+    impl Service<http::Request<BoxBody>> for RecordTimeout {
+        type Response = http::Response<BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = std::future::Ready<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: http::Request<BoxBody>) -> Self::Future {
+            let timeout = request
+                .headers()
+                .get("grpc-timeout")
+                .map(|v| v.to_str().unwrap().to_string());
+            self.timeouts.lock().unwrap().push(timeout);
+
+            let response = http::Response::builder()
+                .header("content-type", "application/grpc")
+                .header("grpc-status", "12")
+                .body(empty_body())
+                .unwrap();
+            std::future::ready(Ok(response))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deadline_sets_request_timeout() {
+        let transport = RecordTimeout::default();
+        let mut client = OpenFgaServiceClient::new(transport.clone());
+
+        let request = CheckRequest::default().with_deadline(Some(Duration::from_secs(2)));
+        assert_eq!(request.metadata().get("grpc-timeout").unwrap(), "2000000u");
+        let status = client.check(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unimplemented);
+
+        client
+            .check(CheckRequest::default().with_deadline(None))
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            *transport.timeouts.lock().unwrap(),
+            vec![Some("2000000u".to_string()), None]
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use crate::CheckRequest;
+use serde_json::{Map, Value, json};
+use std::time::SystemTime;
+
+/// `path` of check decisions, the OPA equivalent of the queried policy rule
+pub const OPA_CHECK_PATH: &str = "openfga/check";
+
+/// Serialize a check decision as an Open Policy Agent decision log entry, so OpenFGA
+/// decisions can flow into pipelines built for OPA:
+///
+/// ```json
+/// {
+///   "decision_id": "<random UUID>",
+///   "path": "openfga/check",
+///   "input": {
+///     "store_id": "...",
+///     "authorization_model_id": "...",
+///     "tuple": { "user": "user:anne", "relation": "viewer", "object": "document:readme" }
+///   },
+///   "result": true,
+///   "timestamp": "2024-05-01T12:00:00Z"
+/// }
+/// ```
+///
+/// `input` also carries the request's `contextual_tuples` and `context` when set.
+pub fn to_opa_decision_log(request: &CheckRequest, allowed: bool, timestamp: SystemTime) -> Value {
+    let mut input = Map::new();
+    input.insert("store_id".to_string(), json!(request.store_id));
+    if !request.authorization_model_id.is_empty() {
+        input.insert(
+            "authorization_model_id".to_string(),
+            json!(request.authorization_model_id),
+        );
+    }
+    if let Some(tuple_key) = &request.tuple_key {
+        input.insert(
+            "tuple".to_string(),
+            json!({
+                "user": tuple_key.user,
+                "relation": tuple_key.relation,
+                "object": tuple_key.object,
+            }),
+        );
+    }
+    if let Some(contextual_tuples) = &request.contextual_tuples {
+        let tuples: Vec<Value> = contextual_tuples
+            .tuple_keys
+            .iter()
+            .map(|tuple| {
+                json!({
+                    "user": tuple.user,
+                    "relation": tuple.relation,
+                    "object": tuple.object,
+                })
+            })
+            .collect();
+        input.insert("contextual_tuples".to_string(), Value::Array(tuples));
+    }
+    if let Some(context) = &request.context {
+        input.insert(
+            "context".to_string(),
+            serde_json::to_value(context).unwrap_or_default(),
+        );
+    }
+
+    json!({
+        "decision_id": uuid::Uuid::new_v4().to_string(),
+        "path": OPA_CHECK_PATH,
+        "input": input,
+        "result": allowed,
+        "timestamp": prost_wkt_types::Timestamp::from(timestamp),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OpenFGAClient;
+    use std::time::Duration;
+
+    #[test]
+    fn test_allowed_check_decision_log() {
+        let mut request = OpenFGAClient::create_check_request(
+            "store".to_string(),
+            "document:readme".to_string(),
+            "viewer".to_string(),
+            "user:anne".to_string(),
+        );
+        request.authorization_model_id = "model".to_string();
+        let timestamp = SystemTime::UNIX_EPOCH + Duration::from_secs(1_714_564_800);
+
+        let entry = to_opa_decision_log(&request, true, timestamp);
+
+        let decision_id = entry["decision_id"].as_str().unwrap();
+        assert!(uuid::Uuid::parse_str(decision_id).is_ok());
+        let mut fields: Vec<&str> = entry
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        fields.sort();
+        assert_eq!(
+            fields,
+            vec!["decision_id", "input", "path", "result", "timestamp"]
+        );
+        assert_eq!(entry["path"], "openfga/check");
+        assert_eq!(
+            entry["input"],
+            json!({
+                "store_id": "store",
+                "authorization_model_id": "model",
+                "tuple": {
+                    "user": "user:anne",
+                    "relation": "viewer",
+                    "object": "document:readme"
+                }
+            })
+        );
+        assert_eq!(entry["result"], json!(true));
+        assert_eq!(entry["timestamp"], "2024-05-01T12:00:00Z");
+
+        // Each entry gets its own decision ID
+        let other = to_opa_decision_log(&request, true, timestamp);
+        assert_ne!(other["decision_id"], entry["decision_id"]);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::{
+    CheckRequest, CheckRequestTupleKey, ConsistencyPreference, OpenFGAClient, TupleKey,
+    TupleKeyWithoutCondition,
+};
+use std::future::Future;
+
+/// Kind of change previewed by a dry run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DryRunOperation {
+    Write,
+    Delete,
+}
+
+/// Whether applying a change would alter the relation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DryRunOutcome {
+    /// The relation already is in the requested state
+    NoOp,
+    /// Applying the change would grant or revoke the relation
+    Effective,
+}
+
+/// A single previewed change
+#[derive(Debug, Clone, PartialEq)]
+pub struct DryRunChange {
+    pub operation: DryRunOperation,
+    pub user: String,
+    pub relation: String,
+    pub object: String,
+    pub outcome: DryRunOutcome,
+}
+
+/// Result of [`OpenFGAClient::write_dry_run`], in the order writes then deletes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DryRunReport {
+    pub changes: Vec<DryRunChange>,
+}
+
+impl DryRunReport {
+    /// Changes that would alter a relation
+    pub fn effective(&self) -> impl Iterator<Item = &DryRunChange> {
+        self.changes
+            .iter()
+            .filter(|c| c.outcome == DryRunOutcome::Effective)
+    }
+
+    /// Changes that would leave the relation as it is
+    pub fn no_ops(&self) -> impl Iterator<Item = &DryRunChange> {
+        self.changes
+            .iter()
+            .filter(|c| c.outcome == DryRunOutcome::NoOp)
+    }
+}
+
+// Client-side preview of write requests
+impl OpenFGAClient {
+    /// Preview a write without persisting it.
+    ///
+    /// Runs a check per change: a write is a no-op if the relation already holds and
+    /// a delete is a no-op if it does not. Relations that hold through another path
+    /// (e.g. a userset) are reported as already holding.
+    pub async fn write_dry_run(
+        &mut self,
+        store_id: String,
+        authorization_model_id: String,
+        writes: Vec<TupleKey>,
+        deletes: Vec<TupleKeyWithoutCondition>,
+    ) -> Result<DryRunReport, tonic::Status> {
+        let client = self.clone();
+        Self::dry_run_with(writes, deletes, |user, relation, object| {
+            let mut client = client.clone();
+            let request = CheckRequest {
+                store_id: store_id.clone(),
+                tuple_key: Some(CheckRequestTupleKey {
+                    user,
+                    relation,
+                    object,
+                }),
+                contextual_tuples: None,
+                authorization_model_id: authorization_model_id.clone(),
+                trace: false,
+                consistency: ConsistencyPreference::HigherConsistency as i32,
+                context: None,
+            };
+            async move { Ok(client.check(request).await?.into_inner().allowed) }
+        })
+        .await
+    }
+
+    /// Classify changes using `check(user, relation, object)` to tell whether a
+    /// relation currently holds
+    pub async fn dry_run_with<F, Fut>(
+        writes: Vec<TupleKey>,
+        deletes: Vec<TupleKeyWithoutCondition>,
+        mut check: F,
+    ) -> Result<DryRunReport, tonic::Status>
+    where
+        F: FnMut(String, String, String) -> Fut,
+        Fut: Future<Output = Result<bool, tonic::Status>>,
+    {
+        let changes = writes
+            .into_iter()
+            .map(|t| (DryRunOperation::Write, t.user, t.relation, t.object))
+            .chain(
+                deletes
+                    .into_iter()
+                    .map(|t| (DryRunOperation::Delete, t.user, t.relation, t.object)),
+            );
+
+        let mut report = DryRunReport::default();
+        for (operation, user, relation, object) in changes {
+            let holds = check(user.clone(), relation.clone(), object.clone()).await?;
+            let outcome = match (operation, holds) {
+                (DryRunOperation::Write, true) | (DryRunOperation::Delete, false) => {
+                    DryRunOutcome::NoOp
+                }
+                _ => DryRunOutcome::Effective,
+            };
+            report.changes.push(DryRunChange {
+                operation,
+                user,
+                relation,
+                object,
+                outcome,
+            });
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tuple_key(user: &str, relation: &str, object: &str) -> TupleKey {
+        TupleKey {
+            user: user.to_string(),
+            relation: relation.to_string(),
+            object: object.to_string(),
+            condition: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_classifies_writes() {
+        let writes = vec![
+            tuple_key("user:alice", "viewer", "document:readme"),
+            tuple_key("user:bob", "viewer", "document:readme"),
+        ];
+
+        // Only alice is already a viewer
+        let report = OpenFGAClient::dry_run_with(writes, vec![], |user, _, _| async move {
+            Ok(user == "user:alice")
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(report.changes.len(), 2);
+        assert_eq!(report.changes[0].user, "user:alice");
+        assert_eq!(report.changes[0].outcome, DryRunOutcome::NoOp);
+        assert_eq!(report.changes[1].user, "user:bob");
+        assert_eq!(report.changes[1].outcome, DryRunOutcome::Effective);
+        assert_eq!(report.effective().count(), 1);
+        assert_eq!(report.no_ops().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_classifies_deletes() {
+        let deletes = vec![TupleKeyWithoutCondition {
+            user: "user:carol".to_string(),
+            relation: "editor".to_string(),
+            object: "document:readme".to_string(),
+        }];
+
+        let report = OpenFGAClient::dry_run_with(vec![], deletes, |_, _, _| async { Ok(false) })
+            .await
+            .unwrap();
+
+        assert_eq!(report.changes[0].operation, DryRunOperation::Delete);
+        assert_eq!(report.changes[0].outcome, DryRunOutcome::NoOp);
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{OpenFGAClient, OpenFGAClientBuilder};
+use std::collections::HashMap;
+use tonic::codec::CompressionEncoding;
+
+/// gRPC endpoint, e.g. `http://localhost:8081`. Required.
+pub const ENV_CLIENT_URL: &str = "OPENFGA_CLIENT_URL";
+/// Bearer token; takes precedence over [`ENV_API_KEY`]
+pub const ENV_API_TOKEN: &str = "OPENFGA_API_TOKEN";
+/// API key sent as the `authorization` header
+pub const ENV_API_KEY: &str = "OPENFGA_API_KEY";
+/// Prefix of the API key, e.g. `Bearer`
+pub const ENV_API_KEY_PREFIX: &str = "OPENFGA_API_KEY_PREFIX";
+/// Path of a PEM CA certificate to trust. Enables TLS.
+pub const ENV_TLS_CA_CERT: &str = "OPENFGA_TLS_CA_CERT";
+/// Path of a PEM client certificate for mutual TLS; requires [`ENV_TLS_CLIENT_KEY`]
+pub const ENV_TLS_CLIENT_CERT: &str = "OPENFGA_TLS_CLIENT_CERT";
+/// Path of the PEM private key of [`ENV_TLS_CLIENT_CERT`]
+pub const ENV_TLS_CLIENT_KEY: &str = "OPENFGA_TLS_CLIENT_KEY";
+/// Domain name for SNI and certificate verification. Enables TLS.
+pub const ENV_TLS_DOMAIN_NAME: &str = "OPENFGA_TLS_DOMAIN_NAME";
+/// `gzip` or `zstd`
+pub const ENV_COMPRESSION: &str = "OPENFGA_COMPRESSION";
+/// Request tags, e.g. `feature=doc-sharing,team=platform`; see
+/// [`OpenFGAClientBuilder::tags`]
+pub const ENV_TAGS: &str = "OPENFGA_TAGS";
+
+/// Error returned by [`OpenFGAClient::from_env`]
+#[derive(Debug)]
+pub enum EnvConfigError {
+    /// A required variable isn't set
+    Missing(&'static str),
+    /// A variable is set to a value that can't be used
+    Invalid { var: &'static str, message: String },
+}
+
+impl std::fmt::Display for EnvConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnvConfigError::Missing(var) => write!(f, "{} must be set", var),
+            EnvConfigError::Invalid { var, message } => write!(f, "invalid {}: {}", var, message),
+        }
+    }
+}
+
+impl std::error::Error for EnvConfigError {}
+
+fn invalid(var: &'static str, message: impl ToString) -> EnvConfigError {
+    EnvConfigError::Invalid {
+        var,
+        message: message.to_string(),
+    }
+}
+
+impl OpenFGAClient {
+    /// Build a client from the `OPENFGA_*` environment variables, see the `ENV_*`
+    /// constants of [`crate::env_config`]. The connection is established on first use.
+    pub fn from_env() -> Result<Self, EnvConfigError> {
+        Self::from_vars(std::env::vars())
+    }
+
+    /// Build a client from the given variables, as [`OpenFGAClient::from_env`] does;
+    /// empty values count as unset
+    pub fn from_vars(
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, EnvConfigError> {
+        let vars: HashMap<String, String> = vars
+            .into_iter()
+            .filter(|(key, value)| key.starts_with("OPENFGA_") && !value.is_empty())
+            .collect();
+        let var = |name: &str| vars.get(name).map(String::as_str);
+        let read_pem = |name: &'static str| {
+            var(name)
+                .map(|path| {
+                    std::fs::read(path).map_err(|e| invalid(name, format!("{}: {}", path, e)))
+                })
+                .transpose()
+        };
+
+        let url = var(ENV_CLIENT_URL).ok_or(EnvConfigError::Missing(ENV_CLIENT_URL))?;
+        let mut builder = OpenFGAClientBuilder::new(url);
+
+        if let Some(token) = var(ENV_API_TOKEN) {
+            builder = builder
+                .api_token(token)
+                .map_err(|e| invalid(ENV_API_TOKEN, e))?;
+        } else if let Some(key) = var(ENV_API_KEY) {
+            builder = builder
+                .api_key(key, var(ENV_API_KEY_PREFIX))
+                .map_err(|e| invalid(ENV_API_KEY, e))?;
+        }
+
+        if let Some(pem) = read_pem(ENV_TLS_CA_CERT)? {
+            builder = builder.ca_certificate(pem);
+        }
+        match (
+            read_pem(ENV_TLS_CLIENT_CERT)?,
+            read_pem(ENV_TLS_CLIENT_KEY)?,
+        ) {
+            (Some(cert), Some(key)) => builder = builder.client_identity(cert, key),
+            (None, None) => {}
+            (Some(_), None) => return Err(EnvConfigError::Missing(ENV_TLS_CLIENT_KEY)),
+            (None, Some(_)) => return Err(EnvConfigError::Missing(ENV_TLS_CLIENT_CERT)),
+        }
+        if let Some(domain_name) = var(ENV_TLS_DOMAIN_NAME) {
+            builder = builder.tls_domain_name(domain_name);
+        }
+
+        match var(ENV_COMPRESSION) {
+            Some("gzip") => builder = builder.compression(CompressionEncoding::Gzip),
+            Some("zstd") => builder = builder.compression(CompressionEncoding::Zstd),
+            Some(other) => {
+                return Err(invalid(
+                    ENV_COMPRESSION,
+                    format!("{:?}, expected gzip or zstd", other),
+                ));
+            }
+            None => {}
+        }
+
+        if let Some(tags) = var(ENV_TAGS) {
+            let tags = tags
+                .split(',')
+                .map(|tag| {
+                    tag.split_once('=')
+                        .map(|(name, value)| (name.trim(), value.trim()))
+                        .ok_or_else(|| invalid(ENV_TAGS, format!("{:?}, expected name=value", tag)))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            builder = builder.tags(tags).map_err(|e| invalid(ENV_TAGS, e))?;
+        }
+
+        builder
+            .connect_lazy()
+            .map_err(|e| invalid(ENV_CLIENT_URL, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn from_vars_error(pairs: &[(&str, &str)]) -> EnvConfigError {
+        match OpenFGAClient::from_vars(vars(pairs)) {
+            Ok(_) => panic!("expected an error for {:?}", pairs),
+            Err(e) => e,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_from_vars() {
+        let error = from_vars_error(&[(ENV_API_TOKEN, "token")]);
+        assert!(matches!(error, EnvConfigError::Missing(ENV_CLIENT_URL)));
+        assert_eq!(error.to_string(), "OPENFGA_CLIENT_URL must be set");
+
+        let client = OpenFGAClient::from_vars(vars(&[
+            (ENV_CLIENT_URL, "http://127.0.0.1:8081"),
+            (ENV_API_TOKEN, "token"),
+            (ENV_COMPRESSION, "gzip"),
+            (ENV_TAGS, "feature=doc-sharing, team=platform"),
+        ]))
+        .unwrap();
+        assert_eq!(client.authorization.as_ref().unwrap(), "Bearer token");
+        assert_eq!(client.compression(), Some(CompressionEncoding::Gzip));
+        assert_eq!(client.tags.len(), 2);
+        assert!(!client.is_tls());
+
+        let client = OpenFGAClient::from_vars(vars(&[
+            (ENV_CLIENT_URL, "https://openfga.internal:8081"),
+            (ENV_API_KEY, "key"),
+            (ENV_TLS_CA_CERT, "tests/certs/ca.pem"),
+        ]))
+        .unwrap();
+        assert_eq!(client.authorization.as_ref().unwrap(), "key");
+        assert!(client.is_tls());
+
+        let error = from_vars_error(&[
+            (ENV_CLIENT_URL, "http://127.0.0.1:8081"),
+            (ENV_TLS_CLIENT_CERT, "tests/certs/client.pem"),
+        ]);
+        assert!(matches!(error, EnvConfigError::Missing(ENV_TLS_CLIENT_KEY)));
+
+        let error = from_vars_error(&[
+            (ENV_CLIENT_URL, "http://127.0.0.1:8081"),
+            (ENV_COMPRESSION, "brotli"),
+        ]);
+        assert!(matches!(
+            error,
+            EnvConfigError::Invalid {
+                var: ENV_COMPRESSION,
+                ..
+            }
+        ));
+
+        let error = from_vars_error(&[
+            (ENV_CLIENT_URL, "http://127.0.0.1:8081"),
+            (ENV_TAGS, "feature"),
+        ]);
+        assert!(matches!(
+            error,
+            EnvConfigError::Invalid { var: ENV_TAGS, .. }
+        ));
+    }
+}
@@ -0,0 +1,131 @@
+/// Error returned by the authorization model calls of [`crate::OpenFGAClient`]
+#[derive(Debug)]
+pub enum OpenFgaClientError {
+    /// A message exceeded the gRPC size limit, typically a large authorization model.
+    /// `limit` is the limit in bytes when the error reports it.
+    MessageTooLarge {
+        limit: Option<usize>,
+        status: tonic::Status,
+    },
+    /// Any other error returned by OpenFGA
+    Status(tonic::Status),
+}
+
+impl OpenFgaClientError {
+    /// Get the underlying gRPC status
+    pub fn status(&self) -> &tonic::Status {
+        match self {
+            OpenFgaClientError::MessageTooLarge { status, .. }
+            | OpenFgaClientError::Status(status) => status,
+        }
+    }
+}
+
+impl From<tonic::Status> for OpenFgaClientError {
+    fn from(status: tonic::Status) -> Self {
+        if is_message_too_large(status.message()) {
+            OpenFgaClientError::MessageTooLarge {
+                limit: size_limit(status.message()),
+                status,
+            }
+        } else {
+            OpenFgaClientError::Status(status)
+        }
+    }
+}
+
+/// Keeps the status code, with the guidance in the message for size errors
+impl From<OpenFgaClientError> for tonic::Status {
+    fn from(error: OpenFgaClientError) -> Self {
+        match error {
+            OpenFgaClientError::MessageTooLarge { ref status, .. } => {
+                tonic::Status::new(status.code(), error.to_string())
+            }
+            OpenFgaClientError::Status(status) => status,
+        }
+    }
+}
+
+impl std::fmt::Display for OpenFgaClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OpenFgaClientError::MessageTooLarge { limit, .. } => {
+                write!(f, "message exceeds the gRPC size limit")?;
+                if let Some(limit) = limit {
+                    write!(f, " of {} bytes", limit)?;
+                }
+                write!(
+                    f,
+                    "; the authorization model is likely too large, increase \
+                     `max_decoding_message_size` (and `max_encoding_message_size` for writes) \
+                     on the client"
+                )
+            }
+            OpenFgaClientError::Status(status) => write!(f, "{}", status),
+        }
+    }
+}
+
+impl std::error::Error for OpenFgaClientError {}
+
+/// Whether a status message is a size error from tonic's codec
+/// ("decoded message length too large") or from the server
+/// ("received message larger than max")
+fn is_message_too_large(message: &str) -> bool {
+    message.contains("message length too large") || message.contains("larger than max")
+}
+
+/// Limit from tonic's "..., the limit is: N bytes" or grpc-go's "(found vs. N)"
+fn size_limit(message: &str) -> Option<usize> {
+    let rest = match message.split_once("the limit is:") {
+        Some((_, rest)) => rest,
+        None => message.rsplit_once(" vs. ")?.1,
+    };
+    let digits: String = rest
+        .trim_start()
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_too_large() {
+        let status = tonic::Status::out_of_range(
+            "Error, decoded message length too large: found 5242880 bytes, the limit is: 4194304 bytes",
+        );
+        let error = OpenFgaClientError::from(status);
+        assert!(matches!(
+            error,
+            OpenFgaClientError::MessageTooLarge {
+                limit: Some(4194304),
+                ..
+            }
+        ));
+        assert!(error.to_string().contains("of 4194304 bytes"));
+        assert!(error.to_string().contains("max_decoding_message_size"));
+
+        let status = tonic::Status::from(error);
+        assert_eq!(status.code(), tonic::Code::OutOfRange);
+        assert!(status.message().contains("max_decoding_message_size"));
+
+        let error = OpenFgaClientError::from(tonic::Status::resource_exhausted(
+            "grpc: received message larger than max (5242880 vs. 4194304)",
+        ));
+        assert!(matches!(
+            error,
+            OpenFgaClientError::MessageTooLarge {
+                limit: Some(4194304),
+                ..
+            }
+        ));
+
+        let error = OpenFgaClientError::from(tonic::Status::not_found("model not found"));
+        assert!(matches!(error, OpenFgaClientError::Status(_)));
+        assert_eq!(error.status().code(), tonic::Code::NotFound);
+    }
+}
@@ -0,0 +1,200 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tonic::body::BoxBody;
+use tonic::codegen::{BoxFuture, Bytes, Service, http};
+use tonic::transport::Channel;
+
+/// Metadata key carrying OpenFGA's own error code on a failed call, see
+/// [`openfga_error_code`]
+pub const ERROR_CODE_METADATA: &str = "openfga-error-code";
+
+/// Highest status code defined by gRPC (`UNAUTHENTICATED`)
+const MAX_GRPC_CODE: u32 = 16;
+
+/// Channel service keeping OpenFGA's error codes on failed calls.
+///
+/// OpenFGA answers errors with its own codes as the `grpc-status`, e.g. 2027 for
+/// `invalid_tuple`, which tonic reads as `Unknown` without keeping the number. This
+/// copies such codes to the `openfga-error-code` metadata of the resulting status.
+#[derive(Debug, Clone)]
+pub struct ErrorCodeService<S> {
+    inner: S,
+}
+
+impl<S> ErrorCodeService<S> {
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+}
+
+/// Channel whose failed calls keep OpenFGA's error code, e.g.
+/// `OpenFgaServiceClient::new(ErrorCodeService::new(channel))`
+pub type ErrorCodeChannel = ErrorCodeService<Channel>;
+
+impl<S, B> Service<http::Request<B>> for ErrorCodeService<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<BoxBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = S::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            // Trailers-only responses carry the status in their headers
+            let mut response = response.await?;
+            copy_error_code(response.headers_mut());
+            Ok(response.map(|body| BoxBody::new(ErrorCodeBody { inner: body })))
+        })
+    }
+}
+
+/// Response body copying the error code of its trailers
+struct ErrorCodeBody {
+    inner: BoxBody,
+}
+
+impl http_body::Body for ErrorCodeBody {
+    type Data = Bytes;
+    type Error = tonic::Status;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<http_body::Frame<Bytes>, tonic::Status>>> {
+        Pin::new(&mut self.inner)
+            .poll_frame(cx)
+            .map_ok(|mut frame| {
+                if let Some(trailers) = frame.trailers_mut() {
+                    copy_error_code(trailers);
+                }
+                frame
+            })
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// Copy a `grpc-status` outside gRPC's own codes to `openfga-error-code`
+fn copy_error_code(headers: &mut http::HeaderMap) {
+    let Some(code) = headers.get("grpc-status").cloned() else {
+        return;
+    };
+    let custom = code
+        .to_str()
+        .ok()
+        .and_then(|code| code.parse::<u32>().ok())
+        .is_some_and(|code| code > MAX_GRPC_CODE);
+    if custom {
+        headers.insert(ERROR_CODE_METADATA, code);
+    }
+}
+
+/// OpenFGA's error code of a status from a channel wrapped in [`ErrorCodeService`],
+/// e.g. 2027 for `invalid_tuple`; `None` for gRPC's own codes
+pub fn openfga_error_code(status: &tonic::Status) -> Option<i32> {
+    status
+        .metadata()
+        .get(ERROR_CODE_METADATA)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::BodyExt;
+    use std::convert::Infallible;
+
+    /// Service answering every call with the given headers and trailers
+    #[derive(Clone)]
+    struct StaticResponse {
+        headers: http::HeaderMap,
+        trailers: Option<http::HeaderMap>,
+    }
+
+    impl Service<http::Request<()>> for StaticResponse {
+        type Response = http::Response<BoxBody>;
+        type Error = Infallible;
+        type Future = std::future::Ready<Result<Self::Response, Infallible>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _: http::Request<()>) -> Self::Future {
+            let body = http_body_util::Empty::<Bytes>::new()
+                .with_trailers(std::future::ready(self.trailers.clone().map(Ok)))
+                .map_err(|never: Infallible| match never {});
+            let mut response = http::Response::new(BoxBody::new(body));
+            *response.headers_mut() = self.headers.clone();
+            std::future::ready(Ok(response))
+        }
+    }
+
+    fn grpc_status(code: &str) -> http::HeaderMap {
+        let mut headers = http::HeaderMap::new();
+        headers.insert("grpc-status", code.parse().unwrap());
+        headers.insert("grpc-message", "invalid tuple".parse().unwrap());
+        headers
+    }
+
+    #[tokio::test]
+    async fn test_error_code_from_headers() {
+        let mut service = ErrorCodeService::new(StaticResponse {
+            headers: grpc_status("2027"),
+            trailers: None,
+        });
+        let response = service.call(http::Request::new(())).await.unwrap();
+
+        let status = tonic::Status::from_header_map(response.headers()).unwrap();
+        assert_eq!(status.code(), tonic::Code::Unknown);
+        assert_eq!(openfga_error_code(&status), Some(2027));
+    }
+
+    #[tokio::test]
+    async fn test_error_code_from_trailers() {
+        let mut service = ErrorCodeService::new(StaticResponse {
+            headers: http::HeaderMap::new(),
+            trailers: Some(grpc_status("5002")),
+        });
+        let response = service.call(http::Request::new(())).await.unwrap();
+        let trailers = response
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .trailers()
+            .cloned();
+
+        let status = tonic::Status::from_header_map(&trailers.unwrap()).unwrap();
+        assert_eq!(openfga_error_code(&status), Some(5002));
+    }
+
+    #[tokio::test]
+    async fn test_grpc_codes_are_not_copied() {
+        let mut service = ErrorCodeService::new(StaticResponse {
+            headers: grpc_status("3"),
+            trailers: None,
+        });
+        let response = service.call(http::Request::new(())).await.unwrap();
+
+        let status = tonic::Status::from_header_map(response.headers()).unwrap();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert_eq!(openfga_error_code(&status), None);
+    }
+}
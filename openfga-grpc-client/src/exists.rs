@@ -0,0 +1,132 @@
+use crate::bundle::StoreBackend;
+use crate::{ConsistencyPreference, OpenFGAClient, ReadRequest, ReadRequestTupleKey, TupleKey};
+use std::collections::HashMap;
+use tokio::task::JoinSet;
+
+/// Reads in flight at once in [`OpenFGAClient::tuples_exist`]
+pub const DEFAULT_EXISTS_CONCURRENCY: usize = 8;
+
+/// User, relation and object of a tuple.
+///
+/// Used as the key of [`tuples_exist`] results instead of [`TupleKey`], which can't be
+/// hashed because of its condition context. A store holds at most one tuple per
+/// user, relation and object, so the condition isn't part of its identity.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TupleId {
+    pub user: String,
+    pub relation: String,
+    pub object: String,
+}
+
+impl From<&TupleKey> for TupleId {
+    fn from(key: &TupleKey) -> Self {
+        Self {
+            user: key.user.clone(),
+            relation: key.relation.clone(),
+            object: key.object.clone(),
+        }
+    }
+}
+
+impl OpenFGAClient {
+    /// Whether each tuple is stored, regardless of what the model derives from it.
+    ///
+    /// Unlike a check, this reads the relationship storage directly, for reconciliation
+    /// jobs. Issues one read per tuple, [`DEFAULT_EXISTS_CONCURRENCY`] at a time.
+    pub async fn tuples_exist(
+        &self,
+        store_id: &str,
+        keys: &[TupleKey],
+    ) -> Result<HashMap<TupleId, bool>, tonic::Status> {
+        tuples_exist(self, store_id, keys, DEFAULT_EXISTS_CONCURRENCY).await
+    }
+}
+
+/// Read each fully-specified tuple, with at most `concurrency` reads in flight, and
+/// report whether it exists. Fails with the first error returned by a read.
+pub async fn tuples_exist<B>(
+    client: &B,
+    store_id: &str,
+    keys: &[TupleKey],
+    concurrency: usize,
+) -> Result<HashMap<TupleId, bool>, tonic::Status>
+where
+    B: StoreBackend + Clone + Send + 'static,
+{
+    let mut exists = HashMap::with_capacity(keys.len());
+    let mut reads = JoinSet::new();
+
+    for key in keys {
+        if reads.len() >= concurrency.max(1) {
+            let (id, found) = join_read(&mut reads).await?;
+            exists.insert(id, found);
+        }
+
+        let mut client = client.clone();
+        let id = TupleId::from(key);
+        let request = ReadRequest {
+            store_id: store_id.to_string(),
+            tuple_key: Some(ReadRequestTupleKey {
+                user: key.user.clone(),
+                relation: key.relation.clone(),
+                object: key.object.clone(),
+            }),
+            page_size: Some(1),
+            continuation_token: String::new(),
+            consistency: ConsistencyPreference::HigherConsistency as i32,
+        };
+        reads.spawn(async move {
+            let response = client.read(request).await?.into_inner();
+            Ok((id, !response.tuples.is_empty()))
+        });
+    }
+
+    while !reads.is_empty() {
+        let (id, found) = join_read(&mut reads).await?;
+        exists.insert(id, found);
+    }
+
+    Ok(exists)
+}
+
+async fn join_read(
+    reads: &mut JoinSet<Result<(TupleId, bool), tonic::Status>>,
+) -> Result<(TupleId, bool), tonic::Status> {
+    match reads.join_next().await {
+        Some(Ok(result)) => result,
+        Some(Err(e)) => Err(tonic::Status::internal(format!("read task failed: {}", e))),
+        None => unreachable!("join_read called without reads in flight"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MemoryStore, tuple};
+
+    #[tokio::test]
+    async fn test_tuples_exist() {
+        let anne = tuple("user:anne", "owner", "resource:roadmap");
+        let eng = tuple("group:engineering#member", "viewer", "resource:roadmap");
+        let bob = tuple("user:bob", "owner", "resource:roadmap");
+        let client = MemoryStore::with_tuples(vec![anne.clone(), eng.clone()]);
+
+        let exists = tuples_exist(
+            &client,
+            "store",
+            &[anne.clone(), eng.clone(), bob.clone()],
+            2,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            exists,
+            HashMap::from([
+                (TupleId::from(&anne), true),
+                (TupleId::from(&eng), true),
+                (TupleId::from(&bob), false),
+            ])
+        );
+        assert_eq!(client.state().reads.len(), 3);
+    }
+}
@@ -0,0 +1,218 @@
+use crate::ExpandResponse;
+use crate::userset_tree::{Leaf, Node, leaf, node};
+use std::collections::BTreeSet;
+
+/// Tuple-to-userset leaf of an [`ExpandTree`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TupleToUsersetLeaf {
+    /// Userset of the tupleset relation, e.g. `document:readme#parent`
+    pub tupleset: String,
+    /// Usersets the objects of the tupleset are rewritten to, e.g. `folder:docs#viewer`
+    pub computed: Vec<String>,
+}
+
+/// Typed form of the [`UsersetTree`](crate::UsersetTree) returned by expand.
+///
+/// A leaf holds one of its three kinds, like the generated leaf; the others are
+/// empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpandTree {
+    Leaf {
+        /// Users and usersets related directly, e.g. `user:anne` or `group:eng#member`
+        users: Vec<String>,
+        /// Userset the relation is computed from, e.g. `document:readme#owner`
+        computed: Option<String>,
+        tuple_to_userset: Option<TupleToUsersetLeaf>,
+    },
+    Union(Vec<ExpandTree>),
+    Intersection(Vec<ExpandTree>),
+    Difference {
+        base: Box<ExpandTree>,
+        subtract: Box<ExpandTree>,
+    },
+}
+
+impl ExpandTree {
+    /// Tree of an expand response, `None` when the response has no tree
+    pub fn from_response(response: &ExpandResponse) -> Option<Self> {
+        response
+            .tree
+            .as_ref()?
+            .root
+            .as_ref()
+            .and_then(Self::from_node)
+    }
+
+    /// Tree of a generated node. Nodes without a value, and differences missing a
+    /// side, yield `None`; such children are left out of unions and intersections.
+    pub fn from_node(node: &Node) -> Option<Self> {
+        match node.value.as_ref()? {
+            node::Value::Leaf(Leaf { value }) => Some(match value.as_ref()? {
+                leaf::Value::Users(users) => ExpandTree::Leaf {
+                    users: users.users.clone(),
+                    computed: None,
+                    tuple_to_userset: None,
+                },
+                leaf::Value::Computed(computed) => ExpandTree::Leaf {
+                    users: Vec::new(),
+                    computed: Some(computed.userset.clone()),
+                    tuple_to_userset: None,
+                },
+                leaf::Value::TupleToUserset(ttu) => ExpandTree::Leaf {
+                    users: Vec::new(),
+                    computed: None,
+                    tuple_to_userset: Some(TupleToUsersetLeaf {
+                        tupleset: ttu.tupleset.clone(),
+                        computed: ttu.computed.iter().map(|c| c.userset.clone()).collect(),
+                    }),
+                },
+            }),
+            node::Value::Union(nodes) => Some(ExpandTree::Union(
+                nodes.nodes.iter().filter_map(Self::from_node).collect(),
+            )),
+            node::Value::Intersection(nodes) => Some(ExpandTree::Intersection(
+                nodes.nodes.iter().filter_map(Self::from_node).collect(),
+            )),
+            node::Value::Difference(difference) => Some(ExpandTree::Difference {
+                base: Box::new(Self::from_node(difference.base.as_deref()?)?),
+                subtract: Box::new(Self::from_node(difference.subtract.as_deref()?)?),
+            }),
+        }
+    }
+
+    /// Users and usersets named in the tree's leaves, deduplicated and sorted, since
+    /// expand returns no stable order and can reach a user through several paths.
+    ///
+    /// Subtracted sides of differences are left out. Intersections aren't evaluated,
+    /// so their users are candidates rather than grants.
+    pub fn flatten_users(&self) -> BTreeSet<String> {
+        let mut users = BTreeSet::new();
+        self.collect_users(&mut users);
+        users
+    }
+
+    fn collect_users(&self, users: &mut BTreeSet<String>) {
+        match self {
+            ExpandTree::Leaf { users: leaf, .. } => users.extend(leaf.iter().cloned()),
+            ExpandTree::Union(trees) | ExpandTree::Intersection(trees) => {
+                for tree in trees {
+                    tree.collect_users(users);
+                }
+            }
+            ExpandTree::Difference { base, .. } => base.collect_users(users),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UsersetTree;
+    use crate::userset_tree::{Computed, Difference, Nodes, TupleToUserset, Users};
+
+    fn leaf(value: leaf::Value) -> Node {
+        Node {
+            name: "document:readme#viewer".to_string(),
+            value: Some(node::Value::Leaf(Leaf { value: Some(value) })),
+        }
+    }
+
+    #[test]
+    fn test_from_response() {
+        // viewer: [user] or owner or viewer from parent, but not blocked
+        let union = Node {
+            name: "document:readme#viewer".to_string(),
+            value: Some(node::Value::Union(Nodes {
+                nodes: vec![
+                    leaf(leaf::Value::Users(Users {
+                        users: vec!["user:anne".to_string()],
+                    })),
+                    leaf(leaf::Value::Computed(Computed {
+                        userset: "document:readme#owner".to_string(),
+                    })),
+                    leaf(leaf::Value::TupleToUserset(TupleToUserset {
+                        tupleset: "document:readme#parent".to_string(),
+                        computed: vec![Computed {
+                            userset: "folder:docs#viewer".to_string(),
+                        }],
+                    })),
+                ],
+            })),
+        };
+        let response = ExpandResponse {
+            tree: Some(UsersetTree {
+                root: Some(Node {
+                    name: "document:readme#viewer".to_string(),
+                    value: Some(node::Value::Difference(Box::new(Difference {
+                        base: Some(Box::new(union)),
+                        subtract: Some(Box::new(leaf(leaf::Value::Computed(Computed {
+                            userset: "document:readme#blocked".to_string(),
+                        })))),
+                    }))),
+                }),
+            }),
+        };
+
+        let tree = ExpandTree::from_response(&response).unwrap();
+        let ExpandTree::Difference { base, subtract } = tree else {
+            panic!("expected a difference, got {:?}", tree);
+        };
+        assert_eq!(
+            *subtract,
+            ExpandTree::Leaf {
+                users: Vec::new(),
+                computed: Some("document:readme#blocked".to_string()),
+                tuple_to_userset: None,
+            }
+        );
+        assert_eq!(
+            *base,
+            ExpandTree::Union(vec![
+                ExpandTree::Leaf {
+                    users: vec!["user:anne".to_string()],
+                    computed: None,
+                    tuple_to_userset: None,
+                },
+                ExpandTree::Leaf {
+                    users: Vec::new(),
+                    computed: Some("document:readme#owner".to_string()),
+                    tuple_to_userset: None,
+                },
+                ExpandTree::Leaf {
+                    users: Vec::new(),
+                    computed: None,
+                    tuple_to_userset: Some(TupleToUsersetLeaf {
+                        tupleset: "document:readme#parent".to_string(),
+                        computed: vec!["folder:docs#viewer".to_string()],
+                    }),
+                },
+            ])
+        );
+
+        assert_eq!(
+            ExpandTree::from_response(&ExpandResponse { tree: None }),
+            None
+        );
+    }
+
+    #[test]
+    fn test_flatten_users_dedupes_and_sorts() {
+        let users = |users: &[&str]| ExpandTree::Leaf {
+            users: users.iter().map(|u| u.to_string()).collect(),
+            computed: None,
+            tuple_to_userset: None,
+        };
+        let tree = ExpandTree::Difference {
+            base: Box::new(ExpandTree::Union(vec![
+                users(&["user:carl", "user:alice"]),
+                users(&["user:alice", "group:eng#member"]),
+            ])),
+            subtract: Box::new(users(&["user:bob"])),
+        };
+
+        assert_eq!(
+            tree.flatten_users().into_iter().collect::<Vec<_>>(),
+            vec!["group:eng#member", "user:alice", "user:carl"]
+        );
+    }
+}
@@ -0,0 +1,253 @@
+use crate::userset_tree::{Leaf, Node, leaf, node};
+use crate::{
+    CheckRequest, CheckRequestTupleKey, ExpandRequest, ExpandRequestTupleKey, OpenFGAClient,
+    UsersetTree,
+};
+use serde::Serialize;
+
+/// Reason given for a denied check
+pub const NO_PATH_FOUND: &str = "no path found";
+
+/// Result of [`OpenFGAClient::check_detailed`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckDetail {
+    pub allowed: bool,
+    /// How the relation holds, or [`NO_PATH_FOUND`] when denied. `None` when allowed
+    /// but no path of the expanded tree could be confirmed.
+    pub reason: Option<String>,
+    /// Relation of the checked object the satisfying path goes through, e.g. `owner`
+    /// for a `viewer` check allowed because the user owns the object
+    pub via_relation: Option<String>,
+}
+
+/// A way the user may hold the expanded relation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CandidatePath {
+    pub via_relation: String,
+    pub reason: String,
+    /// `object#relation` usersets the user must belong to for the path to hold;
+    /// empty when the tree lists the user directly
+    pub requires: Vec<String>,
+}
+
+/// Candidate paths for `user` in an expanded tree, in the tree's order.
+///
+/// Expand resolves a single level, so computed and tuple-to-userset leaves and
+/// userset members become paths requiring further checks. Intersections and
+/// exclusions are reported as a whole.
+pub fn candidate_paths(tree: &UsersetTree, user: &str) -> Vec<CandidatePath> {
+    let mut paths = Vec::new();
+    if let Some(root) = &tree.root {
+        collect_paths(root, user, &mut paths);
+    }
+    paths
+}
+
+fn collect_paths(node: &Node, user: &str, paths: &mut Vec<CandidatePath>) {
+    let via_relation = relation_of(&node.name);
+    let path = |reason: String, requires: Vec<String>| CandidatePath {
+        via_relation: via_relation.clone(),
+        reason,
+        requires,
+    };
+
+    match &node.value {
+        Some(node::Value::Leaf(Leaf { value: Some(value) })) => match value {
+            leaf::Value::Users(users) => {
+                let wildcard = user.split_once(':').map(|(t, _)| format!("{}:*", t));
+                for member in &users.users {
+                    if member == user {
+                        paths.push(path("direct relationship".to_string(), Vec::new()));
+                    } else if Some(member) == wildcard.as_ref() {
+                        paths.push(path(format!("public access ({})", member), Vec::new()));
+                    } else if member.contains('#') {
+                        paths.push(path(format!("member of {}", member), vec![member.clone()]));
+                    }
+                }
+            }
+            leaf::Value::Computed(computed) => {
+                // The relation the computed userset rewrites to is the one the path goes through
+                paths.push(CandidatePath {
+                    via_relation: relation_of(&computed.userset),
+                    reason: format!("computed userset {}", computed.userset),
+                    requires: vec![computed.userset.clone()],
+                });
+            }
+            leaf::Value::TupleToUserset(ttu) => {
+                for computed in &ttu.computed {
+                    paths.push(CandidatePath {
+                        via_relation: relation_of(&ttu.tupleset),
+                        reason: format!("{} through {}", computed.userset, ttu.tupleset),
+                        requires: vec![computed.userset.clone()],
+                    });
+                }
+            }
+        },
+        Some(node::Value::Leaf(Leaf { value: None })) | None => {}
+        Some(node::Value::Union(nodes)) => {
+            for child in &nodes.nodes {
+                collect_paths(child, user, paths);
+            }
+        }
+        Some(node::Value::Intersection(_)) => {
+            paths.push(path(format!("intersection of {}", node.name), Vec::new()));
+        }
+        Some(node::Value::Difference(_)) => {
+            paths.push(path(format!("exclusion in {}", node.name), Vec::new()));
+        }
+    }
+}
+
+/// `viewer` of `document:readme#viewer`
+fn relation_of(userset: &str) -> String {
+    userset
+        .rsplit_once('#')
+        .map(|(_, relation)| relation.to_string())
+        .unwrap_or_default()
+}
+
+/// First path whose required usersets all contain the user, according to `check`
+pub async fn first_satisfying_path<F>(
+    paths: Vec<CandidatePath>,
+    mut check: F,
+) -> Result<Option<CandidatePath>, tonic::Status>
+where
+    F: AsyncFnMut(&str) -> Result<bool, tonic::Status>,
+{
+    'paths: for path in paths {
+        for userset in &path.requires {
+            if !check(userset).await? {
+                continue 'paths;
+            }
+        }
+        return Ok(Some(path));
+    }
+    Ok(None)
+}
+
+impl OpenFGAClient {
+    /// Check a relation and explain the result.
+    ///
+    /// When allowed, the relation is expanded and the first path of the tree the user
+    /// satisfies is reported, confirming computed and userset paths with further
+    /// checks. Those checks use the request's store, model, context and contextual
+    /// tuples.
+    pub async fn check_detailed(
+        &mut self,
+        request: CheckRequest,
+    ) -> Result<CheckDetail, tonic::Status> {
+        let tuple_key = request
+            .tuple_key
+            .clone()
+            .ok_or_else(|| tonic::Status::invalid_argument("check request without tuple key"))?;
+
+        if !self.check(request.clone()).await?.into_inner().allowed {
+            return Ok(CheckDetail {
+                allowed: false,
+                reason: Some(NO_PATH_FOUND.to_string()),
+                via_relation: None,
+            });
+        }
+
+        let tree = self
+            .expand(ExpandRequest {
+                store_id: request.store_id.clone(),
+                tuple_key: Some(ExpandRequestTupleKey {
+                    relation: tuple_key.relation.clone(),
+                    object: tuple_key.object.clone(),
+                }),
+                authorization_model_id: request.authorization_model_id.clone(),
+                consistency: request.consistency,
+                contextual_tuples: request.contextual_tuples.clone(),
+            })
+            .await?
+            .into_inner()
+            .tree
+            .unwrap_or_default();
+
+        let paths = candidate_paths(&tree, &tuple_key.user);
+        let path = first_satisfying_path(paths, async |userset: &str| {
+            let Some((object, relation)) = userset.rsplit_once('#') else {
+                return Ok(false);
+            };
+            let check = CheckRequest {
+                tuple_key: Some(CheckRequestTupleKey {
+                    user: tuple_key.user.clone(),
+                    relation: relation.to_string(),
+                    object: object.to_string(),
+                }),
+                ..request.clone()
+            };
+            Ok(self.check(check).await?.into_inner().allowed)
+        })
+        .await?;
+
+        Ok(CheckDetail {
+            allowed: true,
+            reason: path.as_ref().map(|p| p.reason.clone()),
+            via_relation: path.map(|p| p.via_relation),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::userset_tree::{Computed, Nodes, Users};
+
+    fn leaf(name: &str, value: leaf::Value) -> Node {
+        Node {
+            name: name.to_string(),
+            value: Some(node::Value::Leaf(Leaf { value: Some(value) })),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_allowed_via_owner() {
+        // viewer: [user, group#member] or owner
+        let tree = UsersetTree {
+            root: Some(Node {
+                name: "document:readme#viewer".to_string(),
+                value: Some(node::Value::Union(Nodes {
+                    nodes: vec![
+                        leaf(
+                            "document:readme#viewer",
+                            leaf::Value::Users(Users {
+                                users: vec!["user:bob".to_string(), "group:eng#member".to_string()],
+                            }),
+                        ),
+                        leaf(
+                            "document:readme#viewer",
+                            leaf::Value::Computed(Computed {
+                                userset: "document:readme#owner".to_string(),
+                            }),
+                        ),
+                    ],
+                })),
+            }),
+        };
+
+        let paths = candidate_paths(&tree, "user:anne");
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].requires, vec!["group:eng#member"]);
+
+        let mut checked = Vec::new();
+        let path = first_satisfying_path(paths, async |userset: &str| {
+            checked.push(userset.to_string());
+            Ok(userset == "document:readme#owner")
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(checked, vec!["group:eng#member", "document:readme#owner"]);
+        assert_eq!(path.via_relation, "owner");
+        assert_eq!(path.reason, "computed userset document:readme#owner");
+
+        // A user listed in the tree needs no further check
+        let paths = candidate_paths(&tree, "user:bob");
+        assert_eq!(paths[0].reason, "direct relationship");
+        assert_eq!(paths[0].via_relation, "viewer");
+        assert!(paths[0].requires.is_empty());
+    }
+}
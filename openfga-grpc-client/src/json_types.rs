@@ -1,8 +1,9 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// JSON representation of an authorization model from OpenFGA playground
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct JsonAuthModel {
     pub schema_version: String,
     pub type_definitions: Vec<JsonTypeDefinition>,
@@ -12,17 +13,61 @@ pub struct JsonAuthModel {
 
 /// JSON representation of a type definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct JsonTypeDefinition {
     #[serde(rename = "type")]
     pub type_name: String,
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    /// Parsing fails if a relation is defined twice
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        deserialize_with = "unique_relations"
+    )]
+    #[cfg_attr(feature = "schema", schemars(with = "HashMap<String, JsonUserset>"))]
     pub relations: HashMap<String, JsonUserset>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<JsonMetadata>,
 }
 
+/// Deserialize a relations object, rejecting a relation name that appears twice
+/// rather than keeping its last definition
+fn unique_relations<'de, D>(deserializer: D) -> Result<HashMap<String, JsonUserset>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct RelationsVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for RelationsVisitor {
+        type Value = HashMap<String, JsonUserset>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("a map of relation names to usersets")
+        }
+
+        fn visit_map<A: serde::de::MapAccess<'de>>(
+            self,
+            mut map: A,
+        ) -> Result<Self::Value, A::Error> {
+            let mut relations = HashMap::new();
+            while let Some((name, userset)) = map.next_entry::<String, JsonUserset>()? {
+                if relations.contains_key(&name) {
+                    return Err(serde::de::Error::custom(format!(
+                        "duplicate relation: {}",
+                        name
+                    )));
+                }
+                relations.insert(name, userset);
+            }
+            Ok(relations)
+        }
+    }
+
+    deserializer.deserialize_map(RelationsVisitor)
+}
+
 /// JSON representation of metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct JsonMetadata {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub relations: Option<HashMap<String, JsonRelationMetadata>>,
@@ -34,6 +79,7 @@ pub struct JsonMetadata {
 
 /// JSON representation of relation metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct JsonRelationMetadata {
     #[serde(default)]
     pub directly_related_user_types: Vec<JsonDirectlyRelatedUserType>,
@@ -44,16 +90,26 @@ pub struct JsonRelationMetadata {
 }
 
 /// JSON representation of directly related user type
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct JsonDirectlyRelatedUserType {
     #[serde(rename = "type")]
     pub type_name: String,
     pub relation: Option<String>,
+    /// `{}` for the type's wildcard, e.g. `user:*`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wildcard: Option<JsonWildcard>,
     pub condition: Option<String>,
 }
 
+/// JSON representation of a wildcard, always `{}`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct JsonWildcard {}
+
 /// JSON representation of a userset - matches exactly what comes from OpenFGA playground
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct JsonUserset {
     // Direct userset
     #[serde(rename = "this")]
@@ -79,10 +135,12 @@ pub struct JsonUserset {
 
 /// Direct userset - just an empty object
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct JsonDirectUserset {}
 
 /// Computed userset
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct JsonComputedUserset {
     #[serde(default)]
     pub object: String,
@@ -91,6 +149,7 @@ pub struct JsonComputedUserset {
 
 /// Tuple to userset
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct JsonTupleToUserset {
     pub tupleset: JsonObjectRelation,
     #[serde(rename = "computedUserset")]
@@ -99,6 +158,7 @@ pub struct JsonTupleToUserset {
 
 /// Object relation
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct JsonObjectRelation {
     #[serde(default)]
     pub object: String,
@@ -107,26 +167,58 @@ pub struct JsonObjectRelation {
 
 /// Union of usersets
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct JsonUnion {
     pub child: Vec<JsonUserset>,
 }
 
 /// Intersection of usersets
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct JsonIntersection {
     pub child: Vec<JsonUserset>,
 }
 
 /// Difference of usersets
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct JsonDifference {
     pub base: Box<JsonUserset>,
     pub subtract: Box<JsonUserset>,
 }
 
+/// An ambiguous input the conversion resolved by guessing, e.g. an empty relation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConversionWarning {
+    /// Where the input is, e.g. `document#viewer`
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ConversionWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+fn log_warnings(warnings: &[ConversionWarning]) {
+    for warning in warnings {
+        tracing::warn!("Model conversion: {}", warning);
+    }
+}
+
+/// A converted model with the warnings raised while converting it
+#[derive(Debug, Clone)]
+pub struct ConvertedModel {
+    pub type_definitions: Vec<crate::TypeDefinition>,
+    pub schema_version: String,
+    pub conditions: HashMap<String, crate::Condition>,
+    pub warnings: Vec<ConversionWarning>,
+}
+
 // Conversion functions to OpenFGA generated types
 impl JsonAuthModel {
-    /// Convert to OpenFGA generated types
+    /// Convert to OpenFGA generated types, logging any conversion warnings
     pub fn to_openfga_types(
         self,
     ) -> Result<
@@ -137,22 +229,95 @@ impl JsonAuthModel {
         ),
         String,
     > {
+        let converted = self.to_openfga_types_with_warnings()?;
+        log_warnings(&converted.warnings);
+
+        Ok((
+            converted.type_definitions,
+            converted.schema_version,
+            converted.conditions,
+        ))
+    }
+
+    /// Convert to OpenFGA generated types, returning the conversion warnings to the caller.
+    /// Fails if two type definitions share a name.
+    pub fn to_openfga_types_with_warnings(self) -> Result<ConvertedModel, String> {
         let mut type_definitions = Vec::new();
+        let mut warnings = Vec::new();
+        let mut type_names = HashSet::new();
 
         for json_type_def in self.type_definitions {
-            type_definitions.push(json_type_def.to_openfga_type()?);
+            if !type_names.insert(json_type_def.type_name.clone()) {
+                return Err(format!(
+                    "duplicate type definition: {}",
+                    json_type_def.type_name
+                ));
+            }
+            type_definitions.push(json_type_def.convert(&mut warnings)?);
         }
 
         // For now, return empty conditions - can be enhanced later
         let conditions = HashMap::new();
 
-        Ok((type_definitions, self.schema_version, conditions))
+        Ok(ConvertedModel {
+            type_definitions,
+            schema_version: self.schema_version,
+            conditions,
+            warnings,
+        })
     }
 }
 
+/// Sorted names of the relations defined on a type, or `None` if the model has no
+/// such type
+pub fn relations_for_type(model: &JsonAuthModel, type_name: &str) -> Option<Vec<String>> {
+    let type_def = model
+        .type_definitions
+        .iter()
+        .find(|t| t.type_name == type_name)?;
+
+    let mut relations: Vec<String> = type_def.relations.keys().cloned().collect();
+    relations.sort();
+    Some(relations)
+}
+
+/// User types that can be assigned to a relation directly; empty for unknown types
+/// and relations, or relations that are only computed
+pub fn user_types_for_relation(
+    model: &JsonAuthModel,
+    type_name: &str,
+    relation: &str,
+) -> Vec<JsonDirectlyRelatedUserType> {
+    model
+        .type_definitions
+        .iter()
+        .find(|t| t.type_name == type_name)
+        .and_then(|t| t.metadata.as_ref())
+        .and_then(|m| m.relations.as_ref())
+        .and_then(|relations| relations.get(relation))
+        .map(|m| m.directly_related_user_types.clone())
+        .unwrap_or_default()
+}
+
+/// JSON Schema of [`JsonAuthModel`], for validating models before submitting them
+#[cfg(feature = "schema")]
+pub fn model_json_schema() -> serde_json::Value {
+    schemars::schema_for!(JsonAuthModel).to_value()
+}
+
 impl JsonTypeDefinition {
     /// Convert to OpenFGA TypeDefinition
     pub fn to_openfga_type(self) -> Result<crate::TypeDefinition, String> {
+        let mut warnings = Vec::new();
+        let type_def = self.convert(&mut warnings)?;
+        log_warnings(&warnings);
+        Ok(type_def)
+    }
+
+    fn convert(
+        self,
+        warnings: &mut Vec<ConversionWarning>,
+    ) -> Result<crate::TypeDefinition, String> {
         let mut relations = HashMap::new();
 
         for (relation_name, json_userset) in self.relations {
@@ -161,7 +326,7 @@ impl JsonTypeDefinition {
 
         // Convert metadata if present
         let metadata = if let Some(json_metadata) = self.metadata {
-            Some(json_metadata.to_openfga_metadata()?)
+            Some(json_metadata.convert(&self.type_name, warnings)?)
         } else {
             None
         };
@@ -241,37 +406,71 @@ impl JsonUserset {
 impl JsonMetadata {
     /// Convert to OpenFGA Metadata
     pub fn to_openfga_metadata(self) -> Result<crate::Metadata, String> {
+        let mut warnings = Vec::new();
+        let metadata = self.convert("", &mut warnings)?;
+        log_warnings(&warnings);
+        Ok(metadata)
+    }
+
+    fn convert(
+        self,
+        type_name: &str,
+        warnings: &mut Vec<ConversionWarning>,
+    ) -> Result<crate::Metadata, String> {
         let mut relations = HashMap::new();
 
         if let Some(json_relations) = self.relations {
             for (relation_name, json_relation_metadata) in json_relations {
+                let path = format!("{}#{}", type_name, relation_name);
                 relations.insert(
                     relation_name,
-                    json_relation_metadata.to_openfga_relation_metadata()?,
+                    json_relation_metadata.convert(&path, warnings)?,
                 );
             }
         }
 
         Ok(crate::Metadata {
             relations,
-            module: self.module.unwrap_or_default(),
+            module: module_from_json(self.module),
             source_info: None, // We can implement source_info conversion later if needed
         })
     }
 }
 
+/// Module name for the protobuf metadata. Protobuf can't tell an empty string from
+/// an absent one, so both an absent and an empty JSON module become `""`.
+pub(crate) fn module_from_json(module: Option<String>) -> String {
+    module.unwrap_or_default()
+}
+
+/// Module name for the JSON metadata; `None` when the model isn't modular
+pub(crate) fn module_to_json(module: &str) -> Option<String> {
+    (!module.is_empty()).then(|| module.to_string())
+}
+
 impl JsonRelationMetadata {
     /// Convert to OpenFGA RelationMetadata
     pub fn to_openfga_relation_metadata(self) -> Result<crate::RelationMetadata, String> {
+        let mut warnings = Vec::new();
+        let relation_metadata = self.convert("", &mut warnings)?;
+        log_warnings(&warnings);
+        Ok(relation_metadata)
+    }
+
+    fn convert(
+        self,
+        path: &str,
+        warnings: &mut Vec<ConversionWarning>,
+    ) -> Result<crate::RelationMetadata, String> {
         let mut directly_related_user_types = Vec::new();
 
         for json_user_type in self.directly_related_user_types {
-            directly_related_user_types.push(json_user_type.to_openfga_relation_reference()?);
+            directly_related_user_types.push(json_user_type.convert(path, warnings)?);
         }
 
         Ok(crate::RelationMetadata {
             directly_related_user_types,
-            module: self.module.unwrap_or_default(),
+            module: module_from_json(self.module),
             source_info: None, // We can implement source_info conversion later if needed
         })
     }
@@ -280,24 +479,62 @@ impl JsonRelationMetadata {
 impl JsonDirectlyRelatedUserType {
     /// Convert to OpenFGA RelationReference
     pub fn to_openfga_relation_reference(self) -> Result<crate::RelationReference, String> {
+        let mut warnings = Vec::new();
+        let reference = self.convert("", &mut warnings)?;
+        log_warnings(&warnings);
+        Ok(reference)
+    }
+
+    fn convert(
+        self,
+        path: &str,
+        warnings: &mut Vec<ConversionWarning>,
+    ) -> Result<crate::RelationReference, String> {
         use crate::{RelationReference, relation_reference};
 
         // Debug log the input
         tracing::debug!(
-            "Converting relation reference: type={}, relation={:?}, condition={:?}",
+            "Converting relation reference: type={}, relation={:?}, wildcard={}, condition={:?}",
             self.type_name,
             self.relation,
+            self.wildcard.is_some(),
             self.condition
         );
 
+        if self.wildcard.is_some() {
+            if let Some(relation) = self.relation.filter(|r| !r.is_empty()) {
+                return Err(format!(
+                    "user type {}#{} can't also be a wildcard",
+                    self.type_name, relation
+                ));
+            }
+            return Ok(RelationReference {
+                r#type: self.type_name,
+                condition: self.condition.unwrap_or_default(),
+                relation_or_wildcard: Some(relation_reference::RelationOrWildcard::Wildcard(
+                    crate::Wildcard {},
+                )),
+            });
+        }
+
         let relation_or_wildcard = match self.relation {
             Some(relation) if !relation.is_empty() => {
                 // Specific relation like "group#member"
                 tracing::debug!("Using specific relation: {}", relation);
                 Some(relation_reference::RelationOrWildcard::Relation(relation))
             }
-            Some(_) | None => {
-                // Empty string or None means wildcard (any instance of the type)
+            Some(_) => {
+                warnings.push(ConversionWarning {
+                    path: path.to_string(),
+                    message: format!(
+                        "relation '' on type {} interpreted as direct assignment; use explicit wildcard for {}:*",
+                        self.type_name, self.type_name
+                    ),
+                });
+                None
+            }
+            None => {
+                // None means a direct assignment of any instance of the type
                 // Try setting to None instead of Wildcard to see if that fixes the issue
                 tracing::debug!(
                     "Using None (no relation specified) for type: {}",
@@ -328,6 +565,59 @@ impl JsonDirectlyRelatedUserType {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_relations_for_type() {
+        let model: JsonAuthModel =
+            serde_json::from_str(include_str!("../../etc/fga/auth-model-example.json")).unwrap();
+
+        // The example model names its document type `resource`
+        assert_eq!(
+            relations_for_type(&model, "resource").unwrap(),
+            vec!["admin", "editor", "owner", "parent_org", "viewer"]
+        );
+        assert_eq!(
+            relations_for_type(&model, "user").unwrap(),
+            Vec::<String>::new()
+        );
+        assert_eq!(relations_for_type(&model, "document"), None);
+    }
+
+    #[test]
+    fn test_user_types_for_relation() {
+        let model: JsonAuthModel =
+            serde_json::from_str(include_str!("../../etc/fga/auth-model-example.json")).unwrap();
+
+        let user_type = |type_name: &str, relation: Option<&str>| JsonDirectlyRelatedUserType {
+            type_name: type_name.to_string(),
+            relation: relation.map(str::to_string),
+            wildcard: None,
+            condition: None,
+        };
+        assert_eq!(
+            user_types_for_relation(&model, "resource", "editor"),
+            vec![
+                user_type("user", None),
+                user_type("group", Some("member")),
+                user_type("organisation", Some("member")),
+            ]
+        );
+        assert!(user_types_for_relation(&model, "organisation", "descendant_member").is_empty());
+        assert!(user_types_for_relation(&model, "resource", "unknown").is_empty());
+    }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn test_model_json_schema() {
+        let validator = jsonschema::validator_for(&model_json_schema()).unwrap();
+
+        let mut model: serde_json::Value =
+            serde_json::from_str(include_str!("../../etc/fga/auth-model-example.json")).unwrap();
+        assert!(validator.is_valid(&model));
+
+        model.as_object_mut().unwrap().remove("schema_version");
+        assert!(!validator.is_valid(&model));
+    }
+
     #[test]
     fn test_parse_simple_this_relation() {
         let json = r#"{"this": {}}"#;
@@ -356,6 +646,110 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_empty_relation_produces_warning() {
+        let json = r#"{
+            "schema_version": "1.1",
+            "type_definitions": [
+                { "type": "user" },
+                {
+                    "type": "document",
+                    "relations": { "viewer": { "this": {} } },
+                    "metadata": {
+                        "relations": {
+                            "viewer": {
+                                "directly_related_user_types": [
+                                    { "type": "user", "relation": "" }
+                                ]
+                            }
+                        }
+                    }
+                }
+            ]
+        }"#;
+        let model: JsonAuthModel = serde_json::from_str(json).unwrap();
+
+        let converted = model.to_openfga_types_with_warnings().unwrap();
+
+        assert_eq!(
+            converted.warnings,
+            vec![ConversionWarning {
+                path: "document#viewer".to_string(),
+                message: "relation '' on type user interpreted as direct assignment; \
+                          use explicit wildcard for user:*"
+                    .to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_wildcard_user_type_conversion() {
+        let json = r#"[{ "type": "user", "wildcard": {} }, { "type": "user" }]"#;
+        let user_types: Vec<JsonDirectlyRelatedUserType> = serde_json::from_str(json).unwrap();
+
+        let wildcard = user_types[0]
+            .clone()
+            .to_openfga_relation_reference()
+            .unwrap();
+        assert_eq!(
+            wildcard.relation_or_wildcard,
+            Some(crate::relation_reference::RelationOrWildcard::Wildcard(
+                crate::Wildcard {}
+            ))
+        );
+        let direct = user_types[1]
+            .clone()
+            .to_openfga_relation_reference()
+            .unwrap();
+        assert_eq!(direct.relation_or_wildcard, None);
+
+        // Only wildcards are written back with the field
+        assert_eq!(
+            serde_json::to_value(&user_types[0]).unwrap()["wildcard"],
+            serde_json::json!({})
+        );
+        assert!(
+            serde_json::to_value(&user_types[1])
+                .unwrap()
+                .get("wildcard")
+                .is_none()
+        );
+
+        let userset_wildcard: JsonDirectlyRelatedUserType =
+            serde_json::from_str(r#"{ "type": "group", "relation": "member", "wildcard": {} }"#)
+                .unwrap();
+        assert!(userset_wildcard.to_openfga_relation_reference().is_err());
+    }
+
+    #[test]
+    fn test_duplicate_type_definitions_are_rejected() {
+        let json = r#"{
+            "schema_version": "1.1",
+            "type_definitions": [
+                { "type": "user" },
+                { "type": "document", "relations": { "viewer": { "this": {} } } },
+                { "type": "document", "relations": { "editor": { "this": {} } } }
+            ]
+        }"#;
+        let model: JsonAuthModel = serde_json::from_str(json).unwrap();
+
+        let error = model.to_openfga_types().unwrap_err();
+
+        assert_eq!(error, "duplicate type definition: document");
+    }
+
+    #[test]
+    fn test_duplicate_relations_are_rejected() {
+        let json = r#"{
+            "type": "document",
+            "relations": { "viewer": { "this": {} }, "viewer": { "this": {} } }
+        }"#;
+
+        let error = serde_json::from_str::<JsonTypeDefinition>(json).unwrap_err();
+
+        assert!(error.to_string().contains("duplicate relation: viewer"));
+    }
+
     #[test]
     fn test_parse_union() {
         let json = r#"{"union": {"child": [{"this": {}}, {"computedUserset": {"object": "", "relation": "owner"}}]}}"#;
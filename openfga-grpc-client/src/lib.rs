@@ -1,162 +1,515 @@
+pub mod access_diff;
+pub mod access_summary;
+pub mod assertions;
+pub mod batch_stream;
+pub mod bundle;
+pub mod check_cache;
+pub mod check_request;
+pub mod compact_tuple;
+pub mod conditions;
+pub mod conflict;
+pub mod connection;
+pub mod deadline;
+pub mod decision_log;
+pub mod dry_run;
+pub mod env_config;
+pub mod error;
+pub mod error_code;
+pub mod exists;
+pub mod expand_tree;
+pub mod explain;
 pub mod generated;
 pub mod json_types;
+pub mod list_objects_session;
+pub mod local_check;
+pub mod model_builder;
+pub mod model_merge;
+pub mod pagination;
+pub mod purge;
+pub mod read_by_object;
+pub mod request_id;
+pub mod resilience;
+pub mod schema;
+pub mod tags;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_support;
+pub mod tuple_validation;
+pub mod unused;
 
 // Re-export the generated types and client for convenience
 pub use generated::open_fga_service_client::OpenFgaServiceClient;
 pub use generated::*;
 
 // Re-export JSON types for public API
+pub use access_diff::{AccessChange, AccessDiff};
+pub use access_summary::{
+    CheckBackend, RelationAccess, user_access_summary, user_access_summary_with_model,
+};
+pub use assertions::{AssertionFailure, AssertionReport};
+pub use batch_stream::{MAX_BATCH_CHECK_ITEMS, batch_check_stream};
+pub use bundle::{ImportReport, StoreBackend, StoreBundle, export_store, import_store};
+pub use check_cache::{
+    CachingCheckClient, CheckCache, CheckCacheKey, DEFAULT_CHECK_CACHE_CAPACITY, InvalidationMode,
+};
+pub use check_request::CheckRequestBuilder;
+pub use compact_tuple::InvalidCompactTuple;
+pub use conditions::update_conditions;
+pub use conflict::{ConflictResolution, write_with_resolution};
+pub use connection::Http2Settings;
+pub use deadline::WithDeadline;
+pub use decision_log::{OPA_CHECK_PATH, to_opa_decision_log};
+pub use dry_run::{DryRunChange, DryRunOperation, DryRunOutcome, DryRunReport};
+pub use env_config::EnvConfigError;
+pub use error::OpenFgaClientError;
+pub use error_code::{ERROR_CODE_METADATA, ErrorCodeChannel, ErrorCodeService, openfga_error_code};
+pub use exists::{TupleId, tuples_exist};
+pub use expand_tree::{ExpandTree, TupleToUsersetLeaf};
+pub use explain::CheckDetail;
 pub use json_types::*;
+pub use list_objects_session::ListObjectsSession;
+pub use local_check::{LocalModel, Unsupported};
+pub use model_builder::{ModelValidationError, WriteModelRequestBuilder};
+pub use model_merge::{MergeError, merge_models};
+pub use pagination::PaginationError;
+pub use purge::purge_object;
+pub use read_by_object::read_by_object;
+pub use request_id::{
+    REQUEST_ID_METADATA, RequestIdChannel, RequestIdInterceptor, with_request_id,
+};
+pub use resilience::{
+    CircuitBreaker, CircuitPermit, CircuitState, Resilience, ResilienceConfig, ResilientClient,
+    RetryBudget,
+};
+pub use schema::{SchemaSummary, model_schema, schema_summary};
+pub use tags::{InvalidTag, TAG_METADATA_PREFIX};
+pub use tuple_validation::{
+    TupleValidationError, TupleValidationReason, validate_contextual_tuples, validate_tuple,
+    validate_tuples,
+};
+pub use unused::{UnusedReport, find_unused};
 
 // High-level client wrapper for easier usage
-use tonic::transport::Channel;
+use futures_util::StreamExt;
+use std::collections::HashMap;
+use std::time::Duration;
+use tonic::codec::CompressionEncoding;
+use tonic::metadata::{AsciiMetadataKey, AsciiMetadataValue};
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Endpoint, Identity};
 
+#[derive(Clone)]
 pub struct OpenFGAClient {
     client: OpenFgaServiceClient<Channel>,
+    compression: Option<CompressionEncoding>,
+    tls: bool,
+    /// `authorization` metadata sent with every call
+    authorization: Option<AsciiMetadataValue>,
+    /// Request tags sent with every call, see [`OpenFGAClientBuilder::tags`]
+    tags: Vec<(AsciiMetadataKey, AsciiMetadataValue)>,
+    http2: Http2Settings,
+}
+
+/// Builder for [`OpenFGAClient`] with optional transport settings
+pub struct OpenFGAClientBuilder {
+    endpoint: String,
+    compression: Option<CompressionEncoding>,
+    ca_certificate: Option<Certificate>,
+    identity: Option<Identity>,
+    tls_domain_name: Option<String>,
+    authorization: Option<AsciiMetadataValue>,
+    tags: Vec<(AsciiMetadataKey, AsciiMetadataValue)>,
+    http2: Http2Settings,
+    max_decoding_message_size: Option<usize>,
+    max_encoding_message_size: Option<usize>,
+}
+
+impl OpenFGAClientBuilder {
+    /// Create a builder for the given endpoint, with compression disabled, a
+    /// plaintext connection and the default [`Http2Settings`]
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            compression: None,
+            ca_certificate: None,
+            identity: None,
+            tls_domain_name: None,
+            authorization: None,
+            tags: Vec::new(),
+            http2: Http2Settings::default(),
+            max_decoding_message_size: None,
+            max_encoding_message_size: None,
+        }
+    }
+
+    /// Authenticate with a bearer token, sent as `authorization: Bearer <token>`.
+    /// Fails if the token isn't a valid header value.
+    pub fn api_token(
+        mut self,
+        token: &str,
+    ) -> Result<Self, tonic::metadata::errors::InvalidMetadataValue> {
+        self.authorization = Some(format!("Bearer {}", token).parse()?);
+        Ok(self)
+    }
+
+    /// Authenticate with an API key, sent as `authorization: <prefix> <key>`, or just
+    /// the key without a prefix. Fails if the key isn't a valid header value.
+    pub fn api_key(
+        mut self,
+        key: &str,
+        prefix: Option<&str>,
+    ) -> Result<Self, tonic::metadata::errors::InvalidMetadataValue> {
+        let value = match prefix {
+            Some(prefix) => format!("{} {}", prefix, key),
+            None => key.to_string(),
+        };
+        self.authorization = Some(value.parse()?);
+        Ok(self)
+    }
+
+    /// Trust the given PEM-encoded CA certificate, e.g. for a self-signed server.
+    /// Enables TLS.
+    pub fn ca_certificate(mut self, pem: impl AsRef<[u8]>) -> Self {
+        self.ca_certificate = Some(Certificate::from_pem(pem));
+        self
+    }
+
+    /// Present a PEM-encoded client certificate and private key for mutual TLS.
+    /// Enables TLS.
+    pub fn client_identity(
+        mut self,
+        cert_pem: impl AsRef<[u8]>,
+        key_pem: impl AsRef<[u8]>,
+    ) -> Self {
+        self.identity = Some(Identity::from_pem(cert_pem, key_pem));
+        self
+    }
+
+    /// Override the domain name used for SNI and server certificate verification.
+    /// Enables TLS.
+    pub fn tls_domain_name(mut self, domain_name: impl Into<String>) -> Self {
+        self.tls_domain_name = Some(domain_name.into());
+        self
+    }
+
+    /// TLS settings for the channel, or `None` for plaintext. `https` endpoints use
+    /// TLS, trusting the platform's certificates unless a CA certificate is given.
+    fn tls_config(&self) -> Option<ClientTlsConfig> {
+        if !self.endpoint.starts_with("https://")
+            && self.ca_certificate.is_none()
+            && self.identity.is_none()
+            && self.tls_domain_name.is_none()
+        {
+            return None;
+        }
+
+        let mut config = ClientTlsConfig::new();
+        config = match &self.ca_certificate {
+            Some(ca_certificate) => config.ca_certificate(ca_certificate.clone()),
+            None => config.with_native_roots(),
+        };
+        if let Some(identity) = &self.identity {
+            config = config.identity(identity.clone());
+        }
+        if let Some(domain_name) = &self.tls_domain_name {
+            config = config.domain_name(domain_name.clone());
+        }
+
+        Some(config)
+    }
+
+    /// Send HTTP/2 keepalive pings every `interval`, so proxies and load balancers
+    /// don't drop the connection while it's idle; `None` disables them. Defaults to
+    /// [`connection::DEFAULT_KEEP_ALIVE_INTERVAL`].
+    pub fn http2_keep_alive_interval(mut self, interval: Option<Duration>) -> Self {
+        self.http2.keep_alive_interval = interval;
+        self
+    }
+
+    /// Close the connection when a keepalive ping isn't acknowledged within `timeout`.
+    /// Defaults to [`connection::DEFAULT_KEEP_ALIVE_TIMEOUT`].
+    pub fn keep_alive_timeout(mut self, timeout: Duration) -> Self {
+        self.http2.keep_alive_timeout = timeout;
+        self
+    }
+
+    /// Whether to send keepalive pings while no call is in flight. Defaults to `true`.
+    pub fn keep_alive_while_idle(mut self, enabled: bool) -> Self {
+        self.http2.keep_alive_while_idle = enabled;
+        self
+    }
+
+    /// Whether to size the HTTP/2 flow control windows adaptively. Defaults to `false`.
+    pub fn http2_adaptive_window(mut self, enabled: bool) -> Self {
+        self.http2.adaptive_window = enabled;
+        self
+    }
+
+    fn channel_endpoint(&self) -> Result<Endpoint, Box<dyn std::error::Error>> {
+        let mut endpoint = self
+            .http2
+            .apply(Channel::from_shared(self.endpoint.clone())?);
+        if let Some(tls_config) = self.tls_config() {
+            endpoint = endpoint.tls_config(tls_config)?;
+        }
+
+        Ok(endpoint)
+    }
+
+    /// Compress requests and accept compressed responses with the given encoding
+    /// (`Gzip` or `Zstd`). The server must support the encoding.
+    pub fn compression(mut self, encoding: CompressionEncoding) -> Self {
+        self.compression = Some(encoding);
+        self
+    }
+
+    /// Largest response the client accepts, in bytes. Defaults to tonic's 4 MiB; raise
+    /// it for large authorization models.
+    pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+        self.max_decoding_message_size = Some(limit);
+        self
+    }
+
+    /// Largest request the client sends, in bytes. Unlimited by default.
+    pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+        self.max_encoding_message_size = Some(limit);
+        self
+    }
+
+    /// Connect to the endpoint and build the client
+    pub async fn connect(self) -> Result<OpenFGAClient, Box<dyn std::error::Error>> {
+        let channel = self.channel_endpoint()?.connect().await?;
+
+        Ok(self.build(channel))
+    }
+
+    /// Build the client without connecting; the connection is established on first use
+    pub fn connect_lazy(self) -> Result<OpenFGAClient, Box<dyn std::error::Error>> {
+        let channel = self.channel_endpoint()?.connect_lazy();
+
+        Ok(self.build(channel))
+    }
+
+    fn build(self, channel: Channel) -> OpenFGAClient {
+        let mut client = OpenFgaServiceClient::new(channel);
+        if let Some(encoding) = self.compression {
+            client = client.send_compressed(encoding).accept_compressed(encoding);
+        }
+        if let Some(limit) = self.max_decoding_message_size {
+            client = client.max_decoding_message_size(limit);
+        }
+        if let Some(limit) = self.max_encoding_message_size {
+            client = client.max_encoding_message_size(limit);
+        }
+
+        OpenFGAClient {
+            client,
+            compression: self.compression,
+            tls: self.tls_config().is_some(),
+            authorization: self.authorization,
+            tags: self.tags,
+            http2: self.http2,
+        }
+    }
 }
 
 impl OpenFGAClient {
     /// Create a new OpenFGA client
     pub async fn new(endpoint: String) -> Result<Self, Box<dyn std::error::Error>> {
-        let channel = Channel::from_shared(endpoint)?.connect().await?;
+        OpenFGAClientBuilder::new(endpoint).connect().await
+    }
 
-        let client = OpenFgaServiceClient::new(channel);
+    /// Create a builder to configure the client before connecting
+    pub fn builder(endpoint: impl Into<String>) -> OpenFGAClientBuilder {
+        OpenFGAClientBuilder::new(endpoint)
+    }
+
+    /// Get the compression encoding configured on the client, if any
+    pub fn compression(&self) -> Option<CompressionEncoding> {
+        self.compression
+    }
+
+    /// Whether the client connects over TLS
+    pub fn is_tls(&self) -> bool {
+        self.tls
+    }
 
-        Ok(Self { client })
+    /// HTTP/2 keepalive and flow control settings of the connection
+    pub fn http2_settings(&self) -> Http2Settings {
+        self.http2
     }
 
-    /// Get the underlying gRPC client
+    /// Get the underlying gRPC client. Calls made through it don't carry the
+    /// client's credentials.
     pub fn inner(&mut self) -> &mut OpenFgaServiceClient<Channel> {
         &mut self.client
     }
 
+    /// Add the client's credentials and tags and the task's request ID to a request
+    fn authorize<T>(&self, request: impl tonic::IntoRequest<T>) -> tonic::Request<T> {
+        let mut request = request.into_request();
+        request_id::insert_request_id(&mut request);
+        if let Some(authorization) = &self.authorization {
+            request
+                .metadata_mut()
+                .insert("authorization", authorization.clone());
+        }
+        for (key, value) in &self.tags {
+            request.metadata_mut().insert(key.clone(), value.clone());
+        }
+        request
+    }
+
     /// Read tuples from the store
     pub async fn read(
         &mut self,
-        request: ReadRequest,
+        request: impl tonic::IntoRequest<ReadRequest>,
     ) -> Result<tonic::Response<ReadResponse>, tonic::Status> {
-        self.client.read(request).await
+        self.client.read(self.authorize(request)).await
     }
 
     /// Write tuples to the store
     pub async fn write(
         &mut self,
-        request: WriteRequest,
+        request: impl tonic::IntoRequest<WriteRequest>,
     ) -> Result<tonic::Response<WriteResponse>, tonic::Status> {
-        self.client.write(request).await
+        self.client.write(self.authorize(request)).await
     }
 
     /// Check if a user has a relation to an object
     pub async fn check(
         &mut self,
-        request: CheckRequest,
+        request: impl tonic::IntoRequest<CheckRequest>,
     ) -> Result<tonic::Response<CheckResponse>, tonic::Status> {
-        self.client.check(request).await
+        self.client.check(self.authorize(request)).await
     }
 
     /// Expand a userset
     pub async fn expand(
         &mut self,
-        request: ExpandRequest,
+        request: impl tonic::IntoRequest<ExpandRequest>,
     ) -> Result<tonic::Response<ExpandResponse>, tonic::Status> {
-        self.client.expand(request).await
+        self.client.expand(self.authorize(request)).await
     }
 
-    /// Get authorization model
+    /// Get authorization model. A model over the gRPC size limit fails with
+    /// [`OpenFgaClientError::MessageTooLarge`].
     pub async fn read_authorization_model(
         &mut self,
-        request: ReadAuthorizationModelRequest,
-    ) -> Result<tonic::Response<ReadAuthorizationModelResponse>, tonic::Status> {
-        self.client.read_authorization_model(request).await
+        request: impl tonic::IntoRequest<ReadAuthorizationModelRequest>,
+    ) -> Result<tonic::Response<ReadAuthorizationModelResponse>, OpenFgaClientError> {
+        Ok(self
+            .client
+            .read_authorization_model(self.authorize(request))
+            .await?)
     }
 
-    /// Write authorization model
+    /// Write authorization model. A model over the gRPC size limit fails with
+    /// [`OpenFgaClientError::MessageTooLarge`].
     pub async fn write_authorization_model(
         &mut self,
-        request: WriteAuthorizationModelRequest,
-    ) -> Result<tonic::Response<WriteAuthorizationModelResponse>, tonic::Status> {
-        self.client.write_authorization_model(request).await
+        request: impl tonic::IntoRequest<WriteAuthorizationModelRequest>,
+    ) -> Result<tonic::Response<WriteAuthorizationModelResponse>, OpenFgaClientError> {
+        Ok(self
+            .client
+            .write_authorization_model(self.authorize(request))
+            .await?)
     }
 
     /// List authorization models
     pub async fn read_authorization_models(
         &mut self,
-        request: ReadAuthorizationModelsRequest,
+        request: impl tonic::IntoRequest<ReadAuthorizationModelsRequest>,
     ) -> Result<tonic::Response<ReadAuthorizationModelsResponse>, tonic::Status> {
-        self.client.read_authorization_models(request).await
+        self.client
+            .read_authorization_models(self.authorize(request))
+            .await
     }
 
     /// Get store
     pub async fn get_store(
         &mut self,
-        request: GetStoreRequest,
+        request: impl tonic::IntoRequest<GetStoreRequest>,
     ) -> Result<tonic::Response<GetStoreResponse>, tonic::Status> {
-        self.client.get_store(request).await
+        self.client.get_store(self.authorize(request)).await
     }
 
     /// List stores
     pub async fn list_stores(
         &mut self,
-        request: ListStoresRequest,
+        request: impl tonic::IntoRequest<ListStoresRequest>,
     ) -> Result<tonic::Response<ListStoresResponse>, tonic::Status> {
-        self.client.list_stores(request).await
+        self.client.list_stores(self.authorize(request)).await
     }
 
     /// Create store
     pub async fn create_store(
         &mut self,
-        request: CreateStoreRequest,
+        request: impl tonic::IntoRequest<CreateStoreRequest>,
     ) -> Result<tonic::Response<CreateStoreResponse>, tonic::Status> {
-        self.client.create_store(request).await
+        self.client.create_store(self.authorize(request)).await
     }
 
     /// Delete store
     pub async fn delete_store(
         &mut self,
-        request: DeleteStoreRequest,
+        request: impl tonic::IntoRequest<DeleteStoreRequest>,
     ) -> Result<tonic::Response<DeleteStoreResponse>, tonic::Status> {
-        self.client.delete_store(request).await
+        self.client.delete_store(self.authorize(request)).await
     }
 
     /// List objects
     pub async fn list_objects(
         &mut self,
-        request: ListObjectsRequest,
+        request: impl tonic::IntoRequest<ListObjectsRequest>,
     ) -> Result<tonic::Response<ListObjectsResponse>, tonic::Status> {
-        self.client.list_objects(request).await
+        self.client.list_objects(self.authorize(request)).await
+    }
+
+    /// Batch check multiple tuples in a single request
+    pub async fn batch_check(
+        &mut self,
+        request: impl tonic::IntoRequest<BatchCheckRequest>,
+    ) -> Result<tonic::Response<BatchCheckResponse>, tonic::Status> {
+        self.client.batch_check(self.authorize(request)).await
     }
 
     /// Stream changes
     pub async fn read_changes(
         &mut self,
-        request: ReadChangesRequest,
+        request: impl tonic::IntoRequest<ReadChangesRequest>,
     ) -> Result<tonic::Response<ReadChangesResponse>, tonic::Status> {
-        self.client.read_changes(request).await
+        self.client.read_changes(self.authorize(request)).await
+    }
+
+    /// Read the assertions of an authorization model
+    pub async fn read_assertions(
+        &mut self,
+        request: impl tonic::IntoRequest<ReadAssertionsRequest>,
+    ) -> Result<tonic::Response<ReadAssertionsResponse>, tonic::Status> {
+        self.client.read_assertions(self.authorize(request)).await
+    }
+
+    /// Replace the assertions of an authorization model
+    pub async fn write_assertions(
+        &mut self,
+        request: impl tonic::IntoRequest<WriteAssertionsRequest>,
+    ) -> Result<tonic::Response<WriteAssertionsResponse>, tonic::Status> {
+        self.client.write_assertions(self.authorize(request)).await
     }
 }
 
 // Helper functions for creating common request types
 impl OpenFGAClient {
-    /// Create a simple check request
+    /// Create a simple check request; see [`CheckRequestBuilder`] for the other fields
     pub fn create_check_request(
         store_id: String,
         object: String,
         relation: String,
         user: String,
     ) -> CheckRequest {
-        CheckRequest {
-            store_id,
-            tuple_key: Some(CheckRequestTupleKey {
-                object,
-                relation,
-                user,
-            }),
-            contextual_tuples: None,
-            authorization_model_id: String::new(),
-            trace: false,
-            consistency: ConsistencyPreference::Unspecified as i32,
-            context: None,
-        }
+        CheckRequestBuilder::new()
+            .store(store_id)
+            .tuple(object, relation, user)
+            .build()
     }
 
     /// Create a simple write request
@@ -183,6 +536,249 @@ impl OpenFGAClient {
             authorization_model_id: String::new(),
         }
     }
+
+    /// Convert a JSON object into the `context` of a request, for evaluating the
+    /// conditions of an ABAC model
+    pub fn context_from_json(
+        context: serde_json::Value,
+    ) -> Result<prost_wkt_types::Struct, serde_json::Error> {
+        serde_json::from_value(context)
+    }
+
+    /// Create a list objects request, with an optional JSON object as condition context
+    pub fn create_list_objects_request(
+        store_id: String,
+        object_type: String,
+        relation: String,
+        user: String,
+        context: Option<serde_json::Value>,
+    ) -> Result<ListObjectsRequest, serde_json::Error> {
+        Ok(ListObjectsRequest {
+            store_id,
+            authorization_model_id: String::new(),
+            r#type: object_type,
+            relation,
+            user,
+            contextual_tuples: None,
+            context: context.map(Self::context_from_json).transpose()?,
+            consistency: ConsistencyPreference::Unspecified as i32,
+        })
+    }
+
+    /// Create an expand request evaluated with `contextual_tuples`. Expand takes no
+    /// request-level `context`; the conditions of the contextual tuples carry theirs.
+    pub fn create_expand_request(
+        store_id: String,
+        object: String,
+        relation: String,
+        contextual_tuples: Vec<TupleKey>,
+    ) -> ExpandRequest {
+        ExpandRequest {
+            store_id,
+            tuple_key: Some(ExpandRequestTupleKey { relation, object }),
+            authorization_model_id: String::new(),
+            consistency: ConsistencyPreference::Unspecified as i32,
+            contextual_tuples: (!contextual_tuples.is_empty()).then_some(ContextualTupleKeys {
+                tuple_keys: contextual_tuples,
+            }),
+        }
+    }
+
+    /// Create a list users request, with an optional JSON object as condition context
+    pub fn create_list_users_request(
+        store_id: String,
+        object: Object,
+        relation: String,
+        user_filters: Vec<UserTypeFilter>,
+        context: Option<serde_json::Value>,
+    ) -> Result<ListUsersRequest, serde_json::Error> {
+        Ok(ListUsersRequest {
+            store_id,
+            authorization_model_id: String::new(),
+            object: Some(object),
+            relation,
+            user_filters,
+            contextual_tuples: Vec::new(),
+            context: context.map(Self::context_from_json).transpose()?,
+            consistency: ConsistencyPreference::Unspecified as i32,
+        })
+    }
+}
+
+// Relation helpers built on top of batch check
+impl OpenFGAClient {
+    /// Check which of the given relations a user has on an object.
+    ///
+    /// Issues batch checks of at most [`MAX_BATCH_CHECK_ITEMS`] items, one item per
+    /// relation, and returns a map keyed by relation name. Relations whose check
+    /// errored are reported as denied.
+    pub async fn check_relations(
+        &mut self,
+        store_id: String,
+        authorization_model_id: String,
+        object: String,
+        user: String,
+        relations: &[String],
+    ) -> Result<HashMap<String, bool>, tonic::Status> {
+        let request = Self::create_check_relations_request(
+            store_id,
+            authorization_model_id,
+            object,
+            user,
+            relations,
+        );
+        check_keyed(self.clone(), request, relations).await
+    }
+
+    /// Create a batch check request with one item per relation, correlated by the
+    /// relation's index in `relations`.
+    ///
+    /// The request isn't split; OpenFGA rejects more than [`MAX_BATCH_CHECK_ITEMS`]
+    /// items, which [`check_relations`](Self::check_relations) sends in several batches.
+    pub fn create_check_relations_request(
+        store_id: String,
+        authorization_model_id: String,
+        object: String,
+        user: String,
+        relations: &[String],
+    ) -> BatchCheckRequest {
+        keyed_request(
+            store_id,
+            authorization_model_id,
+            relations.iter().map(|relation| CheckRequestTupleKey {
+                user: user.clone(),
+                relation: relation.clone(),
+                object: object.clone(),
+            }),
+        )
+    }
+
+    /// Map a batch check response back onto the requested relations
+    pub fn relations_from_batch_check_response(
+        relations: &[String],
+        response: BatchCheckResponse,
+    ) -> HashMap<String, bool> {
+        keyed_results(relations, response)
+    }
+
+    /// Check which of the given users have a relation on an object, e.g. for an
+    /// access list.
+    ///
+    /// Issues batch checks of at most [`MAX_BATCH_CHECK_ITEMS`] items, one item per
+    /// user, and returns a map keyed by user. Users whose check errored are reported
+    /// as denied.
+    pub async fn check_users(
+        &mut self,
+        store_id: String,
+        authorization_model_id: String,
+        object: String,
+        relation: String,
+        users: &[String],
+    ) -> Result<HashMap<String, bool>, tonic::Status> {
+        let request = Self::create_check_users_request(
+            store_id,
+            authorization_model_id,
+            object,
+            relation,
+            users,
+        );
+        check_keyed(self.clone(), request, users).await
+    }
+
+    /// Create a batch check request with one item per user, correlated by the user's
+    /// index in `users`: OpenFGA only accepts `[A-Za-z0-9_-]` in correlation ids, so
+    /// user ids like `user:alice` can't be used.
+    ///
+    /// The request isn't split; OpenFGA rejects more than [`MAX_BATCH_CHECK_ITEMS`]
+    /// items, which [`check_users`](Self::check_users) sends in several batches.
+    pub fn create_check_users_request(
+        store_id: String,
+        authorization_model_id: String,
+        object: String,
+        relation: String,
+        users: &[String],
+    ) -> BatchCheckRequest {
+        keyed_request(
+            store_id,
+            authorization_model_id,
+            users.iter().map(|user| CheckRequestTupleKey {
+                user: user.clone(),
+                relation: relation.clone(),
+                object: object.clone(),
+            }),
+        )
+    }
+
+    /// Map a batch check response back onto the requested users
+    pub fn users_from_batch_check_response(
+        users: &[String],
+        response: BatchCheckResponse,
+    ) -> HashMap<String, bool> {
+        keyed_results(users, response)
+    }
+}
+
+/// Sub-batches [`OpenFGAClient::check_relations`] and [`OpenFGAClient::check_users`]
+/// have in flight at a time
+const KEYED_CHECK_CONCURRENCY: usize = 4;
+
+/// Batch check request with one item per tuple, correlated by the tuple's index
+fn keyed_request(
+    store_id: String,
+    authorization_model_id: String,
+    tuple_keys: impl Iterator<Item = CheckRequestTupleKey>,
+) -> BatchCheckRequest {
+    BatchCheckRequest {
+        store_id,
+        authorization_model_id,
+        consistency: ConsistencyPreference::Unspecified as i32,
+        checks: tuple_keys
+            .enumerate()
+            .map(|(index, tuple_key)| BatchCheckItem {
+                tuple_key: Some(tuple_key),
+                contextual_tuples: None,
+                context: None,
+                correlation_id: index.to_string(),
+            })
+            .collect(),
+    }
+}
+
+/// Results of a [`keyed_request`] by key; a check that errored, or got no result, is
+/// denied
+fn keyed_results(keys: &[String], response: BatchCheckResponse) -> HashMap<String, bool> {
+    let ids = (0..keys.len()).map(|index| index.to_string()).collect();
+    batch_stream::chunk_results(ids, response)
+        .into_iter()
+        .filter_map(|(id, allowed)| Some((keyed(keys, &id)?.clone(), allowed)))
+        .collect()
+}
+
+/// Send a request of [`keyed_request`] in sub-batches OpenFGA accepts, mapping the
+/// results back onto `keys`
+async fn check_keyed<B: CheckBackend + Clone + Send + 'static>(
+    client: B,
+    request: BatchCheckRequest,
+    keys: &[String],
+) -> Result<HashMap<String, bool>, tonic::Status> {
+    let mut allowed = HashMap::with_capacity(keys.len());
+    let mut results = std::pin::pin!(batch_check_stream(
+        client,
+        request,
+        MAX_BATCH_CHECK_ITEMS,
+        KEYED_CHECK_CONCURRENCY
+    ));
+    while let Some((id, result)) = results.next().await.transpose()? {
+        if let Some(key) = keyed(keys, &id) {
+            allowed.insert(key.clone(), result);
+        }
+    }
+    Ok(allowed)
+}
+
+/// Key of a [`keyed_request`] correlation id
+fn keyed<'a>(keys: &'a [String], id: &str) -> Option<&'a String> {
+    keys.get(id.parse::<usize>().ok()?)
 }
 
 // JSON-friendly wrapper methods
@@ -193,16 +789,19 @@ impl OpenFGAClient {
         store_id: String,
         json_model: JsonAuthModel,
     ) -> Result<tonic::Response<WriteAuthorizationModelResponse>, Box<dyn std::error::Error>> {
-        let (type_definitions, _schema_version, _conditions) = json_model
+        let (type_definitions, schema_version, conditions) = json_model
             .to_openfga_types()
             .map_err(|e| format!("Failed to convert JSON model: {}", e))?;
 
-        let request = WriteAuthorizationModelRequest {
-            store_id,
-            type_definitions,
-            schema_version: "1.1".to_string(),
-            conditions: std::collections::HashMap::new(),
-        };
+        let request = WriteModelRequestBuilder::new(store_id)
+            .schema_version(schema_version)
+            .type_definitions(type_definitions)
+            .conditions(conditions)
+            .build()
+            .map_err(|errors| {
+                let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+                format!("Invalid authorization model: {}", errors.join("; "))
+            })?;
 
         Ok(self.write_authorization_model(request).await?)
     }
@@ -350,17 +949,20 @@ impl OpenFGAClient {
             let mut json_user_types = Vec::new();
 
             for relation_ref in &relation_metadata.directly_related_user_types {
-                let relation = match &relation_ref.relation_or_wildcard {
+                let (relation, wildcard) = match &relation_ref.relation_or_wildcard {
                     Some(crate::relation_reference::RelationOrWildcard::Relation(rel)) => {
-                        Some(rel.clone())
+                        (Some(rel.clone()), None)
+                    }
+                    Some(crate::relation_reference::RelationOrWildcard::Wildcard(_)) => {
+                        (None, Some(JsonWildcard {}))
                     }
-                    Some(crate::relation_reference::RelationOrWildcard::Wildcard(_)) => None,
-                    None => None,
+                    None => (None, None),
                 };
 
                 json_user_types.push(JsonDirectlyRelatedUserType {
                     type_name: relation_ref.r#type.clone(),
                     relation,
+                    wildcard,
                     condition: if relation_ref.condition.is_empty() {
                         None
                     } else {
@@ -373,11 +975,7 @@ impl OpenFGAClient {
                 relation_name.clone(),
                 JsonRelationMetadata {
                     directly_related_user_types: json_user_types,
-                    module: if relation_metadata.module.is_empty() {
-                        None
-                    } else {
-                        Some(relation_metadata.module.clone())
-                    },
+                    module: json_types::module_to_json(&relation_metadata.module),
                     source_info: None,
                 },
             );
@@ -389,12 +987,517 @@ impl OpenFGAClient {
             } else {
                 Some(json_relations)
             },
-            module: if metadata.module.is_empty() {
-                None
-            } else {
-                Some(metadata.module.clone())
-            },
+            module: json_types::module_to_json(&metadata.module),
             source_info: None,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::batch_check_single_result::CheckResult;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tonic::transport::ServerTlsConfig;
+
+    fn single_result(check_result: CheckResult) -> BatchCheckSingleResult {
+        BatchCheckSingleResult {
+            check_result: Some(check_result),
+        }
+    }
+
+    #[test]
+    fn test_check_relations_request_uses_index_as_correlation_id() {
+        let relations = vec!["viewer".to_string(), "editor".to_string()];
+        let request = OpenFGAClient::create_check_relations_request(
+            "store".to_string(),
+            "model".to_string(),
+            "document:readme".to_string(),
+            "user:alice".to_string(),
+            &relations,
+        );
+
+        assert_eq!(request.checks.len(), 2);
+        for (index, (item, relation)) in request.checks.iter().zip(&relations).enumerate() {
+            assert_eq!(item.correlation_id, index.to_string());
+            let tuple_key = item.tuple_key.as_ref().unwrap();
+            assert_eq!(&tuple_key.relation, relation);
+            assert_eq!(tuple_key.object, "document:readme");
+            assert_eq!(tuple_key.user, "user:alice");
+        }
+    }
+
+    #[test]
+    fn test_expand_request_encodes_contextual_tuples() {
+        let membership = TupleKey {
+            user: "user:alice".to_string(),
+            relation: "member".to_string(),
+            object: "group:eng".to_string(),
+            condition: Some(RelationshipCondition {
+                name: "in_office".to_string(),
+                context: Some(
+                    OpenFGAClient::context_from_json(serde_json::json!({ "ip": "10.0.0.1" }))
+                        .unwrap(),
+                ),
+            }),
+        };
+        let request = OpenFGAClient::create_expand_request(
+            "store".to_string(),
+            "document:readme".to_string(),
+            "viewer".to_string(),
+            vec![membership.clone()],
+        );
+
+        let tuple_key = request.tuple_key.unwrap();
+        assert_eq!(
+            (tuple_key.object.as_str(), tuple_key.relation.as_str()),
+            ("document:readme", "viewer")
+        );
+        let tuple_keys = request.contextual_tuples.unwrap().tuple_keys;
+        assert_eq!(tuple_keys, vec![membership]);
+        let context = tuple_keys[0].condition.as_ref().unwrap().context.as_ref();
+        assert_eq!(
+            serde_json::to_value(context).unwrap(),
+            serde_json::json!({ "ip": "10.0.0.1" })
+        );
+
+        let request = OpenFGAClient::create_expand_request(
+            "store".to_string(),
+            "document:readme".to_string(),
+            "viewer".to_string(),
+            Vec::new(),
+        );
+        assert!(request.contextual_tuples.is_none());
+    }
+
+    #[test]
+    fn test_list_objects_request_encodes_context() {
+        let request = OpenFGAClient::create_list_objects_request(
+            "store".to_string(),
+            "document".to_string(),
+            "viewer".to_string(),
+            "user:alice".to_string(),
+            Some(serde_json::json!({ "ip_address": "10.0.0.1", "attempts": 2, "admin": false })),
+        )
+        .unwrap();
+
+        let context = request.context.expect("context is set");
+        assert_eq!(
+            serde_json::to_value(&context).unwrap(),
+            serde_json::json!({ "ip_address": "10.0.0.1", "attempts": 2.0, "admin": false })
+        );
+
+        let request = OpenFGAClient::create_list_objects_request(
+            "store".to_string(),
+            "document".to_string(),
+            "viewer".to_string(),
+            "user:alice".to_string(),
+            None,
+        )
+        .unwrap();
+        assert!(request.context.is_none());
+
+        assert!(
+            OpenFGAClient::context_from_json(serde_json::json!(["not", "an", "object"])).is_err()
+        );
+    }
+
+    #[test]
+    fn test_check_relations_mixed_results() {
+        let relations = vec![
+            "viewer".to_string(),
+            "editor".to_string(),
+            "owner".to_string(),
+        ];
+        let response = BatchCheckResponse {
+            result: HashMap::from([
+                ("0".to_string(), single_result(CheckResult::Allowed(true))),
+                ("1".to_string(), single_result(CheckResult::Allowed(false))),
+                (
+                    "2".to_string(),
+                    single_result(CheckResult::Error(CheckError {
+                        message: "boom".to_string(),
+                        code: None,
+                    })),
+                ),
+            ]),
+        };
+
+        let result = OpenFGAClient::relations_from_batch_check_response(&relations, response);
+
+        let mut keys: Vec<_> = result.keys().cloned().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["editor", "owner", "viewer"]);
+        assert!(result["viewer"]);
+        assert!(!result["editor"]);
+        assert!(!result["owner"]);
+    }
+
+    #[test]
+    fn test_check_users_mixed_results() {
+        let users = vec![
+            "user:alice".to_string(),
+            "user:bob".to_string(),
+            "group:eng#member".to_string(),
+        ];
+        let request = OpenFGAClient::create_check_users_request(
+            "store".to_string(),
+            "model".to_string(),
+            "document:readme".to_string(),
+            "viewer".to_string(),
+            &users,
+        );
+        let correlation_ids: Vec<_> = request
+            .checks
+            .iter()
+            .map(|c| c.correlation_id.as_str())
+            .collect();
+        assert_eq!(correlation_ids, vec!["0", "1", "2"]);
+
+        let response = BatchCheckResponse {
+            result: HashMap::from([
+                ("0".to_string(), single_result(CheckResult::Allowed(true))),
+                ("1".to_string(), single_result(CheckResult::Allowed(false))),
+                ("2".to_string(), single_result(CheckResult::Allowed(true))),
+            ]),
+        };
+
+        let result = OpenFGAClient::users_from_batch_check_response(&users, response);
+
+        assert_eq!(
+            result,
+            HashMap::from([
+                ("user:alice".to_string(), true),
+                ("user:bob".to_string(), false),
+                ("group:eng#member".to_string(), true),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_users_splits_long_lists() {
+        use crate::test_support::{MemoryStore, tuple};
+
+        let users: Vec<String> = (0..120).map(|i| format!("user:{}", i)).collect();
+        let store = MemoryStore::with_tuples(vec![
+            tuple("user:7", "viewer", "document:readme"),
+            tuple("user:99", "viewer", "document:readme"),
+        ]);
+        let request = OpenFGAClient::create_check_users_request(
+            "store".to_string(),
+            "model".to_string(),
+            "document:readme".to_string(),
+            "viewer".to_string(),
+            &users,
+        );
+
+        let allowed = check_keyed(store.clone(), request, &users).await.unwrap();
+        assert_eq!(allowed.len(), 120);
+        let mut granted: Vec<_> = allowed
+            .iter()
+            .filter(|(_, a)| **a)
+            .map(|(u, _)| u)
+            .collect();
+        granted.sort();
+        assert_eq!(granted, vec!["user:7", "user:99"]);
+
+        let mut sizes: Vec<_> = store
+            .state()
+            .batch_checks
+            .iter()
+            .map(|request| request.checks.len())
+            .collect();
+        sizes.sort();
+        assert_eq!(
+            sizes,
+            vec![20, MAX_BATCH_CHECK_ITEMS, MAX_BATCH_CHECK_ITEMS]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_builder_compression() {
+        let client = OpenFGAClient::builder("http://localhost:8081")
+            .connect_lazy()
+            .unwrap();
+        assert_eq!(client.compression(), None);
+
+        let client = OpenFGAClient::builder("http://localhost:8081")
+            .compression(CompressionEncoding::Gzip)
+            .connect_lazy()
+            .unwrap();
+        assert_eq!(client.compression(), Some(CompressionEncoding::Gzip));
+
+        let client = OpenFGAClient::builder("http://localhost:8081")
+            .compression(CompressionEncoding::Zstd)
+            .connect_lazy()
+            .unwrap();
+        assert_eq!(client.compression(), Some(CompressionEncoding::Zstd));
+    }
+
+    #[tokio::test]
+    async fn test_builder_http2_settings() {
+        let client = OpenFGAClient::builder("http://localhost:8081")
+            .connect_lazy()
+            .unwrap();
+        assert_eq!(client.http2_settings(), Http2Settings::default());
+        assert_eq!(
+            client.http2_settings().keep_alive_interval,
+            Some(connection::DEFAULT_KEEP_ALIVE_INTERVAL)
+        );
+        assert!(client.http2_settings().keep_alive_while_idle);
+
+        let client = OpenFGAClient::builder("http://localhost:8081")
+            .http2_keep_alive_interval(Some(Duration::from_secs(15)))
+            .keep_alive_timeout(Duration::from_secs(5))
+            .keep_alive_while_idle(false)
+            .http2_adaptive_window(true)
+            .connect_lazy()
+            .unwrap();
+        assert_eq!(
+            client.http2_settings(),
+            Http2Settings {
+                keep_alive_interval: Some(Duration::from_secs(15)),
+                keep_alive_timeout: Duration::from_secs(5),
+                keep_alive_while_idle: false,
+                adaptive_window: true,
+            }
+        );
+
+        let client = OpenFGAClient::builder("http://localhost:8081")
+            .http2_keep_alive_interval(None)
+            .connect_lazy()
+            .unwrap();
+        assert_eq!(client.http2_settings().keep_alive_interval, None);
+    }
+
+    /// OpenFGA service answering every `Read` with `tuples` tuples, counting them
+    #[derive(Clone, Default)]
+    struct ReadServer {
+        tuples: usize,
+        reads: Arc<AtomicUsize>,
+    }
+
+    impl tonic::server::NamedService for ReadServer {
+        const NAME: &'static str = "openfga.v1.OpenFGAService";
+    }
+
+    impl tonic::server::UnaryService<ReadRequest> for ReadServer {
+        type Response = ReadResponse;
+        type Future = tonic::codegen::BoxFuture<tonic::Response<ReadResponse>, tonic::Status>;
+
+        fn call(&mut self, _request: tonic::Request<ReadRequest>) -> Self::Future {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            let tuples = (0..self.tuples)
+                .map(|i| Tuple {
+                    key: Some(TupleKey {
+                        user: format!("user:{}", i),
+                        relation: "viewer".to_string(),
+                        object: "document:readme".to_string(),
+                        condition: None,
+                    }),
+                    timestamp: None,
+                })
+                .collect();
+            Box::pin(async move {
+                Ok(tonic::Response::new(ReadResponse {
+                    tuples,
+                    continuation_token: String::new(),
+                }))
+            })
+        }
+    }
+
+    impl<B> tonic::codegen::Service<tonic::codegen::http::Request<B>> for ReadServer
+    where
+        B: tonic::codegen::Body + Send + 'static,
+        B::Error: Into<tonic::codegen::StdError> + Send + 'static,
+    {
+        type Response = tonic::codegen::http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = tonic::codegen::BoxFuture<Self::Response, Self::Error>;
+
+        fn poll_ready(
+            &mut self,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: tonic::codegen::http::Request<B>) -> Self::Future {
+            let service = self.clone();
+            Box::pin(async move {
+                let mut grpc = tonic::server::Grpc::new(tonic::codec::ProstCodec::default());
+                Ok(grpc.unary(service, request).await)
+            })
+        }
+    }
+
+    /// Serve `server` on a local port, over TLS when `tls` is given, returning its
+    /// endpoint
+    async fn serve(server: ReadServer, tls: Option<ServerTlsConfig>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let scheme = if tls.is_some() { "https" } else { "http" };
+        let endpoint = format!("{}://{}", scheme, listener.local_addr().unwrap());
+        let incoming =
+            tonic::transport::server::TcpIncoming::from_listener(listener, true, None).unwrap();
+        let mut builder = tonic::transport::Server::builder();
+        if let Some(tls) = tls {
+            builder = builder.tls_config(tls).unwrap();
+        }
+        tokio::spawn(builder.add_service(server).serve_with_incoming(incoming));
+        endpoint
+    }
+
+    async fn read_once(client: &mut OpenFGAClient) -> Result<ReadResponse, tonic::Status> {
+        client
+            .read(ReadRequest {
+                store_id: "store".to_string(),
+                ..Default::default()
+            })
+            .await
+            .map(tonic::Response::into_inner)
+    }
+
+    #[tokio::test]
+    async fn test_builder_tls() {
+        let client = OpenFGAClient::builder("http://localhost:8081")
+            .connect_lazy()
+            .unwrap();
+        assert!(!client.is_tls());
+
+        // https endpoints trust the platform's certificates without further settings
+        let client = OpenFGAClient::builder("https://openfga.example.com")
+            .connect_lazy()
+            .unwrap();
+        assert!(client.is_tls());
+
+        // The server requires a client certificate issued by the test CA
+        let server = ReadServer {
+            tuples: 1,
+            ..Default::default()
+        };
+        let tls = ServerTlsConfig::new()
+            .identity(Identity::from_pem(
+                include_str!("../tests/certs/server.pem"),
+                include_str!("../tests/certs/server.key"),
+            ))
+            .client_ca_root(Certificate::from_pem(include_str!("../tests/certs/ca.pem")));
+        let endpoint = serve(server.clone(), Some(tls)).await;
+        let builder = || {
+            OpenFGAClient::builder(endpoint.clone())
+                .ca_certificate(include_str!("../tests/certs/ca.pem"))
+                .client_identity(
+                    include_str!("../tests/certs/client.pem"),
+                    include_str!("../tests/certs/client.key"),
+                )
+                .tls_domain_name("openfga.internal")
+        };
+
+        let mut client = builder().connect().await.unwrap();
+        assert!(client.is_tls());
+        assert_eq!(read_once(&mut client).await.unwrap().tuples.len(), 1);
+
+        // The server's certificate is only valid for its domain name
+        let mut client = builder()
+            .tls_domain_name("openfga.example.com")
+            .connect_lazy()
+            .unwrap();
+        assert!(read_once(&mut client).await.is_err());
+
+        // Without the test CA the server isn't trusted
+        let mut client = OpenFGAClient::builder(endpoint.clone())
+            .tls_domain_name("openfga.internal")
+            .connect_lazy()
+            .unwrap();
+        assert!(read_once(&mut client).await.is_err());
+
+        // Without a client certificate the server rejects the connection
+        let mut client = OpenFGAClient::builder(endpoint)
+            .ca_certificate(include_str!("../tests/certs/ca.pem"))
+            .tls_domain_name("openfga.internal")
+            .connect_lazy()
+            .unwrap();
+        assert!(read_once(&mut client).await.is_err());
+        assert_eq!(server.reads.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_builder_message_size_limits() {
+        let server = ReadServer {
+            tuples: 100,
+            ..Default::default()
+        };
+        let reads = server.reads.clone();
+        let endpoint = serve(server, None).await;
+
+        let mut client = OpenFGAClient::builder(&endpoint).connect_lazy().unwrap();
+        let response = client.read(ReadRequest::default()).await.unwrap();
+        assert_eq!(response.into_inner().tuples.len(), 100);
+
+        let mut client = OpenFGAClient::builder(&endpoint)
+            .max_decoding_message_size(256)
+            .connect_lazy()
+            .unwrap();
+        let status = client.read(ReadRequest::default()).await.unwrap_err();
+        let error = OpenFgaClientError::from(status);
+        assert!(
+            matches!(
+                error,
+                OpenFgaClientError::MessageTooLarge {
+                    limit: Some(256),
+                    ..
+                }
+            ),
+            "{}",
+            error
+        );
+
+        let mut client = OpenFGAClient::builder(&endpoint)
+            .max_encoding_message_size(64)
+            .connect_lazy()
+            .unwrap();
+        let request = ReadRequest {
+            store_id: "x".repeat(128),
+            ..Default::default()
+        };
+        // tonic fails the stream rather than the call with a size error, so the
+        // request not reaching the server shows the limit
+        assert_eq!(reads.load(Ordering::SeqCst), 2);
+        client.read(request).await.unwrap_err();
+        assert_eq!(reads.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_module_metadata_round_trip() {
+        let json: JsonMetadata = serde_json::from_value(serde_json::json!({
+            "module": "core",
+            "relations": {
+                "viewer": {
+                    "directly_related_user_types": [{ "type": "user" }],
+                    "module": "core"
+                },
+                "owner": {
+                    "directly_related_user_types": [{ "type": "user" }],
+                    "module": ""
+                },
+                "editor": { "directly_related_user_types": [{ "type": "user" }] }
+            }
+        }))
+        .unwrap();
+
+        let metadata = json.to_openfga_metadata().unwrap();
+        assert_eq!(metadata.module, "core");
+        assert_eq!(metadata.relations["viewer"].module, "core");
+
+        let json = OpenFGAClient::metadata_to_json(&metadata).unwrap();
+        assert_eq!(json.module.as_deref(), Some("core"));
+        let relations = json.relations.as_ref().unwrap();
+        assert_eq!(relations["viewer"].module.as_deref(), Some("core"));
+        // An empty module is the same as an absent one
+        assert_eq!(relations["owner"].module, None);
+        assert_eq!(relations["editor"].module, None);
+
+        assert_eq!(json.to_openfga_metadata().unwrap(), metadata);
+    }
+}
@@ -0,0 +1,178 @@
+use crate::{
+    ConsistencyPreference, ContextualTupleKeys, ListObjectsRequest, ListObjectsResponse,
+    OpenFGAClient, TupleKey,
+};
+use std::future::Future;
+
+/// Store, model, condition context and contextual tuples shared by a series of
+/// `list_objects` calls, so each call only names its type, relation and user.
+///
+/// ```ignore
+/// let mut session = client
+///     .list_objects_session(store_id, model_id)
+///     .with_context_json(json!({ "ip_address": "10.0.0.1" }))?
+///     .with_contextual_tuples(vec![membership]);
+/// let documents = session.list("document", "viewer", "user:anne").await?;
+/// let folders = session.list("folder", "viewer", "user:anne").await?;
+/// ```
+#[derive(Clone)]
+pub struct ListObjectsSession {
+    client: OpenFGAClient,
+    store_id: String,
+    authorization_model_id: String,
+    context: Option<prost_wkt_types::Struct>,
+    contextual_tuples: Vec<TupleKey>,
+    consistency: ConsistencyPreference,
+}
+
+impl OpenFGAClient {
+    /// Start a [`ListObjectsSession`] against `authorization_model_id`, or the latest
+    /// model when it's empty
+    pub fn list_objects_session(
+        &self,
+        store_id: impl Into<String>,
+        authorization_model_id: impl Into<String>,
+    ) -> ListObjectsSession {
+        ListObjectsSession {
+            client: self.clone(),
+            store_id: store_id.into(),
+            authorization_model_id: authorization_model_id.into(),
+            context: None,
+            contextual_tuples: Vec::new(),
+            consistency: ConsistencyPreference::Unspecified,
+        }
+    }
+}
+
+impl ListObjectsSession {
+    /// Condition context sent with every call
+    pub fn with_context(mut self, context: prost_wkt_types::Struct) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Condition context sent with every call, from a JSON object
+    pub fn with_context_json(self, context: serde_json::Value) -> Result<Self, serde_json::Error> {
+        Ok(self.with_context(OpenFGAClient::context_from_json(context)?))
+    }
+
+    /// Contextual tuples sent with every call
+    pub fn with_contextual_tuples(mut self, tuples: Vec<TupleKey>) -> Self {
+        self.contextual_tuples = tuples;
+        self
+    }
+
+    pub fn with_consistency(mut self, consistency: ConsistencyPreference) -> Self {
+        self.consistency = consistency;
+        self
+    }
+
+    /// The request listing the objects of `object_type` `user` has `relation` with
+    pub fn request(
+        &self,
+        object_type: impl Into<String>,
+        relation: impl Into<String>,
+        user: impl Into<String>,
+    ) -> ListObjectsRequest {
+        ListObjectsRequest {
+            store_id: self.store_id.clone(),
+            authorization_model_id: self.authorization_model_id.clone(),
+            r#type: object_type.into(),
+            relation: relation.into(),
+            user: user.into(),
+            contextual_tuples: (!self.contextual_tuples.is_empty()).then(|| ContextualTupleKeys {
+                tuple_keys: self.contextual_tuples.clone(),
+            }),
+            context: self.context.clone(),
+            consistency: self.consistency as i32,
+        }
+    }
+
+    /// Objects of `object_type` `user` has `relation` with
+    pub async fn list(
+        &mut self,
+        object_type: impl Into<String>,
+        relation: impl Into<String>,
+        user: impl Into<String>,
+    ) -> Result<Vec<String>, tonic::Status> {
+        let mut client = self.client.clone();
+        self.list_with(object_type, relation, user, |request| async move {
+            Ok(client.list_objects(request).await?.into_inner())
+        })
+        .await
+    }
+
+    /// List the objects using `list_objects` to send the request
+    pub async fn list_with<F, Fut>(
+        &self,
+        object_type: impl Into<String>,
+        relation: impl Into<String>,
+        user: impl Into<String>,
+        list_objects: F,
+    ) -> Result<Vec<String>, tonic::Status>
+    where
+        F: FnOnce(ListObjectsRequest) -> Fut,
+        Fut: Future<Output = Result<ListObjectsResponse, tonic::Status>>,
+    {
+        let request = self.request(object_type, relation, user);
+        Ok(list_objects(request).await?.objects)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_session_shares_context() {
+        let client = OpenFGAClient::builder("http://localhost:8081")
+            .connect_lazy()
+            .unwrap();
+        let membership = TupleKey {
+            user: "user:anne".to_string(),
+            relation: "member".to_string(),
+            object: "group:engineering".to_string(),
+            condition: None,
+        };
+        let session = client
+            .list_objects_session("store", "model")
+            .with_context_json(json!({ "ip_address": "10.0.0.1" }))
+            .unwrap()
+            .with_contextual_tuples(vec![membership.clone()]);
+
+        let requests = Mutex::new(Vec::new());
+        let list_objects = |request: ListObjectsRequest| {
+            let objects = vec![format!("{}:roadmap", request.r#type)];
+            requests.lock().unwrap().push(request);
+            async move { Ok(ListObjectsResponse { objects }) }
+        };
+
+        let documents = session
+            .list_with("document", "viewer", "user:anne", list_objects)
+            .await
+            .unwrap();
+        let folders = session
+            .list_with("folder", "viewer", "user:anne", list_objects)
+            .await
+            .unwrap();
+        assert_eq!(documents, vec!["document:roadmap"]);
+        assert_eq!(folders, vec!["folder:roadmap"]);
+
+        let requests = requests.into_inner().unwrap();
+        assert_eq!(requests.len(), 2);
+        for request in &requests {
+            assert_eq!(request.store_id, "store");
+            assert_eq!(request.authorization_model_id, "model");
+            assert_eq!(
+                request.contextual_tuples.as_ref().unwrap().tuple_keys,
+                vec![membership.clone()]
+            );
+        }
+        assert!(requests[0].context.is_some());
+        assert_eq!(requests[0].context, requests[1].context);
+        assert_eq!(requests[0].r#type, "document");
+        assert_eq!(requests[1].r#type, "folder");
+    }
+}
@@ -0,0 +1,389 @@
+use crate::{CheckRequestBuilder, JsonAuthModel, JsonUserset, OpenFGAClient, TupleKey};
+use std::collections::HashMap;
+
+/// Deepest chain of computed usersets a [`LocalModel`] follows
+pub const MAX_LOCAL_DEPTH: usize = 25;
+
+/// Why a [`LocalModel`] can't resolve a check; the check has to go to OpenFGA
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Unsupported {
+    /// The relation is rewritten through a tuple-to-userset, e.g. `viewer from parent`
+    TupleToUserset {
+        object_type: String,
+        relation: String,
+    },
+    /// The relation is defined as an intersection
+    Intersection {
+        object_type: String,
+        relation: String,
+    },
+    /// The relation is defined as a difference (`but not`)
+    Difference {
+        object_type: String,
+        relation: String,
+    },
+    /// A tuple granting the relation has the named condition
+    Condition(String),
+    /// A tuple grants the relation to a userset, e.g. `group:eng#member`
+    Userset(String),
+    /// The model doesn't define the relation on the object's type
+    UnknownRelation {
+        object_type: String,
+        relation: String,
+    },
+    /// Computed usersets nest deeper than [`MAX_LOCAL_DEPTH`]
+    TooDeep,
+}
+
+impl std::fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Unsupported::TupleToUserset {
+                object_type,
+                relation,
+            } => write!(f, "{}#{} uses a tuple-to-userset", object_type, relation),
+            Unsupported::Intersection {
+                object_type,
+                relation,
+            } => write!(f, "{}#{} uses an intersection", object_type, relation),
+            Unsupported::Difference {
+                object_type,
+                relation,
+            } => write!(f, "{}#{} uses a difference", object_type, relation),
+            Unsupported::Condition(name) => write!(f, "a matching tuple has condition {}", name),
+            Unsupported::Userset(userset) => write!(f, "a tuple grants userset {}", userset),
+            Unsupported::UnknownRelation {
+                object_type,
+                relation,
+            } => write!(f, "{}#{} isn't defined in the model", object_type, relation),
+            Unsupported::TooDeep => {
+                write!(f, "computed usersets nest deeper than {}", MAX_LOCAL_DEPTH)
+            }
+        }
+    }
+}
+
+/// Experimental in-process evaluator over a cached model and cached tuples, for
+/// latency-sensitive checks on simple models.
+///
+/// Supported userset forms:
+/// - direct assignment (`this`), matching tuples of the user or its type's wildcard,
+///   e.g. `user:*`
+/// - computed usersets on the same object, e.g. `viewer: editor`
+/// - unions of supported forms, e.g. `editor: [user] or owner`
+///
+/// Tuple-to-usersets, intersections and differences, conditioned tuples and tuples
+/// granting usersets such as `group:eng#member` are reported as [`Unsupported`], so the
+/// check can fall back to OpenFGA. Decisions only reflect the cached tuples.
+#[derive(Debug, Clone)]
+pub struct LocalModel {
+    /// Relation definitions by type, then relation
+    relations: HashMap<String, HashMap<String, JsonUserset>>,
+    /// Tuples by object, then relation
+    tuples: HashMap<String, HashMap<String, Vec<TupleKey>>>,
+}
+
+impl LocalModel {
+    pub fn new(model: JsonAuthModel, tuples: impl IntoIterator<Item = TupleKey>) -> Self {
+        let relations = model
+            .type_definitions
+            .into_iter()
+            .map(|definition| (definition.type_name, definition.relations))
+            .collect();
+
+        let mut by_object: HashMap<String, HashMap<String, Vec<TupleKey>>> = HashMap::new();
+        for tuple in tuples {
+            by_object
+                .entry(tuple.object.clone())
+                .or_default()
+                .entry(tuple.relation.clone())
+                .or_default()
+                .push(tuple);
+        }
+
+        LocalModel {
+            relations,
+            tuples: by_object,
+        }
+    }
+
+    /// Whether `user` has `relation` to `object`, or why it can't be decided locally
+    pub fn local_check(
+        &self,
+        object: &str,
+        relation: &str,
+        user: &str,
+    ) -> Result<bool, Unsupported> {
+        self.check_relation(object, relation, user, 0)
+    }
+
+    fn check_relation(
+        &self,
+        object: &str,
+        relation: &str,
+        user: &str,
+        depth: usize,
+    ) -> Result<bool, Unsupported> {
+        if depth > MAX_LOCAL_DEPTH {
+            return Err(Unsupported::TooDeep);
+        }
+        let object_type = object.split_once(':').map_or(object, |(t, _)| t);
+        let userset = self
+            .relations
+            .get(object_type)
+            .and_then(|relations| relations.get(relation))
+            .ok_or_else(|| Unsupported::UnknownRelation {
+                object_type: object_type.to_string(),
+                relation: relation.to_string(),
+            })?;
+
+        self.check_userset(userset, object, relation, user, depth)
+    }
+
+    fn check_userset(
+        &self,
+        userset: &JsonUserset,
+        object: &str,
+        relation: &str,
+        user: &str,
+        depth: usize,
+    ) -> Result<bool, Unsupported> {
+        if userset.this.is_some() {
+            return self.check_direct(object, relation, user);
+        }
+        if let Some(computed) = &userset.computed_userset {
+            return self.check_relation(object, &computed.relation, user, depth + 1);
+        }
+        if let Some(union) = &userset.union {
+            // Any allowed child decides, even if another one is unsupported
+            let mut unsupported = None;
+            for child in &union.child {
+                match self.check_userset(child, object, relation, user, depth) {
+                    Ok(true) => return Ok(true),
+                    Ok(false) => {}
+                    Err(reason) => {
+                        unsupported.get_or_insert(reason);
+                    }
+                }
+            }
+            return unsupported.map_or(Ok(false), Err);
+        }
+
+        let object_type = object
+            .split_once(':')
+            .map_or(object, |(t, _)| t)
+            .to_string();
+        let relation = relation.to_string();
+        Err(if userset.tuple_to_userset.is_some() {
+            Unsupported::TupleToUserset {
+                object_type,
+                relation,
+            }
+        } else if userset.intersection.is_some() {
+            Unsupported::Intersection {
+                object_type,
+                relation,
+            }
+        } else if userset.difference.is_some() {
+            Unsupported::Difference {
+                object_type,
+                relation,
+            }
+        } else {
+            Unsupported::UnknownRelation {
+                object_type,
+                relation,
+            }
+        })
+    }
+
+    /// Tuples of `object#relation` granting `user`, directly or through its wildcard
+    fn check_direct(&self, object: &str, relation: &str, user: &str) -> Result<bool, Unsupported> {
+        let wildcard = user.split_once(':').map(|(t, _)| format!("{}:*", t));
+        let tuples = self
+            .tuples
+            .get(object)
+            .and_then(|relations| relations.get(relation));
+
+        let mut unsupported = None;
+        for tuple in tuples.into_iter().flatten() {
+            if tuple.user != user && Some(&tuple.user) != wildcard.as_ref() {
+                if tuple.user.contains('#') {
+                    unsupported.get_or_insert(Unsupported::Userset(tuple.user.clone()));
+                }
+                continue;
+            }
+            match &tuple.condition {
+                None => return Ok(true),
+                Some(condition) => {
+                    unsupported.get_or_insert(Unsupported::Condition(condition.name.clone()));
+                }
+            }
+        }
+        unsupported.map_or(Ok(false), Err)
+    }
+}
+
+impl OpenFGAClient {
+    /// Resolve a check with `local` when it supports it, otherwise with OpenFGA
+    /// against `authorization_model_id`
+    pub async fn check_with_local_model(
+        &mut self,
+        local: &LocalModel,
+        store_id: &str,
+        authorization_model_id: &str,
+        object: &str,
+        relation: &str,
+        user: &str,
+    ) -> Result<bool, tonic::Status> {
+        match local.local_check(object, relation, user) {
+            Ok(allowed) => Ok(allowed),
+            Err(reason) => {
+                tracing::debug!(
+                    "Checking {}#{}@{} in OpenFGA: {}",
+                    object,
+                    relation,
+                    user,
+                    reason
+                );
+                let request = CheckRequestBuilder::new()
+                    .store(store_id)
+                    .model(authorization_model_id)
+                    .tuple(object, relation, user)
+                    .build();
+                Ok(self.check(request).await?.into_inner().allowed)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RelationshipCondition;
+    use crate::test_support::tuple;
+    use serde_json::json;
+
+    /// owner: [user], editor: [user] or owner, viewer: editor,
+    /// reader: [user:*] or viewer from parent
+    fn local_model(tuples: Vec<TupleKey>) -> LocalModel {
+        let model: JsonAuthModel = serde_json::from_value(json!({
+            "schema_version": "1.1",
+            "type_definitions": [
+                { "type": "user" },
+                { "type": "folder", "relations": { "viewer": { "this": {} } } },
+                {
+                    "type": "document",
+                    "relations": {
+                        "owner": { "this": {} },
+                        "parent": { "this": {} },
+                        "editor": { "union": { "child": [
+                            { "this": {} },
+                            { "computedUserset": { "relation": "owner" } }
+                        ] } },
+                        "viewer": { "computedUserset": { "relation": "editor" } },
+                        "reader": { "union": { "child": [
+                            { "this": {} },
+                            { "tupleToUserset": {
+                                "tupleset": { "relation": "parent" },
+                                "computedUserset": { "relation": "viewer" }
+                            } }
+                        ] } }
+                    }
+                }
+            ]
+        }))
+        .unwrap();
+        LocalModel::new(model, tuples)
+    }
+
+    #[test]
+    fn test_direct() {
+        let mut conditioned = tuple("user:carl", "owner", "document:readme");
+        conditioned.condition = Some(RelationshipCondition {
+            name: "in_office".to_string(),
+            context: None,
+        });
+        let local = local_model(vec![
+            tuple("user:anne", "owner", "document:readme"),
+            tuple("group:eng#member", "owner", "document:roadmap"),
+            conditioned,
+        ]);
+
+        assert_eq!(
+            local.local_check("document:readme", "owner", "user:anne"),
+            Ok(true)
+        );
+        assert_eq!(
+            local.local_check("document:readme", "owner", "user:bob"),
+            Ok(false)
+        );
+        assert_eq!(
+            local.local_check("document:readme", "owner", "user:carl"),
+            Err(Unsupported::Condition("in_office".to_string()))
+        );
+        assert_eq!(
+            local.local_check("document:roadmap", "owner", "user:anne"),
+            Err(Unsupported::Userset("group:eng#member".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_computed_and_union() {
+        let local = local_model(vec![
+            tuple("user:anne", "owner", "document:readme"),
+            tuple("user:bob", "editor", "document:readme"),
+            tuple("user:*", "reader", "document:readme"),
+        ]);
+
+        // editor: [user] or owner
+        assert_eq!(
+            local.local_check("document:readme", "editor", "user:anne"),
+            Ok(true)
+        );
+        assert_eq!(
+            local.local_check("document:readme", "editor", "user:bob"),
+            Ok(true)
+        );
+        assert_eq!(
+            local.local_check("document:readme", "editor", "user:carl"),
+            Ok(false)
+        );
+
+        // viewer: editor
+        assert_eq!(
+            local.local_check("document:readme", "viewer", "user:anne"),
+            Ok(true)
+        );
+        assert_eq!(
+            local.local_check("document:readme", "viewer", "user:carl"),
+            Ok(false)
+        );
+
+        // The wildcard decides the union before its tuple-to-userset is reached
+        assert_eq!(
+            local.local_check("document:readme", "reader", "user:carl"),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn test_tuple_to_userset_falls_back() {
+        let local = local_model(vec![tuple("folder:docs", "parent", "document:readme")]);
+
+        assert_eq!(
+            local.local_check("document:readme", "reader", "user:anne"),
+            Err(Unsupported::TupleToUserset {
+                object_type: "document".to_string(),
+                relation: "reader".to_string(),
+            })
+        );
+        assert_eq!(
+            local.local_check("document:readme", "approver", "user:anne"),
+            Err(Unsupported::UnknownRelation {
+                object_type: "document".to_string(),
+                relation: "approver".to_string(),
+            })
+        );
+    }
+}
@@ -0,0 +1,685 @@
+use crate::relation_reference::RelationOrWildcard;
+use crate::{
+    Condition, Metadata, TypeDefinition, Userset, WriteAuthorizationModelRequest, userset,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Schema version used when none is given
+pub const DEFAULT_SCHEMA_VERSION: &str = "1.1";
+
+/// Schema versions accepted by the builder
+pub const SUPPORTED_SCHEMA_VERSIONS: &[&str] = &["1.1", "1.2"];
+
+/// A problem found while validating an authorization model locally
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModelValidationError {
+    /// Where the problem is, e.g. `document#viewer` or `schema_version`
+    pub path: String,
+    pub message: String,
+}
+
+impl ModelValidationError {
+    fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ModelValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Builds a [`WriteAuthorizationModelRequest`], validating the model before it is sent
+#[derive(Debug, Clone, Default)]
+pub struct WriteModelRequestBuilder {
+    store_id: String,
+    schema_version: Option<String>,
+    type_definitions: Vec<TypeDefinition>,
+    conditions: HashMap<String, Condition>,
+    /// Type, relation and module of relations added by another module
+    relation_modules: Vec<(String, String, String)>,
+}
+
+impl WriteModelRequestBuilder {
+    /// Create a builder for the given store
+    pub fn new(store_id: impl Into<String>) -> Self {
+        Self {
+            store_id: store_id.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the schema version; defaults to [`DEFAULT_SCHEMA_VERSION`]
+    pub fn schema_version(mut self, schema_version: impl Into<String>) -> Self {
+        self.schema_version = Some(schema_version.into());
+        self
+    }
+
+    /// Add a type definition
+    pub fn type_definition(mut self, type_definition: TypeDefinition) -> Self {
+        self.type_definitions.push(type_definition);
+        self
+    }
+
+    /// Add several type definitions
+    pub fn type_definitions(
+        mut self,
+        type_definitions: impl IntoIterator<Item = TypeDefinition>,
+    ) -> Self {
+        self.type_definitions.extend(type_definitions);
+        self
+    }
+
+    /// Add a type definition declared in a module of a modular model
+    pub fn module_type_definition(
+        self,
+        module: impl Into<String>,
+        mut type_definition: TypeDefinition,
+    ) -> Self {
+        type_definition
+            .metadata
+            .get_or_insert_with(Metadata::default)
+            .module = module.into();
+        self.type_definition(type_definition)
+    }
+
+    /// Mark a relation as added to its type by `module`, as with `extend type` in a
+    /// modular model. The type and relation must be added to the builder.
+    pub fn relation_module(
+        mut self,
+        type_name: impl Into<String>,
+        relation: impl Into<String>,
+        module: impl Into<String>,
+    ) -> Self {
+        self.relation_modules
+            .push((type_name.into(), relation.into(), module.into()));
+        self
+    }
+
+    /// Add a condition
+    pub fn condition(mut self, name: impl Into<String>, condition: Condition) -> Self {
+        self.conditions.insert(name.into(), condition);
+        self
+    }
+
+    /// Add several conditions
+    pub fn conditions(mut self, conditions: HashMap<String, Condition>) -> Self {
+        self.conditions.extend(conditions);
+        self
+    }
+
+    /// Validate the model and produce the request, or every problem found
+    pub fn build(self) -> Result<WriteAuthorizationModelRequest, Vec<ModelValidationError>> {
+        let mut errors = Vec::new();
+
+        let schema_version = match canonical_schema_version(self.schema_version.as_deref()) {
+            Ok(version) => version,
+            Err(e) => {
+                errors.push(e);
+                String::new()
+            }
+        };
+        let mut type_definitions = self.type_definitions;
+        for (type_name, relation, module) in self.relation_modules {
+            let path = format!("{}#{}", type_name, relation);
+            match type_definitions
+                .iter_mut()
+                .find(|t| t.r#type == type_name && t.relations.contains_key(&relation))
+            {
+                Some(type_def) => {
+                    type_def
+                        .metadata
+                        .get_or_insert_with(Metadata::default)
+                        .relations
+                        .entry(relation)
+                        .or_default()
+                        .module = module;
+                }
+                None => errors.push(ModelValidationError::new(
+                    path,
+                    "module set on an undefined relation",
+                )),
+            }
+        }
+        errors.extend(validate_model(&type_definitions, &self.conditions));
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(WriteAuthorizationModelRequest {
+            store_id: self.store_id,
+            type_definitions,
+            schema_version,
+            conditions: self.conditions,
+        })
+    }
+}
+
+/// Trim the schema version, default it when empty and reject unsupported versions
+pub fn canonical_schema_version(
+    schema_version: Option<&str>,
+) -> Result<String, ModelValidationError> {
+    let version = schema_version.map(str::trim).unwrap_or_default();
+    if version.is_empty() {
+        return Ok(DEFAULT_SCHEMA_VERSION.to_string());
+    }
+    if SUPPORTED_SCHEMA_VERSIONS.contains(&version) {
+        return Ok(version.to_string());
+    }
+
+    Err(ModelValidationError::new(
+        "schema_version",
+        format!(
+            "unsupported schema version {:?}, expected one of {:?}",
+            version, SUPPORTED_SCHEMA_VERSIONS
+        ),
+    ))
+}
+
+/// Check a model for problems OpenFGA would reject: unknown types, dangling relations,
+/// direct relations without allowed user types, undefined conditions and condition
+/// expressions using undeclared parameters
+pub fn validate_model(
+    type_definitions: &[TypeDefinition],
+    conditions: &HashMap<String, Condition>,
+) -> Vec<ModelValidationError> {
+    let mut errors = Vec::new();
+
+    if type_definitions.is_empty() {
+        errors.push(ModelValidationError::new(
+            "type_definitions",
+            "model must define at least one type",
+        ));
+    }
+
+    let mut types: HashMap<&str, &TypeDefinition> = HashMap::new();
+    for type_def in type_definitions {
+        if type_def.r#type.is_empty() {
+            errors.push(ModelValidationError::new(
+                "type_definitions",
+                "type name must not be empty",
+            ));
+        } else if types.insert(&type_def.r#type, type_def).is_some() {
+            errors.push(ModelValidationError::new(
+                &type_def.r#type,
+                "type is defined more than once",
+            ));
+        }
+    }
+
+    for type_def in type_definitions {
+        let validator = TypeValidator {
+            types: &types,
+            type_def,
+            conditions,
+        };
+        validator.validate(&mut errors);
+    }
+
+    let mut condition_names: Vec<_> = conditions.keys().collect();
+    condition_names.sort();
+    for name in condition_names {
+        let condition = &conditions[name];
+        for identifier in undeclared_parameters(condition) {
+            errors.push(ModelValidationError::new(
+                format!("conditions.{}", name),
+                format!(
+                    "expression references undeclared parameter {:?}",
+                    identifier
+                ),
+            ));
+        }
+    }
+
+    errors
+}
+
+/// Words of the CEL grammar that aren't parameter references
+const CEL_KEYWORDS: &[&str] = &["true", "false", "null", "in"];
+
+/// Macros whose first argument binds a variable, as in `list.all(x, x > 0)`
+const CEL_BINDING_MACROS: &[&str] = &["all", "exists", "exists_one", "map", "filter"];
+
+/// Identifiers in a condition expression that aren't declared as parameters.
+///
+/// This is a shallow scan rather than a CEL parser: string literals, member and
+/// function names, keywords and macro variables are skipped, and any other identifier
+/// must be a declared parameter.
+fn undeclared_parameters(condition: &Condition) -> Vec<String> {
+    let chars: Vec<char> = condition.expression.chars().collect();
+    let mut bound: Vec<String> = Vec::new();
+    let mut undeclared: Vec<String> = Vec::new();
+    let mut bind_next = false;
+
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '"' || c == '\'' {
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                if chars[i] == '\\' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            i += 1;
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                i += 1;
+            }
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let identifier: String = chars[start..i].iter().collect();
+            let is_member = chars[..start]
+                .iter()
+                .rev()
+                .find(|c| !c.is_whitespace())
+                .is_some_and(|c| *c == '.');
+            let is_call = chars[i..]
+                .iter()
+                .find(|c| !c.is_whitespace())
+                .is_some_and(|c| *c == '(');
+
+            if bind_next {
+                bind_next = false;
+                bound.push(identifier);
+            } else if is_member || is_call {
+                bind_next =
+                    is_member && is_call && CEL_BINDING_MACROS.contains(&identifier.as_str());
+            } else if !CEL_KEYWORDS.contains(&identifier.as_str())
+                && !bound.contains(&identifier)
+                && !condition.parameters.contains_key(&identifier)
+                && !undeclared.contains(&identifier)
+            {
+                undeclared.push(identifier);
+            }
+        } else {
+            i += 1;
+        }
+    }
+
+    undeclared
+}
+
+struct TypeValidator<'a> {
+    types: &'a HashMap<&'a str, &'a TypeDefinition>,
+    type_def: &'a TypeDefinition,
+    conditions: &'a HashMap<String, Condition>,
+}
+
+impl TypeValidator<'_> {
+    fn validate(&self, errors: &mut Vec<ModelValidationError>) {
+        let type_name = &self.type_def.r#type;
+
+        for (relation, userset) in &self.type_def.relations {
+            let path = format!("{}#{}", type_name, relation);
+            self.validate_userset(&path, relation, userset, errors);
+        }
+
+        let Some(metadata) = &self.type_def.metadata else {
+            return;
+        };
+        for (relation, relation_metadata) in &metadata.relations {
+            let path = format!("{}#{}", type_name, relation);
+            if !self.type_def.relations.contains_key(relation) {
+                errors.push(ModelValidationError::new(
+                    &path,
+                    "metadata refers to an undefined relation",
+                ));
+            }
+
+            for reference in &relation_metadata.directly_related_user_types {
+                let Some(target) = self.types.get(reference.r#type.as_str()) else {
+                    errors.push(ModelValidationError::new(
+                        &path,
+                        format!(
+                            "directly related type {:?} is not defined",
+                            reference.r#type
+                        ),
+                    ));
+                    continue;
+                };
+                if let Some(RelationOrWildcard::Relation(target_relation)) =
+                    &reference.relation_or_wildcard
+                    && !target.relations.contains_key(target_relation)
+                {
+                    errors.push(ModelValidationError::new(
+                        &path,
+                        format!(
+                            "directly related userset {}#{} is not defined",
+                            reference.r#type, target_relation
+                        ),
+                    ));
+                }
+                if !reference.condition.is_empty()
+                    && !self.conditions.contains_key(&reference.condition)
+                {
+                    errors.push(ModelValidationError::new(
+                        &path,
+                        format!("condition {:?} is not defined", reference.condition),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn validate_userset(
+        &self,
+        path: &str,
+        relation: &str,
+        userset: &Userset,
+        errors: &mut Vec<ModelValidationError>,
+    ) {
+        match &userset.userset {
+            None => errors.push(ModelValidationError::new(path, "userset is empty")),
+            Some(userset::Userset::This(_)) => {
+                if self.directly_related_types(relation).is_empty() {
+                    errors.push(ModelValidationError::new(
+                        path,
+                        "direct relation has no directly related user types",
+                    ));
+                }
+            }
+            Some(userset::Userset::ComputedUserset(computed)) => {
+                if !self.type_def.relations.contains_key(&computed.relation) {
+                    errors.push(ModelValidationError::new(
+                        path,
+                        format!(
+                            "computed userset refers to undefined relation {:?}",
+                            computed.relation
+                        ),
+                    ));
+                }
+            }
+            Some(userset::Userset::TupleToUserset(ttu)) => {
+                let tupleset = ttu
+                    .tupleset
+                    .as_ref()
+                    .map(|t| t.relation.as_str())
+                    .unwrap_or_default();
+                let computed = ttu
+                    .computed_userset
+                    .as_ref()
+                    .map(|c| c.relation.as_str())
+                    .unwrap_or_default();
+
+                if !self.type_def.relations.contains_key(tupleset) {
+                    errors.push(ModelValidationError::new(
+                        path,
+                        format!("tupleset refers to undefined relation {:?}", tupleset),
+                    ));
+                    return;
+                }
+
+                let parents = self.directly_related_types(tupleset);
+                let defined_on_parent = parents.iter().any(|parent| {
+                    self.types
+                        .get(parent)
+                        .is_some_and(|t| t.relations.contains_key(computed))
+                });
+                if !parents.is_empty() && !defined_on_parent {
+                    errors.push(ModelValidationError::new(
+                        path,
+                        format!(
+                            "relation {:?} is not defined on any type related through {:?}",
+                            computed, tupleset
+                        ),
+                    ));
+                }
+            }
+            Some(userset::Userset::Union(usersets))
+            | Some(userset::Userset::Intersection(usersets)) => {
+                if usersets.child.is_empty() {
+                    errors.push(ModelValidationError::new(path, "operator has no children"));
+                }
+                for child in &usersets.child {
+                    self.validate_userset(path, relation, child, errors);
+                }
+            }
+            Some(userset::Userset::Difference(difference)) => {
+                for part in [&difference.base, &difference.subtract] {
+                    match part {
+                        Some(part) => self.validate_userset(path, relation, part, errors),
+                        None => errors.push(ModelValidationError::new(
+                            path,
+                            "difference requires base and subtract",
+                        )),
+                    }
+                }
+            }
+        }
+    }
+
+    fn directly_related_types(&self, relation: &str) -> Vec<&str> {
+        self.type_def
+            .metadata
+            .as_ref()
+            .and_then(|m| m.relations.get(relation))
+            .map(|m| {
+                m.directly_related_user_types
+                    .iter()
+                    .map(|r| r.r#type.as_str())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JsonAuthModel;
+
+    fn type_definitions(json: &str) -> Vec<TypeDefinition> {
+        let model: JsonAuthModel = serde_json::from_str(json).unwrap();
+        model.to_openfga_types().unwrap().0
+    }
+
+    const DOCUMENT_MODEL: &str = r#"{
+        "schema_version": "1.1",
+        "type_definitions": [
+            { "type": "user" },
+            {
+                "type": "folder",
+                "relations": { "viewer": { "this": {} } },
+                "metadata": {
+                    "relations": {
+                        "viewer": { "directly_related_user_types": [{ "type": "user" }] }
+                    }
+                }
+            },
+            {
+                "type": "document",
+                "relations": {
+                    "parent": { "this": {} },
+                    "owner": { "this": {} },
+                    "viewer": {
+                        "union": {
+                            "child": [
+                                { "computedUserset": { "relation": "owner" } },
+                                {
+                                    "tupleToUserset": {
+                                        "tupleset": { "relation": "parent" },
+                                        "computedUserset": { "relation": "viewer" }
+                                    }
+                                }
+                            ]
+                        }
+                    }
+                },
+                "metadata": {
+                    "relations": {
+                        "parent": { "directly_related_user_types": [{ "type": "folder" }] },
+                        "owner": { "directly_related_user_types": [{ "type": "user" }] }
+                    }
+                }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_build_valid_model() {
+        let request = WriteModelRequestBuilder::new("store")
+            .schema_version(" 1.1 ")
+            .type_definitions(type_definitions(DOCUMENT_MODEL))
+            .build()
+            .unwrap();
+
+        assert_eq!(request.store_id, "store");
+        assert_eq!(request.schema_version, "1.1");
+        assert_eq!(request.type_definitions.len(), 3);
+    }
+
+    #[test]
+    fn test_build_defaults_schema_version() {
+        let request = WriteModelRequestBuilder::new("store")
+            .type_definitions(type_definitions(DOCUMENT_MODEL))
+            .build()
+            .unwrap();
+
+        assert_eq!(request.schema_version, DEFAULT_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_build_sets_modules() {
+        let mut types = type_definitions(DOCUMENT_MODEL).into_iter();
+        let request = WriteModelRequestBuilder::new("store")
+            .module_type_definition("core", types.next().unwrap())
+            .type_definitions(types)
+            .relation_module("document", "viewer", "sharing")
+            .build()
+            .unwrap();
+
+        let user = &request.type_definitions[0];
+        assert_eq!(user.metadata.as_ref().unwrap().module, "core");
+        let document = &request.type_definitions[2];
+        let relations = &document.metadata.as_ref().unwrap().relations;
+        assert_eq!(relations["viewer"].module, "sharing");
+        assert_eq!(relations["owner"].module, "");
+
+        let errors = WriteModelRequestBuilder::new("store")
+            .type_definitions(type_definitions(DOCUMENT_MODEL))
+            .relation_module("document", "editor", "sharing")
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            errors,
+            vec![ModelValidationError::new(
+                "document#editor",
+                "module set on an undefined relation"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_build_reports_validation_errors() {
+        let json = r#"{
+            "schema_version": "1.1",
+            "type_definitions": [
+                { "type": "user" },
+                {
+                    "type": "document",
+                    "relations": {
+                        "owner": { "this": {} },
+                        "viewer": { "computedUserset": { "relation": "editor" } }
+                    },
+                    "metadata": {
+                        "relations": {
+                            "owner": { "directly_related_user_types": [{ "type": "team" }] }
+                        }
+                    }
+                }
+            ]
+        }"#;
+
+        let errors = WriteModelRequestBuilder::new("store")
+            .schema_version("2.0")
+            .type_definitions(type_definitions(json))
+            .build()
+            .unwrap_err();
+
+        let mut errors: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+        errors.sort();
+        assert_eq!(
+            errors,
+            vec!["document#owner", "document#viewer", "schema_version"]
+        );
+    }
+
+    #[test]
+    fn test_dangling_relation_message() {
+        let json = r#"{
+            "schema_version": "1.1",
+            "type_definitions": [
+                {
+                    "type": "document",
+                    "relations": { "viewer": { "computedUserset": { "relation": "editor" } } }
+                }
+            ]
+        }"#;
+
+        let errors = validate_model(&type_definitions(json), &HashMap::new());
+
+        assert_eq!(
+            errors,
+            vec![ModelValidationError::new(
+                "document#viewer",
+                "computed userset refers to undefined relation \"editor\"",
+            )]
+        );
+    }
+
+    #[test]
+    fn test_condition_with_undeclared_parameter() {
+        use crate::ConditionParamTypeRef;
+        use crate::condition_param_type_ref::TypeName;
+
+        let int_param = ConditionParamTypeRef {
+            type_name: TypeName::Int as i32,
+            generic_types: Vec::new(),
+        };
+        let conditions = HashMap::from([
+            (
+                "less_than".to_string(),
+                Condition {
+                    name: "less_than".to_string(),
+                    expression: "x < limit".to_string(),
+                    parameters: HashMap::from([("limit".to_string(), int_param.clone())]),
+                    metadata: None,
+                },
+            ),
+            (
+                "allowed_ip".to_string(),
+                Condition {
+                    name: "allowed_ip".to_string(),
+                    expression: "cidrs.exists(c, ip.in_cidr(c)) && \"x\" != 'y' && size(cidrs) > 0"
+                        .to_string(),
+                    parameters: HashMap::from([
+                        ("cidrs".to_string(), int_param.clone()),
+                        ("ip".to_string(), int_param),
+                    ]),
+                    metadata: None,
+                },
+            ),
+        ]);
+
+        let errors = validate_model(&type_definitions(DOCUMENT_MODEL), &conditions);
+
+        assert_eq!(
+            errors,
+            vec![ModelValidationError::new(
+                "conditions.less_than",
+                "expression references undeclared parameter \"x\"",
+            )]
+        );
+    }
+}
@@ -0,0 +1,305 @@
+use crate::json_types::{JsonAuthModel, JsonMetadata, JsonTypeDefinition};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Error returned by [`merge_models`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeError {
+    /// No fragments were given
+    NoFragments,
+    /// A fragment uses a different schema version than the first fragment
+    SchemaVersionMismatch {
+        fragment: usize,
+        expected: String,
+        found: String,
+    },
+    /// Two fragments define the same relation of a type differently
+    RelationConflict {
+        type_name: String,
+        relation: String,
+        fragments: (usize, usize),
+    },
+    /// Two fragments define the same condition differently
+    ConditionConflict {
+        name: String,
+        fragments: (usize, usize),
+    },
+}
+
+impl std::fmt::Display for MergeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MergeError::NoFragments => write!(f, "no model fragments to merge"),
+            MergeError::SchemaVersionMismatch {
+                fragment,
+                expected,
+                found,
+            } => write!(
+                f,
+                "fragment {} uses schema version {:?}, expected {:?}",
+                fragment, found, expected
+            ),
+            MergeError::RelationConflict {
+                type_name,
+                relation,
+                fragments: (first, second),
+            } => write!(
+                f,
+                "relation {}#{} is defined differently in fragments {} and {}",
+                type_name, relation, first, second
+            ),
+            MergeError::ConditionConflict {
+                name,
+                fragments: (first, second),
+            } => write!(
+                f,
+                "condition {:?} is defined differently in fragments {} and {}",
+                name, first, second
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MergeError {}
+
+/// Merge modular model fragments into a single model.
+///
+/// A type may appear in several fragments, as with `extend type` in modular models:
+/// its relations are unioned, and a relation defined in more than one fragment must be
+/// identical everywhere. Conditions are merged the same way. Type order follows the
+/// first fragment that defines each type.
+pub fn merge_models(fragments: &[JsonAuthModel]) -> Result<JsonAuthModel, MergeError> {
+    let first = fragments.first().ok_or(MergeError::NoFragments)?;
+
+    let mut merged = JsonAuthModel {
+        schema_version: first.schema_version.clone(),
+        type_definitions: Vec::new(),
+        conditions: HashMap::new(),
+    };
+    // Fragment that first defined each type, relation and condition
+    let mut type_index: HashMap<String, usize> = HashMap::new();
+    let mut relation_source: HashMap<(String, String), usize> = HashMap::new();
+    let mut condition_source: HashMap<String, usize> = HashMap::new();
+
+    for (fragment_index, fragment) in fragments.iter().enumerate() {
+        if fragment.schema_version != merged.schema_version {
+            return Err(MergeError::SchemaVersionMismatch {
+                fragment: fragment_index,
+                expected: merged.schema_version.clone(),
+                found: fragment.schema_version.clone(),
+            });
+        }
+
+        for type_def in &fragment.type_definitions {
+            let Some(&index) = type_index.get(&type_def.type_name) else {
+                type_index.insert(type_def.type_name.clone(), merged.type_definitions.len());
+                for relation in type_def.relations.keys() {
+                    relation_source.insert(
+                        (type_def.type_name.clone(), relation.clone()),
+                        fragment_index,
+                    );
+                }
+                merged.type_definitions.push(type_def.clone());
+                continue;
+            };
+
+            let target = &mut merged.type_definitions[index];
+            for (relation, userset) in &type_def.relations {
+                let key = (type_def.type_name.clone(), relation.clone());
+                match target.relations.get(relation) {
+                    Some(existing)
+                        if !same_json(existing, userset)
+                            || !same_json(
+                                &directly_related_user_types(target, relation),
+                                &directly_related_user_types(type_def, relation),
+                            ) =>
+                    {
+                        return Err(MergeError::RelationConflict {
+                            type_name: type_def.type_name.clone(),
+                            relation: relation.clone(),
+                            fragments: (relation_source[&key], fragment_index),
+                        });
+                    }
+                    Some(_) => {}
+                    None => {
+                        target.relations.insert(relation.clone(), userset.clone());
+                        merge_relation_metadata(target, type_def, relation);
+                        relation_source.insert(key, fragment_index);
+                    }
+                }
+            }
+        }
+
+        for (name, condition) in &fragment.conditions {
+            match merged.conditions.get(name) {
+                Some(existing) if existing != condition => {
+                    return Err(MergeError::ConditionConflict {
+                        name: name.clone(),
+                        fragments: (condition_source[name], fragment_index),
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    merged.conditions.insert(name.clone(), condition.clone());
+                    condition_source.insert(name.clone(), fragment_index);
+                }
+            }
+        }
+    }
+
+    Ok(merged)
+}
+
+fn same_json<T: Serialize>(a: &T, b: &T) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+fn directly_related_user_types(type_def: &JsonTypeDefinition, relation: &str) -> serde_json::Value {
+    type_def
+        .metadata
+        .as_ref()
+        .and_then(|m| m.relations.as_ref())
+        .and_then(|r| r.get(relation))
+        .and_then(|m| serde_json::to_value(&m.directly_related_user_types).ok())
+        .unwrap_or_else(|| serde_json::Value::Array(Vec::new()))
+}
+
+/// Copy the metadata of `relation` from `source` into `target`
+fn merge_relation_metadata(
+    target: &mut JsonTypeDefinition,
+    source: &JsonTypeDefinition,
+    relation: &str,
+) {
+    let Some(relation_metadata) = source
+        .metadata
+        .as_ref()
+        .and_then(|m| m.relations.as_ref())
+        .and_then(|r| r.get(relation))
+    else {
+        return;
+    };
+
+    let metadata = target.metadata.get_or_insert(JsonMetadata {
+        relations: None,
+        module: None,
+        source_info: None,
+    });
+    metadata
+        .relations
+        .get_or_insert_with(HashMap::new)
+        .insert(relation.to_string(), relation_metadata.clone());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(json: &str) -> JsonAuthModel {
+        serde_json::from_str(json).unwrap()
+    }
+
+    const CORE: &str = r#"{
+        "schema_version": "1.2",
+        "type_definitions": [
+            { "type": "user" },
+            {
+                "type": "organization",
+                "relations": { "member": { "this": {} } },
+                "metadata": {
+                    "relations": {
+                        "member": { "directly_related_user_types": [{ "type": "user" }] }
+                    }
+                }
+            }
+        ]
+    }"#;
+
+    const ISSUE_TRACKER: &str = r#"{
+        "schema_version": "1.2",
+        "type_definitions": [
+            { "type": "user" },
+            {
+                "type": "organization",
+                "relations": { "can_create_project": { "computedUserset": { "relation": "member" } } }
+            },
+            {
+                "type": "project",
+                "relations": { "owner": { "this": {} } },
+                "metadata": {
+                    "relations": {
+                        "owner": { "directly_related_user_types": [{ "type": "organization" }] }
+                    }
+                }
+            }
+        ],
+        "conditions": {
+            "non_expired": { "name": "non_expired", "expression": "current_time < expires_at" }
+        }
+    }"#;
+
+    #[test]
+    fn test_merge_two_fragments() {
+        let merged = merge_models(&[model(CORE), model(ISSUE_TRACKER)]).unwrap();
+
+        let types: Vec<_> = merged
+            .type_definitions
+            .iter()
+            .map(|t| t.type_name.as_str())
+            .collect();
+        assert_eq!(types, vec!["user", "organization", "project"]);
+
+        let organization = &merged.type_definitions[1];
+        let mut relations: Vec<_> = organization.relations.keys().cloned().collect();
+        relations.sort();
+        assert_eq!(relations, vec!["can_create_project", "member"]);
+        assert!(merged.conditions.contains_key("non_expired"));
+
+        // The merged model is still a valid model
+        assert!(merged.to_openfga_types().is_ok());
+    }
+
+    #[test]
+    fn test_merge_conflicting_type_redefinition() {
+        let conflicting = r#"{
+            "schema_version": "1.2",
+            "type_definitions": [
+                {
+                    "type": "organization",
+                    "relations": { "member": { "computedUserset": { "relation": "admin" } } }
+                }
+            ]
+        }"#;
+
+        let err = merge_models(&[model(CORE), model(conflicting)]).unwrap_err();
+
+        assert_eq!(
+            err,
+            MergeError::RelationConflict {
+                type_name: "organization".to_string(),
+                relation: "member".to_string(),
+                fragments: (0, 1),
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "relation organization#member is defined differently in fragments 0 and 1"
+        );
+    }
+
+    #[test]
+    fn test_merge_conflicting_conditions() {
+        let other = r#"{
+            "schema_version": "1.2",
+            "type_definitions": [],
+            "conditions": {
+                "non_expired": { "name": "non_expired", "expression": "true" }
+            }
+        }"#;
+
+        let err = merge_models(&[model(ISSUE_TRACKER), model(other)]).unwrap_err();
+
+        assert!(
+            matches!(err, MergeError::ConditionConflict { ref name, .. } if name == "non_expired")
+        );
+    }
+}
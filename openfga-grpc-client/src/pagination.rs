@@ -0,0 +1,235 @@
+use crate::bundle::StoreBackend;
+use crate::{
+    AuthorizationModel, ErrorCode, ListStoresRequest, OpenFGAClient,
+    ReadAuthorizationModelsRequest, ReadRequest, Store, Tuple,
+};
+
+/// Error returned by the paginated `read_all_*`/`list_all_*` helpers
+#[derive(Debug)]
+pub enum PaginationError {
+    /// The continuation token expired or is malformed; restart from the beginning
+    InvalidToken(tonic::Status),
+    /// Any other error returned by OpenFGA
+    Status(tonic::Status),
+}
+
+impl PaginationError {
+    /// Get the underlying gRPC status
+    pub fn status(&self) -> &tonic::Status {
+        match self {
+            PaginationError::InvalidToken(status) | PaginationError::Status(status) => status,
+        }
+    }
+}
+
+impl From<tonic::Status> for PaginationError {
+    fn from(status: tonic::Status) -> Self {
+        if is_invalid_continuation_token(&status) {
+            PaginationError::InvalidToken(status)
+        } else {
+            PaginationError::Status(status)
+        }
+    }
+}
+
+impl std::fmt::Display for PaginationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaginationError::InvalidToken(status) => {
+                write!(f, "invalid continuation token: {}", status.message())
+            }
+            PaginationError::Status(status) => write!(f, "{}", status),
+        }
+    }
+}
+
+impl std::error::Error for PaginationError {}
+
+/// Check whether a status is OpenFGA's "invalid_continuation_token" error.
+///
+/// OpenFGA reports it with its own error code (2007) as the gRPC code, which tonic
+/// surfaces as `Unknown`, so the classification relies on the status message.
+pub fn is_invalid_continuation_token(status: &tonic::Status) -> bool {
+    let message = status.message().to_lowercase();
+    message.contains(ErrorCode::InvalidContinuationToken.as_str_name())
+        || message.contains("invalid continuation token")
+}
+
+// Helpers that follow continuation tokens until all pages are read
+impl OpenFGAClient {
+    /// Read all tuples matching the request, following continuation tokens
+    pub async fn read_all(
+        &mut self,
+        mut request: ReadRequest,
+    ) -> Result<Vec<Tuple>, PaginationError> {
+        let mut tuples = Vec::new();
+
+        loop {
+            let response = self.read(request.clone()).await?.into_inner();
+            tuples.extend(response.tuples);
+
+            if response.continuation_token.is_empty() {
+                break;
+            }
+            request.continuation_token = response.continuation_token;
+        }
+
+        Ok(tuples)
+    }
+
+    /// List all stores, following continuation tokens
+    pub async fn list_all_stores(
+        &mut self,
+        mut request: ListStoresRequest,
+    ) -> Result<Vec<Store>, PaginationError> {
+        let mut stores = Vec::new();
+
+        loop {
+            let response = self.list_stores(request.clone()).await?.into_inner();
+            stores.extend(response.stores);
+
+            if response.continuation_token.is_empty() {
+                break;
+            }
+            request.continuation_token = response.continuation_token;
+        }
+
+        Ok(stores)
+    }
+
+    /// Read all authorization models of a store, following continuation tokens
+    pub async fn read_all_authorization_models(
+        &mut self,
+        mut request: ReadAuthorizationModelsRequest,
+    ) -> Result<Vec<AuthorizationModel>, PaginationError> {
+        let mut models = Vec::new();
+
+        loop {
+            let response = self
+                .read_authorization_models(request.clone())
+                .await?
+                .into_inner();
+            models.extend(response.authorization_models);
+
+            if response.continuation_token.is_empty() {
+                break;
+            }
+            request.continuation_token = response.continuation_token;
+        }
+
+        Ok(models)
+    }
+
+    /// Get a store's authorization model by age: 0 is the latest model, 1 the one
+    /// before it, and so on
+    pub async fn get_model_at_offset(
+        &mut self,
+        store_id: &str,
+        offset: usize,
+    ) -> Result<AuthorizationModel, tonic::Status> {
+        model_at_offset(self, store_id, offset).await
+    }
+}
+
+/// Page through a store's models, newest first, until the model at `offset` is reached.
+///
+/// Fails with `OutOfRange` if the store has `offset` or fewer models.
+pub async fn model_at_offset<B: StoreBackend>(
+    client: &mut B,
+    store_id: &str,
+    offset: usize,
+) -> Result<AuthorizationModel, tonic::Status> {
+    let mut request = ReadAuthorizationModelsRequest {
+        store_id: store_id.to_string(),
+        page_size: None,
+        continuation_token: String::new(),
+    };
+    let mut seen = 0;
+
+    loop {
+        let response = client
+            .read_authorization_models(request.clone())
+            .await?
+            .into_inner();
+        let page_len = response.authorization_models.len();
+        if let Some(model) = response.authorization_models.into_iter().nth(offset - seen) {
+            return Ok(model);
+        }
+        seen += page_len;
+
+        if response.continuation_token.is_empty() {
+            return Err(tonic::Status::out_of_range(format!(
+                "store {} has {} authorization model(s), offset {} is out of range",
+                store_id, seen, offset
+            )));
+        }
+        request.continuation_token = response.continuation_token;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MemoryState, MemoryStore};
+
+    /// Store serving `ids` newest first, one model per page
+    fn models(ids: &[&str]) -> MemoryStore {
+        MemoryStore::from(MemoryState {
+            models: ids
+                .iter()
+                .rev()
+                .map(|id| AuthorizationModel {
+                    id: id.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            page_size: 1,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_model_at_offset() {
+        let mut client = models(&["newest", "previous", "oldest"]);
+
+        let model = model_at_offset(&mut client, "store", 1).await.unwrap();
+        assert_eq!(model.id, "previous");
+
+        let model = model_at_offset(&mut client, "store", 0).await.unwrap();
+        assert_eq!(model.id, "newest");
+
+        let status = model_at_offset(&mut client, "store", 3).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::OutOfRange);
+        assert_eq!(
+            status.message(),
+            "store store has 3 authorization model(s), offset 3 is out of range"
+        );
+    }
+
+    #[test]
+    fn test_invalid_continuation_token_is_classified() {
+        let status = tonic::Status::invalid_argument("invalid continuation token");
+        assert!(matches!(
+            PaginationError::from(status),
+            PaginationError::InvalidToken(_)
+        ));
+
+        let status = tonic::Status::new(
+            tonic::Code::Unknown,
+            "invalid_continuation_token: token could not be decoded",
+        );
+        assert!(matches!(
+            PaginationError::from(status),
+            PaginationError::InvalidToken(_)
+        ));
+    }
+
+    #[test]
+    fn test_other_errors_are_not_classified_as_invalid_token() {
+        let status = tonic::Status::not_found("store not found");
+        let error = PaginationError::from(status);
+
+        assert!(matches!(error, PaginationError::Status(_)));
+        assert_eq!(error.status().code(), tonic::Code::NotFound);
+    }
+}
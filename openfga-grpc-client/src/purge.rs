@@ -0,0 +1,172 @@
+use crate::bundle::StoreBackend;
+use crate::exists::TupleId;
+use crate::{
+    ConsistencyPreference, OpenFGAClient, ReadRequest, ReadRequestTupleKey, TupleKey,
+    TupleKeyWithoutCondition, WriteRequest, WriteRequestDeletes,
+};
+use std::collections::HashSet;
+
+/// Tuples deleted per write in [`purge_object`], OpenFGA's default limit
+pub const PURGE_CHUNK_SIZE: usize = 100;
+
+/// Page size used when reading the tuples to purge
+const PURGE_PAGE_SIZE: i32 = 100;
+
+impl OpenFGAClient {
+    /// Delete every tuple of a decommissioned object, e.g. `document:readme`, and
+    /// return how many were deleted. See [`purge_object`].
+    pub async fn purge_object(
+        &mut self,
+        store_id: &str,
+        object: &str,
+        as_user_types: &[&str],
+    ) -> Result<usize, tonic::Status> {
+        purge_object(self, store_id, object, as_user_types).await
+    }
+}
+
+/// Delete the tuples with `object` as their object, across relations, in chunks of
+/// [`PURGE_CHUNK_SIZE`].
+///
+/// Tuples with `object` as their user are only found by type, so they're purged from
+/// the objects of `as_user_types`, with one paginated read per type; pass no types to
+/// skip that sweep. Usersets of the object, such as `group:eng#member`, aren't matched.
+/// All tuples are read before the first delete.
+pub async fn purge_object<B: StoreBackend>(
+    client: &mut B,
+    store_id: &str,
+    object: &str,
+    as_user_types: &[&str],
+) -> Result<usize, tonic::Status> {
+    let mut tuples = read_tuples(client, store_id, String::new(), object.to_string()).await?;
+    for object_type in as_user_types {
+        tuples.extend(
+            read_tuples(
+                client,
+                store_id,
+                object.to_string(),
+                format!("{}:", object_type),
+            )
+            .await?,
+        );
+    }
+
+    let mut seen = HashSet::new();
+    let deletes: Vec<TupleKeyWithoutCondition> = tuples
+        .into_iter()
+        .filter(|key| seen.insert(TupleId::from(key)))
+        .map(|key| TupleKeyWithoutCondition {
+            user: key.user,
+            relation: key.relation,
+            object: key.object,
+        })
+        .collect();
+
+    for chunk in deletes.chunks(PURGE_CHUNK_SIZE) {
+        client
+            .write(WriteRequest {
+                store_id: store_id.to_string(),
+                authorization_model_id: String::new(),
+                writes: None,
+                deletes: Some(WriteRequestDeletes {
+                    tuple_keys: chunk.to_vec(),
+                    on_missing: "ignore".to_string(),
+                }),
+            })
+            .await?;
+    }
+
+    Ok(deletes.len())
+}
+
+/// All tuples matching `user` and `object`, following continuation tokens
+async fn read_tuples<B: StoreBackend>(
+    client: &mut B,
+    store_id: &str,
+    user: String,
+    object: String,
+) -> Result<Vec<TupleKey>, tonic::Status> {
+    let mut tuples = Vec::new();
+    let mut request = ReadRequest {
+        store_id: store_id.to_string(),
+        tuple_key: Some(ReadRequestTupleKey {
+            user,
+            relation: String::new(),
+            object,
+        }),
+        page_size: Some(PURGE_PAGE_SIZE),
+        continuation_token: String::new(),
+        consistency: ConsistencyPreference::HigherConsistency as i32,
+    };
+    loop {
+        let response = client.read(request.clone()).await?.into_inner();
+        tuples.extend(response.tuples.into_iter().filter_map(|tuple| tuple.key));
+
+        if response.continuation_token.is_empty() {
+            return Ok(tuples);
+        }
+        request.continuation_token = response.continuation_token;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MemoryStore, tuple};
+
+    fn delete(user: &str, relation: &str, object: &str) -> TupleKeyWithoutCondition {
+        TupleKeyWithoutCondition {
+            user: user.to_string(),
+            relation: relation.to_string(),
+            object: object.to_string(),
+        }
+    }
+
+    /// Tuples deleted by each write to `store`
+    fn deletes(store: &MemoryStore) -> Vec<Vec<TupleKeyWithoutCondition>> {
+        store
+            .state()
+            .writes
+            .iter()
+            .map(|write| write.deletes.clone().unwrap().tuple_keys)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_purge_object() {
+        let store = || {
+            MemoryStore::with_tuples(vec![
+                tuple("user:anne", "owner", "document:readme"),
+                tuple("group:eng#member", "viewer", "document:readme"),
+                tuple("user:anne", "owner", "document:roadmap"),
+                tuple("document:readme", "item", "folder:docs"),
+            ])
+        };
+
+        let mut purged = store();
+        let deleted = purge_object(&mut purged, "store", "document:readme", &[])
+            .await
+            .unwrap();
+        assert_eq!(deleted, 2);
+        assert_eq!(purged.state().reads.len(), 1);
+        assert_eq!(
+            deletes(&purged),
+            vec![vec![
+                delete("user:anne", "owner", "document:readme"),
+                delete("group:eng#member", "viewer", "document:readme"),
+            ]]
+        );
+        assert_eq!(purged.state().tuples.len(), 2);
+
+        // The sweep for the object as a user is opt-in
+        let mut purged = store();
+        let deleted = purge_object(&mut purged, "store", "document:readme", &["folder"])
+            .await
+            .unwrap();
+        assert_eq!(deleted, 3);
+        assert_eq!(
+            deletes(&purged)[0].last(),
+            Some(&delete("document:readme", "item", "folder:docs"))
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use crate::bundle::StoreBackend;
+use crate::{ConsistencyPreference, OpenFGAClient, ReadRequest, ReadRequestTupleKey};
+use std::collections::HashMap;
+
+/// Page size used by [`read_by_object`]
+const READ_PAGE_SIZE: i32 = 100;
+
+impl OpenFGAClient {
+    /// Users holding each relation to `object`, e.g. `document:readme`. See
+    /// [`read_by_object`].
+    pub async fn read_by_object(
+        &mut self,
+        store_id: &str,
+        object: &str,
+    ) -> Result<HashMap<String, Vec<String>>, tonic::Status> {
+        read_by_object(self, store_id, object).await
+    }
+}
+
+/// Read every tuple of `object`, following continuation tokens, and group their users
+/// by relation, in the order they were read. Only the stored tuples are returned;
+/// relations computed by the model, such as a viewer implied by owner, aren't.
+pub async fn read_by_object<B: StoreBackend>(
+    client: &mut B,
+    store_id: &str,
+    object: &str,
+) -> Result<HashMap<String, Vec<String>>, tonic::Status> {
+    let mut request = ReadRequest {
+        store_id: store_id.to_string(),
+        tuple_key: Some(ReadRequestTupleKey {
+            user: String::new(),
+            relation: String::new(),
+            object: object.to_string(),
+        }),
+        page_size: Some(READ_PAGE_SIZE),
+        continuation_token: String::new(),
+        consistency: ConsistencyPreference::Unspecified as i32,
+    };
+
+    let mut relations: HashMap<String, Vec<String>> = HashMap::new();
+    loop {
+        let response = client.read(request.clone()).await?.into_inner();
+        for key in response.tuples.into_iter().filter_map(|tuple| tuple.key) {
+            relations.entry(key.relation).or_default().push(key.user);
+        }
+
+        if response.continuation_token.is_empty() {
+            return Ok(relations);
+        }
+        request.continuation_token = response.continuation_token;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MemoryState, MemoryStore, tuple};
+
+    #[tokio::test]
+    async fn test_read_by_object_groups_by_relation() {
+        let mut store = MemoryStore::from(MemoryState {
+            tuples: vec![
+                tuple("user:anne", "owner", "document:readme"),
+                tuple("user:anne", "owner", "document:roadmap"),
+                tuple("user:bob", "viewer", "document:readme"),
+                tuple("group:eng#member", "viewer", "document:readme"),
+            ],
+            page_size: 2,
+            ..Default::default()
+        });
+
+        let relations = read_by_object(&mut store, "store", "document:readme")
+            .await
+            .unwrap();
+        assert_eq!(
+            relations,
+            HashMap::from([
+                ("owner".to_string(), vec!["user:anne".to_string()]),
+                (
+                    "viewer".to_string(),
+                    vec!["user:bob".to_string(), "group:eng#member".to_string()]
+                ),
+            ])
+        );
+
+        // Only the object is filtered on, across both pages
+        let state = store.state();
+        assert_eq!(state.reads.len(), 2);
+        let filter = state.reads[0].tuple_key.as_ref().unwrap();
+        assert_eq!(
+            (
+                filter.object.as_str(),
+                filter.relation.as_str(),
+                filter.user.as_str()
+            ),
+            ("document:readme", "", "")
+        );
+        assert_eq!(state.reads[1].continuation_token, "2");
+    }
+}
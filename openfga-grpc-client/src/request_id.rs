@@ -0,0 +1,70 @@
+use std::future::Future;
+use tonic::metadata::AsciiMetadataValue;
+use tonic::service::Interceptor;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::Channel;
+
+/// Metadata key carrying the request ID to OpenFGA
+pub const REQUEST_ID_METADATA: &str = "x-request-id";
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Interceptor adding the request ID of the current task to each call
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequestIdInterceptor;
+
+impl Interceptor for RequestIdInterceptor {
+    fn call(
+        &mut self,
+        mut request: tonic::Request<()>,
+    ) -> Result<tonic::Request<()>, tonic::Status> {
+        insert_request_id(&mut request);
+        Ok(request)
+    }
+}
+
+/// Channel of a generated client whose calls carry the request ID, e.g.
+/// `OpenFgaServiceClient::with_interceptor(channel, RequestIdInterceptor)`. `C` is
+/// the wrapped channel, e.g. an [`ErrorCodeChannel`](crate::ErrorCodeChannel).
+pub type RequestIdChannel<C = Channel> = InterceptedService<C, RequestIdInterceptor>;
+
+/// Run `future` with `request_id` sent as `x-request-id` metadata on the OpenFGA
+/// calls made through [`OpenFGAClient`](crate::OpenFGAClient) or a [`RequestIdChannel`]
+pub async fn with_request_id<F: Future>(request_id: String, future: F) -> F::Output {
+    REQUEST_ID.scope(request_id, future).await
+}
+
+/// Request ID of the current task, `None` outside [`with_request_id`]
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+/// Add the request ID of the current task to `request`'s metadata. IDs that aren't
+/// valid metadata values are left out.
+pub fn insert_request_id<T>(request: &mut tonic::Request<T>) {
+    if let Some(value) = current_request_id().and_then(|id| id.parse::<AsciiMetadataValue>().ok()) {
+        request.metadata_mut().insert(REQUEST_ID_METADATA, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_request_id_metadata() {
+        let request = RequestIdInterceptor.call(tonic::Request::new(())).unwrap();
+        assert!(request.metadata().get(REQUEST_ID_METADATA).is_none());
+
+        let request = with_request_id("req-42".to_string(), async {
+            RequestIdInterceptor.call(tonic::Request::new(())).unwrap()
+        })
+        .await;
+        assert_eq!(
+            request.metadata().get(REQUEST_ID_METADATA).unwrap(),
+            "req-42"
+        );
+    }
+}
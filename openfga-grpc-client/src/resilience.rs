@@ -0,0 +1,482 @@
+use crate::{CheckRequest, OpenFGAClient};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tonic::{Code, Status};
+
+/// Thresholds for the circuit breaker and retry budget of a [`Resilience`]
+#[derive(Debug, Clone)]
+pub struct ResilienceConfig {
+    /// Consecutive failures that open the circuit
+    pub failure_threshold: u32,
+    /// How long an open circuit fast-fails before letting a probe through
+    pub cool_down: Duration,
+    /// Retries of a single call after its first attempt
+    pub max_retries: u32,
+    /// Delay before each retry
+    pub retry_backoff: Duration,
+    /// Retries earned per call, e.g. `0.1` allows one retry for every ten calls
+    pub retry_ratio: f64,
+    /// Retries available up front, which is also the most that can be saved up
+    pub min_retries: u32,
+}
+
+impl Default for ResilienceConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cool_down: Duration::from_secs(30),
+            max_retries: 2,
+            retry_backoff: Duration::from_millis(100),
+            retry_ratio: 0.1,
+            min_retries: 10,
+        }
+    }
+}
+
+/// State of a [`CircuitBreaker`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls go through
+    Closed,
+    /// Calls fail fast until the cool-down has passed
+    Open,
+    /// A single probe call is let through to test recovery
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct BreakerState {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    probing: bool,
+}
+
+/// Circuit breaker that opens after consecutive failures
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    inner: Mutex<BreakerState>,
+    failure_threshold: u32,
+    cool_down: Duration,
+}
+
+impl CircuitBreaker {
+    /// Create a closed breaker
+    pub fn new(failure_threshold: u32, cool_down: Duration) -> Self {
+        Self {
+            inner: Mutex::new(BreakerState {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                probing: false,
+            }),
+            failure_threshold: failure_threshold.max(1),
+            cool_down,
+        }
+    }
+
+    /// Current state; an open breaker whose cool-down has passed reports `HalfOpen`
+    pub fn state(&self) -> CircuitState {
+        let mut inner = self.inner.lock().unwrap();
+        self.refresh(&mut inner);
+        inner.state
+    }
+
+    /// Get permission for a call; denied while the circuit is open or a half-open
+    /// probe is already in flight. The call's outcome is recorded through the permit.
+    pub fn try_acquire(&self) -> Option<CircuitPermit<'_>> {
+        let mut inner = self.inner.lock().unwrap();
+        self.refresh(&mut inner);
+        let probe = match inner.state {
+            CircuitState::Closed => false,
+            CircuitState::HalfOpen if !inner.probing => {
+                inner.probing = true;
+                true
+            }
+            CircuitState::Open | CircuitState::HalfOpen => return None,
+        };
+        Some(CircuitPermit {
+            breaker: self,
+            probe,
+        })
+    }
+
+    /// Record a call that reached a healthy server, closing the circuit
+    fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.state = CircuitState::Closed;
+        inner.consecutive_failures = 0;
+        inner.opened_at = None;
+        inner.probing = false;
+    }
+
+    /// Record a failed call, opening the circuit at the threshold or when a probe fails
+    fn record_failure(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.probing = false;
+        inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+
+        let open = match inner.state {
+            CircuitState::Closed => inner.consecutive_failures >= self.failure_threshold,
+            CircuitState::HalfOpen => true,
+            CircuitState::Open => false,
+        };
+        if open {
+            tracing::warn!(
+                "Opening OpenFGA circuit breaker after {} consecutive failure(s)",
+                inner.consecutive_failures
+            );
+            inner.state = CircuitState::Open;
+            inner.opened_at = Some(Instant::now());
+        }
+    }
+
+    fn refresh(&self, inner: &mut BreakerState) {
+        if inner.state == CircuitState::Open
+            && inner
+                .opened_at
+                .is_some_and(|opened_at| opened_at.elapsed() >= self.cool_down)
+        {
+            inner.state = CircuitState::HalfOpen;
+            inner.probing = false;
+        }
+    }
+}
+
+/// Permission for one call through a [`CircuitBreaker`].
+///
+/// A permit dropped without an outcome, e.g. because the call was cancelled or timed
+/// out, records nothing but frees the half-open probe slot so a later call can probe.
+#[derive(Debug)]
+pub struct CircuitPermit<'a> {
+    breaker: &'a CircuitBreaker,
+    probe: bool,
+}
+
+impl CircuitPermit<'_> {
+    /// Record that the call reached a healthy server, closing the circuit
+    pub fn record_success(self) {
+        self.breaker.record_success();
+        std::mem::forget(self);
+    }
+
+    /// Record a failed call, opening the circuit at the threshold or when a probe fails
+    pub fn record_failure(self) {
+        self.breaker.record_failure();
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for CircuitPermit<'_> {
+    fn drop(&mut self) {
+        if self.probe {
+            self.breaker.inner.lock().unwrap().probing = false;
+        }
+    }
+}
+
+/// Token bucket limiting retries to a fraction of calls.
+///
+/// Every call deposits `ratio` tokens and every retry withdraws one, so once the
+/// initial `min_retries` are spent retries can't exceed `ratio` of the calls made.
+#[derive(Debug)]
+pub struct RetryBudget {
+    balance: Mutex<f64>,
+    ratio: f64,
+    max_balance: f64,
+}
+
+impl RetryBudget {
+    /// Create a budget with `min_retries` tokens available
+    pub fn new(ratio: f64, min_retries: u32) -> Self {
+        let max_balance = f64::from(min_retries.max(1));
+        Self {
+            balance: Mutex::new(f64::from(min_retries)),
+            ratio: ratio.max(0.0),
+            max_balance,
+        }
+    }
+
+    /// Deposit the tokens earned by a call
+    pub fn record_call(&self) {
+        let mut balance = self.balance.lock().unwrap();
+        *balance = (*balance + self.ratio).min(self.max_balance);
+    }
+
+    /// Withdraw a token for a retry, returning whether the retry is allowed
+    pub fn try_withdraw(&self) -> bool {
+        let mut balance = self.balance.lock().unwrap();
+        if *balance >= 1.0 {
+            *balance -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Circuit breaker and retry budget shared by the calls of one or more clients
+#[derive(Debug)]
+pub struct Resilience {
+    breaker: CircuitBreaker,
+    budget: RetryBudget,
+    max_retries: u32,
+    retry_backoff: Duration,
+}
+
+impl Resilience {
+    pub fn new(config: &ResilienceConfig) -> Self {
+        Self {
+            breaker: CircuitBreaker::new(config.failure_threshold, config.cool_down),
+            budget: RetryBudget::new(config.retry_ratio, config.min_retries),
+            max_retries: config.max_retries,
+            retry_backoff: config.retry_backoff,
+        }
+    }
+
+    /// Get the circuit breaker
+    pub fn breaker(&self) -> &CircuitBreaker {
+        &self.breaker
+    }
+
+    /// Run a call through the circuit breaker, retrying transient failures while the
+    /// retry budget allows. Fails fast with `UNAVAILABLE` while the circuit is open.
+    ///
+    /// Only outage errors (`UNAVAILABLE`, `DEADLINE_EXCEEDED`, `INTERNAL`, `UNKNOWN`)
+    /// count as failures; any other response shows the server is up.
+    pub async fn run<T>(
+        &self,
+        mut call: impl AsyncFnMut() -> Result<T, Status>,
+    ) -> Result<T, Status> {
+        self.budget.record_call();
+
+        let mut retries = 0;
+        loop {
+            let Some(permit) = self.breaker.try_acquire() else {
+                return Err(Status::unavailable("OpenFGA circuit breaker is open"));
+            };
+
+            let result = call().await;
+            match &result {
+                Err(status) if is_outage(status.code()) => permit.record_failure(),
+                _ => permit.record_success(),
+            }
+
+            match result {
+                Err(status)
+                    if is_retryable(status.code())
+                        && retries < self.max_retries
+                        && self.budget.try_withdraw() =>
+                {
+                    retries += 1;
+                    tracing::debug!(
+                        "Retrying OpenFGA call ({}/{}) after: {}",
+                        retries,
+                        self.max_retries,
+                        status.message()
+                    );
+                    tokio::time::sleep(self.retry_backoff).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Client whose calls go through a (possibly shared) [`Resilience`]
+pub struct ResilientClient {
+    client: OpenFGAClient,
+    resilience: Arc<Resilience>,
+}
+
+impl ResilientClient {
+    pub fn new(client: OpenFGAClient, resilience: Arc<Resilience>) -> Self {
+        Self { client, resilience }
+    }
+
+    /// Get the shared circuit breaker and retry budget
+    pub fn resilience(&self) -> &Arc<Resilience> {
+        &self.resilience
+    }
+
+    /// Run any client call with circuit breaking and retries.
+    ///
+    /// The call may run more than once, so non-idempotent requests such as writes
+    /// can fail on retry with an "already exists" error after a lost response.
+    pub async fn call<T>(
+        &mut self,
+        mut call: impl AsyncFnMut(&mut OpenFGAClient) -> Result<T, Status>,
+    ) -> Result<T, Status> {
+        let client = &mut self.client;
+        self.resilience.run(async || call(client).await).await
+    }
+
+    /// Check a relation
+    pub async fn check(&mut self, request: CheckRequest) -> Result<bool, Status> {
+        self.call(async |client| {
+            let response = client.check(request.clone()).await?;
+            Ok(response.into_inner().allowed)
+        })
+        .await
+    }
+}
+
+fn is_outage(code: Code) -> bool {
+    matches!(
+        code,
+        Code::Unavailable | Code::DeadlineExceeded | Code::Internal | Code::Unknown
+    )
+}
+
+fn is_retryable(code: Code) -> bool {
+    matches!(code, Code::Unavailable | Code::DeadlineExceeded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Mock server replaying scripted results
+    struct MockServer {
+        results: VecDeque<Result<u32, Status>>,
+        calls: usize,
+    }
+
+    impl MockServer {
+        fn new(results: impl IntoIterator<Item = Result<u32, Status>>) -> Self {
+            Self {
+                results: results.into_iter().collect(),
+                calls: 0,
+            }
+        }
+
+        async fn call(&mut self) -> Result<u32, Status> {
+            self.calls += 1;
+            self.results.pop_front().expect("unexpected call")
+        }
+    }
+
+    fn config() -> ResilienceConfig {
+        ResilienceConfig {
+            failure_threshold: 2,
+            cool_down: Duration::from_millis(50),
+            max_retries: 0,
+            retry_backoff: Duration::ZERO,
+            retry_ratio: 0.0,
+            min_retries: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_circuit_state_transitions() {
+        let resilience = Resilience::new(&config());
+        let mut server = MockServer::new([
+            Err(Status::unavailable("down")),
+            Err(Status::unavailable("down")),
+            Err(Status::unavailable("still down")),
+            Ok(1),
+            Ok(2),
+        ]);
+
+        for _ in 0..2 {
+            assert!(resilience.run(async || server.call().await).await.is_err());
+        }
+        assert_eq!(resilience.breaker().state(), CircuitState::Open);
+
+        // Open: fails fast without reaching the server
+        let status = resilience
+            .run(async || server.call().await)
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unavailable);
+        assert_eq!(server.calls, 2);
+
+        // Half-open: a failing probe opens the circuit again
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(resilience.breaker().state(), CircuitState::HalfOpen);
+        assert!(resilience.run(async || server.call().await).await.is_err());
+        assert_eq!(resilience.breaker().state(), CircuitState::Open);
+
+        // Half-open: a successful probe closes it
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(resilience.breaker().state(), CircuitState::HalfOpen);
+        assert_eq!(
+            resilience.run(async || server.call().await).await.unwrap(),
+            1
+        );
+        assert_eq!(resilience.breaker().state(), CircuitState::Closed);
+        assert_eq!(
+            resilience.run(async || server.call().await).await.unwrap(),
+            2
+        );
+        assert_eq!(server.calls, 5);
+    }
+
+    #[tokio::test]
+    async fn test_retries_limited_by_budget() {
+        let resilience = Resilience::new(&ResilienceConfig {
+            failure_threshold: 10,
+            max_retries: 3,
+            min_retries: 1,
+            ..config()
+        });
+        let mut server = MockServer::new([
+            Err(Status::unavailable("down")),
+            Ok(1),
+            Err(Status::unavailable("down")),
+            // Client errors are neither retried nor counted as failures
+            Err(Status::invalid_argument("bad request")),
+        ]);
+
+        // The single token in the budget pays for one retry
+        assert_eq!(
+            resilience.run(async || server.call().await).await.unwrap(),
+            1
+        );
+
+        let status = resilience
+            .run(async || server.call().await)
+            .await
+            .unwrap_err();
+        assert_eq!(status.message(), "down");
+        assert_eq!(server.calls, 3);
+
+        let status = resilience
+            .run(async || server.call().await)
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert_eq!(resilience.breaker().state(), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_probe_frees_half_open() {
+        let resilience = Resilience::new(&config());
+        let mut server = MockServer::new([
+            Err(Status::unavailable("down")),
+            Err(Status::unavailable("down")),
+            Ok(1),
+        ]);
+        for _ in 0..2 {
+            assert!(resilience.run(async || server.call().await).await.is_err());
+        }
+        tokio::time::sleep(Duration::from_millis(60)).await;
+
+        // The probe times out and its future is dropped mid-flight
+        let stalled = tokio::time::timeout(
+            Duration::from_millis(10),
+            resilience.run(async || std::future::pending::<Result<u32, Status>>().await),
+        )
+        .await;
+        assert!(stalled.is_err());
+        assert_eq!(resilience.breaker().state(), CircuitState::HalfOpen);
+
+        // The next call is let through as the probe and closes the circuit
+        assert_eq!(
+            resilience.run(async || server.call().await).await.unwrap(),
+            1
+        );
+        assert_eq!(resilience.breaker().state(), CircuitState::Closed);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::bundle::StoreBackend;
+use crate::{AuthorizationModel, OpenFGAClient, ReadAuthorizationModelsRequest};
+use std::collections::HashMap;
+
+/// Type names of a model and the sorted names of their relations, e.g.
+/// `{ "document": ["owner", "reader", "writer"], "user": [] }`
+pub type SchemaSummary = HashMap<String, Vec<String>>;
+
+impl OpenFGAClient {
+    /// Types and relations of the store's latest model, see [`schema_summary`]
+    pub async fn schema_summary(&mut self, store_id: &str) -> Result<SchemaSummary, tonic::Status> {
+        schema_summary(self, store_id).await
+    }
+}
+
+/// Read the latest model of a store and summarize it with [`model_schema`], for
+/// permission-aware UIs that only need type and relation names
+pub async fn schema_summary<B: StoreBackend>(
+    client: &mut B,
+    store_id: &str,
+) -> Result<SchemaSummary, tonic::Status> {
+    let latest = client
+        .read_authorization_models(ReadAuthorizationModelsRequest {
+            store_id: store_id.to_string(),
+            page_size: Some(1),
+            continuation_token: String::new(),
+        })
+        .await?
+        .into_inner()
+        .authorization_models
+        .into_iter()
+        .next()
+        .ok_or_else(|| {
+            tonic::Status::not_found(format!("store {} has no authorization model", store_id))
+        })?;
+
+    Ok(model_schema(&latest))
+}
+
+/// Each type of `model` with its relation names, sorted
+pub fn model_schema(model: &AuthorizationModel) -> SchemaSummary {
+    model
+        .type_definitions
+        .iter()
+        .map(|definition| {
+            let mut relations: Vec<String> = definition.relations.keys().cloned().collect();
+            relations.sort();
+            (definition.r#type.clone(), relations)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JsonAuthModel;
+    use crate::test_support::MemoryStore;
+
+    #[tokio::test]
+    async fn test_schema_summary_of_example_model() {
+        let json: JsonAuthModel =
+            serde_json::from_str(include_str!("../../etc/fga/auth-model-example.json")).unwrap();
+        let (type_definitions, schema_version, conditions) = json.to_openfga_types().unwrap();
+        let mut store = MemoryStore::with_models(vec![AuthorizationModel {
+            id: "model".to_string(),
+            schema_version,
+            type_definitions,
+            conditions,
+        }]);
+
+        let schema = schema_summary(&mut store, "store").await.unwrap();
+        // Only the latest model is read
+        assert_eq!(store.state().model_reads[0].page_size, Some(1));
+
+        let expected: SchemaSummary = [
+            ("user", vec![]),
+            ("group", vec!["member"]),
+            (
+                "organisation",
+                vec!["admin", "child", "descendant_member", "member"],
+            ),
+            (
+                "resource",
+                vec!["admin", "editor", "owner", "parent_org", "viewer"],
+            ),
+        ]
+        .into_iter()
+        .map(|(t, relations)| {
+            let relations = relations.into_iter().map(String::from).collect();
+            (t.to_string(), relations)
+        })
+        .collect();
+        assert_eq!(schema, expected);
+
+        let error = schema_summary(&mut MemoryStore::default(), "store")
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), tonic::Code::NotFound);
+    }
+}
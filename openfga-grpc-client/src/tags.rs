@@ -0,0 +1,103 @@
+use crate::{OpenFGAClient, OpenFGAClientBuilder};
+use tonic::metadata::{AsciiMetadataKey, AsciiMetadataValue};
+
+/// Prefix of the metadata carrying a request tag, e.g. `x-openfga-feature`
+pub const TAG_METADATA_PREFIX: &str = "x-openfga-";
+
+/// A request tag whose name or value can't be sent as gRPC metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidTag {
+    pub tag: String,
+}
+
+impl std::fmt::Display for InvalidTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "request tag {} is not a valid metadata entry", self.tag)
+    }
+}
+
+impl std::error::Error for InvalidTag {}
+
+/// Metadata entries of `tags`, each sent as `x-openfga-<name>: <value>`
+fn tag_metadata<K: AsRef<str>, V: AsRef<str>>(
+    tags: impl IntoIterator<Item = (K, V)>,
+) -> Result<Vec<(AsciiMetadataKey, AsciiMetadataValue)>, InvalidTag> {
+    tags.into_iter()
+        .map(|(name, value)| {
+            let (name, value) = (name.as_ref(), value.as_ref());
+            let invalid = || InvalidTag {
+                tag: name.to_string(),
+            };
+            let key = format!("{}{}", TAG_METADATA_PREFIX, name.to_lowercase())
+                .parse()
+                .map_err(|_| invalid())?;
+            let value = value.parse().map_err(|_| invalid())?;
+            Ok((key, value))
+        })
+        .collect()
+}
+
+impl OpenFGAClientBuilder {
+    /// Tag every call, e.g. `[("feature", "doc-sharing"), ("team", "platform")]`, so
+    /// OpenFGA's logs and metrics can be sliced by application context. Each tag is
+    /// sent as `x-openfga-<name>` metadata.
+    pub fn tags<K: AsRef<str>, V: AsRef<str>>(
+        mut self,
+        tags: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<Self, InvalidTag> {
+        self.tags.extend(tag_metadata(tags)?);
+        Ok(self)
+    }
+}
+
+impl OpenFGAClient {
+    /// A copy of the client whose calls also carry `tags`, e.g. to tag the calls of
+    /// one feature; a tag the client already has is replaced. See
+    /// [`OpenFGAClientBuilder::tags`].
+    pub fn with_tags<K: AsRef<str>, V: AsRef<str>>(
+        &self,
+        tags: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<OpenFGAClient, InvalidTag> {
+        let mut client = self.clone();
+        for (key, value) in tag_metadata(tags)? {
+            client.tags.retain(|(k, _)| *k != key);
+            client.tags.push((key, value));
+        }
+        Ok(client)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CheckRequest;
+
+    #[tokio::test]
+    async fn test_tags_are_sent_as_metadata() {
+        let client = OpenFGAClient::builder("http://localhost:8081")
+            .tags([("feature", "doc-sharing"), ("team", "platform")])
+            .unwrap()
+            .connect_lazy()
+            .unwrap();
+
+        let request = client.authorize(CheckRequest::default());
+        let metadata = request.metadata();
+        assert_eq!(metadata.get("x-openfga-feature").unwrap(), "doc-sharing");
+        assert_eq!(metadata.get("x-openfga-team").unwrap(), "platform");
+
+        let request = client
+            .with_tags([("Feature", "audit")])
+            .unwrap()
+            .authorize(CheckRequest::default());
+        let metadata = request.metadata();
+        assert_eq!(metadata.get("x-openfga-feature").unwrap(), "audit");
+        assert_eq!(metadata.get("x-openfga-team").unwrap(), "platform");
+
+        assert_eq!(
+            client.with_tags([("bad tag", "x")]).err(),
+            Some(InvalidTag {
+                tag: "bad tag".to_string()
+            })
+        );
+    }
+}
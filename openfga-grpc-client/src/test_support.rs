@@ -0,0 +1,694 @@
+//! Shared fixture for tests: a folder/document model, sample tuples, check builders,
+//! [`FixtureStore`], an in-memory stand-in for OpenFGA checks, and [`MemoryStore`], an
+//! in-memory [`StoreBackend`].
+//!
+//! Compiled for this crate's tests and, for downstream crates, with the `test-util`
+//! feature.
+
+use crate::access_summary::CheckBackend;
+use crate::batch_check_single_result::CheckResult;
+use crate::batch_stream::MAX_BATCH_CHECK_ITEMS;
+use crate::local_check::{MAX_LOCAL_DEPTH, Unsupported};
+use crate::{
+    AuthorizationModel, BatchCheckItem, BatchCheckRequest, BatchCheckResponse,
+    BatchCheckSingleResult, CheckRequest, CheckRequestBuilder, CheckRequestTupleKey, JsonAuthModel,
+    JsonUserset, ReadAuthorizationModelRequest, ReadAuthorizationModelResponse,
+    ReadAuthorizationModelsRequest, ReadAuthorizationModelsResponse, ReadRequest,
+    ReadRequestTupleKey, ReadResponse, StoreBackend, Tuple, TupleKey,
+    WriteAuthorizationModelRequest, WriteAuthorizationModelResponse, WriteRequest, WriteResponse,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Store ID of the fixture's requests
+pub const STORE_ID: &str = "01HFIXTURESTORE0000000000";
+/// Authorization model ID of the fixture's requests
+pub const MODEL_ID: &str = "01HFIXTUREMODEL0000000000";
+
+/// Folders and documents, where access to a folder is inherited by its subfolders and
+/// documents:
+///
+/// ```text
+/// type user
+/// type folder
+///   relations
+///     define parent: [folder]
+///     define owner: [user]
+///     define viewer: [user] or owner or viewer from parent
+/// type document
+///   relations
+///     define parent: [folder]
+///     define owner: [user]
+///     define editor: [user] or owner
+///     define viewer: [user] or editor or viewer from parent
+/// ```
+pub fn document_model() -> JsonAuthModel {
+    let direct = |types: &[&str]| {
+        serde_json::json!({
+            "directly_related_user_types": types
+                .iter()
+                .map(|t| serde_json::json!({ "type": t }))
+                .collect::<Vec<_>>()
+        })
+    };
+    let this = serde_json::json!({ "this": {} });
+    let computed = |relation: &str| serde_json::json!({ "computedUserset": { "object": "", "relation": relation } });
+    let from_parent = |relation: &str| {
+        serde_json::json!({
+            "tupleToUserset": {
+                "tupleset": { "object": "", "relation": "parent" },
+                "computedUserset": { "object": "", "relation": relation }
+            }
+        })
+    };
+
+    serde_json::from_value(serde_json::json!({
+        "schema_version": "1.1",
+        "type_definitions": [
+            { "type": "user" },
+            {
+                "type": "folder",
+                "relations": {
+                    "parent": this,
+                    "owner": this,
+                    "viewer": {
+                        "union": { "child": [this, computed("owner"), from_parent("viewer")] }
+                    }
+                },
+                "metadata": {
+                    "relations": {
+                        "parent": direct(&["folder"]),
+                        "owner": direct(&["user"]),
+                        "viewer": direct(&["user"])
+                    }
+                }
+            },
+            {
+                "type": "document",
+                "relations": {
+                    "parent": this,
+                    "owner": this,
+                    "editor": { "union": { "child": [this, computed("owner")] } },
+                    "viewer": {
+                        "union": { "child": [this, computed("editor"), from_parent("viewer")] }
+                    }
+                },
+                "metadata": {
+                    "relations": {
+                        "parent": direct(&["folder"]),
+                        "owner": direct(&["user"]),
+                        "editor": direct(&["user"]),
+                        "viewer": direct(&["user"])
+                    }
+                }
+            }
+        ]
+    }))
+    .expect("fixture model is valid")
+}
+
+/// A tuple without a condition
+pub fn tuple(user: &str, relation: &str, object: &str) -> TupleKey {
+    TupleKey {
+        user: user.to_string(),
+        relation: relation.to_string(),
+        object: object.to_string(),
+        condition: None,
+    }
+}
+
+/// Tuples of [`document_model`]:
+///
+/// - anne owns `folder:engineering`, which contains `folder:roadmaps`
+/// - bob views `folder:roadmaps`, which contains `document:q3-roadmap`
+/// - carol edits `document:q3-roadmap`
+/// - dave owns `document:handbook`, which isn't in a folder
+pub fn sample_tuples() -> Vec<TupleKey> {
+    vec![
+        tuple("user:anne", "owner", "folder:engineering"),
+        tuple("folder:engineering", "parent", "folder:roadmaps"),
+        tuple("user:bob", "viewer", "folder:roadmaps"),
+        tuple("folder:roadmaps", "parent", "document:q3-roadmap"),
+        tuple("user:carol", "editor", "document:q3-roadmap"),
+        tuple("user:dave", "owner", "document:handbook"),
+    ]
+}
+
+/// Check against the fixture's store and model
+pub fn check_request(object: &str, relation: &str, user: &str) -> CheckRequest {
+    CheckRequestBuilder::new()
+        .store(STORE_ID)
+        .model(MODEL_ID)
+        .tuple(object, relation, user)
+        .build()
+}
+
+/// Batch check of `(object, relation, user)` checks against the fixture's store and
+/// model; each check's correlation ID is its index
+pub fn batch_check_request(checks: &[(&str, &str, &str)]) -> BatchCheckRequest {
+    BatchCheckRequest {
+        store_id: STORE_ID.to_string(),
+        checks: checks
+            .iter()
+            .enumerate()
+            .map(|(i, (object, relation, user))| BatchCheckItem {
+                tuple_key: Some(CheckRequestTupleKey {
+                    object: object.to_string(),
+                    relation: relation.to_string(),
+                    user: user.to_string(),
+                }),
+                contextual_tuples: None,
+                context: None,
+                correlation_id: i.to_string(),
+            })
+            .collect(),
+        authorization_model_id: MODEL_ID.to_string(),
+        consistency: 0,
+    }
+}
+
+/// Resolves checks in memory against a model and tuples, in place of OpenFGA.
+///
+/// Supports direct assignment, including tuples granting usersets such as
+/// `folder:x#viewer`, computed usersets, tuple-to-usersets and unions; intersections,
+/// differences and conditioned tuples are [`Unsupported`]. In a batch check, unknown
+/// relations fail with `InvalidArgument` and other unsupported checks with
+/// `Unimplemented`.
+#[derive(Debug, Clone)]
+pub struct FixtureStore {
+    /// Relation definitions by type, then relation
+    relations: HashMap<String, HashMap<String, JsonUserset>>,
+    tuples: Vec<TupleKey>,
+}
+
+impl Default for FixtureStore {
+    /// [`document_model`] with [`sample_tuples`]
+    fn default() -> Self {
+        Self::new(document_model(), sample_tuples())
+    }
+}
+
+impl FixtureStore {
+    pub fn new(model: JsonAuthModel, tuples: Vec<TupleKey>) -> Self {
+        let relations = model
+            .type_definitions
+            .into_iter()
+            .map(|definition| (definition.type_name, definition.relations))
+            .collect();
+        FixtureStore { relations, tuples }
+    }
+
+    /// Whether `user` has `relation` with `object`
+    pub fn check(&self, object: &str, relation: &str, user: &str) -> Result<bool, Unsupported> {
+        self.check_relation(object, relation, user, 0)
+    }
+
+    fn check_relation(
+        &self,
+        object: &str,
+        relation: &str,
+        user: &str,
+        depth: usize,
+    ) -> Result<bool, Unsupported> {
+        if depth > MAX_LOCAL_DEPTH {
+            return Err(Unsupported::TooDeep);
+        }
+        let object_type = object.split_once(':').map_or(object, |(t, _)| t);
+        let userset = self
+            .relations
+            .get(object_type)
+            .and_then(|relations| relations.get(relation))
+            .ok_or_else(|| Unsupported::UnknownRelation {
+                object_type: object_type.to_string(),
+                relation: relation.to_string(),
+            })?;
+
+        self.check_userset(userset, object, relation, user, depth + 1)
+    }
+
+    fn check_userset(
+        &self,
+        userset: &JsonUserset,
+        object: &str,
+        relation: &str,
+        user: &str,
+        depth: usize,
+    ) -> Result<bool, Unsupported> {
+        if userset.this.is_some() {
+            for tuple in self.related(object, relation) {
+                if let Some(condition) = &tuple.condition {
+                    return Err(Unsupported::Condition(condition.name.clone()));
+                }
+                let allowed = match tuple.user.split_once('#') {
+                    Some((userset_object, userset_relation)) => {
+                        self.check_relation(userset_object, userset_relation, user, depth)?
+                    }
+                    None => tuple.user == user,
+                };
+                if allowed {
+                    return Ok(true);
+                }
+            }
+            return Ok(false);
+        }
+        if let Some(computed) = &userset.computed_userset {
+            return self.check_relation(object, &computed.relation, user, depth);
+        }
+        if let Some(ttu) = &userset.tuple_to_userset {
+            for tuple in self.related(object, &ttu.tupleset.relation) {
+                let computed = &ttu.computed_userset.relation;
+                if self.check_relation(&tuple.user, computed, user, depth)? {
+                    return Ok(true);
+                }
+            }
+            return Ok(false);
+        }
+        if let Some(union) = &userset.union {
+            for child in &union.child {
+                if self.check_userset(child, object, relation, user, depth)? {
+                    return Ok(true);
+                }
+            }
+            return Ok(false);
+        }
+        let object_type = object.split_once(':').map_or(object, |(t, _)| t);
+        let (object_type, relation) = (object_type.to_string(), relation.to_string());
+        Err(if userset.intersection.is_some() {
+            Unsupported::Intersection {
+                object_type,
+                relation,
+            }
+        } else {
+            Unsupported::Difference {
+                object_type,
+                relation,
+            }
+        })
+    }
+
+    fn related<'a>(
+        &'a self,
+        object: &'a str,
+        relation: &'a str,
+    ) -> impl Iterator<Item = &'a TupleKey> {
+        self.tuples
+            .iter()
+            .filter(move |tuple| tuple.object == object && tuple.relation == relation)
+    }
+}
+
+impl CheckBackend for FixtureStore {
+    async fn batch_check(
+        &mut self,
+        request: BatchCheckRequest,
+    ) -> Result<tonic::Response<BatchCheckResponse>, tonic::Status> {
+        let mut result = HashMap::new();
+        for check in request.checks {
+            let key = check
+                .tuple_key
+                .ok_or_else(|| tonic::Status::invalid_argument("missing tuple_key"))?;
+            let allowed = self
+                .check(&key.object, &key.relation, &key.user)
+                .map_err(|reason| match reason {
+                    Unsupported::UnknownRelation { .. } => {
+                        tonic::Status::invalid_argument(reason.to_string())
+                    }
+                    _ => tonic::Status::unimplemented(reason.to_string()),
+                })?;
+            result.insert(
+                check.correlation_id,
+                BatchCheckSingleResult {
+                    check_result: Some(CheckResult::Allowed(allowed)),
+                },
+            );
+        }
+        Ok(tonic::Response::new(BatchCheckResponse { result }))
+    }
+}
+
+/// Models, tuples and requests of a [`MemoryStore`]
+#[derive(Debug, Default)]
+pub struct MemoryState {
+    /// Authorization models, oldest first
+    pub models: Vec<AuthorizationModel>,
+    pub tuples: Vec<TupleKey>,
+    /// Largest page served, as a server caps page sizes; unlimited when 0
+    pub page_size: usize,
+    /// Requests received, in order
+    pub reads: Vec<ReadRequest>,
+    pub writes: Vec<WriteRequest>,
+    pub model_reads: Vec<ReadAuthorizationModelsRequest>,
+    pub model_writes: Vec<WriteAuthorizationModelRequest>,
+    pub batch_checks: Vec<BatchCheckRequest>,
+}
+
+/// Store held in memory, in place of OpenFGA, recording the requests it receives.
+///
+/// Reads filter tuples as OpenFGA does, by object or object type, relation and user,
+/// and pages are continued from the index of their next tuple or model. Writes delete,
+/// then add their tuples without checking them, and written models get the IDs
+/// `model-1`, `model-2`, ... by their position. Batch checks of up to
+/// [`MAX_BATCH_CHECK_ITEMS`] items only find stored tuples; see
+/// [`FixtureStore`] to resolve a model's relations. Clones share the store, as clones
+/// of a client share the server.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    state: Arc<Mutex<MemoryState>>,
+}
+
+impl From<MemoryState> for MemoryStore {
+    fn from(state: MemoryState) -> Self {
+        MemoryStore {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+}
+
+impl MemoryStore {
+    /// Store holding `tuples`
+    pub fn with_tuples(tuples: Vec<TupleKey>) -> Self {
+        Self::from(MemoryState {
+            tuples,
+            ..Default::default()
+        })
+    }
+
+    /// Store holding `models`, oldest first
+    pub fn with_models(models: Vec<AuthorizationModel>) -> Self {
+        Self::from(MemoryState {
+            models,
+            ..Default::default()
+        })
+    }
+
+    pub fn state(&self) -> MutexGuard<'_, MemoryState> {
+        self.state.lock().unwrap()
+    }
+}
+
+impl MemoryState {
+    /// Items of the page starting at `continuation_token`, with the token of the next;
+    /// `None` for a token this store didn't hand out
+    fn page<T: Clone>(
+        &self,
+        items: &[T],
+        page_size: Option<i32>,
+        continuation_token: &str,
+    ) -> Option<(Vec<T>, String)> {
+        let start = match continuation_token {
+            "" => 0,
+            token => token
+                .parse::<usize>()
+                .ok()
+                .filter(|start| *start <= items.len())?,
+        };
+        let requested = page_size.and_then(|size| usize::try_from(size).ok());
+        let page_size = match (requested.filter(|size| *size > 0), self.page_size) {
+            (Some(requested), 0) => requested,
+            (Some(requested), max) => requested.min(max),
+            (None, 0) => items.len(),
+            (None, max) => max,
+        };
+        let end = start.saturating_add(page_size).min(items.len());
+        let continuation_token = if end < items.len() {
+            end.to_string()
+        } else {
+            String::new()
+        };
+        Some((items[start..end].to_vec(), continuation_token))
+    }
+}
+
+fn invalid_continuation_token() -> tonic::Status {
+    tonic::Status::invalid_argument("invalid continuation token")
+}
+
+/// Whether `tuple` matches a read's filter; the filter's object may be a type, `type:`
+fn read_matches(filter: &ReadRequestTupleKey, tuple: &TupleKey) -> bool {
+    let object = match filter.object.strip_suffix(':') {
+        Some(object_type) => tuple
+            .object
+            .split_once(':')
+            .is_some_and(|(t, _)| t == object_type),
+        None => filter.object.is_empty() || tuple.object == filter.object,
+    };
+    object
+        && (filter.relation.is_empty() || tuple.relation == filter.relation)
+        && (filter.user.is_empty() || tuple.user == filter.user)
+}
+
+impl StoreBackend for MemoryStore {
+    async fn read_authorization_models(
+        &mut self,
+        request: ReadAuthorizationModelsRequest,
+    ) -> Result<tonic::Response<ReadAuthorizationModelsResponse>, tonic::Status> {
+        let mut state = self.state();
+        let newest_first: Vec<_> = state.models.iter().rev().cloned().collect();
+        let (authorization_models, continuation_token) = state
+            .page(
+                &newest_first,
+                request.page_size,
+                &request.continuation_token,
+            )
+            .ok_or_else(invalid_continuation_token)?;
+        state.model_reads.push(request);
+
+        Ok(tonic::Response::new(ReadAuthorizationModelsResponse {
+            authorization_models,
+            continuation_token,
+        }))
+    }
+
+    async fn read_authorization_model(
+        &mut self,
+        request: ReadAuthorizationModelRequest,
+    ) -> Result<tonic::Response<ReadAuthorizationModelResponse>, tonic::Status> {
+        Ok(tonic::Response::new(ReadAuthorizationModelResponse {
+            authorization_model: self
+                .state()
+                .models
+                .iter()
+                .find(|model| model.id == request.id)
+                .cloned(),
+        }))
+    }
+
+    async fn read(
+        &mut self,
+        request: ReadRequest,
+    ) -> Result<tonic::Response<ReadResponse>, tonic::Status> {
+        let mut state = self.state();
+        let matching: Vec<_> = state
+            .tuples
+            .iter()
+            .filter(|tuple| {
+                request
+                    .tuple_key
+                    .as_ref()
+                    .is_none_or(|filter| read_matches(filter, tuple))
+            })
+            .cloned()
+            .collect();
+        let (tuples, continuation_token) = state
+            .page(&matching, request.page_size, &request.continuation_token)
+            .ok_or_else(invalid_continuation_token)?;
+        state.reads.push(request);
+
+        Ok(tonic::Response::new(ReadResponse {
+            tuples: tuples
+                .into_iter()
+                .map(|key| Tuple {
+                    key: Some(key),
+                    timestamp: None,
+                })
+                .collect(),
+            continuation_token,
+        }))
+    }
+
+    async fn write_authorization_model(
+        &mut self,
+        request: WriteAuthorizationModelRequest,
+    ) -> Result<tonic::Response<WriteAuthorizationModelResponse>, tonic::Status> {
+        let mut state = self.state();
+        let id = format!("model-{}", state.models.len() + 1);
+        state.models.push(AuthorizationModel {
+            id: id.clone(),
+            schema_version: request.schema_version.clone(),
+            type_definitions: request.type_definitions.clone(),
+            conditions: request.conditions.clone(),
+        });
+        state.model_writes.push(request);
+
+        Ok(tonic::Response::new(WriteAuthorizationModelResponse {
+            authorization_model_id: id,
+        }))
+    }
+
+    async fn write(
+        &mut self,
+        request: WriteRequest,
+    ) -> Result<tonic::Response<WriteResponse>, tonic::Status> {
+        let mut state = self.state();
+        for delete in request.deletes.iter().flat_map(|d| &d.tuple_keys) {
+            state.tuples.retain(|tuple| {
+                (&tuple.user, &tuple.relation, &tuple.object)
+                    != (&delete.user, &delete.relation, &delete.object)
+            });
+        }
+        let writes = request.writes.iter().flat_map(|w| w.tuple_keys.clone());
+        state.tuples.extend(writes);
+        state.writes.push(request);
+
+        Ok(tonic::Response::new(WriteResponse {}))
+    }
+}
+
+impl CheckBackend for MemoryStore {
+    async fn batch_check(
+        &mut self,
+        request: BatchCheckRequest,
+    ) -> Result<tonic::Response<BatchCheckResponse>, tonic::Status> {
+        let mut state = self.state();
+        if request.checks.len() > MAX_BATCH_CHECK_ITEMS {
+            return Err(tonic::Status::invalid_argument(format!(
+                "batch check of {} items exceeds the maximum of {}",
+                request.checks.len(),
+                MAX_BATCH_CHECK_ITEMS
+            )));
+        }
+        state.batch_checks.push(request.clone());
+        let mut result = HashMap::new();
+        for check in request.checks {
+            let key = check
+                .tuple_key
+                .ok_or_else(|| tonic::Status::invalid_argument("missing tuple_key"))?;
+            let allowed = state.tuples.iter().any(|tuple| {
+                (&tuple.user, &tuple.relation, &tuple.object)
+                    == (&key.user, &key.relation, &key.object)
+            });
+            result.insert(
+                check.correlation_id,
+                BatchCheckSingleResult {
+                    check_result: Some(CheckResult::Allowed(allowed)),
+                },
+            );
+        }
+        Ok(tonic::Response::new(BatchCheckResponse { result }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_model_converts_cleanly() {
+        let converted = document_model().to_openfga_types_with_warnings().unwrap();
+
+        assert!(converted.warnings.is_empty());
+        let mut types: Vec<&str> = converted
+            .type_definitions
+            .iter()
+            .map(|definition| definition.r#type.as_str())
+            .collect();
+        types.sort();
+        assert_eq!(types, vec!["document", "folder", "user"]);
+    }
+
+    #[tokio::test]
+    async fn test_nested_inheritance_check() {
+        let mut store = FixtureStore::default();
+        let request = batch_check_request(&[
+            // anne owns the grandparent folder
+            ("document:q3-roadmap", "viewer", "user:anne"),
+            ("document:q3-roadmap", "editor", "user:anne"),
+            // bob views the parent folder
+            ("document:q3-roadmap", "viewer", "user:bob"),
+            ("folder:engineering", "viewer", "user:bob"),
+            ("document:q3-roadmap", "viewer", "user:carol"),
+            ("document:handbook", "viewer", "user:anne"),
+        ]);
+
+        let response = store.batch_check(request).await.unwrap().into_inner();
+
+        let allowed = |i: usize| match &response.result[&i.to_string()].check_result {
+            Some(CheckResult::Allowed(allowed)) => *allowed,
+            other => panic!("unexpected result {:?}", other),
+        };
+        assert!(allowed(0));
+        assert!(!allowed(1));
+        assert!(allowed(2));
+        assert!(!allowed(3));
+        assert!(allowed(4));
+        assert!(!allowed(5));
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_reads_and_writes() {
+        let mut store = MemoryStore::with_tuples(sample_tuples());
+        let read = |object: &str, user: &str, continuation_token: &str| ReadRequest {
+            tuple_key: Some(ReadRequestTupleKey {
+                user: user.to_string(),
+                relation: String::new(),
+                object: object.to_string(),
+            }),
+            page_size: Some(1),
+            continuation_token: continuation_token.to_string(),
+            ..Default::default()
+        };
+
+        // Filtered by object type and user, one tuple per page
+        let page = store
+            .read(read("folder:", "folder:engineering", ""))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(page.tuples.len(), 1);
+        assert_eq!(page.continuation_token, "");
+
+        let page = store
+            .read(read("document:q3-roadmap", "", ""))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(page.continuation_token, "1");
+        let page = store
+            .read(read("document:q3-roadmap", "", &page.continuation_token))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(
+            page.tuples[0].key,
+            Some(tuple("user:carol", "editor", "document:q3-roadmap"))
+        );
+
+        // Clones share the store
+        let mut clone = store.clone();
+        clone
+            .write(WriteRequest {
+                writes: Some(crate::WriteRequestWrites {
+                    tuple_keys: vec![tuple("user:erin", "owner", "document:handbook")],
+                    on_duplicate: String::new(),
+                }),
+                deletes: Some(crate::WriteRequestDeletes {
+                    tuple_keys: vec![crate::TupleKeyWithoutCondition {
+                        user: "user:dave".to_string(),
+                        relation: "owner".to_string(),
+                        object: "document:handbook".to_string(),
+                    }],
+                    on_missing: String::new(),
+                }),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let state = store.state();
+        assert_eq!(state.reads.len(), 3);
+        assert_eq!(
+            state.tuples.last(),
+            Some(&tuple("user:erin", "owner", "document:handbook"))
+        );
+        assert!(!state.tuples.iter().any(|t| t.user == "user:dave"));
+    }
+}
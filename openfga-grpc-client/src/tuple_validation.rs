@@ -0,0 +1,334 @@
+use crate::json_types::{JsonAuthModel, JsonDirectlyRelatedUserType};
+use crate::{OpenFGAClient, TupleKey, WriteRequest, WriteResponse};
+
+/// Why a tuple doesn't fit the model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TupleValidationReason {
+    /// The object's type isn't defined in the model
+    UnknownType(String),
+    /// The object's type has no such relation, e.g. a typo like `reedr`
+    UnknownRelation {
+        object_type: String,
+        relation: String,
+    },
+    /// The relation doesn't accept the user's type, e.g. `group#member` where only
+    /// `user` is directly related
+    UserTypeNotAllowed {
+        user_type: String,
+        object_type: String,
+        relation: String,
+    },
+}
+
+impl std::fmt::Display for TupleValidationReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TupleValidationReason::UnknownType(object_type) => {
+                write!(f, "type {} isn't defined in the model", object_type)
+            }
+            TupleValidationReason::UnknownRelation {
+                object_type,
+                relation,
+            } => write!(f, "type {} has no relation {}", object_type, relation),
+            TupleValidationReason::UserTypeNotAllowed {
+                user_type,
+                object_type,
+                relation,
+            } => write!(
+                f,
+                "{}#{} doesn't accept users of type {}",
+                object_type, relation, user_type
+            ),
+        }
+    }
+}
+
+/// A tuple of a write that doesn't fit the model, with its position in the writes
+#[derive(Debug, Clone, PartialEq)]
+pub struct TupleValidationError {
+    pub index: usize,
+    pub tuple: Box<TupleKey>,
+    pub reason: TupleValidationReason,
+}
+
+impl std::fmt::Display for TupleValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "tuple {} ({}#{}@{}): {}",
+            self.index, self.tuple.object, self.tuple.relation, self.tuple.user, self.reason
+        )
+    }
+}
+
+impl std::error::Error for TupleValidationError {}
+
+/// Check that the tuple's relation exists on its object's type and accepts its user's
+/// type, according to the directly related user types of the model.
+///
+/// Relations of types without metadata, as in 1.0 models, accept any user. Wildcard
+/// users such as `user:*` are only accepted by a wildcard user type (`"wildcard": {}`).
+pub fn validate_tuple(
+    model: &JsonAuthModel,
+    tuple: &TupleKey,
+) -> Result<(), TupleValidationReason> {
+    let object_type = tuple
+        .object
+        .split_once(':')
+        .map_or(tuple.object.as_str(), |(t, _)| t);
+    let definition = model
+        .type_definitions
+        .iter()
+        .find(|t| t.type_name == object_type)
+        .ok_or_else(|| TupleValidationReason::UnknownType(object_type.to_string()))?;
+    if !definition.relations.contains_key(&tuple.relation) {
+        return Err(TupleValidationReason::UnknownRelation {
+            object_type: object_type.to_string(),
+            relation: tuple.relation.clone(),
+        });
+    }
+
+    let Some(metadata) = definition
+        .metadata
+        .as_ref()
+        .and_then(|m| m.relations.as_ref())
+    else {
+        return Ok(());
+    };
+    let allowed = metadata
+        .get(&tuple.relation)
+        .map(|m| m.directly_related_user_types.as_slice())
+        .unwrap_or_default();
+
+    let (user_type, user_relation) = match tuple.user.split_once('#') {
+        Some((user, relation)) => (user, Some(relation)),
+        None => (tuple.user.as_str(), None),
+    };
+    let (user_type, is_wildcard) = match user_type.split_once(':') {
+        Some((user_type, id)) => (user_type, id == "*"),
+        None => (user_type, false),
+    };
+    let accepts = |candidate: &JsonDirectlyRelatedUserType| {
+        candidate.type_name == user_type
+            && candidate.relation.as_deref().filter(|r| !r.is_empty()) == user_relation
+            && candidate.wildcard.is_some() == is_wildcard
+    };
+    if allowed.iter().any(accepts) {
+        return Ok(());
+    }
+
+    Err(TupleValidationReason::UserTypeNotAllowed {
+        user_type: match user_relation {
+            Some(relation) => format!("{}#{}", user_type, relation),
+            None if is_wildcard => format!("{}:*", user_type),
+            None => user_type.to_string(),
+        },
+        object_type: object_type.to_string(),
+        relation: tuple.relation.clone(),
+    })
+}
+
+/// Validate `tuples` with [`validate_tuple`], failing on the first that doesn't fit
+pub fn validate_tuples(
+    model: &JsonAuthModel,
+    tuples: &[TupleKey],
+) -> Result<(), TupleValidationError> {
+    for (index, tuple) in tuples.iter().enumerate() {
+        validate_tuple(model, tuple).map_err(|reason| TupleValidationError {
+            index,
+            tuple: Box::new(tuple.clone()),
+            reason,
+        })?;
+    }
+    Ok(())
+}
+
+/// Validate the contextual tuples of a check or query with [`validate_tuple`],
+/// returning every tuple that doesn't fit. OpenFGA ignores contextual tuples that
+/// name an unknown relation rather than rejecting them, so a typo would otherwise go
+/// unnoticed.
+pub fn validate_contextual_tuples(
+    model: &JsonAuthModel,
+    tuples: &[TupleKey],
+) -> Result<(), Vec<TupleValidationError>> {
+    let errors: Vec<TupleValidationError> = tuples
+        .iter()
+        .enumerate()
+        .filter_map(|(index, tuple)| {
+            validate_tuple(model, tuple)
+                .err()
+                .map(|reason| TupleValidationError {
+                    index,
+                    tuple: Box::new(tuple.clone()),
+                    reason,
+                })
+        })
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+impl OpenFGAClient {
+    /// Write after validating the request's writes against `model`, the model the
+    /// store is expected to use. A tuple that doesn't fit fails the write with
+    /// `InvalidArgument` naming it, before anything is sent. Deletes aren't validated.
+    pub async fn write_validated(
+        &mut self,
+        model: &JsonAuthModel,
+        request: WriteRequest,
+    ) -> Result<tonic::Response<WriteResponse>, tonic::Status> {
+        if let Some(writes) = &request.writes {
+            validate_tuples(model, &writes.tuple_keys)
+                .map_err(|e| tonic::Status::invalid_argument(e.to_string()))?;
+        }
+        self.write(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::tuple;
+
+    const MODEL: &str = r#"{
+        "schema_version": "1.1",
+        "type_definitions": [
+            { "type": "user" },
+            {
+                "type": "group",
+                "relations": { "member": { "this": {} } },
+                "metadata": {
+                    "relations": {
+                        "member": { "directly_related_user_types": [{ "type": "user" }] }
+                    }
+                }
+            },
+            {
+                "type": "document",
+                "relations": {
+                    "owner": { "this": {} },
+                    "reader": { "this": {} }
+                },
+                "metadata": {
+                    "relations": {
+                        "owner": { "directly_related_user_types": [{ "type": "user" }] },
+                        "reader": { "directly_related_user_types": [
+                            { "type": "user" },
+                            { "type": "user", "wildcard": {} },
+                            { "type": "group", "relation": "member" }
+                        ] }
+                    }
+                }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_validate_tuple() {
+        let model: JsonAuthModel = serde_json::from_str(MODEL).unwrap();
+
+        assert_eq!(
+            validate_tuple(&model, &tuple("user:anne", "reader", "document:readme")),
+            Ok(())
+        );
+        assert_eq!(
+            validate_tuple(
+                &model,
+                &tuple("group:eng#member", "reader", "document:readme")
+            ),
+            Ok(())
+        );
+
+        assert_eq!(
+            validate_tuple(&model, &tuple("user:anne", "reedr", "document:readme")),
+            Err(TupleValidationReason::UnknownRelation {
+                object_type: "document".to_string(),
+                relation: "reedr".to_string(),
+            })
+        );
+        assert_eq!(
+            validate_tuple(&model, &tuple("user:anne", "reader", "folder:docs")),
+            Err(TupleValidationReason::UnknownType("folder".to_string()))
+        );
+
+        assert_eq!(
+            validate_tuple(
+                &model,
+                &tuple("group:eng#member", "owner", "document:readme")
+            ),
+            Err(TupleValidationReason::UserTypeNotAllowed {
+                user_type: "group#member".to_string(),
+                object_type: "document".to_string(),
+                relation: "owner".to_string(),
+            })
+        );
+        assert!(validate_tuple(&model, &tuple("group:eng", "reader", "document:readme")).is_err());
+
+        // Wildcards only where the model allows them
+        assert_eq!(
+            validate_tuple(&model, &tuple("user:*", "reader", "document:readme")),
+            Ok(())
+        );
+        assert_eq!(
+            validate_tuple(&model, &tuple("user:*", "owner", "document:readme")),
+            Err(TupleValidationReason::UserTypeNotAllowed {
+                user_type: "user:*".to_string(),
+                object_type: "document".to_string(),
+                relation: "owner".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_tuples_reports_the_failing_tuple() {
+        let model: JsonAuthModel = serde_json::from_str(MODEL).unwrap();
+        let tuples = vec![
+            tuple("user:anne", "owner", "document:readme"),
+            tuple("user:bob", "reedr", "document:readme"),
+        ];
+
+        let error = validate_tuples(&model, &tuples).unwrap_err();
+        assert_eq!(error.index, 1);
+        assert_eq!(*error.tuple, tuples[1]);
+        assert_eq!(
+            error.to_string(),
+            "tuple 1 (document:readme#reedr@user:bob): type document has no relation reedr"
+        );
+    }
+
+    #[test]
+    fn test_validate_contextual_tuples() {
+        let model: JsonAuthModel = serde_json::from_str(MODEL).unwrap();
+
+        assert_eq!(
+            validate_contextual_tuples(&model, &[tuple("user:anne", "member", "group:eng")]),
+            Ok(())
+        );
+
+        let tuples = vec![
+            tuple("user:anne", "member", "group:eng"),
+            tuple("user:anne", "membr", "group:eng"),
+            tuple("user:anne", "reader", "folder:docs"),
+        ];
+        let errors = validate_contextual_tuples(&model, &tuples).unwrap_err();
+        let reasons: Vec<(usize, TupleValidationReason)> =
+            errors.into_iter().map(|e| (e.index, e.reason)).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (
+                    1,
+                    TupleValidationReason::UnknownRelation {
+                        object_type: "group".to_string(),
+                        relation: "membr".to_string(),
+                    }
+                ),
+                (2, TupleValidationReason::UnknownType("folder".to_string())),
+            ]
+        );
+    }
+}
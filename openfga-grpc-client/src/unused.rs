@@ -0,0 +1,187 @@
+use crate::json_types::{JsonAuthModel, JsonUserset, user_types_for_relation};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Types and relations of a model that nothing references, see [`find_unused`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnusedReport {
+    /// Types that define no relations and are never a directly related user type,
+    /// sorted
+    pub unused_types: Vec<String>,
+    /// Relations, as `type#relation`, that no rewrite or directly related user type
+    /// references, sorted. Applications may still check them directly.
+    pub unreferenced_relations: Vec<String>,
+}
+
+impl UnusedReport {
+    pub fn is_empty(&self) -> bool {
+        self.unused_types.is_empty() && self.unreferenced_relations.is_empty()
+    }
+}
+
+/// Find the types and relations of a model that nothing references, for cleaning
+/// up models as they evolve.
+///
+/// The analysis only sees the model, so it classifies conservatively:
+/// - a type is unused if no relation accepts it as a directly related user type and
+///   it defines no relations. Types with relations, e.g. `document`, are left out,
+///   since they can be checked without being referenced.
+/// - a relation is unreferenced if no computed userset, tuple-to-userset or
+///   `type#relation` directly related user type names it. Such relations are
+///   candidates to confirm against the application's checks, not dead code.
+///
+/// The computed relation of a tuple-to-userset counts as referenced on the user types
+/// of its tupleset, or on every type when the tupleset has no type metadata.
+pub fn find_unused(model: &JsonAuthModel) -> UnusedReport {
+    let mut user_types = HashSet::new();
+    let mut referenced: HashSet<(String, String)> = HashSet::new();
+
+    for definition in &model.type_definitions {
+        let metadata = definition
+            .metadata
+            .as_ref()
+            .and_then(|m| m.relations.as_ref());
+        for relation_metadata in metadata.into_iter().flat_map(HashMap::values) {
+            for user_type in &relation_metadata.directly_related_user_types {
+                user_types.insert(user_type.type_name.as_str());
+                if let Some(relation) = &user_type.relation {
+                    referenced.insert((user_type.type_name.clone(), relation.clone()));
+                }
+            }
+        }
+
+        for userset in definition.relations.values() {
+            collect_references(model, &definition.type_name, userset, &mut referenced);
+        }
+    }
+
+    let mut unused_types = BTreeSet::new();
+    let mut unreferenced_relations = BTreeSet::new();
+    for definition in &model.type_definitions {
+        if definition.relations.is_empty() && !user_types.contains(definition.type_name.as_str()) {
+            unused_types.insert(definition.type_name.clone());
+        }
+        for relation in definition.relations.keys() {
+            if !referenced.contains(&(definition.type_name.clone(), relation.clone())) {
+                unreferenced_relations.insert(format!("{}#{}", definition.type_name, relation));
+            }
+        }
+    }
+
+    UnusedReport {
+        unused_types: unused_types.into_iter().collect(),
+        unreferenced_relations: unreferenced_relations.into_iter().collect(),
+    }
+}
+
+/// Add the relations `userset` of `type_name` rewrites to
+fn collect_references(
+    model: &JsonAuthModel,
+    type_name: &str,
+    userset: &JsonUserset,
+    referenced: &mut HashSet<(String, String)>,
+) {
+    if let Some(computed) = &userset.computed_userset {
+        referenced.insert((type_name.to_string(), computed.relation.clone()));
+    }
+    if let Some(ttu) = &userset.tuple_to_userset {
+        let tupleset = &ttu.tupleset.relation;
+        referenced.insert((type_name.to_string(), tupleset.clone()));
+
+        let targets = user_types_for_relation(model, type_name, tupleset);
+        if targets.is_empty() {
+            for definition in &model.type_definitions {
+                referenced.insert((
+                    definition.type_name.clone(),
+                    ttu.computed_userset.relation.clone(),
+                ));
+            }
+        }
+        for target in targets {
+            referenced.insert((target.type_name, ttu.computed_userset.relation.clone()));
+        }
+    }
+
+    let children = userset
+        .union
+        .iter()
+        .flat_map(|union| &union.child)
+        .chain(userset.intersection.iter().flat_map(|i| &i.child));
+    for child in children {
+        collect_references(model, type_name, child, referenced);
+    }
+    if let Some(difference) = &userset.difference {
+        collect_references(model, type_name, &difference.base, referenced);
+        collect_references(model, type_name, &difference.subtract, referenced);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `team` and `document#approver` are referenced by nothing
+    const MODEL: &str = r#"{
+        "schema_version": "1.1",
+        "type_definitions": [
+            { "type": "user" },
+            { "type": "team" },
+            {
+                "type": "group",
+                "relations": { "member": { "this": {} } },
+                "metadata": {
+                    "relations": {
+                        "member": { "directly_related_user_types": [{ "type": "user" }] }
+                    }
+                }
+            },
+            {
+                "type": "folder",
+                "relations": { "viewer": { "this": {} } },
+                "metadata": {
+                    "relations": {
+                        "viewer": { "directly_related_user_types": [
+                            { "type": "user" },
+                            { "type": "group", "relation": "member" }
+                        ] }
+                    }
+                }
+            },
+            {
+                "type": "document",
+                "relations": {
+                    "parent": { "this": {} },
+                    "owner": { "this": {} },
+                    "approver": { "this": {} },
+                    "viewer": { "union": { "child": [
+                        { "computedUserset": { "relation": "owner" } },
+                        { "tupleToUserset": {
+                            "tupleset": { "relation": "parent" },
+                            "computedUserset": { "relation": "viewer" }
+                        } }
+                    ] } }
+                },
+                "metadata": {
+                    "relations": {
+                        "parent": { "directly_related_user_types": [{ "type": "folder" }] },
+                        "owner": { "directly_related_user_types": [{ "type": "user" }] },
+                        "approver": { "directly_related_user_types": [{ "type": "user" }] }
+                    }
+                }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_find_unused() {
+        let model: JsonAuthModel = serde_json::from_str(MODEL).unwrap();
+        let report = find_unused(&model);
+
+        assert_eq!(report.unused_types, vec!["team"]);
+        // document#viewer is only checked, so it's reported for confirmation too
+        assert_eq!(
+            report.unreferenced_relations,
+            vec!["document#approver", "document#viewer"]
+        );
+        assert!(!report.is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+// End-to-end tests against a real OpenFGA server.
+//
+// Run with `cargo test -p openfga-grpc-client --features integration --test integration`.
+// The tests are skipped when Docker is not available.
+
+#![cfg(feature = "integration")]
+
+use openfga_grpc_client::{
+    ConsistencyPreference, CreateStoreRequest, ListObjectsRequest, ListStoresRequest,
+    OpenFGAClient, TupleKey, WriteRequest, WriteRequestWrites,
+};
+use std::time::Duration;
+use testcontainers::core::IntoContainerPort;
+use testcontainers::runners::AsyncRunner;
+use testcontainers::{ContainerAsync, GenericImage, ImageExt};
+
+const OPENFGA_IMAGE: &str = "openfga/openfga";
+const OPENFGA_TAG: &str = "v1.8.4";
+const OPENFGA_GRPC_PORT: u16 = 8081;
+
+const MODEL: &str = r#"{
+    "schema_version": "1.1",
+    "type_definitions": [
+        { "type": "user" },
+        {
+            "type": "group",
+            "relations": { "member": { "this": {} } },
+            "metadata": {
+                "relations": {
+                    "member": { "directly_related_user_types": [{ "type": "user" }] }
+                }
+            }
+        },
+        {
+            "type": "document",
+            "relations": {
+                "owner": { "this": {} },
+                "viewer": {
+                    "union": {
+                        "child": [
+                            { "this": {} },
+                            { "computedUserset": { "relation": "owner" } }
+                        ]
+                    }
+                }
+            },
+            "metadata": {
+                "relations": {
+                    "owner": { "directly_related_user_types": [{ "type": "user" }] },
+                    "viewer": {
+                        "directly_related_user_types": [
+                            { "type": "user" },
+                            { "type": "user", "wildcard": {} },
+                            { "type": "group", "relation": "member" }
+                        ]
+                    }
+                }
+            }
+        }
+    ]
+}"#;
+
+/// Start an OpenFGA container, or `None` if Docker is not available
+async fn start_openfga() -> Option<(ContainerAsync<GenericImage>, OpenFGAClient)> {
+    let container = match GenericImage::new(OPENFGA_IMAGE, OPENFGA_TAG)
+        .with_exposed_port(OPENFGA_GRPC_PORT.tcp())
+        .with_cmd(["run"])
+        .start()
+        .await
+    {
+        Ok(container) => container,
+        Err(e) => {
+            eprintln!("Skipping integration test, Docker is not available: {}", e);
+            return None;
+        }
+    };
+
+    let host = container.get_host().await.expect("container host");
+    let port = container
+        .get_host_port_ipv4(OPENFGA_GRPC_PORT)
+        .await
+        .expect("mapped gRPC port");
+    let mut client = OpenFGAClient::builder(format!("http://{}:{}", host, port))
+        .connect_lazy()
+        .expect("valid endpoint");
+
+    // The container reports running before the gRPC server accepts requests
+    for _ in 0..60 {
+        if client
+            .list_stores(ListStoresRequest::default())
+            .await
+            .is_ok()
+        {
+            return Some((container, client));
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    panic!("OpenFGA did not become ready");
+}
+
+fn tuple(user: &str, relation: &str, object: &str) -> TupleKey {
+    TupleKey {
+        user: user.to_string(),
+        relation: relation.to_string(),
+        object: object.to_string(),
+        condition: None,
+    }
+}
+
+#[tokio::test]
+async fn test_create_model_write_tuples_and_check() {
+    let Some((_container, mut client)) = start_openfga().await else {
+        return;
+    };
+
+    let store_id = client
+        .create_store(CreateStoreRequest {
+            name: "integration".to_string(),
+        })
+        .await
+        .expect("create store")
+        .into_inner()
+        .id;
+
+    let model_id = client
+        .write_authorization_model_from_json_string(store_id.clone(), MODEL)
+        .await
+        .expect("write model from JSON")
+        .into_inner()
+        .authorization_model_id;
+
+    client
+        .write(WriteRequest {
+            store_id: store_id.clone(),
+            authorization_model_id: model_id.clone(),
+            writes: Some(WriteRequestWrites {
+                tuple_keys: vec![
+                    tuple("user:alice", "owner", "document:roadmap"),
+                    tuple("user:bob", "member", "group:eng"),
+                    tuple("group:eng#member", "viewer", "document:design"),
+                    tuple("user:*", "viewer", "document:handbook"),
+                ],
+                on_duplicate: String::new(),
+            }),
+            deletes: None,
+        })
+        .await
+        .expect("write tuples");
+
+    let cases = [
+        ("user:alice", "owner", "document:roadmap", true),
+        // viewer is implied by owner through the computed userset
+        ("user:alice", "viewer", "document:roadmap", true),
+        // viewer through group membership
+        ("user:bob", "viewer", "document:design", true),
+        ("user:bob", "viewer", "document:roadmap", false),
+        ("user:alice", "viewer", "document:design", false),
+        // viewer through the type's wildcard
+        ("user:carol", "viewer", "document:handbook", true),
+        ("user:carol", "owner", "document:handbook", false),
+    ];
+    for (user, relation, object, expected) in cases {
+        let mut request = OpenFGAClient::create_check_request(
+            store_id.clone(),
+            object.to_string(),
+            relation.to_string(),
+            user.to_string(),
+        );
+        request.authorization_model_id = model_id.clone();
+
+        let allowed = client
+            .check(request)
+            .await
+            .expect("check")
+            .into_inner()
+            .allowed;
+        assert_eq!(allowed, expected, "{} {} {}", user, relation, object);
+    }
+
+    let mut objects = client
+        .list_objects(ListObjectsRequest {
+            store_id: store_id.clone(),
+            authorization_model_id: model_id.clone(),
+            r#type: "document".to_string(),
+            relation: "viewer".to_string(),
+            user: "user:alice".to_string(),
+            contextual_tuples: None,
+            context: None,
+            consistency: ConsistencyPreference::HigherConsistency as i32,
+        })
+        .await
+        .expect("list objects")
+        .into_inner()
+        .objects;
+    objects.sort();
+    assert_eq!(objects, vec!["document:handbook", "document:roadmap"]);
+}
@@ -0,0 +1,22 @@
+-- Per-organization authentication configuration, looked up by subdomain
+CREATE TABLE IF NOT EXISTS organizations (
+    org_id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    subdomain TEXT NOT NULL UNIQUE,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    dex_connector_id TEXT NOT NULL,
+    auth0_organization_id TEXT,
+    session_secret TEXT NOT NULL,
+    session_config JSONB NOT NULL,
+    pkce_required BOOLEAN NOT NULL DEFAULT TRUE,
+    max_age_seconds INTEGER NOT NULL DEFAULT 300,
+    prompt TEXT,
+    additional_params JSONB,
+    profile_sync_policy TEXT DEFAULT 'always',
+    require_email BOOLEAN DEFAULT FALSE,
+    scopes TEXT[],
+    request_offline_access BOOLEAN DEFAULT FALSE,
+    allowed_return_url_prefixes TEXT[],
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
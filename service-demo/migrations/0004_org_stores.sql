@@ -0,0 +1,8 @@
+-- OpenFGA stores each organization may operate on
+CREATE TABLE IF NOT EXISTS org_stores (
+    org_id TEXT NOT NULL,
+    store_id TEXT NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+
+    PRIMARY KEY(org_id, store_id)
+);
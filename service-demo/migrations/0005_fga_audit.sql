@@ -0,0 +1,15 @@
+-- Sampled OpenFGA checks, writes and deletes
+CREATE TABLE IF NOT EXISTS fga_audit (
+    audit_id BIGSERIAL PRIMARY KEY,
+    user_id TEXT,
+    store_id TEXT NOT NULL,
+    operation TEXT NOT NULL,
+    tuple_user TEXT NOT NULL,
+    tuple_relation TEXT NOT NULL,
+    tuple_object TEXT NOT NULL,
+    decision BOOLEAN,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_fga_audit_store_id ON fga_audit(store_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_fga_audit_user_id ON fga_audit(user_id);
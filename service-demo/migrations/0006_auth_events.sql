@@ -0,0 +1,16 @@
+-- Authentication events such as logins and session creation
+CREATE TABLE IF NOT EXISTS auth_events (
+    event_id BIGSERIAL PRIMARY KEY,
+    event_type TEXT NOT NULL,
+    user_id TEXT,
+    org_id TEXT NOT NULL,
+    ip_address TEXT NOT NULL,
+    user_agent TEXT NOT NULL,
+    success BOOLEAN NOT NULL,
+    error_message TEXT,
+    session_id TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_auth_events_org_id ON auth_events(org_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_auth_events_user_id ON auth_events(user_id);
@@ -0,0 +1,4 @@
+-- Session secrets replaced by rotation, newest first; signed states still verify
+-- against them until they age out
+ALTER TABLE organizations
+    ADD COLUMN IF NOT EXISTS previous_secrets TEXT[] NOT NULL DEFAULT '{}';
@@ -1,8 +1,9 @@
 use axum::extract::{Query, State};
+use axum::http::HeaderMap;
 use axum::response::IntoResponse;
 use openidconnect::{
     ClientId, ClientSecret, CsrfToken, IssuerUrl, Nonce, RedirectUrl, Scope,
-    core::{CoreAuthenticationFlow, CoreClient, CoreProviderMetadata},
+    core::{CoreAuthenticationFlow, CoreClient},
 };
 use reqwest::Client as HttpClient;
 use serde::{Deserialize, Serialize};
@@ -10,18 +11,15 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Mutex;
 
-use crate::context::Ctx;
+use crate::auth::jwt::decode_jwt_segment;
+use crate::context::{Auth0Config, Ctx};
+use crate::i18n::{Key, Lang, request_lang, translate};
 
-// Auth0 Configuration (Static for now - replace with your Auth0 tenant details)
+// Auth0 configuration is read from the AUTH0_* environment variables (see `Auth0Config`).
 // IMPORTANT: To use Organizations feature, ensure your Auth0 tenant is configured with:
 // 1. New Universal Login enabled (Branding → Universal Login → New Experience)
 // 2. Organizations feature enabled in your Auth0 plan
 // 3. The organization is created and configured in Auth0 Dashboard
-const AUTH0_DOMAIN: &str = "genai-157672027117145.jp.auth0.com";
-const AUTH0_CLIENT_ID: &str = "LnlvbZ4nYVqvceavKfrcgKS506Us4ze5";
-const AUTH0_CLIENT_SECRET: &str =
-    "zE5oX1Al14lsKlC7-bhhZruSmi42qbksDOoY1LZyPA8675jPmM_9fBO3MgdJDZ1q";
-const AUTH0_REDIRECT_URL: &str = "http://127.0.0.1:5001/auth/auth0/callback";
 
 // Structure to store state data including nonce
 #[derive(Debug, Clone)]
@@ -58,12 +56,18 @@ pub struct LoginWithParams {
 }
 
 pub async fn login_with(
-    State(_ctx): State<Ctx>,
+    State(ctx): State<Ctx>,
     Query(params): Query<LoginWithParams>,
+    headers: HeaderMap,
 ) -> axum::response::Response {
+    let lang = request_lang(&headers);
+    let Some(auth0) = ctx.auth0_config() else {
+        return build_error_response(lang, "Auth0 is not configured");
+    };
+
     // Construct Auth0 issuer URL
     let issuer_url =
-        IssuerUrl::new(format!("https://{}/", AUTH0_DOMAIN)).expect("Invalid Auth0 issuer URL");
+        IssuerUrl::new(format!("https://{}/", auth0.domain)).expect("Invalid Auth0 issuer URL");
 
     // Create HTTP client using reqwest
     let http_client = HttpClient::builder()
@@ -72,19 +76,17 @@ pub async fn login_with(
         .expect("Failed to create HTTP client");
 
     // Fetch provider metadata using reqwest async client
-    let provider_metadata = CoreProviderMetadata::discover_async(issuer_url, &http_client)
+    let provider_metadata = super::provider_cache::discover(issuer_url, &http_client)
         .await
         .expect("Failed to discover Auth0 provider metadata");
 
     // Create OpenID Connect client
     let client = CoreClient::from_provider_metadata(
         provider_metadata,
-        ClientId::new(AUTH0_CLIENT_ID.to_string()),
-        Some(ClientSecret::new(AUTH0_CLIENT_SECRET.to_string())),
+        ClientId::new(auth0.client_id.clone()),
+        Some(ClientSecret::new(auth0.client_secret.clone())),
     )
-    .set_redirect_uri(
-        RedirectUrl::new(AUTH0_REDIRECT_URL.to_string()).expect("Invalid redirect URL"),
-    );
+    .set_redirect_uri(RedirectUrl::new(auth0.redirect_url.clone()).expect("Invalid redirect URL"));
 
     // Generate CSRF token and nonce for state parameter
     let csrf_token = CsrfToken::new_random();
@@ -140,10 +142,8 @@ pub async fn login_with(
     // IMPORTANT: Organizations require "New Universal Login" in Auth0 Dashboard
     // (Branding → Universal Login → New Experience)
     // Classic Universal Login does NOT support organization parameters
-    if let Some(ref organization) = params.organization {
+    if let Some(organization) = params.organization.as_ref().or(auth0.organization.as_ref()) {
         auth_url_builder = auth_url_builder.add_extra_param("organization", organization);
-    } else {
-        auth_url_builder = auth_url_builder.add_extra_param("organization", "org_eAZQLB5R2udB63jQ");
     }
 
     let (auth_url, _csrf_token, _nonce) = auth_url_builder.url();
@@ -172,10 +172,31 @@ pub struct Auth0CallbackParams {
     pub error_description: Option<String>,
 }
 
+/// Form of the authorization code exchange
+fn token_params<'a>(auth0: &'a Auth0Config, code: &'a str) -> Vec<(&'static str, &'a str)> {
+    let mut params = vec![
+        ("grant_type", "authorization_code"),
+        ("client_id", auth0.client_id.as_str()),
+        ("client_secret", auth0.client_secret.as_str()),
+        ("code", code),
+        ("redirect_uri", auth0.redirect_url.as_str()),
+    ];
+    if let Some(organization) = &auth0.organization {
+        params.push(("organization", organization.as_str()));
+    }
+    params
+}
+
 pub async fn handle_auth0_callback(
-    State(_ctx): State<Ctx>,
+    State(ctx): State<Ctx>,
     Query(params): Query<Auth0CallbackParams>,
+    headers: HeaderMap,
 ) -> axum::response::Response {
+    let lang = request_lang(&headers);
+    let Some(auth0) = ctx.auth0_config() else {
+        return build_error_response(lang, "Auth0 is not configured");
+    };
+
     println!("Auth0 callback params: {:?}", params);
 
     // Check if Auth0 returned an error
@@ -195,7 +216,7 @@ pub async fn handle_auth0_callback(
             store.remove(&params.state);
         }
 
-        return build_auth0_error_response(error, error_description);
+        return build_auth0_error_response(lang, error, error_description);
     }
 
     // Extract authorization code (required if no error)
@@ -203,7 +224,7 @@ pub async fn handle_auth0_callback(
         Some(c) => c,
         None => {
             println!("No authorization code provided in callback");
-            return build_error_response("No authorization code received from Auth0");
+            return build_error_response(lang, "No authorization code received from Auth0");
         }
     };
 
@@ -230,16 +251,8 @@ pub async fn handle_auth0_callback(
     let http_client = HttpClient::new();
 
     // Manually exchange authorization code for tokens using Auth0's token endpoint
-    let token_url = format!("https://{}/oauth/token", AUTH0_DOMAIN);
-
-    let token_params = [
-        ("grant_type", "authorization_code"),
-        ("client_id", AUTH0_CLIENT_ID),
-        ("client_secret", AUTH0_CLIENT_SECRET),
-        ("code", code),
-        ("redirect_uri", AUTH0_REDIRECT_URL),
-        ("organization", "org_eAZQLB5R2udB63jQ"),
-    ];
+    let token_url = format!("https://{}/oauth/token", auth0.domain);
+    let token_params = token_params(auth0, code);
 
     let token_response_result = http_client
         .post(&token_url)
@@ -259,7 +272,7 @@ pub async fn handle_auth0_callback(
                 store.remove(&params.state);
             }
 
-            return build_error_response(&error_msg);
+            return build_error_response(lang, &error_msg);
         }
     };
 
@@ -277,7 +290,7 @@ pub async fn handle_auth0_callback(
                 store.remove(&params.state);
             }
 
-            return build_error_response(&error_msg);
+            return build_error_response(lang, &error_msg);
         }
     };
 
@@ -291,7 +304,7 @@ pub async fn handle_auth0_callback(
             store.remove(&params.state);
         }
 
-        return build_error_response(&error_msg);
+        return build_error_response(lang, &error_msg);
     }
 
     // Parse the token response
@@ -310,7 +323,7 @@ pub async fn handle_auth0_callback(
                 store.remove(&params.state);
             }
 
-            return build_error_response(&error_msg);
+            return build_error_response(lang, &error_msg);
         }
     };
 
@@ -352,8 +365,7 @@ fn verify_id_token(id_token_str: &str) -> Result<String, String> {
     }
 
     // Decode the payload (second part)
-    use base64::{Engine as _, engine::general_purpose};
-    let payload = match general_purpose::URL_SAFE_NO_PAD.decode(parts[1]) {
+    let payload = match decode_jwt_segment(parts[1]) {
         Ok(data) => data,
         Err(e) => return Err(format!("Failed to decode ID token payload: {}", e)),
     };
@@ -372,7 +384,11 @@ fn verify_id_token(id_token_str: &str) -> Result<String, String> {
 }
 
 // Helper function to build Auth0 error response with error code and description
-fn build_auth0_error_response(error: &str, error_description: &str) -> axum::response::Response {
+fn build_auth0_error_response(
+    lang: Lang,
+    error: &str,
+    error_description: &str,
+) -> axum::response::Response {
     axum::response::Response::builder()
         .status(axum::http::StatusCode::BAD_REQUEST)
         .header("Content-Type", "text/html; charset=utf-8")
@@ -383,7 +399,7 @@ fn build_auth0_error_response(error: &str, error_description: &str) -> axum::res
             <head>
                 <meta charset="UTF-8">
                 <meta name="viewport" content="width=device-width, initial-scale=1.0">
-                <title>Auth0 Authentication Error</title>
+                <title>Auth0 - {auth_error}</title>
                 <style>
                     body {{ font-family: Arial, sans-serif; text-align: center; margin-top: 50px; background-color: #f5f5f5; }}
                     .container {{ max-width: 700px; margin: 0 auto; background: white; padding: 30px; border-radius: 8px; box-shadow: 0 2px 4px rgba(0,0,0,0.1); }}
@@ -405,40 +421,48 @@ fn build_auth0_error_response(error: &str, error_description: &str) -> axum::res
                 <div class="container">
                     <div class="error-icon">⚠️</div>
                     <div class="error">
-                        <h1>Auth0 Authentication Failed</h1>
+                        <h1>Auth0: {auth_failed}</h1>
                     </div>
 
                     <div class="error-code">
-                        <div class="error-code-title">Error Code:</div>
-                        <div class="error-code-value">{}</div>
-                        <div class="error-code-title">Error Description:</div>
-                        <div class="error-description">{}</div>
+                        <div class="error-code-title">{error_code}</div>
+                        <div class="error-code-value">{error}</div>
+                        <div class="error-code-title">{error_description_label}</div>
+                        <div class="error-description">{error_description}</div>
                     </div>
 
                     <div class="info-box">
-                        <div class="info-title">Common Causes:</div>
+                        <div class="info-title">{common_causes}</div>
                         <div class="info-text">
-                            • <strong>access_denied:</strong> User cancelled the login or doesn't have access to the organization<br>
-                            • <strong>unauthorized:</strong> Invalid organization or missing permissions<br>
-                            • <strong>invalid_request:</strong> Malformed request parameters<br>
-                            • <strong>organization_required:</strong> Organization parameter is required but not provided
+                            • <strong>access_denied:</strong> {cause_access_denied}<br>
+                            • <strong>unauthorized:</strong> {cause_unauthorized}<br>
+                            • <strong>invalid_request:</strong> {cause_invalid_request}<br>
+                            • <strong>organization_required:</strong> {cause_organization_required}
                         </div>
                     </div>
 
-                    <a href="/auth/auth0" class="back-link">← Return to Login</a>
+                    <a href="/auth/auth0" class="back-link">← {return_to_login}</a>
                 </div>
             </body>
             </html>
             "#,
-            error,
-            error_description
+            auth_error = translate(lang, Key::AuthError),
+            auth_failed = translate(lang, Key::AuthFailed),
+            error_code = translate(lang, Key::ErrorCode),
+            error_description_label = translate(lang, Key::ErrorDescription),
+            common_causes = translate(lang, Key::CommonCauses),
+            cause_access_denied = translate(lang, Key::CauseAccessDenied),
+            cause_unauthorized = translate(lang, Key::CauseUnauthorized),
+            cause_invalid_request = translate(lang, Key::CauseInvalidRequest),
+            cause_organization_required = translate(lang, Key::CauseOrganizationRequired),
+            return_to_login = translate(lang, Key::ReturnToLogin),
         )))
         .unwrap()
         .into_response()
 }
 
 // Helper function to build error response
-fn build_error_response(error_msg: &str) -> axum::response::Response {
+fn build_error_response(lang: Lang, error_msg: &str) -> axum::response::Response {
     axum::response::Response::builder()
         .status(axum::http::StatusCode::BAD_REQUEST)
         .header("Content-Type", "text/html; charset=utf-8")
@@ -449,7 +473,7 @@ fn build_error_response(error_msg: &str) -> axum::response::Response {
             <head>
                 <meta charset="UTF-8">
                 <meta name="viewport" content="width=device-width, initial-scale=1.0">
-                <title>Auth0 Error</title>
+                <title>Auth0 - {auth_error}</title>
                 <style>
                     body {{ font-family: Arial, sans-serif; text-align: center; margin-top: 50px; background-color: #f5f5f5; }}
                     .container {{ max-width: 600px; margin: 0 auto; background: white; padding: 30px; border-radius: 8px; box-shadow: 0 2px 4px rgba(0,0,0,0.1); }}
@@ -462,18 +486,21 @@ fn build_error_response(error_msg: &str) -> axum::response::Response {
             <body>
                 <div class="container">
                     <div class="error">
-                        <h1>✗ Auth0 Authentication Failed!</h1>
+                        <h1>✗ Auth0: {auth_failed}!</h1>
                     </div>
                     <div class="error-details">
-                        <strong>Error Details:</strong><br>
-                        {}
+                        <strong>{error_details}</strong><br>
+                        {error_msg}
                     </div>
-                    <a href="/auth/login" class="back-link">Try Again</a>
+                    <a href="/auth/login" class="back-link">{try_again}</a>
                 </div>
             </body>
             </html>
             "#,
-            error_msg
+            auth_error = translate(lang, Key::AuthError),
+            auth_failed = translate(lang, Key::AuthFailed),
+            error_details = translate(lang, Key::ErrorDetails),
+            try_again = translate(lang, Key::TryAgain),
         )))
         .unwrap()
         .into_response()
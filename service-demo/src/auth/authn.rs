@@ -6,7 +6,8 @@ use hmac::{Hmac, Mac};
 use oauth2::{CsrfToken, PkceCodeChallenge, PkceCodeVerifier};
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use url::Url;
 
 // Type alias for HMAC-SHA256
@@ -79,6 +80,11 @@ pub struct OrgAuthConfig {
     /// In production, this should be encrypted at rest
     pub session_secret: String,
 
+    /// Session secrets replaced by [`rotate_session_secret`](crate::auth::db_ops::rotate_session_secret),
+    /// newest first. States signed with them still verify.
+    #[serde(default)]
+    pub previous_secrets: Vec<String>,
+
     /// Session configuration for this organization
     pub session_config: crate::auth::models::SessionConfig,
 
@@ -96,6 +102,159 @@ pub struct OrgAuthConfig {
     /// Additional custom parameters for the authorization request
     #[serde(default)]
     pub additional_params: std::collections::HashMap<String, String>,
+
+    /// Whether logins update the user's profile from the ID token claims
+    #[serde(default)]
+    pub profile_sync_policy: ProfileSyncPolicy,
+
+    /// Reject logins whose ID token has no email claim.
+    /// When false, such users are stored without an email.
+    #[serde(default)]
+    pub require_email: bool,
+
+    /// OAuth scopes for this organization, replacing the Dex app's default scopes
+    #[serde(default)]
+    pub scopes: Option<Vec<String>>,
+
+    /// Add the `offline_access` scope so the IdP issues a refresh token
+    #[serde(default)]
+    pub request_offline_access: bool,
+
+    /// Absolute URL prefixes (e.g. `https://app.acme.com/`) allowed as return URLs
+    /// besides relative paths and same-origin URLs
+    #[serde(default)]
+    pub allowed_return_url_prefixes: Vec<String>,
+}
+
+/// Where users are sent after login when no valid return URL is given
+pub const DEFAULT_RETURN_URL: &str = "/dashboard";
+
+/// Scope that asks the IdP for a refresh token
+pub const OFFLINE_ACCESS_SCOPE: &str = "offline_access";
+
+impl OrgAuthConfig {
+    /// Scopes to request for this organization: its own scopes if configured, otherwise
+    /// the Dex app's defaults, plus `offline_access` when `request_offline_access` is set
+    pub fn effective_scopes(&self, dex_config: &DexAppConfig) -> Vec<String> {
+        let mut scopes = self
+            .scopes
+            .clone()
+            .unwrap_or_else(|| dex_config.scopes.clone());
+
+        if self.request_offline_access && !scopes.iter().any(|s| s == OFFLINE_ACCESS_SCOPE) {
+            scopes.push(OFFLINE_ACCESS_SCOPE.to_string());
+        }
+
+        scopes
+    }
+
+    /// The requested return URL if [`validate_return_url`] accepts it, otherwise
+    /// [`DEFAULT_RETURN_URL`]
+    pub fn sanitize_return_url(
+        &self,
+        return_url: Option<String>,
+        request_host: Option<&str>,
+    ) -> String {
+        match return_url {
+            Some(url)
+                if validate_return_url(&url, request_host, &self.allowed_return_url_prefixes) =>
+            {
+                url
+            }
+            Some(url) => {
+                tracing::warn!(
+                    "Rejected return URL {:?} for organization {}",
+                    url,
+                    self.org_id
+                );
+                DEFAULT_RETURN_URL.to_string()
+            }
+            None => DEFAULT_RETURN_URL.to_string(),
+        }
+    }
+}
+
+/// Whether a return URL is safe to redirect to after login.
+///
+/// Allows relative paths (but not protocol-relative `//host` URLs), absolute
+/// http(s) URLs on `request_host`, and URLs starting with one of `allowed_prefixes`
+/// at a path boundary, so `https://app.acme.com` doesn't allow
+/// `https://app.acme.com.evil.com`.
+pub fn validate_return_url(
+    return_url: &str,
+    request_host: Option<&str>,
+    allowed_prefixes: &[String],
+) -> bool {
+    if return_url.is_empty() || return_url.chars().any(|c| c.is_control() || c == '\\') {
+        return false;
+    }
+
+    if return_url.starts_with('/') {
+        return !return_url.starts_with("//");
+    }
+
+    let Ok(url) = Url::parse(return_url) else {
+        return false;
+    };
+    if !matches!(url.scheme(), "http" | "https") {
+        return false;
+    }
+
+    let same_origin = request_host.is_some_and(|host| {
+        let authority = match (url.host_str(), url.port()) {
+            (Some(url_host), Some(port)) => format!("{}:{}", url_host, port),
+            (Some(url_host), None) => url_host.to_string(),
+            (None, _) => return false,
+        };
+        authority.eq_ignore_ascii_case(host)
+    });
+
+    same_origin
+        || allowed_prefixes.iter().any(|prefix| {
+            return_url
+                .strip_prefix(prefix.as_str())
+                .is_some_and(|rest| {
+                    prefix.ends_with('/') || rest.is_empty() || rest.starts_with(['/', '?', '#'])
+                })
+        })
+}
+
+/// When user profile fields (name, picture) are taken from ID token claims
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileSyncPolicy {
+    /// Set the profile on creation and refresh it on every login
+    #[default]
+    Always,
+    /// Set the profile on creation only, for orgs that manage it afterwards
+    OnCreateOnly,
+    /// Never take the profile from claims
+    Never,
+}
+
+impl ProfileSyncPolicy {
+    /// Whether a new user's profile is filled from the claims
+    pub fn syncs_on_create(self) -> bool {
+        self != ProfileSyncPolicy::Never
+    }
+
+    /// Whether an existing user's profile is updated from the claims on login
+    pub fn syncs_on_login(self) -> bool {
+        self == ProfileSyncPolicy::Always
+    }
+}
+
+impl std::str::FromStr for ProfileSyncPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "always" => Ok(ProfileSyncPolicy::Always),
+            "on_create_only" => Ok(ProfileSyncPolicy::OnCreateOnly),
+            "never" => Ok(ProfileSyncPolicy::Never),
+            other => Err(format!("unknown profile sync policy {:?}", other)),
+        }
+    }
 }
 
 fn default_pkce_required() -> bool {
@@ -217,36 +376,57 @@ struct SignedState {
     /// Unique state identifier (used as Redis key)
     state_id: String,
 
+    /// Organization the authorization request was made for
+    org_id: String,
+
+    /// Redirect URI of the authorization request, which the callback must present
+    /// again in the token exchange
+    redirect_uri: String,
+
     /// Timestamp when signed
     timestamp: u64,
 
-    /// HMAC signature of state_id + timestamp
+    /// HMAC signature of state_id + org_id + redirect_uri + timestamp
     signature: String,
 }
 
 impl SignedState {
-    /// Create a new signed state
-    fn new(state_id: String, secret: &str) -> Result<Self> {
+    /// Create a new signed state, bound to the organization and redirect URI of the
+    /// authorization request
+    fn new(state_id: String, org_id: &str, redirect_uri: &str, secret: &str) -> Result<Self> {
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
             .as_secs();
 
-        let signature = Self::compute_signature(&state_id, timestamp, secret)?;
+        let signature =
+            Self::compute_signature(&state_id, org_id, redirect_uri, timestamp, secret)?;
 
         Ok(Self {
             state_id,
+            org_id: org_id.to_string(),
+            redirect_uri: redirect_uri.to_string(),
             timestamp,
             signature,
         })
     }
 
     /// Compute HMAC signature
-    fn compute_signature(state_id: &str, timestamp: u64, secret: &str) -> Result<String> {
+    fn compute_signature(
+        state_id: &str,
+        org_id: &str,
+        redirect_uri: &str,
+        timestamp: u64,
+        secret: &str,
+    ) -> Result<String> {
         let mut mac =
             HmacSha256::new_from_slice(secret.as_bytes()).context("Failed to create HMAC")?;
 
-        mac.update(state_id.as_bytes());
+        // Length-prefix the variable fields so they can't be shifted into each other
+        for field in [state_id, org_id, redirect_uri] {
+            mac.update(&(field.len() as u64).to_le_bytes());
+            mac.update(field.as_bytes());
+        }
         mac.update(&timestamp.to_le_bytes());
 
         let result = mac.finalize();
@@ -255,7 +435,13 @@ impl SignedState {
 
     /// Verify the signature
     fn verify(&self, secret: &str) -> Result<()> {
-        let expected_sig = Self::compute_signature(&self.state_id, self.timestamp, secret)?;
+        let expected_sig = Self::compute_signature(
+            &self.state_id,
+            &self.org_id,
+            &self.redirect_uri,
+            self.timestamp,
+            secret,
+        )?;
 
         if self.signature != expected_sig {
             anyhow::bail!("Invalid state signature");
@@ -264,14 +450,29 @@ impl SignedState {
         Ok(())
     }
 
+    /// Ensure the state was issued for `org_id` and `redirect_uri`, so a state can't be
+    /// replayed against another organization or with a substituted redirect URI
+    fn verify_binding(&self, org_id: &str, redirect_uri: &str) -> Result<()> {
+        if self.org_id != org_id {
+            anyhow::bail!("Organization ID mismatch");
+        }
+
+        if self.redirect_uri != redirect_uri {
+            anyhow::bail!("Redirect URI mismatch");
+        }
+
+        Ok(())
+    }
+
     /// Encode to base64url string
     fn encode(&self) -> Result<String> {
         let json = serde_json::to_string(self)?;
         Ok(URL_SAFE_NO_PAD.encode(json.as_bytes()))
     }
 
-    /// Decode from base64url string
-    fn decode(encoded: &str, secret: &str) -> Result<Self> {
+    /// Decode from base64url string, verifying against `secret` or, for states signed
+    /// before a rotation, one of `previous_secrets`
+    fn decode(encoded: &str, secret: &str, previous_secrets: &[String]) -> Result<Self> {
         let decoded = URL_SAFE_NO_PAD
             .decode(encoded)
             .context("Failed to decode state")?;
@@ -279,7 +480,12 @@ impl SignedState {
         let signed_state: SignedState =
             serde_json::from_slice(&decoded).context("Failed to parse signed state")?;
 
-        signed_state.verify(secret)?;
+        let verified = std::iter::once(secret)
+            .chain(previous_secrets.iter().map(String::as_str))
+            .any(|secret| signed_state.verify(secret).is_ok());
+        if !verified {
+            anyhow::bail!("Invalid state signature");
+        }
 
         Ok(signed_state)
     }
@@ -317,79 +523,272 @@ impl StateCache {
 
     /// Store auth state in Redis with TTL
     pub async fn store(&self, state: &AuthState) -> Result<String> {
-        let mut conn = self
-            .pool
-            .get()
-            .await
-            .context("Failed to get Redis connection from pool")?;
+        timed(StateCacheOp::Store, async {
+            let mut conn = self
+                .pool
+                .get()
+                .await
+                .context("Failed to get Redis connection from pool")?;
 
-        let state_id = generate_session_id();
-        let key = format!("auth:state:{}", state_id);
-        let ttl = (state.expires_at - state.created_at) as i64;
+            let state_id = generate_session_id();
+            let key = format!("auth:state:{}", state_id);
+            let ttl = (state.expires_at - state.created_at) as i64;
 
-        let json = serde_json::to_string(state).context("Failed to serialize state")?;
+            let json = serde_json::to_string(state).context("Failed to serialize state")?;
 
-        let _: () = conn
-            .set_ex(&key, json, ttl as u64)
-            .await
-            .context("Failed to store state in Redis")?;
+            let _: () = conn
+                .set_ex(&key, json, ttl as u64)
+                .await
+                .context("Failed to store state in Redis")?;
 
-        Ok(state_id)
+            Ok(state_id)
+        })
+        .await
     }
 
     /// Retrieve and validate auth state from Redis
     pub async fn retrieve(&self, state_id: &str) -> Result<Option<AuthState>> {
-        let mut conn = self
-            .pool
-            .get()
-            .await
-            .context("Failed to get Redis connection from pool")?;
-
-        let key = format!("auth:state:{}", state_id);
-        let json: Option<String> = conn
-            .get(&key)
-            .await
-            .context("Failed to retrieve state from Redis")?;
-
-        match json {
-            Some(data) => {
-                let state: AuthState =
-                    serde_json::from_str(&data).context("Failed to parse state from Redis")?;
-                Ok(Some(state))
+        timed(StateCacheOp::Retrieve, async {
+            let mut conn = self
+                .pool
+                .get()
+                .await
+                .context("Failed to get Redis connection from pool")?;
+
+            let key = format!("auth:state:{}", state_id);
+            let json: Option<String> = conn
+                .get(&key)
+                .await
+                .context("Failed to retrieve state from Redis")?;
+
+            match json {
+                Some(data) => {
+                    let state: AuthState =
+                        serde_json::from_str(&data).context("Failed to parse state from Redis")?;
+                    Ok(Some(state))
+                }
+                None => Ok(None),
             }
-            None => Ok(None),
-        }
+        })
+        .await
     }
 
     /// Invalidate (delete) auth state from Redis
     pub async fn invalidate(&self, state_id: &str) -> Result<()> {
+        timed(StateCacheOp::Invalidate, async {
+            let mut conn = self
+                .pool
+                .get()
+                .await
+                .context("Failed to get Redis connection from pool")?;
+
+            let key = format!("auth:state:{}", state_id);
+            let _: () = conn
+                .del(&key)
+                .await
+                .context("Failed to delete state from Redis")?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// Get pool health status
+    pub async fn health_check(&self) -> Result<bool> {
         let mut conn = self
             .pool
             .get()
             .await
             .context("Failed to get Redis connection from pool")?;
 
-        let key = format!("auth:state:{}", state_id);
-        let _: () = conn
-            .del(&key)
-            .await
-            .context("Failed to delete state from Redis")?;
+        let _: String = conn.ping().await.context("Redis ping failed")?;
 
-        Ok(())
+        Ok(true)
     }
+}
 
-    /// Get pool health status
-    pub async fn health_check(&self) -> Result<bool> {
+/// Checks behind the auth state health report, implemented by [`StateCache`]
+pub trait StateCacheProbe {
+    /// Check that the store is reachable
+    fn ping(&self) -> impl Future<Output = Result<()>> + Send;
+
+    /// Approximate number of outstanding auth states
+    fn approximate_state_count(&self) -> impl Future<Output = Result<u64>> + Send;
+}
+
+impl StateCacheProbe for StateCache {
+    async fn ping(&self) -> Result<()> {
+        self.health_check().await.map(|_| ())
+    }
+
+    /// Uses `DBSIZE`, so this also counts any other keys in the Redis database
+    async fn approximate_state_count(&self) -> Result<u64> {
         let mut conn = self
             .pool
             .get()
             .await
             .context("Failed to get Redis connection from pool")?;
 
-        let _: String = conn.ping().await.context("Redis ping failed")?;
+        bb8_redis::redis::cmd("DBSIZE")
+            .query_async(&mut *conn)
+            .await
+            .context("Redis DBSIZE failed")
+    }
+}
 
-        Ok(true)
+// ============================================================================
+// State Cache Health and Metrics
+// ============================================================================
+
+/// Auth state cache operation whose latency is recorded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateCacheOp {
+    Store,
+    Retrieve,
+    Invalidate,
+}
+
+#[derive(Debug, Default)]
+struct OpMetrics {
+    count: AtomicU64,
+    errors: AtomicU64,
+    total_micros: AtomicU64,
+    max_micros: AtomicU64,
+}
+
+impl OpMetrics {
+    const fn new() -> Self {
+        Self {
+            count: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            total_micros: AtomicU64::new(0),
+            max_micros: AtomicU64::new(0),
+        }
+    }
+
+    fn record(&self, elapsed: Duration, ok: bool) {
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        if !ok {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        self.total_micros.fetch_add(micros, Ordering::Relaxed);
+        self.max_micros.fetch_max(micros, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> OpMetricsSnapshot {
+        let count = self.count.load(Ordering::Relaxed);
+        let total_micros = self.total_micros.load(Ordering::Relaxed);
+        OpMetricsSnapshot {
+            count,
+            errors: self.errors.load(Ordering::Relaxed),
+            avg_latency_ms: if count == 0 {
+                0.0
+            } else {
+                total_micros as f64 / count as f64 / 1000.0
+            },
+            max_latency_ms: self.max_micros.load(Ordering::Relaxed) as f64 / 1000.0,
+        }
+    }
+}
+
+/// Process-wide latency counters of the auth state cache
+#[derive(Debug)]
+pub struct StateCacheMetrics {
+    store: OpMetrics,
+    retrieve: OpMetrics,
+    invalidate: OpMetrics,
+}
+
+static STATE_CACHE_METRICS: StateCacheMetrics = StateCacheMetrics {
+    store: OpMetrics::new(),
+    retrieve: OpMetrics::new(),
+    invalidate: OpMetrics::new(),
+};
+
+impl StateCacheMetrics {
+    /// Get the metrics shared by every [`StateCache`]
+    pub fn global() -> &'static Self {
+        &STATE_CACHE_METRICS
+    }
+
+    /// Record one operation
+    pub fn record(&self, op: StateCacheOp, elapsed: Duration, ok: bool) {
+        let metrics = match op {
+            StateCacheOp::Store => &self.store,
+            StateCacheOp::Retrieve => &self.retrieve,
+            StateCacheOp::Invalidate => &self.invalidate,
+        };
+        metrics.record(elapsed, ok);
+    }
+
+    pub fn snapshot(&self) -> StateCacheMetricsSnapshot {
+        StateCacheMetricsSnapshot {
+            store: self.store.snapshot(),
+            retrieve: self.retrieve.snapshot(),
+            invalidate: self.invalidate.snapshot(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpMetricsSnapshot {
+    pub count: u64,
+    pub errors: u64,
+    pub avg_latency_ms: f64,
+    pub max_latency_ms: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StateCacheMetricsSnapshot {
+    pub store: OpMetricsSnapshot,
+    pub retrieve: OpMetricsSnapshot,
+    pub invalidate: OpMetricsSnapshot,
+}
+
+/// Health of the login subsystem's state store
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StateCacheHealth {
+    pub redis_reachable: bool,
+    /// Why Redis is unreachable
+    pub error: Option<String>,
+    /// `None` when the count couldn't be read
+    pub approximate_auth_states: Option<u64>,
+    pub metrics: StateCacheMetricsSnapshot,
+}
+
+/// Probe the state store and collect the latency metrics
+pub async fn state_cache_health(probe: &impl StateCacheProbe) -> StateCacheHealth {
+    let metrics = StateCacheMetrics::global().snapshot();
+    if let Err(e) = probe.ping().await {
+        return StateCacheHealth {
+            redis_reachable: false,
+            error: Some(format!("{:#}", e)),
+            approximate_auth_states: None,
+            metrics,
+        };
     }
+
+    let approximate_auth_states = probe
+        .approximate_state_count()
+        .await
+        .inspect_err(|e| tracing::warn!("Failed to count auth states: {:?}", e))
+        .ok();
+
+    StateCacheHealth {
+        redis_reachable: true,
+        error: None,
+        approximate_auth_states,
+        metrics,
+    }
+}
+
+/// Run a state cache operation, recording its latency
+async fn timed<T>(op: StateCacheOp, operation: impl Future<Output = Result<T>>) -> Result<T> {
+    let started = Instant::now();
+    let result = operation.await;
+    StateCacheMetrics::global().record(op, started.elapsed(), result.is_ok());
+    result
 }
 
 // ============================================================================
@@ -456,7 +855,12 @@ impl AuthorizationUrlBuilder {
         let state_id = self.state_cache.store(&auth_state).await?;
 
         // 4. Create signed state parameter
-        let signed_state = SignedState::new(state_id, &org_config.session_secret)?;
+        let signed_state = SignedState::new(
+            state_id,
+            &org_config.org_id,
+            &dex_config.redirect_url,
+            &org_config.session_secret,
+        )?;
         let state_param = signed_state.encode()?;
 
         // 5. Build authorization URL with all parameters
@@ -469,7 +873,7 @@ impl AuthorizationUrlBuilder {
             query.append_pair("client_id", &dex_config.client_id);
             query.append_pair("redirect_uri", &dex_config.redirect_url);
             query.append_pair("response_type", "code");
-            query.append_pair("scope", &dex_config.scopes.join(" "));
+            query.append_pair("scope", &org_config.effective_scopes(dex_config).join(" "));
             query.append_pair("state", &state_param);
             query.append_pair("nonce", &auth_state.nonce);
 
@@ -506,16 +910,27 @@ impl AuthorizationUrlBuilder {
     }
 
     /// Retrieve and validate auth state from signed state parameter
+    ///
+    /// `redirect_uri` is the redirect URI the callback presents in the token exchange;
+    /// it must be the one the state was signed with.
     pub async fn retrieve_auth_state(
         &self,
         state_param: &str,
         org_config: &OrgAuthConfig,
+        redirect_uri: &str,
         client_ip: &str,
         client_user_agent: &str,
     ) -> Result<AuthState> {
-        // 1. Decode and verify signed state
-        let signed_state = SignedState::decode(state_param, &org_config.session_secret)
-            .context("Failed to verify state signature")?;
+        // 1. Decode and verify signed state, and what it is bound to
+        let signed_state = SignedState::decode(
+            state_param,
+            &org_config.session_secret,
+            &org_config.previous_secrets,
+        )
+        .context("Failed to verify state signature")?;
+        signed_state
+            .verify_binding(&org_config.org_id, redirect_uri)
+            .context("State binding validation failed")?;
 
         // 2. Retrieve state from Redis
         let auth_state = self
@@ -543,7 +958,11 @@ impl AuthorizationUrlBuilder {
         state_param: &str,
         org_config: &OrgAuthConfig,
     ) -> Result<()> {
-        let signed_state = SignedState::decode(state_param, &org_config.session_secret)?;
+        let signed_state = SignedState::decode(
+            state_param,
+            &org_config.session_secret,
+            &org_config.previous_secrets,
+        )?;
         self.state_cache.invalidate(&signed_state.state_id).await
     }
 }
@@ -574,6 +993,8 @@ fn hash_user_agent(user_agent: &str) -> String {
 mod tests {
     use super::*;
 
+    const REDIRECT_URI: &str = "https://auth.example.com/callback";
+
     #[test]
     fn test_oauth2_random_generators() {
         // Test that oauth2's random generators create unique values
@@ -594,9 +1015,9 @@ mod tests {
         let state_id = generate_session_id();
         let secret = "test-secret-key";
 
-        let signed = SignedState::new(state_id.clone(), secret).unwrap();
+        let signed = SignedState::new(state_id.clone(), "org-123", REDIRECT_URI, secret).unwrap();
         let encoded = signed.encode().unwrap();
-        let decoded = SignedState::decode(&encoded, secret).unwrap();
+        let decoded = SignedState::decode(&encoded, secret, &[]).unwrap();
 
         assert_eq!(signed.state_id, decoded.state_id);
     }
@@ -607,13 +1028,52 @@ mod tests {
         let secret = "test-secret-key";
         let wrong_secret = "wrong-secret";
 
-        let signed = SignedState::new(state_id, secret).unwrap();
+        let signed = SignedState::new(state_id, "org-123", REDIRECT_URI, secret).unwrap();
         let encoded = signed.encode().unwrap();
 
-        let result = SignedState::decode(&encoded, wrong_secret);
+        let result = SignedState::decode(&encoded, wrong_secret, &[]);
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_signed_state_verifies_with_previous_secret() {
+        let signed =
+            SignedState::new(generate_session_id(), "org-123", REDIRECT_URI, "old-secret").unwrap();
+        let encoded = signed.encode().unwrap();
+
+        let previous = vec!["older-secret".to_string(), "old-secret".to_string()];
+        let decoded = SignedState::decode(&encoded, "new-secret", &previous).unwrap();
+        assert_eq!(decoded.state_id, signed.state_id);
+
+        let evicted = vec!["older-secret".to_string()];
+        assert!(SignedState::decode(&encoded, "new-secret", &evicted).is_err());
+    }
+
+    #[test]
+    fn test_signed_state_bound_to_redirect_uri() {
+        let secret = "test-secret-key";
+        let signed =
+            SignedState::new(generate_session_id(), "org-123", REDIRECT_URI, secret).unwrap();
+        let encoded = signed.encode().unwrap();
+
+        let decoded = SignedState::decode(&encoded, secret, &[]).unwrap();
+        assert!(decoded.verify_binding("org-123", REDIRECT_URI).is_ok());
+        assert!(
+            decoded
+                .verify_binding("org-123", "https://attacker.example.com/callback")
+                .is_err()
+        );
+        assert!(decoded.verify_binding("org-456", REDIRECT_URI).is_err());
+
+        // Rewriting the bound redirect URI breaks the signature
+        let tampered = SignedState {
+            redirect_uri: "https://attacker.example.com/callback".to_string(),
+            ..decoded
+        };
+        let tampered = tampered.encode().unwrap();
+        assert!(SignedState::decode(&tampered, secret, &[]).is_err());
+    }
+
     #[test]
     fn test_auth_state_expiration() {
         let state = AuthState::new(
@@ -627,6 +1087,105 @@ mod tests {
         assert!(state.is_expired());
     }
 
+    struct MockProbe {
+        reachable: bool,
+    }
+
+    impl StateCacheProbe for MockProbe {
+        async fn ping(&self) -> Result<()> {
+            if self.reachable {
+                Ok(())
+            } else {
+                anyhow::bail!("connection refused")
+            }
+        }
+
+        async fn approximate_state_count(&self) -> Result<u64> {
+            Ok(3)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_state_cache_health() {
+        let probe = MockProbe { reachable: true };
+        assert!(probe.ping().await.is_ok());
+
+        let health = serde_json::to_value(state_cache_health(&probe).await).unwrap();
+        assert_eq!(health["redis_reachable"], true);
+        assert_eq!(health["error"], serde_json::Value::Null);
+        assert_eq!(health["approximate_auth_states"], 3);
+        for op in ["store", "retrieve", "invalidate"] {
+            for field in ["count", "errors", "avg_latency_ms", "max_latency_ms"] {
+                assert!(health["metrics"][op][field].is_number(), "{}.{}", op, field);
+            }
+        }
+
+        let health = state_cache_health(&MockProbe { reachable: false }).await;
+        assert!(!health.redis_reachable);
+        assert_eq!(health.error.as_deref(), Some("connection refused"));
+        assert_eq!(health.approximate_auth_states, None);
+    }
+
+    #[test]
+    fn test_state_cache_metrics_record() {
+        let metrics = OpMetrics::new();
+        metrics.record(Duration::from_millis(2), true);
+        metrics.record(Duration::from_millis(4), false);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.count, 2);
+        assert_eq!(snapshot.errors, 1);
+        assert_eq!(snapshot.avg_latency_ms, 3.0);
+        assert_eq!(snapshot.max_latency_ms, 4.0);
+    }
+
+    #[test]
+    fn test_validate_return_url() {
+        let allowed = vec!["https://app.acme.com".to_string()];
+
+        // Relative paths
+        assert!(validate_return_url("/dashboard", None, &[]));
+        assert!(validate_return_url("/docs?page=2#top", None, &[]));
+        assert!(!validate_return_url("//evil.com/dashboard", None, &[]));
+        assert!(!validate_return_url("/\\evil.com", None, &[]));
+
+        // Same origin
+        let host = Some("acme.example.com");
+        assert!(validate_return_url(
+            "https://acme.example.com/dashboard",
+            host,
+            &[]
+        ));
+        assert!(!validate_return_url(
+            "https://acme.example.com/dashboard",
+            None,
+            &[]
+        ));
+        assert!(!validate_return_url("javascript:alert(1)", host, &[]));
+
+        // External
+        assert!(!validate_return_url(
+            "https://evil.com/dashboard",
+            host,
+            &[]
+        ));
+        assert!(!validate_return_url(
+            "https://acme.example.com.evil.com/",
+            host,
+            &[]
+        ));
+        assert!(validate_return_url(
+            "https://app.acme.com/home",
+            host,
+            &allowed
+        ));
+        assert!(!validate_return_url(
+            "https://app.acme.com.evil.com/",
+            host,
+            &allowed
+        ));
+    }
+
     #[test]
     fn test_auth_state_security_tokens() {
         let state = AuthState::new(
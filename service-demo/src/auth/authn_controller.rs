@@ -2,6 +2,7 @@
 ///
 /// Handles the OAuth2/OIDC authentication flow with Dex for multi-tenant organizations
 use super::authn::{AuthorizationUrlBuilder, AuthorizeRequest, DexAppConfig, OrgAuthConfig};
+use super::models::{SessionConfig, check_secret_length};
 use axum::{
     Json,
     extract::{Query, State},
@@ -88,11 +89,12 @@ pub async fn login_handler(
         .await
         .map_err(|e| AppError::NotFound(format!("Organization not found: {}", e)))?;
 
-    // 3. Build authorization request
+    // 3. Build authorization request, only redirecting back to allowed URLs
+    let return_url = org_config.sanitize_return_url(query.return_url, request_host(&headers));
     let authorize_request = AuthorizeRequest {
         dex_config: app_state.dex_config.clone(),
         org_config,
-        return_url: query.return_url.unwrap_or_else(|| "/dashboard".to_string()),
+        return_url,
         client_ip,
         client_user_agent: user_agent,
     };
@@ -135,13 +137,12 @@ pub async fn get_authorize_url_handler(
         .await
         .map_err(|e| AppError::NotFound(format!("Organization not found: {}", e)))?;
 
-    // 3. Build authorization request
+    // 3. Build authorization request, only redirecting back to allowed URLs
+    let return_url = org_config.sanitize_return_url(request.return_url, request_host(&headers));
     let authorize_request = AuthorizeRequest {
         dex_config: app_state.dex_config.clone(),
         org_config,
-        return_url: request
-            .return_url
-            .unwrap_or_else(|| "/dashboard".to_string()),
+        return_url,
         client_ip,
         client_user_agent: user_agent,
     };
@@ -187,6 +188,11 @@ pub fn extract_client_ip(headers: &HeaderMap) -> String {
     "unknown".to_string()
 }
 
+/// Host the request was sent to
+fn request_host(headers: &HeaderMap) -> Option<&str> {
+    headers.get("host").and_then(|v| v.to_str().ok())
+}
+
 /// Extract user agent from request headers
 pub fn extract_user_agent(headers: &HeaderMap) -> String {
     headers
@@ -206,10 +212,16 @@ pub fn extract_user_agent(headers: &HeaderMap) -> String {
 ///     dex_connector_id,
 ///     auth0_organization_id,
 ///     session_secret,
+///     previous_secrets,
 ///     pkce_required,
 ///     max_age_seconds,
 ///     prompt,
-///     additional_params
+///     additional_params,
+///     profile_sync_policy,
+///     require_email,
+///     scopes,
+///     request_offline_access,
+///     allowed_return_url_prefixes
 /// FROM organizations
 /// WHERE subdomain = $1 AND active = true
 /// ```
@@ -225,11 +237,17 @@ pub async fn get_org_config_by_subdomain(
             dex_connector_id,
             auth0_organization_id,
             session_secret,
+            previous_secrets,
             session_config,
             pkce_required,
             max_age_seconds,
             prompt,
-            additional_params
+            additional_params,
+            profile_sync_policy,
+            require_email,
+            scopes,
+            request_offline_access,
+            allowed_return_url_prefixes
         FROM organizations
         WHERE subdomain = $1 AND active = true
         "#,
@@ -238,7 +256,7 @@ pub async fn get_org_config_by_subdomain(
     .fetch_one(db)
     .await?;
 
-    Ok(row.into())
+    row.try_into()
 }
 
 /// Database row structure for organization configuration
@@ -249,22 +267,39 @@ struct OrgAuthConfigRow {
     dex_connector_id: String,
     auth0_organization_id: Option<String>,
     session_secret: String,
+    previous_secrets: Vec<String>,
     session_config: sqlx::types::JsonValue,
     pkce_required: bool,
     max_age_seconds: i32,
     prompt: Option<String>,
     additional_params: Option<sqlx::types::JsonValue>,
+    profile_sync_policy: Option<String>,
+    require_email: Option<bool>,
+    scopes: Option<Vec<String>>,
+    request_offline_access: Option<bool>,
+    allowed_return_url_prefixes: Option<Vec<String>>,
 }
 
-impl From<OrgAuthConfigRow> for OrgAuthConfig {
-    fn from(row: OrgAuthConfigRow) -> Self {
-        Self {
+impl TryFrom<OrgAuthConfigRow> for OrgAuthConfig {
+    type Error = anyhow::Error;
+
+    /// Fails when the session or cookie signing secret is shorter than
+    /// [`MIN_SECRET_LENGTH`](crate::auth::models::MIN_SECRET_LENGTH)
+    fn try_from(row: OrgAuthConfigRow) -> anyhow::Result<Self> {
+        let session_config: SessionConfig =
+            serde_json::from_value(row.session_config).unwrap_or_default();
+        check_secret_length("session_secret", &row.session_secret)
+            .and_then(|()| session_config.check_signing_secret())
+            .map_err(|e| anyhow::anyhow!("organization {}: {}", row.org_id, e))?;
+
+        Ok(Self {
             org_id: row.org_id,
             subdomain: row.subdomain,
             dex_connector_id: row.dex_connector_id,
             auth0_organization_id: row.auth0_organization_id,
             session_secret: row.session_secret,
-            session_config: serde_json::from_value(row.session_config).unwrap_or_default(),
+            previous_secrets: row.previous_secrets,
+            session_config,
             pkce_required: row.pkce_required,
             max_age_seconds: row.max_age_seconds as u64,
             prompt: row.prompt,
@@ -272,7 +307,15 @@ impl From<OrgAuthConfigRow> for OrgAuthConfig {
                 .additional_params
                 .and_then(|v| serde_json::from_value(v).ok())
                 .unwrap_or_default(),
-        }
+            profile_sync_policy: row
+                .profile_sync_policy
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_default(),
+            require_email: row.require_email.unwrap_or(false),
+            scopes: row.scopes,
+            request_offline_access: row.request_offline_access.unwrap_or(false),
+            allowed_return_url_prefixes: row.allowed_return_url_prefixes.unwrap_or_default(),
+        })
     }
 }
 
@@ -349,4 +392,48 @@ mod tests {
         );
         assert_eq!(extract_subdomain_from_host("localhost"), None);
     }
+
+    fn config_row(session_secret: &str, cookie_signing_secret: &str) -> OrgAuthConfigRow {
+        let session_config = SessionConfig {
+            cookie_signing_secret: cookie_signing_secret.to_string(),
+            ..SessionConfig::default()
+        };
+        OrgAuthConfigRow {
+            org_id: "org_acme".to_string(),
+            subdomain: "acme".to_string(),
+            dex_connector_id: "mock".to_string(),
+            auth0_organization_id: None,
+            session_secret: session_secret.to_string(),
+            previous_secrets: Vec::new(),
+            session_config: serde_json::to_value(session_config).unwrap(),
+            pkce_required: true,
+            max_age_seconds: 0,
+            prompt: None,
+            additional_params: None,
+            profile_sync_policy: None,
+            require_email: None,
+            scopes: None,
+            request_offline_access: None,
+            allowed_return_url_prefixes: None,
+        }
+    }
+
+    #[test]
+    fn test_org_config_requires_long_secrets() {
+        let adequate = "0123456789abcdef0123456789abcdef";
+
+        let config = OrgAuthConfig::try_from(config_row(adequate, adequate)).unwrap();
+        assert_eq!(config.session_secret, adequate);
+        assert_eq!(config.session_config.cookie_signing_secret, adequate);
+
+        // Empty, as SessionConfig::default() leaves it
+        let error = OrgAuthConfig::try_from(config_row(adequate, "")).unwrap_err();
+        assert!(error.to_string().contains("cookie_signing_secret"));
+
+        let error = OrgAuthConfig::try_from(config_row("too-short", adequate)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "organization org_acme: session_secret must be at least 32 bytes, got 9"
+        );
+    }
 }
@@ -18,7 +18,7 @@
 /// 
 /// ```rust,ignore
 /// use service_demo::auth::authn::{
-///     AuthorizationUrlBuilder, AuthorizeRequest, OrgAuthConfig,
+///     AuthorizationUrlBuilder, AuthorizeRequest, OrgAuthConfig, ProfileSyncPolicy,
 /// };
 /// 
 /// #[tokio::main]
@@ -42,10 +42,16 @@
 ///             "offline_access".to_string(),
 ///         ],
 ///         session_secret: "your-session-secret-key-min-32-chars".to_string(),
+///         previous_secrets: Vec::new(),
 ///         pkce_required: true,
 ///         max_age_seconds: 300, // 5 minutes
 ///         prompt: Some("login".to_string()),
 ///         additional_params: std::collections::HashMap::new(),
+///         profile_sync_policy: ProfileSyncPolicy::Always,
+///         require_email: false,
+///         scopes: None,
+///         request_offline_access: true,
+///         allowed_return_url_prefixes: vec!["https://app.acme.com/".to_string()],
 ///     };
 /// 
 ///     // 2. Create authorization URL builder with Redis connection
@@ -87,6 +93,7 @@
 ///         .retrieve_auth_state(
 ///             state_param,
 ///             &org_config,
+///             "https://acme.example.com/auth/callback", // redirect URI of the token exchange
 ///             client_ip,
 ///             client_user_agent,
 ///         )
@@ -125,6 +132,11 @@
 ///     max_age_seconds INTEGER DEFAULT 300,
 ///     prompt TEXT,
 ///     additional_params JSONB,
+///     profile_sync_policy TEXT DEFAULT 'always', -- always | on_create_only | never
+///     require_email BOOLEAN DEFAULT FALSE,
+///     scopes TEXT[], -- overrides the Dex app scopes when set
+///     request_offline_access BOOLEAN DEFAULT FALSE,
+///     allowed_return_url_prefixes TEXT[], -- absolute return URLs allowed after login
 ///     
 ///     created_at TIMESTAMPTZ DEFAULT NOW(),
 ///     updated_at TIMESTAMPTZ DEFAULT NOW()
@@ -200,7 +212,13 @@
 ///     // 2. Retrieve and validate auth state
 ///     let auth_state = app_state
 ///         .auth_builder
-///         .retrieve_auth_state(&query.state, &org_config, &client_ip, &user_agent)
+///         .retrieve_auth_state(
+///             &query.state,
+///             &org_config,
+///             &app_state.dex_config.redirect_url,
+///             &client_ip,
+///             &user_agent,
+///         )
 ///         .await?;
 /// 
 ///     // 3. Exchange authorization code for tokens
@@ -1,21 +1,31 @@
 /// OAuth Callback Handler
 ///
 /// Handles the OAuth callback with token exchange, user creation/update, and session management
-use super::authn::{AuthorizationUrlBuilder, DexAppConfig, OrgAuthConfig};
+use super::authn::{
+    AuthorizationUrlBuilder, DexAppConfig, OFFLINE_ACCESS_SCOPE, OrgAuthConfig, ProfileSyncPolicy,
+};
 use super::db_ops;
-use super::models::{CreateSession, CreateUser, UpdateUserTokens};
+use super::models::{
+    CreateSession, CreateUser, SessionClaims, SessionConfig, SessionLimitPolicy, SessionStrategy,
+    UpdateUserTokens, User, UserSession,
+};
+use aes_gcm::aead::{Aead, AeadCore, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce as AesNonce};
 use anyhow::{Context, Result};
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
 use chrono::{Duration, Utc};
 use hmac::{Hmac, Mac};
 use openidconnect::{
-    AuthorizationCode, ClientId, ClientSecret, IssuerUrl, Nonce, OAuth2TokenResponse,
-    PkceCodeVerifier, RedirectUrl,
-    core::{CoreClient, CoreIdTokenClaims, CoreProviderMetadata, CoreTokenResponse},
+    AuthorizationCode, ClientId, ClientSecret, EndpointMaybeSet, EndpointNotSet, EndpointSet,
+    IssuerUrl, Nonce, NonceVerifier, OAuth2TokenResponse, PkceCodeVerifier, RedirectUrl,
+    RefreshToken,
+    core::{CoreClient, CoreIdTokenClaims, CoreTokenResponse},
 };
 use reqwest::Client as HttpClient;
 use serde::{Deserialize, Serialize};
-use sha2::Sha256;
+use sha2::{Digest, Sha256};
 use sqlx::PgPool;
+use std::future::Future;
 use tower_cookies::{Cookie, Cookies};
 
 type HmacSha256 = Hmac<Sha256>;
@@ -30,10 +40,21 @@ pub struct CallbackQuery {
     pub state: String,
 }
 
+/// Inputs of one callback request to [`handle_callback`]
+pub struct CallbackRequest<'a> {
+    pub query: &'a CallbackQuery,
+    pub cookies: &'a Cookies,
+    pub client_ip: &'a str,
+    pub client_user_agent: &'a str,
+    /// Scopes of the authorization request, used to detect a missing refresh token
+    pub requested_scopes: &'a [String],
+}
+
 #[derive(Debug, Serialize)]
 pub struct CallbackResult {
     pub user_id: String,
-    pub session_id: String,
+    /// Database session ID, `None` for stateless sessions
+    pub session_id: Option<String>,
     pub return_url: String,
 }
 
@@ -41,35 +62,208 @@ pub struct CallbackResult {
 // Token Exchange with ID Token Verification
 // ============================================================================
 
-/// Exchange authorization code for tokens with automatic ID token signature verification
-pub async fn exchange_code_for_tokens(
-    dex_config: &DexAppConfig,
-    code: &str,
-    code_verifier: &str,
-    expected_nonce: &str,
-) -> Result<(CoreTokenResponse, CoreIdTokenClaims)> {
-    // Create HTTP client
-    let http_client = HttpClient::builder()
+/// Tokens and verified ID token claims of a code exchange, independent of the provider
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExchangedTokens {
+    /// `sub` claim, the user's ID at the provider
+    pub subject: String,
+    pub email: Option<String>,
+    pub name: Option<String>,
+    pub picture: Option<String>,
+    pub preferred_username: Option<String>,
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    /// Raw ID token
+    pub id_token: Option<String>,
+    /// Lifetime of the access token
+    pub expires_in: Option<std::time::Duration>,
+}
+
+impl ExchangedTokens {
+    /// Normalize an OIDC token response and its verified ID token claims
+    pub fn from_oidc(token_response: &CoreTokenResponse, claims: &CoreIdTokenClaims) -> Self {
+        let (email, name, picture, preferred_username) = extract_user_info(claims);
+        Self {
+            subject: claims.subject().as_str().to_string(),
+            email,
+            name,
+            picture,
+            preferred_username,
+            access_token: token_response.access_token().secret().clone(),
+            refresh_token: token_response.refresh_token().map(|t| t.secret().clone()),
+            id_token: token_response
+                .extra_fields()
+                .id_token()
+                .map(|t| t.to_string()),
+            expires_in: token_response.expires_in(),
+        }
+    }
+
+    /// Tokens of a refresh of `previous`. Without a new ID token, `previous`'s claims
+    /// still apply; a new one must be for the same subject. The previous refresh token
+    /// is kept when the IdP doesn't rotate it.
+    pub fn refreshed(
+        previous: &ExchangedTokens,
+        token_response: &CoreTokenResponse,
+        claims: Option<&CoreIdTokenClaims>,
+    ) -> Result<Self> {
+        let mut tokens = match claims {
+            Some(claims) => {
+                let tokens = Self::from_oidc(token_response, claims);
+                if tokens.subject != previous.subject {
+                    anyhow::bail!(
+                        "Refreshed ID token is for subject {}, not {}",
+                        tokens.subject,
+                        previous.subject
+                    );
+                }
+                tokens
+            }
+            None => Self {
+                access_token: token_response.access_token().secret().clone(),
+                refresh_token: token_response.refresh_token().map(|t| t.secret().clone()),
+                expires_in: token_response.expires_in(),
+                ..previous.clone()
+            },
+        };
+        tokens.refresh_token = tokens
+            .refresh_token
+            .or_else(|| previous.refresh_token.clone());
+        Ok(tokens)
+    }
+}
+
+/// Grant of a token request, which decides whether its response must carry an ID token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenGrant {
+    /// Initial exchange of an authorization code, whose response OIDC requires to
+    /// carry an ID token
+    AuthorizationCode,
+    /// Refresh of the access token; the IdP may leave the ID token out, in which case
+    /// the claims of the previous one still apply
+    RefreshToken,
+}
+
+impl TokenGrant {
+    pub fn requires_id_token(self) -> bool {
+        matches!(self, TokenGrant::AuthorizationCode)
+    }
+}
+
+/// The ID token of a `grant`'s response, which only a refresh may leave out
+pub fn require_id_token<T>(grant: TokenGrant, id_token: Option<T>) -> Result<Option<T>> {
+    match id_token {
+        None if grant.requires_id_token() => anyhow::bail!("Server did not return an ID token"),
+        id_token => Ok(id_token),
+    }
+}
+
+/// Exchanges an authorization code for verified tokens at an identity provider
+pub trait TokenExchanger: Send + Sync {
+    /// Redirect URI presented in the token exchange
+    fn redirect_uri(&self) -> &str;
+
+    /// Exchange `code` using the PKCE `code_verifier`, verifying the ID token
+    /// carries `nonce`
+    fn exchange(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> impl Future<Output = Result<ExchangedTokens>> + Send;
+}
+
+/// [`TokenExchanger`] for Dex, verifying the ID token against the issuer's JWKS
+pub struct DexTokenExchanger<'a> {
+    dex_config: &'a DexAppConfig,
+}
+
+impl<'a> DexTokenExchanger<'a> {
+    pub fn new(dex_config: &'a DexAppConfig) -> Self {
+        Self { dex_config }
+    }
+}
+
+impl TokenExchanger for DexTokenExchanger<'_> {
+    fn redirect_uri(&self) -> &str {
+        &self.dex_config.redirect_url
+    }
+
+    async fn exchange(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<ExchangedTokens> {
+        let (token_response, claims) =
+            exchange_code_for_tokens(self.dex_config, code, code_verifier, nonce).await?;
+        Ok(ExchangedTokens::from_oidc(&token_response, &claims))
+    }
+}
+
+/// OIDC client of a discovered provider
+type DexClient = CoreClient<
+    EndpointSet,
+    EndpointNotSet,
+    EndpointNotSet,
+    EndpointNotSet,
+    EndpointMaybeSet,
+    EndpointMaybeSet,
+>;
+
+/// HTTP client for the IdP, which must not follow redirects
+fn idp_http_client() -> Result<HttpClient> {
+    HttpClient::builder()
         .redirect(reqwest::redirect::Policy::none())
         .build()
-        .context("Failed to build HTTP client")?;
+        .context("Failed to build HTTP client")
+}
 
-    // Parse issuer URL and discover provider metadata
+/// Discover the provider, cached per issuer, and create its OIDC client
+async fn dex_client(dex_config: &DexAppConfig, http_client: &HttpClient) -> Result<DexClient> {
     let issuer_url = IssuerUrl::new(dex_config.issuer_url.clone()).context("Invalid issuer URL")?;
 
-    let provider_metadata = CoreProviderMetadata::discover_async(issuer_url, &http_client)
-        .await
-        .context("Failed to discover provider metadata")?;
+    let provider_metadata = super::provider_cache::discover(issuer_url, http_client).await?;
 
-    // Create OIDC client
-    let client = CoreClient::from_provider_metadata(
+    Ok(CoreClient::from_provider_metadata(
         provider_metadata,
         ClientId::new(dex_config.client_id.clone()),
         Some(ClientSecret::new(dex_config.client_secret.clone())),
     )
     .set_redirect_uri(
         RedirectUrl::new(dex_config.redirect_url.clone()).context("Invalid redirect URL")?,
-    );
+    ))
+}
+
+/// Verify the signature and claims of the ID token of a `grant`'s response using JWKS,
+/// see [`require_id_token`]
+fn verify_id_token(
+    client: &DexClient,
+    token_response: &CoreTokenResponse,
+    grant: TokenGrant,
+    nonce_verifier: impl NonceVerifier,
+) -> Result<Option<CoreIdTokenClaims>> {
+    let Some(id_token) = require_id_token(grant, token_response.extra_fields().id_token())? else {
+        return Ok(None);
+    };
+
+    let claims = id_token
+        .claims(&client.id_token_verifier(), nonce_verifier)
+        .context("Failed to verify ID token")?
+        .clone();
+
+    Ok(Some(claims))
+}
+
+/// Exchange authorization code for tokens with automatic ID token signature verification
+pub async fn exchange_code_for_tokens(
+    dex_config: &DexAppConfig,
+    code: &str,
+    code_verifier: &str,
+    expected_nonce: &str,
+) -> Result<(CoreTokenResponse, CoreIdTokenClaims)> {
+    let http_client = idp_http_client()?;
+    let client = dex_client(dex_config, &http_client).await?;
 
     // Exchange authorization code for tokens with PKCE
     let token_response = client
@@ -80,22 +274,63 @@ pub async fn exchange_code_for_tokens(
         .await
         .context("Failed to exchange authorization code for tokens")?;
 
-    // Get ID token (this is already parsed and basic validation is done)
-    let id_token = token_response
-        .extra_fields()
-        .id_token()
-        .ok_or_else(|| anyhow::anyhow!("Server did not return an ID token"))?;
+    let nonce = Nonce::new(expected_nonce.to_string());
+    let claims = verify_id_token(
+        &client,
+        &token_response,
+        TokenGrant::AuthorizationCode,
+        &nonce,
+    )?
+    .expect("the authorization code grant requires an ID token");
+
+    Ok((token_response, claims))
+}
 
-    // Verify ID token signature and claims using JWKS
-    let id_token_verifier = client.id_token_verifier();
-    let nonce_verifier = Nonce::new(expected_nonce.to_string());
+/// Refresh the access token of `previous`, keeping its claims when the IdP returns no
+/// new ID token, see [`ExchangedTokens::refreshed`]
+pub async fn refresh_tokens(
+    dex_config: &DexAppConfig,
+    previous: &ExchangedTokens,
+) -> Result<ExchangedTokens> {
+    let refresh_token = previous
+        .refresh_token
+        .clone()
+        .map(RefreshToken::new)
+        .context("No refresh token to refresh with")?;
 
-    let claims = id_token
-        .claims(&id_token_verifier, &nonce_verifier)
-        .context("Failed to verify ID token")?
-        .clone();
+    let http_client = idp_http_client()?;
+    let client = dex_client(dex_config, &http_client).await?;
 
-    Ok((token_response.clone(), claims))
+    let token_response = client
+        .exchange_refresh_token(&refresh_token)
+        .context("Failed to create refresh request")?
+        .request_async(&http_client)
+        .await
+        .context("Failed to refresh tokens")?;
+
+    // A refreshed ID token has no nonce or the original one, which isn't kept, so
+    // only its signature and standard claims are verified
+    let claims = verify_id_token(
+        &client,
+        &token_response,
+        TokenGrant::RefreshToken,
+        |_: Option<&Nonce>| Ok(()),
+    )?;
+
+    ExchangedTokens::refreshed(previous, &token_response, claims.as_ref())
+}
+
+/// Exchange the code of a callback and check the result against the org's settings
+pub async fn exchange_and_verify<E: TokenExchanger>(
+    exchanger: &E,
+    org_config: &OrgAuthConfig,
+    code: &str,
+    code_verifier: &str,
+    nonce: &str,
+) -> Result<ExchangedTokens> {
+    let mut tokens = exchanger.exchange(code, code_verifier, nonce).await?;
+    tokens.email = resolve_email(tokens.email, org_config.require_email, &tokens.subject)?;
+    Ok(tokens)
 }
 
 // ============================================================================
@@ -105,7 +340,12 @@ pub async fn exchange_code_for_tokens(
 /// Extract user information from verified ID token claims
 pub fn extract_user_info(
     claims: &CoreIdTokenClaims,
-) -> (String, Option<String>, Option<String>, Option<String>) {
+) -> (
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+) {
     let email = claims.email().map(|e| e.as_str().to_string());
 
     let name = claims
@@ -120,46 +360,100 @@ pub fn extract_user_info(
 
     let preferred_username = claims.preferred_username().map(|u| u.as_str().to_string());
 
-    (
-        email.unwrap_or_else(|| format!("{}@unknown", claims.subject().as_str())),
-        name,
-        picture,
-        preferred_username,
-    )
+    (email, name, picture, preferred_username)
+}
+
+/// Check the email claim against the org's `require_email` setting
+///
+/// A missing email is an error when required; otherwise the user is stored without one
+/// and identified by `provider_user_id` alone.
+pub fn resolve_email(
+    email: Option<String>,
+    require_email: bool,
+    subject: &str,
+) -> Result<Option<String>> {
+    match email {
+        None if require_email => anyhow::bail!(
+            "ID token for subject {} has no email claim, which this organization requires",
+            subject
+        ),
+        email => Ok(email),
+    }
 }
 
 // ============================================================================
 // User Management
 // ============================================================================
 
-/// Create or update user from verified ID token claims
+/// Profile fields to write for an existing user on login
+///
+/// Returns `None` when the policy doesn't allow updates or the claims carry nothing new.
+pub fn profile_update(
+    policy: ProfileSyncPolicy,
+    existing: &User,
+    name: Option<String>,
+    picture: Option<String>,
+) -> Option<(Option<String>, Option<String>)> {
+    if !policy.syncs_on_login() {
+        return None;
+    }
+
+    let name = name.filter(|n| existing.name.as_ref() != Some(n));
+    let picture = picture.filter(|p| existing.picture.as_ref() != Some(p));
+    if name.is_none() && picture.is_none() {
+        return None;
+    }
+
+    Some((name, picture))
+}
+
+/// Whether `offline_access` was requested but the IdP returned no refresh token,
+/// which usually means the IdP or connector is misconfigured
+pub fn missing_refresh_token(requested_scopes: &[String], refresh_token: Option<&str>) -> bool {
+    requested_scopes.iter().any(|s| s == OFFLINE_ACCESS_SCOPE) && refresh_token.is_none()
+}
+
+/// Create or update user from exchanged tokens
+///
+/// Profile fields are taken from the claims according to the org's profile sync policy.
+/// A previously stored refresh token is kept when the IdP doesn't return a new one.
 pub async fn create_or_update_user(
     db: &PgPool,
-    org_id: &str,
-    auth_provider: &str,
-    claims: &CoreIdTokenClaims,
-    token_response: &CoreTokenResponse,
+    org_config: &OrgAuthConfig,
+    requested_scopes: &[String],
+    tokens: ExchangedTokens,
 ) -> Result<String> {
-    // Extract user information from claims
-    let (email, name, picture, preferred_username) = extract_user_info(claims);
-    let provider_user_id = claims.subject().as_str().to_string();
+    let org_id = org_config.org_id.as_str();
+    let auth_provider = org_config.dex_connector_id.as_str();
+    let profile_sync_policy = org_config.profile_sync_policy;
+
+    let ExchangedTokens {
+        subject: provider_user_id,
+        email,
+        mut name,
+        mut picture,
+        mut preferred_username,
+        access_token,
+        refresh_token,
+        id_token,
+        expires_in,
+    } = tokens;
 
     // Calculate token expiration
-    let token_expires_at = token_response
-        .expires_in()
-        .map(|exp| Utc::now() + Duration::seconds(exp.as_secs() as i64));
+    let token_expires_at =
+        expires_in.map(|exp| Utc::now() + Duration::seconds(exp.as_secs() as i64));
 
     // Try to find existing user
     let existing_user =
         db_ops::find_user_by_provider(db, org_id, &provider_user_id, auth_provider).await?;
 
-    // Get tokens as strings
-    let access_token = token_response.access_token().secret().clone();
-    let refresh_token = token_response.refresh_token().map(|t| t.secret().clone());
-    let id_token = token_response
-        .extra_fields()
-        .id_token()
-        .map(|t| t.to_string());
+    if missing_refresh_token(requested_scopes, refresh_token.as_deref()) {
+        tracing::warn!(
+            "offline_access was requested for org {} but {} returned no refresh token; check the IdP configuration",
+            org_id,
+            auth_provider
+        );
+    }
 
     match existing_user {
         Some(user) => {
@@ -174,8 +468,9 @@ pub async fn create_or_update_user(
 
             db_ops::update_user_tokens(db, update).await?;
 
-            // Update profile if information has changed
-            if name.is_some() || picture.is_some() {
+            // Update profile if the policy allows it and information has changed
+            if let Some((name, picture)) = profile_update(profile_sync_policy, &user, name, picture)
+            {
                 db_ops::update_user_profile(
                     db,
                     &user.user_id,
@@ -192,6 +487,12 @@ pub async fn create_or_update_user(
             // Create new user
             let user_id = db_ops::generate_user_id();
 
+            if !profile_sync_policy.syncs_on_create() {
+                name = None;
+                picture = None;
+                preferred_username = None;
+            }
+
             let create_user = CreateUser {
                 user_id: user_id.clone(),
                 email,
@@ -217,7 +518,58 @@ pub async fn create_or_update_user(
 // Session Management
 // ============================================================================
 
+/// Login refused because the user has reached `max_sessions_per_user`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionLimitExceeded {
+    pub max_sessions: usize,
+}
+
+impl std::fmt::Display for SessionLimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "User already has the maximum of {} active sessions",
+            self.max_sessions
+        )
+    }
+}
+
+impl std::error::Error for SessionLimitExceeded {}
+
+/// Sessions to end before the user gets a new one, given their active sessions.
+///
+/// Over the limit, [`SessionLimitPolicy::EvictOldest`] returns the oldest sessions
+/// beyond the limit minus one and [`SessionLimitPolicy::Reject`] refuses the login.
+pub fn sessions_to_evict(
+    active_sessions: &[UserSession],
+    session_config: &SessionConfig,
+) -> Result<Vec<String>, SessionLimitExceeded> {
+    let Some(max_sessions) = session_config.max_sessions_per_user else {
+        return Ok(Vec::new());
+    };
+    if active_sessions.len() < max_sessions {
+        return Ok(Vec::new());
+    }
+
+    match session_config.session_limit_policy {
+        SessionLimitPolicy::Reject => Err(SessionLimitExceeded { max_sessions }),
+        SessionLimitPolicy::EvictOldest => {
+            let mut sessions: Vec<&UserSession> = active_sessions.iter().collect();
+            sessions.sort_by_key(|s| s.created_at);
+            let excess = active_sessions.len() + 1 - max_sessions.max(1);
+            Ok(sessions
+                .into_iter()
+                .take(excess)
+                .map(|s| s.session_id.clone())
+                .collect())
+        }
+    }
+}
+
 /// Create a new session for the user
+///
+/// When `max_sessions_per_user` is set, the user's oldest sessions are ended to stay
+/// within it, or the login fails with [`SessionLimitExceeded`], per the policy.
 pub async fn create_user_session(
     db: &PgPool,
     user_id: &str,
@@ -226,6 +578,18 @@ pub async fn create_user_session(
     user_agent: &str,
     session_config: &crate::auth::models::SessionConfig,
 ) -> Result<String> {
+    if session_config.max_sessions_per_user.is_some() {
+        let active_sessions = db_ops::get_user_sessions(db, user_id).await?;
+        for session_id in sessions_to_evict(&active_sessions, session_config)? {
+            tracing::info!(
+                "Ending session {} of user {} over the limit",
+                session_id,
+                user_id
+            );
+            db_ops::invalidate_session(db, &session_id).await?;
+        }
+    }
+
     let session_id = db_ops::generate_session_id();
     let expires_at = Utc::now() + Duration::seconds(session_config.max_age_seconds);
 
@@ -243,6 +607,39 @@ pub async fn create_user_session(
     Ok(session_id)
 }
 
+/// Resolve the session behind a session cookie value
+///
+/// Database sessions are looked up in `user_sessions`; stateless sessions are decrypted
+/// from the cookie. Returns `None` for unknown or expired sessions.
+pub async fn resolve_session(
+    db: &PgPool,
+    cookie_value: &str,
+    session_config: &SessionConfig,
+) -> Result<Option<SessionClaims>> {
+    let secret = &session_config.cookie_signing_secret;
+
+    match session_config.session_strategy {
+        SessionStrategy::Database => {
+            let session_id = verify_and_extract_session_id(cookie_value, secret)?;
+            let session = db_ops::find_session_by_id(db, &session_id).await?;
+
+            Ok(session.map(|s| SessionClaims {
+                user_id: s.user_id,
+                org_id: s.org_id,
+                created_at: Some(s.created_at),
+                expires_at: s.expires_at,
+            }))
+        }
+        SessionStrategy::Stateless => {
+            let claims = decode_stateless_session(cookie_value, secret)?;
+            if claims.expires_at <= Utc::now() {
+                return Ok(None);
+            }
+            Ok(Some(claims))
+        }
+    }
+}
+
 // ============================================================================
 // Cookie Management
 // ============================================================================
@@ -284,20 +681,88 @@ pub fn verify_and_extract_session_id(cookie_value: &str, secret: &str) -> Result
     Ok(session_id.to_string())
 }
 
+/// Derive the AES-256-GCM key for stateless sessions from the cookie signing secret
+fn stateless_session_key(secret: &str) -> Aes256Gcm {
+    use aes_gcm::KeyInit;
+
+    let key = Sha256::new()
+        .chain_update(b"stateless-session:")
+        .chain_update(secret.as_bytes())
+        .finalize();
+    Aes256Gcm::new(&key)
+}
+
+/// Encrypt session claims into a cookie value: base64url(nonce || ciphertext)
+pub fn encode_stateless_session(claims: &SessionClaims, secret: &str) -> Result<String> {
+    let plaintext = serde_json::to_vec(claims).context("Failed to serialize session claims")?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+    let ciphertext = stateless_session_key(secret)
+        .encrypt(&nonce, plaintext.as_slice())
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt session"))?;
+
+    let mut payload = nonce.to_vec();
+    payload.extend(ciphertext);
+    Ok(URL_SAFE_NO_PAD.encode(payload))
+}
+
+/// Decrypt and authenticate session claims from a cookie value
+pub fn decode_stateless_session(cookie_value: &str, secret: &str) -> Result<SessionClaims> {
+    const NONCE_LEN: usize = 12;
+
+    let payload = URL_SAFE_NO_PAD
+        .decode(cookie_value)
+        .context("Invalid session cookie encoding")?;
+    if payload.len() <= NONCE_LEN {
+        anyhow::bail!("Invalid session cookie format");
+    }
+
+    let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+    let plaintext = stateless_session_key(secret)
+        .decrypt(AesNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow::anyhow!("Invalid session cookie"))?;
+
+    serde_json::from_slice(&plaintext).context("Invalid session payload")
+}
+
 /// Set session cookie
+///
+/// With database sessions the cookie holds the signed `session_id`; with stateless
+/// sessions it holds the encrypted `claims`. Fails if the cookie settings don't meet
+/// the constraints of the configured cookie prefix.
 pub fn set_session_cookie(
     cookies: &Cookies,
-    session_id: &str,
+    session_id: Option<&str>,
+    claims: &SessionClaims,
     org_config: &OrgAuthConfig,
 ) -> Result<()> {
     let session_config = &org_config.session_config;
+    let secret = &session_config.cookie_signing_secret;
+
+    let cookie_value = match session_config.session_strategy {
+        SessionStrategy::Database => {
+            let session_id = session_id.context("Database sessions require a session ID")?;
+            create_signed_cookie_value(session_id, secret)?
+        }
+        SessionStrategy::Stateless => encode_stateless_session(claims, secret)?,
+    };
+
+    cookies.add(build_session_cookie(cookie_value, session_config)?);
 
-    // Create signed cookie value
-    let cookie_value =
-        create_signed_cookie_value(session_id, &session_config.cookie_signing_secret)?;
+    Ok(())
+}
+
+/// Build the session cookie with the attributes of the session config
+pub fn build_session_cookie(
+    cookie_value: String,
+    session_config: &SessionConfig,
+) -> Result<Cookie<'static>> {
+    session_config
+        .check_cookie_prefix()
+        .map_err(anyhow::Error::msg)?;
 
     // Build cookie
-    let mut cookie = Cookie::new(session_config.cookie_name.clone(), cookie_value);
+    let mut cookie = Cookie::new(session_config.session_cookie_name(), cookie_value);
 
     // Set cookie attributes
     cookie.set_http_only(session_config.http_only);
@@ -327,10 +792,7 @@ pub fn set_session_cookie(
     // Set path
     cookie.set_path("/");
 
-    // Add cookie to response
-    cookies.add(cookie);
-
-    Ok(())
+    Ok(cookie)
 }
 
 // ============================================================================
@@ -338,30 +800,42 @@ pub fn set_session_cookie(
 // ============================================================================
 
 /// Handle OAuth callback with complete flow
-pub async fn handle_callback(
+pub async fn handle_callback<E: TokenExchanger>(
     db: &PgPool,
-    dex_config: &DexAppConfig,
+    exchanger: &E,
     org_config: &OrgAuthConfig,
     auth_builder: &AuthorizationUrlBuilder,
-    query: &CallbackQuery,
-    cookies: &Cookies,
-    client_ip: &str,
-    client_user_agent: &str,
+    request: CallbackRequest<'_>,
 ) -> Result<CallbackResult> {
+    let CallbackRequest {
+        query,
+        cookies,
+        client_ip,
+        client_user_agent,
+        requested_scopes,
+    } = request;
+
     // 1. Retrieve and validate auth state from Redis
     let auth_state = auth_builder
-        .retrieve_auth_state(&query.state, org_config, client_ip, client_user_agent)
+        .retrieve_auth_state(
+            &query.state,
+            org_config,
+            exchanger.redirect_uri(),
+            client_ip,
+            client_user_agent,
+        )
         .await
         .context("Failed to retrieve or validate auth state")?;
 
-    // 2. Exchange authorization code for tokens with automatic ID token verification
-    // This includes:
+    // 2. Exchange authorization code for tokens with ID token verification
+    // For Dex this includes:
     // - Token exchange with PKCE
     // - ID token signature verification using JWKS
     // - Nonce verification
     // - Standard claims validation (iss, aud, exp, iat)
-    let (token_response, claims) = exchange_code_for_tokens(
-        dex_config,
+    let tokens = exchange_and_verify(
+        exchanger,
+        org_config,
         &query.code,
         &auth_state.code_verifier,
         &auth_state.nonce,
@@ -370,30 +844,37 @@ pub async fn handle_callback(
     .context("Failed to exchange code for tokens and verify ID token")?;
 
     // 3. Create or update user
-    let user_id = create_or_update_user(
-        db,
-        &org_config.org_id,
-        &org_config.dex_connector_id,
-        &claims,
-        &token_response,
-    )
-    .await
-    .context("Failed to create or update user")?;
+    let user_id = create_or_update_user(db, org_config, requested_scopes, tokens)
+        .await
+        .context("Failed to create or update user")?;
 
-    // 4. Create session
-    let session_id = create_user_session(
-        db,
-        &user_id,
-        &org_config.org_id,
-        client_ip,
-        client_user_agent,
-        &org_config.session_config,
-    )
-    .await
-    .context("Failed to create session")?;
+    // 4. Create session (stateless sessions live only in the cookie)
+    let session_config = &org_config.session_config;
+    let claims = SessionClaims {
+        user_id: user_id.clone(),
+        org_id: org_config.org_id.clone(),
+        created_at: Some(Utc::now()),
+        expires_at: Utc::now() + Duration::seconds(session_config.max_age_seconds),
+    };
+    let session_id = match session_config.session_strategy {
+        SessionStrategy::Database => Some(
+            create_user_session(
+                db,
+                &user_id,
+                &org_config.org_id,
+                client_ip,
+                client_user_agent,
+                session_config,
+            )
+            .await
+            .context("Failed to create session")?,
+        ),
+        SessionStrategy::Stateless => None,
+    };
 
     // 5. Set session cookie
-    set_session_cookie(cookies, &session_id, org_config).context("Failed to set session cookie")?;
+    set_session_cookie(cookies, session_id.as_deref(), &claims, org_config)
+        .context("Failed to set session cookie")?;
 
     // 6. Invalidate auth state (one-time use)
     auth_builder
@@ -431,12 +912,381 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_host_cookie_prefix() {
+        let mut session_config = SessionConfig {
+            cookie_prefix: crate::auth::models::CookiePrefix::Host,
+            cookie_domain: Some(".example.com".to_string()),
+            ..Default::default()
+        };
+        let error = build_session_cookie("value".to_string(), &session_config).unwrap_err();
+        assert_eq!(error.to_string(), "__Host- cookies can't set a domain");
+
+        session_config.cookie_domain = None;
+        let cookie = build_session_cookie("value".to_string(), &session_config).unwrap();
+        assert_eq!(cookie.name(), "__Host-session_id");
+        assert_eq!(cookie.secure(), Some(true));
+        assert_eq!(cookie.path(), Some("/"));
+        assert_eq!(cookie.domain(), None);
+
+        session_config.secure = false;
+        assert!(build_session_cookie("value".to_string(), &session_config).is_err());
+    }
+
+    fn active_sessions(count: i64) -> Vec<UserSession> {
+        let now = Utc::now();
+        (0..count)
+            .map(|i| UserSession {
+                session_id: format!("ses_{}", i),
+                user_id: "usr_123".to_string(),
+                org_id: "org_acme".to_string(),
+                ip_address: "127.0.0.1".to_string(),
+                user_agent: "test".to_string(),
+                is_active: true,
+                // ses_0 is the oldest
+                created_at: now - Duration::minutes(count - i),
+                expires_at: now + Duration::hours(1),
+                last_activity_at: now,
+            })
+            .rev()
+            .collect()
+    }
+
+    #[test]
+    fn test_session_limit_evicts_oldest() {
+        let session_config = SessionConfig {
+            max_sessions_per_user: Some(3),
+            ..Default::default()
+        };
+
+        assert!(
+            sessions_to_evict(&active_sessions(2), &session_config)
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            sessions_to_evict(&active_sessions(4), &session_config).unwrap(),
+            vec!["ses_0", "ses_1"]
+        );
+
+        let unlimited = SessionConfig::default();
+        assert!(
+            sessions_to_evict(&active_sessions(10), &unlimited)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_session_limit_rejects_login() {
+        let session_config = SessionConfig {
+            max_sessions_per_user: Some(3),
+            session_limit_policy: SessionLimitPolicy::Reject,
+            ..Default::default()
+        };
+
+        assert!(sessions_to_evict(&active_sessions(2), &session_config).is_ok());
+        assert_eq!(
+            sessions_to_evict(&active_sessions(3), &session_config).unwrap_err(),
+            SessionLimitExceeded { max_sessions: 3 }
+        );
+    }
+
+    #[test]
+    fn test_stateless_session_round_trip() {
+        let secret = "test-secret-key";
+        let claims = SessionClaims {
+            user_id: "usr_123".to_string(),
+            org_id: "org_acme".to_string(),
+            created_at: Some(Utc::now()),
+            expires_at: Utc::now() + Duration::hours(1),
+        };
+
+        let cookie_value = encode_stateless_session(&claims, secret).unwrap();
+        assert!(!cookie_value.contains("usr_123"));
+        assert_eq!(
+            decode_stateless_session(&cookie_value, secret).unwrap(),
+            claims
+        );
+
+        // Each encoding uses a fresh nonce
+        assert_ne!(
+            encode_stateless_session(&claims, secret).unwrap(),
+            cookie_value
+        );
+    }
+
+    #[test]
+    fn test_stateless_session_rejects_tampering() {
+        let claims = SessionClaims {
+            user_id: "usr_123".to_string(),
+            org_id: "org_acme".to_string(),
+            created_at: Some(Utc::now()),
+            expires_at: Utc::now() + Duration::hours(1),
+        };
+        let cookie_value = encode_stateless_session(&claims, "test-secret-key").unwrap();
+
+        assert!(decode_stateless_session(&cookie_value, "wrong-secret").is_err());
+
+        let mut bytes = URL_SAFE_NO_PAD.decode(&cookie_value).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0x01;
+        let tampered = URL_SAFE_NO_PAD.encode(bytes);
+        assert!(decode_stateless_session(&tampered, "test-secret-key").is_err());
+
+        assert!(decode_stateless_session("not-a-cookie", "test-secret-key").is_err());
+    }
+
+    fn existing_user() -> User {
+        let now = Utc::now();
+        User {
+            user_id: "usr_1".to_string(),
+            email: Some("alice@example.com".to_string()),
+            name: Some("Alice (Finance)".to_string()),
+            display_name: None,
+            picture: Some("https://example.com/old.png".to_string()),
+            auth_provider: "auth0".to_string(),
+            provider_user_id: "auth0|1".to_string(),
+            org_id: "org_1".to_string(),
+            access_token: None,
+            refresh_token: None,
+            id_token: None,
+            token_expires_at: None,
+            is_active: true,
+            created_at: now,
+            last_login_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_profile_update_per_policy() {
+        let user = existing_user();
+        let name = Some("Alice".to_string());
+        let picture = Some("https://example.com/new.png".to_string());
+
+        assert_eq!(
+            profile_update(
+                ProfileSyncPolicy::Always,
+                &user,
+                name.clone(),
+                picture.clone()
+            ),
+            Some((name.clone(), picture.clone()))
+        );
+        assert_eq!(
+            profile_update(
+                ProfileSyncPolicy::OnCreateOnly,
+                &user,
+                name.clone(),
+                picture.clone()
+            ),
+            None
+        );
+        assert_eq!(
+            profile_update(ProfileSyncPolicy::Never, &user, name, picture),
+            None
+        );
+
+        assert!(ProfileSyncPolicy::Always.syncs_on_create());
+        assert!(ProfileSyncPolicy::OnCreateOnly.syncs_on_create());
+        assert!(!ProfileSyncPolicy::Never.syncs_on_create());
+    }
+
+    #[test]
+    fn test_profile_update_skips_unchanged_fields() {
+        let user = existing_user();
+
+        // Only the changed name is written
+        assert_eq!(
+            profile_update(
+                ProfileSyncPolicy::Always,
+                &user,
+                Some("Alice".to_string()),
+                user.picture.clone()
+            ),
+            Some((Some("Alice".to_string()), None))
+        );
+        assert_eq!(
+            profile_update(
+                ProfileSyncPolicy::Always,
+                &user,
+                user.name.clone(),
+                user.picture.clone()
+            ),
+            None
+        );
+        assert_eq!(
+            profile_update(ProfileSyncPolicy::Always, &user, None, None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_missing_email_rejected_when_required() {
+        let err = resolve_email(None, true, "auth0|1").unwrap_err();
+        assert!(err.to_string().contains("no email claim"));
+
+        let email = Some("alice@example.com".to_string());
+        assert_eq!(
+            resolve_email(email.clone(), true, "auth0|1").unwrap(),
+            email
+        );
+    }
+
+    #[test]
+    fn test_missing_email_stored_as_null() {
+        assert_eq!(resolve_email(None, false, "auth0|1").unwrap(), None);
+    }
+
+    #[test]
+    fn test_missing_refresh_token_detection() {
+        let scopes = |s: &[&str]| s.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let offline = scopes(&["openid", "email", "offline_access"]);
+        assert!(missing_refresh_token(&offline, None));
+        assert!(!missing_refresh_token(&offline, Some("refresh-token")));
+
+        let online = scopes(&["openid", "email"]);
+        assert!(!missing_refresh_token(&online, None));
+    }
+
+    #[test]
+    fn test_id_token_requirement_by_grant() {
+        assert!(TokenGrant::AuthorizationCode.requires_id_token());
+        assert!(!TokenGrant::RefreshToken.requires_id_token());
+
+        assert!(require_id_token::<&str>(TokenGrant::AuthorizationCode, None).is_err());
+        assert_eq!(
+            require_id_token(TokenGrant::AuthorizationCode, Some("id-token")).unwrap(),
+            Some("id-token")
+        );
+        assert_eq!(
+            require_id_token::<&str>(TokenGrant::RefreshToken, None).unwrap(),
+            None
+        );
+        assert_eq!(
+            require_id_token(TokenGrant::RefreshToken, Some("id-token")).unwrap(),
+            Some("id-token")
+        );
+    }
+
+    #[test]
+    fn test_refresh_without_id_token_keeps_claims() {
+        let previous = ExchangedTokens {
+            subject: "auth0|1".to_string(),
+            email: Some("anne@example.com".to_string()),
+            name: Some("Anne".to_string()),
+            access_token: "old-access-token".to_string(),
+            refresh_token: Some("refresh-token".to_string()),
+            id_token: Some("old-id-token".to_string()),
+            ..Default::default()
+        };
+        let token_response: CoreTokenResponse = serde_json::from_value(serde_json::json!({
+            "access_token": "new-access-token",
+            "token_type": "bearer",
+            "expires_in": 3600
+        }))
+        .unwrap();
+
+        let refreshed = ExchangedTokens::refreshed(&previous, &token_response, None).unwrap();
+
+        assert_eq!(
+            refreshed,
+            ExchangedTokens {
+                access_token: "new-access-token".to_string(),
+                expires_in: Some(std::time::Duration::from_secs(3600)),
+                ..previous
+            }
+        );
+    }
+
     #[test]
     fn test_extract_user_info() {
         // This would require creating a CoreIdTokenClaims which is complex
         // User info extraction is tested via integration tests
     }
 
-    // Note: Token exchange and ID token verification tests require
-    // either mocking the OIDC provider or using integration tests with a real Dex instance
+    /// Exchanger returning fixed tokens and recording the exchanges it was asked for
+    struct MockExchanger {
+        tokens: ExchangedTokens,
+        calls: std::sync::Mutex<Vec<(String, String, String)>>,
+    }
+
+    impl TokenExchanger for MockExchanger {
+        fn redirect_uri(&self) -> &str {
+            "https://auth.example.com/callback"
+        }
+
+        async fn exchange(
+            &self,
+            code: &str,
+            code_verifier: &str,
+            nonce: &str,
+        ) -> Result<ExchangedTokens> {
+            self.calls.lock().unwrap().push((
+                code.to_string(),
+                code_verifier.to_string(),
+                nonce.to_string(),
+            ));
+            Ok(self.tokens.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_callback_exchange_with_mock_exchanger() {
+        let tokens = ExchangedTokens {
+            subject: "CgVhbGljZRIEbW9jaw".to_string(),
+            email: Some("alice@acme.example".to_string()),
+            access_token: "access".to_string(),
+            ..Default::default()
+        };
+        let exchanger = MockExchanger {
+            tokens: tokens.clone(),
+            calls: Default::default(),
+        };
+        let mut org_config: OrgAuthConfig = serde_json::from_value(serde_json::json!({
+            "org_id": "org_acme",
+            "subdomain": "acme",
+            "dex_connector_id": "mock",
+            "auth0_organization_id": null,
+            "session_secret": "secret",
+            "session_config": crate::auth::models::SessionConfig::default(),
+            "prompt": null
+        }))
+        .unwrap();
+
+        let exchanged = exchange_and_verify(&exchanger, &org_config, "code", "verifier", "nonce")
+            .await
+            .unwrap();
+        assert_eq!(exchanged, tokens);
+        assert_eq!(
+            exchanger.calls.lock().unwrap()[0],
+            (
+                "code".to_string(),
+                "verifier".to_string(),
+                "nonce".to_string()
+            )
+        );
+
+        // An org requiring an email rejects tokens without one
+        let exchanger = MockExchanger {
+            tokens: ExchangedTokens {
+                email: None,
+                ..tokens
+            },
+            calls: Default::default(),
+        };
+        org_config.require_email = true;
+        assert!(
+            exchange_and_verify(&exchanger, &org_config, "code", "verifier", "nonce")
+                .await
+                .is_err()
+        );
+        org_config.require_email = false;
+        let exchanged = exchange_and_verify(&exchanger, &org_config, "code", "verifier", "nonce")
+            .await
+            .unwrap();
+        assert_eq!(exchanged.email, None);
+    }
 }
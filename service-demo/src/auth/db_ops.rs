@@ -1,10 +1,11 @@
 /// Database operations for authentication
 ///
 /// This module contains all database operations for users and sessions
-use super::models::{CreateSession, CreateUser, UpdateUserTokens, User, UserSession};
+use super::models::{CreateSession, CreateUser, OrgStore, UpdateUserTokens, User, UserSession};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Duration, Utc};
 use sqlx::PgPool;
+use std::sync::OnceLock;
 
 // ============================================================================
 // User Operations
@@ -118,7 +119,7 @@ pub async fn update_user_tokens(db: &PgPool, update: UpdateUserTokens) -> Result
         r#"
         UPDATE users
         SET access_token = $2,
-            refresh_token = $3,
+            refresh_token = COALESCE($3, refresh_token),
             id_token = $4,
             token_expires_at = $5,
             last_login_at = $6,
@@ -341,6 +342,131 @@ pub async fn get_user_sessions(db: &PgPool, user_id: &str) -> Result<Vec<UserSes
     Ok(sessions)
 }
 
+// ============================================================================
+// Organization Store Operations
+// ============================================================================
+
+/// Get the OpenFGA stores an organization is allowed to operate on
+pub async fn find_org_stores(db: &PgPool, org_id: &str) -> Result<Vec<OrgStore>> {
+    let stores = sqlx::query_as::<_, OrgStore>(
+        r#"
+        SELECT * FROM org_stores
+        WHERE org_id = $1
+        "#,
+    )
+    .bind(org_id)
+    .fetch_all(db)
+    .await
+    .context("Failed to find organization stores")?;
+
+    Ok(stores)
+}
+
+/// Allow an organization to operate on a store, e.g. one it created
+pub async fn insert_org_store(db: &PgPool, org_id: &str, store_id: &str) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO org_stores (org_id, store_id)
+        VALUES ($1, $2)
+        ON CONFLICT (org_id, store_id) DO NOTHING
+        "#,
+    )
+    .bind(org_id)
+    .bind(store_id)
+    .execute(db)
+    .await
+    .context("Failed to insert organization store")?;
+
+    Ok(())
+}
+
+/// Remove a deleted store from every organization
+pub async fn delete_org_store(db: &PgPool, store_id: &str) -> Result<()> {
+    sqlx::query(
+        r#"
+        DELETE FROM org_stores
+        WHERE store_id = $1
+        "#,
+    )
+    .bind(store_id)
+    .execute(db)
+    .await
+    .context("Failed to delete organization store")?;
+
+    Ok(())
+}
+
+// ============================================================================
+// Organization Secret Rotation
+// ============================================================================
+
+/// Previous session secrets kept by [`rotate_session_secret`]; states signed with an
+/// older one no longer verify
+pub const MAX_PREVIOUS_SECRETS: usize = 3;
+
+/// An organization's session secret and the secrets it replaced, newest first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionSecrets {
+    pub current: String,
+    pub previous: Vec<String>,
+}
+
+impl SessionSecrets {
+    /// Sign with `new_secret` from now on, keeping the current secret as the newest
+    /// previous one and dropping the oldest past [`MAX_PREVIOUS_SECRETS`]
+    pub fn rotate(&mut self, new_secret: String) {
+        let current = std::mem::replace(&mut self.current, new_secret);
+        self.previous.insert(0, current);
+        self.previous.truncate(MAX_PREVIOUS_SECRETS);
+    }
+}
+
+/// Generate a session secret of 32 random bytes, hex encoded
+pub fn generate_session_secret() -> String {
+    hex::encode(rand::random::<[u8; 32]>())
+}
+
+/// Replace an organization's session secret with a new random one. States signed with
+/// the replaced secret keep verifying through `previous_secrets`.
+pub async fn rotate_session_secret(db: &PgPool, org_id: &str) -> Result<()> {
+    let mut tx = db.begin().await.context("Failed to start transaction")?;
+
+    let (current, previous): (String, Vec<String>) = sqlx::query_as(
+        r#"
+        SELECT session_secret, previous_secrets FROM organizations
+        WHERE org_id = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(org_id)
+    .fetch_one(&mut *tx)
+    .await
+    .context("Failed to find organization")?;
+
+    let mut secrets = SessionSecrets { current, previous };
+    secrets.rotate(generate_session_secret());
+
+    sqlx::query(
+        r#"
+        UPDATE organizations
+        SET session_secret = $2, previous_secrets = $3, updated_at = NOW()
+        WHERE org_id = $1
+        "#,
+    )
+    .bind(org_id)
+    .bind(&secrets.current)
+    .bind(&secrets.previous)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to rotate session secret")?;
+
+    tx.commit()
+        .await
+        .context("Failed to commit secret rotation")?;
+    tracing::info!("Rotated session secret of organization {}", org_id);
+    Ok(())
+}
+
 // ============================================================================
 // Session Extension Logic
 // ============================================================================
@@ -365,16 +491,148 @@ pub fn calculate_new_expiration(max_age_seconds: i64) -> DateTime<Utc> {
 // Helper Functions
 // ============================================================================
 
+/// Scheme of the user and session IDs
+pub trait IdGenerator: Send + Sync {
+    /// Generate a new unique ID, without prefix
+    fn generate(&self) -> String;
+
+    /// Whether an ID, without prefix, has the form this scheme generates
+    fn is_valid(&self, id: &str) -> bool;
+}
+
+/// Time-ordered UUIDv7, keeping inserts close together in the `user_id` and
+/// `session_id` indexes
+pub struct UuidV7Generator;
+
+impl IdGenerator for UuidV7Generator {
+    fn generate(&self) -> String {
+        uuid::Uuid::now_v7().to_string()
+    }
+
+    fn is_valid(&self, id: &str) -> bool {
+        uuid::Uuid::parse_str(id).is_ok_and(|uuid| uuid.get_version_num() == 7)
+    }
+}
+
+/// Time-ordered ULID
+pub struct UlidGenerator;
+
+impl IdGenerator for UlidGenerator {
+    fn generate(&self) -> String {
+        ulid::Ulid::new().to_string()
+    }
+
+    fn is_valid(&self, id: &str) -> bool {
+        id.parse::<ulid::Ulid>().is_ok()
+    }
+}
+
+/// Random URL-safe base64 token, the original scheme
+pub struct RandomGenerator;
+
+impl IdGenerator for RandomGenerator {
+    fn generate(&self) -> String {
+        oauth2::CsrfToken::new_random().secret().clone()
+    }
+
+    fn is_valid(&self, id: &str) -> bool {
+        !id.is_empty()
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    }
+}
+
+/// How user and session IDs are generated
+pub struct IdConfig {
+    generator: Box<dyn IdGenerator>,
+    /// Prefix user IDs with `usr_` and session IDs with `ses_`
+    prefixed: bool,
+}
+
+impl Default for IdConfig {
+    fn default() -> Self {
+        Self::new(Box::new(UuidV7Generator), true)
+    }
+}
+
+impl IdConfig {
+    pub fn new(generator: Box<dyn IdGenerator>, prefixed: bool) -> Self {
+        Self {
+            generator,
+            prefixed,
+        }
+    }
+
+    /// Read `ID_SCHEME` (`uuidv7`, `ulid` or `random`, default `uuidv7`) and
+    /// `ID_PREFIXES` (default `true`)
+    pub fn from_env() -> Result<Self> {
+        let generator: Box<dyn IdGenerator> =
+            match std::env::var("ID_SCHEME").as_deref().unwrap_or("uuidv7") {
+                "uuidv7" => Box::new(UuidV7Generator),
+                "ulid" => Box::new(UlidGenerator),
+                "random" => Box::new(RandomGenerator),
+                other => anyhow::bail!(
+                    "unknown ID_SCHEME {:?}, expected uuidv7, ulid or random",
+                    other
+                ),
+            };
+        let prefixed = std::env::var("ID_PREFIXES")
+            .map(|v| v != "false" && v != "0")
+            .unwrap_or(true);
+
+        Ok(Self::new(generator, prefixed))
+    }
+
+    pub fn user_id(&self) -> String {
+        self.id("usr_")
+    }
+
+    pub fn session_id(&self) -> String {
+        self.id("ses_")
+    }
+
+    /// Whether an ID, with its prefix if prefixes are on, was generated by this config
+    pub fn is_valid(&self, id: &str, prefix: &str) -> bool {
+        match (self.prefixed, id.strip_prefix(prefix)) {
+            (true, Some(id)) => self.generator.is_valid(id),
+            (true, None) => false,
+            (false, _) => self.generator.is_valid(id),
+        }
+    }
+
+    fn id(&self, prefix: &str) -> String {
+        let id = self.generator.generate();
+        if self.prefixed {
+            format!("{}{}", prefix, id)
+        } else {
+            id
+        }
+    }
+}
+
+static ID_CONFIG: OnceLock<IdConfig> = OnceLock::new();
+
+/// Set how IDs are generated; only the first call has an effect, and it must come
+/// before the first ID is generated
+pub fn set_id_config(config: IdConfig) {
+    if ID_CONFIG.set(config).is_err() {
+        tracing::warn!("ID generation is already configured, ignoring new configuration");
+    }
+}
+
+fn id_config() -> &'static IdConfig {
+    ID_CONFIG.get_or_init(IdConfig::default)
+}
+
 /// Generate unique user ID
 pub fn generate_user_id() -> String {
-    use oauth2::CsrfToken;
-    format!("usr_{}", CsrfToken::new_random().secret())
+    id_config().user_id()
 }
 
 /// Generate unique session ID
 pub fn generate_session_id() -> String {
-    use oauth2::CsrfToken;
-    format!("ses_{}", CsrfToken::new_random().secret())
+    id_config().session_id()
 }
 
 // ============================================================================
@@ -409,6 +667,29 @@ mod tests {
         assert!(!should_extend_session(&session, 0.6));
     }
 
+    #[test]
+    fn test_rotate_session_secrets() {
+        let mut secrets = SessionSecrets {
+            current: "secret-1".to_string(),
+            previous: Vec::new(),
+        };
+
+        secrets.rotate("secret-2".to_string());
+        assert_eq!(secrets.current, "secret-2");
+        assert_eq!(secrets.previous, vec!["secret-1"]);
+
+        for i in 3..=5 {
+            secrets.rotate(format!("secret-{}", i));
+        }
+        assert_eq!(secrets.current, "secret-5");
+        // secret-1 is evicted once MAX_PREVIOUS_SECRETS are kept
+        assert_eq!(secrets.previous, vec!["secret-4", "secret-3", "secret-2"]);
+
+        let generated = generate_session_secret();
+        assert_eq!(generated.len(), 64);
+        assert!(crate::auth::models::check_secret_length("session_secret", &generated).is_ok());
+    }
+
     #[test]
     fn test_calculate_new_expiration() {
         let now = Utc::now();
@@ -430,4 +711,32 @@ mod tests {
         assert_ne!(session_id1, session_id2);
         assert!(session_id1.starts_with("ses_"));
     }
+
+    #[test]
+    fn test_id_schemes() {
+        let schemes: [fn() -> Box<dyn IdGenerator>; 3] = [
+            || Box::new(UuidV7Generator),
+            || Box::new(UlidGenerator),
+            || Box::new(RandomGenerator),
+        ];
+
+        for scheme in schemes {
+            for prefixed in [true, false] {
+                let config = IdConfig::new(scheme(), prefixed);
+                let ids: std::collections::HashSet<_> =
+                    (0..100).map(|_| config.user_id()).collect();
+                assert_eq!(ids.len(), 100);
+                assert!(ids.iter().all(|id| config.is_valid(id, "usr_")));
+                assert_eq!(config.session_id().starts_with("ses_"), prefixed);
+            }
+        }
+
+        let config = IdConfig::default();
+        let id = config.user_id();
+        let uuid = uuid::Uuid::parse_str(id.strip_prefix("usr_").unwrap()).unwrap();
+        assert_eq!(uuid.get_version_num(), 7);
+        assert!(!config.is_valid(&UlidGenerator.generate(), "usr_"));
+        assert!(!UuidV7Generator.is_valid(&uuid::Uuid::nil().to_string()));
+        assert!(!UlidGenerator.is_valid("not-a-ulid"));
+    }
 }
@@ -0,0 +1,47 @@
+//! Decoding of JWT segments from identity providers.
+
+use base64::Engine as _;
+use base64::engine::general_purpose::{STANDARD_NO_PAD, URL_SAFE_NO_PAD};
+
+/// Decode a segment of a JWT, e.g. the claims payload.
+///
+/// JWTs use unpadded base64url, but some IdPs pad it or use the standard alphabet, so
+/// padding is ignored and the standard alphabet is tried when base64url fails.
+pub fn decode_jwt_segment(segment: &str) -> Result<Vec<u8>, base64::DecodeError> {
+    let unpadded = segment.trim_end_matches('=');
+    URL_SAFE_NO_PAD
+        .decode(unpadded)
+        .or_else(|e| STANDARD_NO_PAD.decode(unpadded).map_err(|_| e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::engine::general_purpose::{STANDARD, URL_SAFE};
+    use serde_json::{Value, json};
+
+    #[test]
+    fn test_decode_jwt_segment_tolerates_padding_and_alphabet() {
+        // Encodes to `/` or `_` and needs padding
+        let claims = json!({ "sub": "auth0|abc?>", "name": "Anne~~" });
+        let payload = serde_json::to_vec(&claims).unwrap();
+
+        let unpadded = URL_SAFE_NO_PAD.encode(&payload);
+        let padded = URL_SAFE.encode(&payload);
+        let standard = STANDARD.encode(&payload);
+        assert!(padded.ends_with('='));
+        assert_ne!(standard, padded);
+
+        for segment in [unpadded, padded, standard] {
+            let decoded = decode_jwt_segment(&segment).unwrap();
+            assert_eq!(
+                serde_json::from_slice::<Value>(&decoded).unwrap(),
+                claims,
+                "{}",
+                segment
+            );
+        }
+
+        assert!(decode_jwt_segment("not base64!").is_err());
+    }
+}
@@ -4,7 +4,10 @@ pub mod authn_controller;
 pub mod callback;
 pub mod db_ops;
 pub mod home;
+pub mod jwt;
 pub mod models;
 pub mod oauth;
 pub mod openid;
+pub mod provider_cache;
 pub mod redis_pool;
+pub mod session;
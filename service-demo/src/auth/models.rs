@@ -15,8 +15,8 @@ pub struct User {
     /// Unique user ID
     pub user_id: String,
 
-    /// User email address
-    pub email: String,
+    /// User email address, `None` when the identity provider didn't return one
+    pub email: Option<String>,
 
     /// User's full name
     pub name: Option<String>,
@@ -65,7 +65,7 @@ pub struct User {
 #[derive(Debug, Clone)]
 pub struct CreateUser {
     pub user_id: String,
-    pub email: String,
+    pub email: Option<String>,
     pub name: Option<String>,
     pub display_name: Option<String>,
     pub picture: Option<String>,
@@ -123,6 +123,23 @@ pub struct UserSession {
     pub last_activity_at: DateTime<Utc>,
 }
 
+// ============================================================================
+// Organization Store Model
+// ============================================================================
+
+/// OpenFGA store an organization is allowed to operate on
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct OrgStore {
+    /// Organization ID
+    pub org_id: String,
+
+    /// OpenFGA store ID
+    pub store_id: String,
+
+    /// When access was granted
+    pub created_at: DateTime<Utc>,
+}
+
 /// Session creation data
 #[derive(Debug, Clone)]
 pub struct CreateSession {
@@ -145,6 +162,10 @@ pub struct SessionConfig {
     #[serde(default = "default_cookie_name")]
     pub cookie_name: String,
 
+    /// Prefix added to the cookie name, for which browsers enforce extra constraints
+    #[serde(default)]
+    pub cookie_prefix: CookiePrefix,
+
     /// Cookie domain (e.g., ".example.com" for subdomain sharing)
     pub cookie_domain: Option<String>,
 
@@ -174,6 +195,121 @@ pub struct SessionConfig {
     /// Threshold for session extension (e.g., 0.5 = extend when 50% expired)
     #[serde(default = "default_extension_threshold")]
     pub session_extension_threshold: f64,
+
+    /// Where session state lives (database by default)
+    #[serde(default)]
+    pub session_strategy: SessionStrategy,
+
+    /// Maximum active sessions per user; unlimited when unset. Only enforced for
+    /// database sessions.
+    #[serde(default)]
+    pub max_sessions_per_user: Option<usize>,
+
+    /// What a login over `max_sessions_per_user` does
+    #[serde(default)]
+    pub session_limit_policy: SessionLimitPolicy,
+}
+
+/// What happens to a login of a user who already has the maximum number of sessions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionLimitPolicy {
+    /// End the user's oldest sessions to make room for the new one
+    #[default]
+    EvictOldest,
+    /// Refuse the login
+    Reject,
+}
+
+/// How sessions are stored
+///
+/// Stateless sessions keep the session payload AEAD-encrypted in the cookie itself,
+/// so requests need no database lookup. They cannot be revoked server-side: a
+/// stateless session stays valid until it expires or the signing secret is rotated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionStrategy {
+    /// Cookie holds a signed session ID looked up in `user_sessions`
+    #[default]
+    Database,
+    /// Cookie holds the encrypted session payload
+    Stateless,
+}
+
+/// Session payload resolved from a session cookie
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionClaims {
+    pub user_id: String,
+    pub org_id: String,
+    /// `None` for stateless sessions issued before their creation time was recorded
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Cookie name prefix with browser-enforced guarantees
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CookiePrefix {
+    #[default]
+    None,
+    /// `__Secure-`: the cookie must be Secure
+    Secure,
+    /// `__Host-`: the cookie must be Secure, without a Domain and with `Path=/`,
+    /// so it's bound to the exact host that set it
+    Host,
+}
+
+impl CookiePrefix {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CookiePrefix::None => "",
+            CookiePrefix::Secure => "__Secure-",
+            CookiePrefix::Host => "__Host-",
+        }
+    }
+}
+
+impl SessionConfig {
+    /// Cookie name with the configured prefix
+    pub fn session_cookie_name(&self) -> String {
+        format!("{}{}", self.cookie_prefix.as_str(), self.cookie_name)
+    }
+
+    /// Check the cookie settings meet the constraints of the cookie prefix
+    pub fn check_cookie_prefix(&self) -> Result<(), String> {
+        let prefix = self.cookie_prefix.as_str();
+        match self.cookie_prefix {
+            CookiePrefix::None => Ok(()),
+            _ if !self.secure => Err(format!("{} cookies must be secure", prefix)),
+            CookiePrefix::Host if self.cookie_domain.is_some() => {
+                Err(format!("{} cookies can't set a domain", prefix))
+            }
+            CookiePrefix::Secure | CookiePrefix::Host => Ok(()),
+        }
+    }
+
+    /// Check the cookie signing secret is long enough to sign with, see
+    /// [`check_secret_length`]
+    pub fn check_signing_secret(&self) -> Result<(), String> {
+        check_secret_length("cookie_signing_secret", &self.cookie_signing_secret)
+    }
+}
+
+/// Fewest bytes a signing secret may have; shorter HMAC keys can be brute-forced
+pub const MIN_SECRET_LENGTH: usize = 32;
+
+/// Check the secret named `name` has at least [`MIN_SECRET_LENGTH`] bytes
+pub fn check_secret_length(name: &str, secret: &str) -> Result<(), String> {
+    if secret.len() < MIN_SECRET_LENGTH {
+        return Err(format!(
+            "{} must be at least {} bytes, got {}",
+            name,
+            MIN_SECRET_LENGTH,
+            secret.len()
+        ));
+    }
+    Ok(())
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -226,6 +362,7 @@ impl Default for SessionConfig {
     fn default() -> Self {
         Self {
             cookie_name: default_cookie_name(),
+            cookie_prefix: CookiePrefix::default(),
             cookie_domain: None,
             secure: default_secure(),
             http_only: default_http_only(),
@@ -234,6 +371,9 @@ impl Default for SessionConfig {
             cookie_signing_secret: String::new(), // Must be set
             session_extension_enabled: default_session_extension(),
             session_extension_threshold: default_extension_threshold(),
+            session_strategy: SessionStrategy::default(),
+            max_sessions_per_user: None,
+            session_limit_policy: SessionLimitPolicy::default(),
         }
     }
 }
@@ -294,50 +434,14 @@ pub struct IdTokenClaims {
 // Database Schema SQL
 // ============================================================================
 
+// The migrations in `migrations/`, applied by `crate::migrations::run_migrations`
+
 /// SQL schema for users table
-pub const USERS_TABLE_SCHEMA: &str = r#"
-CREATE TABLE IF NOT EXISTS users (
-    user_id TEXT PRIMARY KEY,
-    email TEXT NOT NULL,
-    name TEXT,
-    display_name TEXT,
-    picture TEXT,
-    auth_provider TEXT NOT NULL,
-    provider_user_id TEXT NOT NULL,
-    org_id TEXT NOT NULL,
-    access_token TEXT,
-    refresh_token TEXT,
-    id_token TEXT,
-    token_expires_at TIMESTAMPTZ,
-    is_active BOOLEAN DEFAULT TRUE,
-    created_at TIMESTAMPTZ DEFAULT NOW(),
-    last_login_at TIMESTAMPTZ DEFAULT NOW(),
-    updated_at TIMESTAMPTZ DEFAULT NOW(),
-    
-    UNIQUE(org_id, provider_user_id, auth_provider)
-);
-
-CREATE INDEX IF NOT EXISTS idx_users_org_id ON users(org_id);
-CREATE INDEX IF NOT EXISTS idx_users_email ON users(email);
-CREATE INDEX IF NOT EXISTS idx_users_provider ON users(provider_user_id, auth_provider);
-"#;
+pub const USERS_TABLE_SCHEMA: &str = include_str!("../../migrations/0001_users.sql");
 
 /// SQL schema for user_sessions table
-pub const USER_SESSIONS_TABLE_SCHEMA: &str = r#"
-CREATE TABLE IF NOT EXISTS user_sessions (
-    session_id TEXT PRIMARY KEY,
-    user_id TEXT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
-    org_id TEXT NOT NULL,
-    ip_address TEXT NOT NULL,
-    user_agent TEXT NOT NULL,
-    is_active BOOLEAN DEFAULT TRUE,
-    created_at TIMESTAMPTZ DEFAULT NOW(),
-    expires_at TIMESTAMPTZ NOT NULL,
-    last_activity_at TIMESTAMPTZ DEFAULT NOW()
-);
-
-CREATE INDEX IF NOT EXISTS idx_sessions_user_id ON user_sessions(user_id);
-CREATE INDEX IF NOT EXISTS idx_sessions_org_id ON user_sessions(org_id);
-CREATE INDEX IF NOT EXISTS idx_sessions_expires_at ON user_sessions(expires_at);
-CREATE INDEX IF NOT EXISTS idx_sessions_active ON user_sessions(is_active) WHERE is_active = TRUE;
-"#;
+pub const USER_SESSIONS_TABLE_SCHEMA: &str =
+    include_str!("../../migrations/0002_user_sessions.sql");
+
+/// SQL schema for org_stores table
+pub const ORG_STORES_TABLE_SCHEMA: &str = include_str!("../../migrations/0004_org_stores.sql");
@@ -1,6 +1,4 @@
 use axum::{extract::Query, response::IntoResponse};
-use base64::Engine as _;
-use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_ENGINE;
 use oauth2::{
     AuthUrl, ClientId, ClientSecret, CsrfToken, PkceCodeChallenge, RedirectUrl, Scope, TokenUrl,
     basic::BasicClient,
@@ -10,6 +8,8 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Mutex;
 
+use crate::auth::jwt::decode_jwt_segment;
+
 // DexIdP OAuth2 Configuration
 const DEX_CLIENT_ID: &str = "example-app";
 const DEX_CLIENT_SECRET: &str = "ZXhhbXBsZS1hcHAtc2VjcmV0";
@@ -41,8 +41,7 @@ fn parse_jwt_claims(id_token: &str) -> Option<IdTokenClaims> {
     if parts.len() < 2 {
         return None;
     }
-    let payload = parts[1];
-    let decoded = BASE64_ENGINE.decode(payload.as_bytes()).ok()?;
+    let decoded = decode_jwt_segment(parts[1]).ok()?;
     serde_json::from_slice::<IdTokenClaims>(&decoded).ok()
 }
 
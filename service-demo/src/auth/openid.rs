@@ -1,8 +1,9 @@
 use axum::extract::{Query, State};
+use axum::http::HeaderMap;
 use axum::response::IntoResponse;
 use openidconnect::{
     ClientId, ClientSecret, CsrfToken, IssuerUrl, Nonce, RedirectUrl, Scope,
-    core::{CoreAuthenticationFlow, CoreClient, CoreProviderMetadata},
+    core::{CoreAuthenticationFlow, CoreClient},
 };
 use reqwest::Client as HttpClient;
 
@@ -10,6 +11,7 @@ use std::collections::HashMap;
 use std::sync::Mutex;
 
 use crate::context::Ctx;
+use crate::i18n::{Key, Lang, request_lang, translate};
 
 // Structure to store state data including nonce
 #[derive(Debug, Clone)]
@@ -48,7 +50,7 @@ pub async fn login_with(
         .expect("Failed to create HTTP client");
 
     // Fetch provider metadata using reqwest async client
-    let provider_metadata = CoreProviderMetadata::discover_async(issuer_url, &http_client)
+    let provider_metadata = super::provider_cache::discover(issuer_url, &http_client)
         .await
         .expect("Failed to discover provider metadata");
 
@@ -119,9 +121,12 @@ pub struct OpenIDCallbackParams {
 pub async fn handle_openid_callback(
     State(ctx): State<Ctx>,
     Query(params): Query<OpenIDCallbackParams>,
+    headers: HeaderMap,
 ) -> axum::response::Response {
     use openidconnect::{AuthorizationCode, OAuth2TokenResponse, TokenResponse};
 
+    let lang = request_lang(&headers);
+
     println!("OpenID Connect callback params: {:?}", params);
 
     // Check if Dex/IdP returned an error
@@ -144,7 +149,7 @@ pub async fn handle_openid_callback(
             store.remove(&params.state);
         }
 
-        return build_openid_error_response(error, error_description);
+        return build_openid_error_response(lang, error, error_description);
     }
 
     // Extract authorization code (required if no error)
@@ -153,6 +158,7 @@ pub async fn handle_openid_callback(
         None => {
             println!("No authorization code provided in callback");
             return build_generic_error_response(
+                lang,
                 "No authorization code received from identity provider",
             );
         }
@@ -169,6 +175,7 @@ pub async fn handle_openid_callback(
         None => {
             println!("No state data found for state: {}", params.state);
             return build_generic_error_response(
+                lang,
                 "Invalid state parameter. The session may have expired or the request is invalid.",
             );
         }
@@ -191,7 +198,7 @@ pub async fn handle_openid_callback(
         .expect("Failed to create HTTP client");
 
     // Fetch provider metadata
-    let provider_metadata = CoreProviderMetadata::discover_async(issuer_url, &http_client)
+    let provider_metadata = super::provider_cache::discover(issuer_url, &http_client)
         .await
         .expect("Failed to discover provider metadata");
 
@@ -373,7 +380,7 @@ pub async fn handle_openid_callback(
                     <head>
                         <meta charset="UTF-8">
                         <meta name="viewport" content="width=device-width, initial-scale=1.0">
-                        <title>OpenID Connect Error</title>
+                        <title>OpenID Connect - {auth_error}</title>
                         <style>
                             body {{ font-family: Arial, sans-serif; text-align: center; margin-top: 50px; background-color: #f5f5f5; }}
                             .container {{ max-width: 600px; margin: 0 auto; background: white; padding: 30px; border-radius: 8px; box-shadow: 0 2px 4px rgba(0,0,0,0.1); }}
@@ -386,18 +393,22 @@ pub async fn handle_openid_callback(
                     <body>
                         <div class="container">
                             <div class="error">
-                                <h1>✗ OpenID Connect Authentication Failed!</h1>
+                                <h1>✗ OpenID Connect: {auth_failed}!</h1>
                             </div>
                             <div class="error-details">
-                                <strong>Error Details:</strong><br>
-                                {}
+                                <strong>{error_details}</strong><br>
+                                {error:?}
                             </div>
-                            <a href="/auth/login" class="back-link">Try Again</a>
+                            <a href="/auth/login" class="back-link">{try_again}</a>
                         </div>
                     </body>
                     </html>
                     "#,
-                    format!("{:?}", e)
+                    auth_error = translate(lang, Key::AuthError),
+                    auth_failed = translate(lang, Key::AuthFailed),
+                    error_details = translate(lang, Key::ErrorDetails),
+                    error = e,
+                    try_again = translate(lang, Key::TryAgain),
                 ))
                 .unwrap()
                 .into_response();
@@ -407,7 +418,11 @@ pub async fn handle_openid_callback(
 }
 
 // Helper function to build OpenID Connect error response with error code and description
-fn build_openid_error_response(error: &str, error_description: &str) -> axum::response::Response {
+fn build_openid_error_response(
+    lang: Lang,
+    error: &str,
+    error_description: &str,
+) -> axum::response::Response {
     axum::response::Response::builder()
         .status(axum::http::StatusCode::BAD_REQUEST)
         .header("Content-Type", "text/html; charset=utf-8")
@@ -418,7 +433,7 @@ fn build_openid_error_response(error: &str, error_description: &str) -> axum::re
             <head>
                 <meta charset="UTF-8">
                 <meta name="viewport" content="width=device-width, initial-scale=1.0">
-                <title>OpenID Connect Authentication Error</title>
+                <title>OpenID Connect - {auth_error}</title>
                 <style>
                     body {{ font-family: Arial, sans-serif; text-align: center; margin-top: 50px; background-color: #f5f5f5; }}
                     .container {{ max-width: 700px; margin: 0 auto; background: white; padding: 30px; border-radius: 8px; box-shadow: 0 2px 4px rgba(0,0,0,0.1); }}
@@ -439,40 +454,48 @@ fn build_openid_error_response(error: &str, error_description: &str) -> axum::re
                 <div class="container">
                     <div class="error-icon">⚠️</div>
                     <div class="error">
-                        <h1>Authentication Failed</h1>
+                        <h1>{auth_failed}</h1>
                     </div>
 
                     <div class="error-code">
-                        <div class="error-code-title">Error Code:</div>
-                        <div class="error-code-value">{}</div>
-                        <div class="error-code-title">Error Description:</div>
-                        <div class="error-description">{}</div>
+                        <div class="error-code-title">{error_code}</div>
+                        <div class="error-code-value">{error}</div>
+                        <div class="error-code-title">{error_description_label}</div>
+                        <div class="error-description">{error_description}</div>
                     </div>
 
                     <div class="info-box">
-                        <div class="info-title">Common Causes:</div>
+                        <div class="info-title">{common_causes}</div>
                         <div class="info-text">
-                            • <strong>access_denied:</strong> User cancelled the login or doesn't have access<br>
-                            • <strong>unauthorized:</strong> Invalid organization or missing permissions<br>
-                            • <strong>invalid_request:</strong> Malformed request parameters<br>
-                            • <strong>server_error:</strong> Issue with the identity provider
+                            • <strong>access_denied:</strong> {cause_access_denied}<br>
+                            • <strong>unauthorized:</strong> {cause_unauthorized}<br>
+                            • <strong>invalid_request:</strong> {cause_invalid_request}<br>
+                            • <strong>server_error:</strong> {cause_server_error}
                         </div>
                     </div>
 
-                    <a href="/auth" class="back-link">← Return to Login</a>
+                    <a href="/auth" class="back-link">← {return_to_login}</a>
                 </div>
             </body>
             </html>
             "#,
-            error,
-            error_description
+            auth_error = translate(lang, Key::AuthError),
+            auth_failed = translate(lang, Key::AuthFailed),
+            error_code = translate(lang, Key::ErrorCode),
+            error_description_label = translate(lang, Key::ErrorDescription),
+            common_causes = translate(lang, Key::CommonCauses),
+            cause_access_denied = translate(lang, Key::CauseAccessDenied),
+            cause_unauthorized = translate(lang, Key::CauseUnauthorized),
+            cause_invalid_request = translate(lang, Key::CauseInvalidRequest),
+            cause_server_error = translate(lang, Key::CauseServerError),
+            return_to_login = translate(lang, Key::ReturnToLogin),
         )))
         .unwrap()
         .into_response()
 }
 
 // Helper function to build generic error response
-fn build_generic_error_response(error_msg: &str) -> axum::response::Response {
+fn build_generic_error_response(lang: Lang, error_msg: &str) -> axum::response::Response {
     axum::response::Response::builder()
         .status(axum::http::StatusCode::BAD_REQUEST)
         .header("Content-Type", "text/html; charset=utf-8")
@@ -483,7 +506,7 @@ fn build_generic_error_response(error_msg: &str) -> axum::response::Response {
             <head>
                 <meta charset="UTF-8">
                 <meta name="viewport" content="width=device-width, initial-scale=1.0">
-                <title>Authentication Error</title>
+                <title>{auth_error}</title>
                 <style>
                     body {{ font-family: Arial, sans-serif; text-align: center; margin-top: 50px; background-color: #f5f5f5; }}
                     .container {{ max-width: 600px; margin: 0 auto; background: white; padding: 30px; border-radius: 8px; box-shadow: 0 2px 4px rgba(0,0,0,0.1); }}
@@ -496,18 +519,21 @@ fn build_generic_error_response(error_msg: &str) -> axum::response::Response {
             <body>
                 <div class="container">
                     <div class="error">
-                        <h1>✗ Authentication Failed!</h1>
+                        <h1>✗ {auth_failed}!</h1>
                     </div>
                     <div class="error-details">
-                        <strong>Error Details:</strong><br>
-                        {}
+                        <strong>{error_details}</strong><br>
+                        {error_msg}
                     </div>
-                    <a href="/auth" class="back-link">Try Again</a>
+                    <a href="/auth" class="back-link">{try_again}</a>
                 </div>
             </body>
             </html>
             "#,
-            error_msg
+            auth_error = translate(lang, Key::AuthError),
+            auth_failed = translate(lang, Key::AuthFailed),
+            error_details = translate(lang, Key::ErrorDetails),
+            try_again = translate(lang, Key::TryAgain),
         )))
         .unwrap()
         .into_response()
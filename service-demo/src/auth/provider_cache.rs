@@ -0,0 +1,217 @@
+//! Process-wide cache of OIDC provider metadata and JWKS, keyed by issuer URL.
+//!
+//! Entries live for the `max-age` of the discovery and JWKS responses (the shorter
+//! of the two), or [`DEFAULT_PROVIDER_TTL`] when neither sets one. An expired entry
+//! is refetched on the next lookup.
+
+use anyhow::{Context, Result};
+use openidconnect::core::CoreProviderMetadata;
+use openidconnect::{AsyncHttpClient, HttpClientError, HttpRequest, HttpResponse, IssuerUrl};
+use reqwest::Client as HttpClient;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long metadata is cached when the provider sends no cache headers
+pub const DEFAULT_PROVIDER_TTL: Duration = Duration::from_secs(15 * 60);
+
+static PROVIDER_CACHE: LazyLock<ProviderCache> =
+    LazyLock::new(|| ProviderCache::new(DEFAULT_PROVIDER_TTL));
+
+/// Discover the provider metadata, including its JWKS, through the process-wide cache
+pub async fn discover(
+    issuer_url: IssuerUrl,
+    http_client: &HttpClient,
+) -> Result<CoreProviderMetadata> {
+    PROVIDER_CACHE.discover(issuer_url, http_client).await
+}
+
+struct CachedProvider {
+    metadata: CoreProviderMetadata,
+    expires_at: Instant,
+}
+
+/// Provider metadata by issuer URL
+pub struct ProviderCache {
+    entries: Mutex<HashMap<String, CachedProvider>>,
+    default_ttl: Duration,
+}
+
+impl ProviderCache {
+    pub fn new(default_ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            default_ttl,
+        }
+    }
+
+    /// Cached metadata of the issuer, discovered again once expired
+    pub async fn discover(
+        &self,
+        issuer_url: IssuerUrl,
+        http_client: &HttpClient,
+    ) -> Result<CoreProviderMetadata> {
+        let issuer = issuer_url.as_str().to_string();
+        self.get_or_fetch(&issuer, async || {
+            let client = CacheHeaderClient::new(http_client);
+            let metadata = CoreProviderMetadata::discover_async(issuer_url, &client)
+                .await
+                .context("Failed to discover provider metadata")?;
+            Ok((metadata, client.max_age()))
+        })
+        .await
+    }
+
+    /// Cached metadata of `issuer`, or the result of `fetch` with its `max-age`
+    async fn get_or_fetch<F>(&self, issuer: &str, fetch: F) -> Result<CoreProviderMetadata>
+    where
+        F: AsyncFnOnce() -> Result<(CoreProviderMetadata, Option<Duration>)>,
+    {
+        if let Some(entry) = self.entries.lock().unwrap().get(issuer)
+            && entry.expires_at > Instant::now()
+        {
+            return Ok(entry.metadata.clone());
+        }
+
+        let (metadata, max_age) = fetch().await?;
+        self.entries.lock().unwrap().insert(
+            issuer.to_string(),
+            CachedProvider {
+                metadata: metadata.clone(),
+                expires_at: Instant::now() + max_age.unwrap_or(self.default_ttl),
+            },
+        );
+        tracing::debug!("Cached provider metadata for {}", issuer);
+
+        Ok(metadata)
+    }
+}
+
+/// `max-age` of a `Cache-Control` header; zero for `no-store` and `no-cache`
+fn cache_control_max_age(value: &str) -> Option<Duration> {
+    value.split(',').map(str::trim).find_map(|directive| {
+        if directive.eq_ignore_ascii_case("no-store") || directive.eq_ignore_ascii_case("no-cache")
+        {
+            return Some(Duration::ZERO);
+        }
+        let (name, seconds) = directive.split_once('=')?;
+        if !name.trim().eq_ignore_ascii_case("max-age") {
+            return None;
+        }
+        seconds.trim().parse().ok().map(Duration::from_secs)
+    })
+}
+
+/// HTTP client keeping the shortest `max-age` of the responses it receives
+struct CacheHeaderClient<'a> {
+    inner: &'a HttpClient,
+    max_age: Mutex<Option<Duration>>,
+}
+
+impl<'a> CacheHeaderClient<'a> {
+    fn new(inner: &'a HttpClient) -> Self {
+        Self {
+            inner,
+            max_age: Mutex::new(None),
+        }
+    }
+
+    fn max_age(&self) -> Option<Duration> {
+        *self.max_age.lock().unwrap()
+    }
+
+    fn record(&self, response: &HttpResponse) {
+        let Some(max_age) = response
+            .headers()
+            .get(reqwest::header::CACHE_CONTROL)
+            .and_then(|v| v.to_str().ok())
+            .and_then(cache_control_max_age)
+        else {
+            return;
+        };
+        let mut current = self.max_age.lock().unwrap();
+        *current = Some(current.map_or(max_age, |c| c.min(max_age)));
+    }
+}
+
+impl<'c> AsyncHttpClient<'c> for CacheHeaderClient<'_> {
+    type Error = HttpClientError<reqwest::Error>;
+    type Future =
+        Pin<Box<dyn Future<Output = Result<HttpResponse, Self::Error>> + Send + Sync + 'c>>;
+
+    fn call(&'c self, request: HttpRequest) -> Self::Future {
+        Box::pin(async move {
+            let response = self.inner.call(request).await?;
+            self.record(&response);
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn metadata() -> CoreProviderMetadata {
+        serde_json::from_value(serde_json::json!({
+            "issuer": "https://dex.example.com",
+            "authorization_endpoint": "https://dex.example.com/auth",
+            "jwks_uri": "https://dex.example.com/keys",
+            "response_types_supported": ["code"],
+            "subject_types_supported": ["public"],
+            "id_token_signing_alg_values_supported": ["RS256"]
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_second_discovery_within_ttl_uses_cache() {
+        let cache = ProviderCache::new(DEFAULT_PROVIDER_TTL);
+        let fetches = AtomicUsize::new(0);
+        let fetch = async || {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            Ok((metadata(), None))
+        };
+
+        let first = cache
+            .get_or_fetch("https://dex.example.com", fetch)
+            .await
+            .unwrap();
+        let second = cache
+            .get_or_fetch("https://dex.example.com", fetch)
+            .await
+            .unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert_eq!(first.issuer(), second.issuer());
+
+        // Another issuer has its own entry
+        cache
+            .get_or_fetch("https://auth0.example.com/", fetch)
+            .await
+            .unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+
+        // A `max-age` of zero expires the entry immediately
+        let uncached = async || {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            Ok((metadata(), Some(Duration::ZERO)))
+        };
+        cache.get_or_fetch("https://idp", uncached).await.unwrap();
+        cache.get_or_fetch("https://idp", uncached).await.unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_cache_control_max_age() {
+        assert_eq!(
+            cache_control_max_age("public, max-age=3600"),
+            Some(Duration::from_secs(3600))
+        );
+        assert_eq!(cache_control_max_age("no-store"), Some(Duration::ZERO));
+        assert_eq!(cache_control_max_age("public"), None);
+        assert_eq!(cache_control_max_age("max-age=soon"), None);
+    }
+}
@@ -0,0 +1,183 @@
+//! "Who am I" endpoint for SPA clients, `GET /auth/session`.
+
+use anyhow::Result;
+use axum::{Json, extract::State, http::HeaderMap};
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+use std::future::Future;
+
+use crate::auth::db_ops;
+use crate::auth::models::User;
+use crate::context::Ctx;
+use crate::fga_apis::store_access::session_claims;
+
+/// User and session behind a request's session cookie
+#[derive(Debug, Clone)]
+pub struct CurrentSession {
+    pub user: User,
+    /// `None` for stateless sessions that don't record it
+    pub created_at: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Finds the session behind a request's session cookie
+pub trait SessionLookup: Send + Sync {
+    /// The valid session of the organization the request was sent to, with its
+    /// active user; `None` without one
+    fn current_session(
+        &self,
+        headers: &HeaderMap,
+    ) -> impl Future<Output = Result<Option<CurrentSession>>> + Send;
+}
+
+impl SessionLookup for Ctx {
+    async fn current_session(&self, headers: &HeaderMap) -> Result<Option<CurrentSession>> {
+        let Some(claims) = session_claims(self, headers).await? else {
+            return Ok(None);
+        };
+        let user = db_ops::find_user_by_id(&self.db, &claims.user_id).await?;
+
+        Ok(user
+            .filter(|user| user.is_active && user.org_id == claims.org_id)
+            .map(|user| CurrentSession {
+                user,
+                created_at: claims.created_at,
+                expires_at: claims.expires_at,
+            }))
+    }
+}
+
+/// Describe the caller's session:
+///
+/// ```json
+/// {
+///   "authenticated": true,
+///   "user": { "id": "usr_...", "email": "...", "name": "...", "picture": "..." },
+///   "session": { "created_at": "...", "expires_at": "..." }
+/// }
+/// ```
+///
+/// or `{ "authenticated": false }` without a valid session. Only these fields are
+/// returned; the user's tokens never are.
+pub async fn get_session<L: SessionLookup>(
+    State(lookup): State<L>,
+    headers: HeaderMap,
+) -> Json<Value> {
+    match lookup.current_session(&headers).await {
+        Ok(Some(session)) => Json(json!({
+            "authenticated": true,
+            "user": {
+                "id": session.user.user_id,
+                "email": session.user.email,
+                "name": session.user.name,
+                "picture": session.user.picture,
+            },
+            "session": {
+                "created_at": session.created_at,
+                "expires_at": session.expires_at,
+            },
+        })),
+        Ok(None) => Json(json!({ "authenticated": false })),
+        Err(e) => {
+            tracing::warn!("Failed to resolve session: {:#}", e);
+            Json(json!({ "authenticated": false }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, http::Request, routing::get};
+    use tower::ServiceExt;
+
+    /// Knows one session, sent as `Cookie: session_id=valid`
+    #[derive(Clone)]
+    struct MockSessions {
+        session: CurrentSession,
+    }
+
+    impl SessionLookup for MockSessions {
+        async fn current_session(&self, headers: &HeaderMap) -> Result<Option<CurrentSession>> {
+            let cookie = headers.get("cookie").and_then(|v| v.to_str().ok());
+            Ok((cookie == Some("session_id=valid")).then(|| self.session.clone()))
+        }
+    }
+
+    fn user() -> User {
+        let now = Utc::now();
+        User {
+            user_id: "usr_alice".to_string(),
+            email: Some("alice@acme.example".to_string()),
+            name: Some("Alice".to_string()),
+            display_name: None,
+            picture: Some("https://acme.example/alice.png".to_string()),
+            auth_provider: "mock".to_string(),
+            provider_user_id: "alice".to_string(),
+            org_id: "org_acme".to_string(),
+            access_token: Some("access-token".to_string()),
+            refresh_token: Some("refresh-token".to_string()),
+            id_token: Some("id-token".to_string()),
+            token_expires_at: None,
+            is_active: true,
+            created_at: now,
+            last_login_at: now,
+            updated_at: now,
+        }
+    }
+
+    async fn get_session_body(app: Router, cookie: Option<&str>) -> Value {
+        let mut request = Request::builder().uri("/auth/session");
+        if let Some(cookie) = cookie {
+            request = request.header("cookie", cookie);
+        }
+        let response = app
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_session_endpoint() {
+        let created_at: DateTime<Utc> = "2024-05-01T12:00:00Z".parse().unwrap();
+        let expires_at: DateTime<Utc> = "2024-05-02T12:00:00Z".parse().unwrap();
+        let sessions = MockSessions {
+            session: CurrentSession {
+                user: user(),
+                created_at: Some(created_at),
+                expires_at,
+            },
+        };
+        let app = Router::new()
+            .route("/auth/session", get(get_session::<MockSessions>))
+            .with_state(sessions);
+
+        let body = get_session_body(app.clone(), Some("session_id=valid")).await;
+        assert_eq!(
+            body,
+            json!({
+                "authenticated": true,
+                "user": {
+                    "id": "usr_alice",
+                    "email": "alice@acme.example",
+                    "name": "Alice",
+                    "picture": "https://acme.example/alice.png"
+                },
+                "session": {
+                    "created_at": "2024-05-01T12:00:00Z",
+                    "expires_at": "2024-05-02T12:00:00Z"
+                }
+            })
+        );
+        assert!(!body.to_string().contains("token"));
+
+        let body = get_session_body(app.clone(), Some("session_id=forged")).await;
+        assert_eq!(body, json!({ "authenticated": false }));
+        let body = get_session_body(app, None).await;
+        assert_eq!(body, json!({ "authenticated": false }));
+    }
+}
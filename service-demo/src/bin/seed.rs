@@ -0,0 +1,36 @@
+use service_demo::seed::{SeedConfig, seed};
+
+/// Bootstrap a store with the example model and sample tuples, printing the IDs to
+/// export as `OPENFGA_STORE_ID` and `OPENFGA_AUTH_MODEL_ID`
+#[tokio::main]
+async fn main() {
+    dotenv::dotenv().ok();
+
+    let endpoint = std::env::var("OPENFGA_CLIENT_URL")
+        .unwrap_or_else(|_| "http://localhost:8081".to_string());
+    let config = match SeedConfig::from_args(std::env::args().skip(1), endpoint) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("seed: {:#}", e);
+            std::process::exit(2);
+        }
+    };
+
+    match seed(&config).await {
+        Ok(output) => {
+            eprintln!(
+                "Seeded store {:?} with {} tuple(s)",
+                config.store_name, output.tuples_written
+            );
+            println!("export OPENFGA_STORE_ID={}", output.store_id);
+            println!(
+                "export OPENFGA_AUTH_MODEL_ID={}",
+                output.authorization_model_id
+            );
+        }
+        Err(e) => {
+            eprintln!("seed failed: {:#}", e);
+            std::process::exit(1);
+        }
+    }
+}
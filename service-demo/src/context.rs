@@ -1,18 +1,156 @@
-use openfga_grpc_client::OpenFgaServiceClient;
+use crate::fga_apis::audit::AuditConfig;
+use crate::fga_apis::contextual_tuples::HeaderTupleMapping;
+use crate::fga_apis::dto::Consistency;
+use crate::fga_apis::http::retry::HttpRetryConfig;
+use crate::fga_apis::store_names::{STORE_NAME_TTL, StoreNameCache};
+use axum::http::HeaderName;
+use openfga_grpc_client::{
+    ErrorCodeChannel, ErrorCodeService, Http2Settings, ListStoresRequest, OpenFgaServiceClient,
+    RequestIdChannel, RequestIdInterceptor,
+};
 use openfga_http_client::apis::configuration::Configuration;
 use sqlx::PgPool;
 use sqlx::postgres::PgPoolOptions;
 use std::env;
+use std::future::Future;
+use std::net::IpAddr;
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
+use tokio::time::Instant;
+use tokio::time::error::Elapsed;
 use tonic::transport::Channel;
 
+/// Default time budget for OpenFGA to become responsive on startup
+const DEFAULT_FGA_WARMUP_TIMEOUT_SECS: u64 = 30;
+
+/// Delay before the first warm-up retry, doubled after every failed attempt
+const FGA_WARMUP_INITIAL_DELAY: Duration = Duration::from_millis(200);
+
+/// Upper bound for the delay between warm-up retries
+const FGA_WARMUP_MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// Default request body limit for the model and bulk-write FGA routes (4 MiB)
+pub const DEFAULT_FGA_MAX_BODY_SIZE: usize = 4 * 1024 * 1024;
+
+/// Page size used when the caller doesn't ask for one
+pub const DEFAULT_FGA_PAGE_SIZE: i32 = 50;
+
+/// Largest page size a caller may ask for, matching OpenFGA's own limit
+pub const DEFAULT_FGA_MAX_PAGE_SIZE: i32 = 100;
+
+/// gRPC client of the OpenFGA service, sending the request ID of the current task
+/// (see [`crate::request_id`]) with each call and keeping OpenFGA's error codes
+/// (see [`crate::fga_apis::errors::grpc_error_status`])
+pub type FgaGrpcClient = OpenFgaServiceClient<RequestIdChannel<ErrorCodeChannel>>;
+
 /// OpenFGA configuration parameters
 #[derive(Clone, Debug)]
 pub struct OpenFgaConfig {
     /// OpenFGA store ID
     pub store_id: String,
-    /// OpenFGA authorization model ID
-    pub authorization_model_id: String,
+    /// OpenFGA authorization model ID, used when a request doesn't name one
+    pub authorization_model_id: CurrentModelId,
+    /// Switch to each model created in the configured store, see
+    /// [`OpenFgaConfig::model_created`]. Read from `OPENFGA_USE_CREATED_MODELS`, off by
+    /// default so deployments can pin their model.
+    pub use_created_models: bool,
+    /// Maximum request body size in bytes for the model and bulk-write routes.
+    /// Read from `OPENFGA_MAX_BODY_SIZE`, defaults to [`DEFAULT_FGA_MAX_BODY_SIZE`].
+    pub max_body_size: usize,
+    /// Page size for paginated reads when none is given.
+    /// Read from `OPENFGA_DEFAULT_PAGE_SIZE`, defaults to [`DEFAULT_FGA_PAGE_SIZE`].
+    pub default_page_size: i32,
+    /// Upper bound for caller-provided page sizes.
+    /// Read from `OPENFGA_MAX_PAGE_SIZE`, defaults to [`DEFAULT_FGA_MAX_PAGE_SIZE`].
+    pub max_page_size: i32,
+    /// Consistency of each operation when the request doesn't ask for one
+    pub consistency: ConsistencyDefaults,
+    /// Headers attached to the request's checks and queries as contextual tuples.
+    /// Read from `OPENFGA_CONTEXTUAL_TUPLE_HEADERS`, none by default.
+    pub contextual_tuple_headers: Vec<HeaderTupleMapping>,
+    /// Peers whose `contextual_tuple_headers` are mapped, e.g. the reverse proxy that
+    /// sets them. Read from `OPENFGA_CONTEXTUAL_TUPLE_TRUSTED_PROXIES`, a comma-separated
+    /// list of IP addresses, none by default.
+    pub contextual_tuple_trusted_proxies: Vec<IpAddr>,
+}
+
+impl OpenFgaConfig {
+    /// Record a model just written to `store_id`. With `use_created_models`, a model of
+    /// the configured store becomes the default of subsequent requests; returns
+    /// whether it did.
+    pub fn model_created(&self, store_id: &str, authorization_model_id: &str) -> bool {
+        if !self.use_created_models || store_id != self.store_id {
+            return false;
+        }
+        self.authorization_model_id.set(authorization_model_id);
+        tracing::info!(
+            "Using authorization model {} of store {}",
+            authorization_model_id,
+            store_id
+        );
+        true
+    }
+
+    /// Resolve the page size for a paginated request: the default when unspecified,
+    /// otherwise the requested size clamped to `1..=max_page_size`
+    pub fn page_size(&self, requested: Option<i32>) -> i32 {
+        let max_page_size = self.max_page_size.max(1);
+        requested
+            .unwrap_or(self.default_page_size)
+            .clamp(1, max_page_size)
+    }
+}
+
+/// Authorization model ID shared by every clone of the context, so the running service
+/// switches models at once
+#[derive(Clone, Debug, Default)]
+pub struct CurrentModelId(Arc<RwLock<String>>);
+
+impl CurrentModelId {
+    pub fn new(authorization_model_id: impl Into<String>) -> Self {
+        Self(Arc::new(RwLock::new(authorization_model_id.into())))
+    }
+
+    pub fn get(&self) -> String {
+        self.0.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn set(&self, authorization_model_id: impl Into<String>) {
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = authorization_model_id.into();
+    }
+}
+
+/// Consistency preference of each OpenFGA query when the request doesn't name one.
+///
+/// Read from the `OPENFGA_CONSISTENCY_*` environment variables, e.g.
+/// `OPENFGA_CONSISTENCY_LIST_OBJECTS=higher_consistency`. Unset operations leave the
+/// choice to the server.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConsistencyDefaults {
+    pub check: Consistency,
+    pub read: Consistency,
+    pub list_objects: Consistency,
+    pub list_users: Consistency,
+    pub expand: Consistency,
+}
+
+impl ConsistencyDefaults {
+    const ENV_PREFIX: &str = "OPENFGA_CONSISTENCY_";
+
+    /// Build from `OPENFGA_CONSISTENCY_*` variables; unset variables keep their default
+    pub fn from_vars(vars: impl IntoIterator<Item = (String, String)>) -> anyhow::Result<Self> {
+        let fields: serde_json::Map<String, serde_json::Value> = vars
+            .into_iter()
+            .filter_map(|(key, value)| {
+                key.strip_prefix(Self::ENV_PREFIX)
+                    .map(|field| (field.to_lowercase(), serde_json::Value::String(value)))
+            })
+            .collect();
+
+        serde_json::from_value(serde_json::Value::Object(fields))
+            .map_err(|e| anyhow::anyhow!("invalid OpenFGA consistency configuration: {}", e))
+    }
 }
 
 #[derive(Clone, Debug, serde::Deserialize)]
@@ -25,6 +163,67 @@ pub struct DexConfig {
     pub scopes: Vec<String>,
 }
 
+/// Auth0 application used by the `/auth/auth0` routes.
+///
+/// Read from the `AUTH0_*` environment variables, e.g. `AUTH0_CLIENT_SECRET` for
+/// `client_secret`. `Debug` masks the client secret.
+#[derive(Clone, serde::Deserialize)]
+pub struct Auth0Config {
+    /// Tenant domain, e.g. `tenant.eu.auth0.com`
+    pub domain: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_url: String,
+    /// Organization used when the login request doesn't name one
+    pub organization: Option<String>,
+}
+
+impl Auth0Config {
+    const ENV_PREFIX: &str = "AUTH0_";
+
+    /// Build from `AUTH0_*` variables; `None` when none are set
+    pub fn from_vars(
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> anyhow::Result<Option<Self>> {
+        let fields: serde_json::Map<String, serde_json::Value> = vars
+            .into_iter()
+            .filter_map(|(key, value)| {
+                key.strip_prefix(Self::ENV_PREFIX)
+                    .map(|field| (field.to_lowercase(), serde_json::Value::String(value)))
+            })
+            .collect();
+        if fields.is_empty() {
+            return Ok(None);
+        }
+
+        let config = serde_json::from_value(serde_json::Value::Object(fields))
+            .map_err(|e| anyhow::anyhow!("invalid Auth0 configuration: {}", e))?;
+        Ok(Some(config))
+    }
+}
+
+impl std::fmt::Debug for Auth0Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Auth0Config")
+            .field("domain", &self.domain)
+            .field("client_id", &self.client_id)
+            .field("client_secret", &mask_secret(&self.client_secret))
+            .field("redirect_url", &self.redirect_url)
+            .field("organization", &self.organization)
+            .finish()
+    }
+}
+
+/// Mask a secret for logging, keeping only a short prefix of long values
+pub fn mask_secret(secret: &str) -> String {
+    const VISIBLE: usize = 4;
+    if secret.chars().count() < 4 * VISIBLE {
+        return "****".to_string();
+    }
+    let prefix: String = secret.chars().take(VISIBLE).collect();
+    format!("{}****", prefix)
+}
+
 /// Application context that holds shared resources
 #[derive(Clone)]
 pub struct Ctx {
@@ -33,13 +232,25 @@ pub struct Ctx {
     /// Application profile name (e.g., "dev", "prod")
     pub profile: String,
     /// OpenFGA gRPC client
-    pub fga_client: OpenFgaServiceClient<Channel>,
+    pub fga_client: FgaGrpcClient,
     /// OpenFGA HTTP client configuration
     pub fga_http_config: Configuration,
+    /// Retries of the OpenFGA HTTP calls (`OPENFGA_HTTP_RETRY_*`)
+    pub fga_http_retry: HttpRetryConfig,
     /// OpenFGA configuration
     pub fga_config: OpenFgaConfig,
     /// Dex OIDC Apps
     pub dex: Vec<DexConfig>,
+    /// Auth0 application, `None` when `AUTH0_*` isn't configured
+    pub auth0: Option<Auth0Config>,
+    /// Always render error responses as RFC 7807 problem+json (`PROBLEM_JSON`)
+    pub problem_json: bool,
+    /// Audit trail of OpenFGA operations (`OPENFGA_AUDIT_*`)
+    pub fga_audit: AuditConfig,
+    /// Header carrying the request ID (`REQUEST_ID_HEADER`, default `x-request-id`)
+    pub request_id_header: HeaderName,
+    /// Store IDs resolved from `name:<store name>` path segments
+    pub store_names: Arc<StoreNameCache>,
 }
 
 impl Ctx {
@@ -52,19 +263,40 @@ impl Ctx {
         let profile = env::var("PROFILE").unwrap_or_else(|_| "dev".to_string());
         tracing::info!("Starting application with profile: {}", profile);
 
+        crate::auth::db_ops::set_id_config(crate::auth::db_ops::IdConfig::from_env()?);
+
         // Create database connection pool
         let db = pg_pool().await?;
+        crate::migrations::run_migrations(&db).await?;
 
-        // Initialize OpenFGA gRPC client
+        // Initialize OpenFGA gRPC client and wait until it is responsive
         let fga_client = init_fga_client().await?;
+        warm_up(&fga_client).await?;
 
         // Initialize OpenFGA HTTP client configuration
         let fga_http_config = init_fga_http_config();
+        let fga_http_retry = HttpRetryConfig::from_vars(env::vars())?;
+        tracing::info!("OpenFGA HTTP retry policy: {:?}", fga_http_retry);
 
         // Get OpenFGA configuration
-        let fga_config = get_fga_config();
+        let fga_config = get_fga_config()?;
 
         let dex = get_dex_config()?;
+        let auth0 = get_auth0_config()?;
+
+        let fga_audit = AuditConfig::from_vars(env::vars())?;
+        if fga_audit.enabled {
+            tracing::info!("Auditing OpenFGA operations: {:?}", fga_audit);
+        }
+
+        let request_id_header = match env::var("REQUEST_ID_HEADER") {
+            Ok(name) => HeaderName::try_from(name)?,
+            Err(_) => HeaderName::from_static(crate::request_id::DEFAULT_REQUEST_ID_HEADER),
+        };
+
+        let problem_json = env::var("PROBLEM_JSON")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false);
 
         // Log OpenFGA configuration
         if !fga_config.store_id.is_empty() {
@@ -76,10 +308,21 @@ impl Ctx {
             profile,
             fga_client,
             fga_http_config,
+            fga_http_retry,
             fga_config,
             dex,
+            auth0,
+            problem_json,
+            fga_audit,
+            request_id_header,
+            store_names: Arc::new(StoreNameCache::new(STORE_NAME_TTL)),
         })
     }
+
+    /// Get the Auth0 application, if configured
+    pub fn auth0_config(&self) -> Option<&Auth0Config> {
+        self.auth0.as_ref()
+    }
 }
 
 async fn pg_pool() -> Result<PgPool, Box<dyn std::error::Error>> {
@@ -101,19 +344,100 @@ async fn pg_pool() -> Result<PgPool, Box<dyn std::error::Error>> {
 }
 
 /// Initialize the OpenFGA gRPC client
-async fn init_fga_client() -> Result<OpenFgaServiceClient<Channel>, Box<dyn std::error::Error>> {
+async fn init_fga_client() -> Result<FgaGrpcClient, Box<dyn std::error::Error>> {
     // Get OpenFGA client URL from environment, default to localhost
     let fga_url =
         env::var("OPENFGA_CLIENT_URL").unwrap_or_else(|_| "http://localhost:8081".to_string());
     tracing::info!("Connecting to OpenFGA gRPC at {}", fga_url);
 
-    // Create OpenFGA client without authentication
-    let client = OpenFgaServiceClient::connect(fga_url).await?;
+    // Create OpenFGA client without authentication. The channel connects lazily so
+    // that the warm-up below can retry while OpenFGA is still starting, and sends
+    // keepalive pings so idle connections survive proxies.
+    let channel = Http2Settings::default()
+        .apply(Channel::from_shared(fga_url)?)
+        .connect_lazy();
+    let client = OpenFgaServiceClient::with_interceptor(
+        ErrorCodeService::new(channel),
+        RequestIdInterceptor,
+    );
     tracing::info!("OpenFGA gRPC client initialized successfully");
 
     Ok(client)
 }
 
+/// Wait until OpenFGA answers a trivial request, retrying with backoff
+///
+/// The time budget is read from `OPENFGA_WARMUP_TIMEOUT_SECS` (default: 30 seconds).
+async fn warm_up(client: &FgaGrpcClient) -> Result<(), Box<dyn std::error::Error>> {
+    let budget = env::var("OPENFGA_WARMUP_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(DEFAULT_FGA_WARMUP_TIMEOUT_SECS));
+    tracing::info!("Waiting up to {:?} for OpenFGA to become ready", budget);
+
+    retry_with_backoff(
+        budget,
+        FGA_WARMUP_INITIAL_DELAY,
+        FGA_WARMUP_MAX_DELAY,
+        || {
+            let mut client = client.clone();
+            async move {
+                client
+                    .list_stores(ListStoresRequest {
+                        page_size: Some(1),
+                        ..Default::default()
+                    })
+                    .await
+                    .map(|_| ())
+                    .map_err(Box::<dyn std::error::Error + Send + Sync>::from)
+            }
+        },
+    )
+    .await
+    .map_err(|e| format!("OpenFGA did not become ready within {:?}: {}", budget, e))?;
+
+    tracing::info!("OpenFGA is ready");
+    Ok(())
+}
+
+/// Run `op` until it succeeds, doubling the delay between attempts up to `max_delay`
+///
+/// Returns the last error once the next retry would exceed `budget`. An attempt still
+/// running when the budget runs out fails with [`Elapsed`].
+pub async fn retry_with_backoff<F, Fut, T, E>(
+    budget: Duration,
+    initial_delay: Duration,
+    max_delay: Duration,
+    mut op: F,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: std::fmt::Display + From<Elapsed>,
+{
+    let deadline = Instant::now() + budget;
+    let mut delay = initial_delay;
+    let mut attempt = 1;
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match tokio::time::timeout(remaining, op()).await? {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                if Instant::now() + delay > deadline {
+                    return Err(e);
+                }
+
+                tracing::warn!("Attempt {} failed: {}. Retrying in {:?}", attempt, e, delay);
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(max_delay);
+                attempt += 1;
+            }
+        }
+    }
+}
+
 /// Initialize the OpenFGA HTTP client configuration
 fn init_fga_http_config() -> Configuration {
     // Get OpenFGA HTTP URL from environment, default to localhost:8080
@@ -127,7 +451,10 @@ fn init_fga_http_config() -> Configuration {
 
     // Configure authentication if provided
     if let Ok(api_token) = env::var("OPENFGA_API_TOKEN") {
-        tracing::info!("Using OpenFGA API token authentication");
+        tracing::info!(
+            "Using OpenFGA API token authentication ({})",
+            mask_secret(&api_token)
+        );
         config.bearer_access_token = Some(api_token);
     } else if let Ok(api_key) = env::var("OPENFGA_API_KEY") {
         tracing::info!("Using OpenFGA API key authentication");
@@ -149,7 +476,7 @@ fn init_fga_http_config() -> Configuration {
 }
 
 /// Get OpenFGA configuration from environment variables
-fn get_fga_config() -> OpenFgaConfig {
+fn get_fga_config() -> anyhow::Result<OpenFgaConfig> {
     // Get OpenFGA store ID from environment, default to empty string which will need to be set later
     let store_id = env::var("OPENFGA_STORE_ID").unwrap_or_else(|_| {
         tracing::warn!("OPENFGA_STORE_ID not set, using empty string");
@@ -168,10 +495,68 @@ fn get_fga_config() -> OpenFgaConfig {
         }
     };
 
-    OpenFgaConfig {
-        store_id,
-        authorization_model_id,
+    let max_body_size = env::var("OPENFGA_MAX_BODY_SIZE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_FGA_MAX_BODY_SIZE);
+
+    let default_page_size = env::var("OPENFGA_DEFAULT_PAGE_SIZE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_FGA_PAGE_SIZE);
+    let max_page_size = env::var("OPENFGA_MAX_PAGE_SIZE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_FGA_MAX_PAGE_SIZE);
+
+    let consistency = ConsistencyDefaults::from_vars(env::vars())?;
+    tracing::info!("Using OpenFGA consistency defaults: {:?}", consistency);
+
+    let contextual_tuple_headers = match env::var("OPENFGA_CONTEXTUAL_TUPLE_HEADERS") {
+        Ok(mappings) => serde_json::from_str(&mappings)
+            .map_err(|e| anyhow::anyhow!("invalid OPENFGA_CONTEXTUAL_TUPLE_HEADERS: {}", e))?,
+        Err(_) => Vec::new(),
+    };
+    let contextual_tuple_trusted_proxies =
+        match env::var("OPENFGA_CONTEXTUAL_TUPLE_TRUSTED_PROXIES") {
+            Ok(proxies) => parse_ip_list(&proxies).map_err(|e| {
+                anyhow::anyhow!("invalid OPENFGA_CONTEXTUAL_TUPLE_TRUSTED_PROXIES: {}", e)
+            })?,
+            Err(_) => Vec::new(),
+        };
+    if !contextual_tuple_headers.is_empty() && contextual_tuple_trusted_proxies.is_empty() {
+        tracing::warn!(
+            "OPENFGA_CONTEXTUAL_TUPLE_HEADERS is set without OPENFGA_CONTEXTUAL_TUPLE_TRUSTED_PROXIES; no headers will be mapped"
+        );
+    }
+
+    let use_created_models = env::var("OPENFGA_USE_CREATED_MODELS")
+        .map(|v| v == "true" || v == "1")
+        .unwrap_or(false);
+    if use_created_models {
+        tracing::info!("Models created in store {} will be used at once", store_id);
     }
+
+    Ok(OpenFgaConfig {
+        store_id,
+        authorization_model_id: CurrentModelId::new(authorization_model_id),
+        use_created_models,
+        max_body_size,
+        default_page_size,
+        max_page_size,
+        consistency,
+        contextual_tuple_headers,
+        contextual_tuple_trusted_proxies,
+    })
+}
+
+/// Comma-separated IP addresses; blank entries are skipped
+fn parse_ip_list(list: &str) -> Result<Vec<IpAddr>, std::net::AddrParseError> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|ip| !ip.is_empty())
+        .map(str::parse)
+        .collect()
 }
 
 pub fn get_dex_config() -> anyhow::Result<Vec<DexConfig>> {
@@ -181,3 +566,200 @@ pub fn get_dex_config() -> anyhow::Result<Vec<DexConfig>> {
         serde_json::from_str(std::fs::read_to_string(config_path)?.as_str())?;
     Ok(config)
 }
+
+/// Load the Auth0 application from the environment
+fn get_auth0_config() -> anyhow::Result<Option<Auth0Config>> {
+    let config = Auth0Config::from_vars(env::vars())?;
+    match &config {
+        Some(config) => tracing::info!("Using Auth0 configuration: {:?}", config),
+        None => tracing::info!("AUTH0_* not set, Auth0 login is disabled"),
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+impl Ctx {
+    /// Build a context whose database and OpenFGA connections are never opened
+    pub(crate) fn for_test() -> Self {
+        let db = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/test")
+            .expect("valid database url");
+        let channel = Channel::from_static("http://localhost:8081").connect_lazy();
+
+        Self {
+            db,
+            profile: "test".to_string(),
+            fga_client: OpenFgaServiceClient::with_interceptor(
+                ErrorCodeService::new(channel),
+                RequestIdInterceptor,
+            ),
+            fga_http_config: Configuration::default(),
+            fga_http_retry: HttpRetryConfig::default(),
+            fga_config: OpenFgaConfig {
+                store_id: "store".to_string(),
+                authorization_model_id: CurrentModelId::new("model"),
+                use_created_models: false,
+                max_body_size: DEFAULT_FGA_MAX_BODY_SIZE,
+                default_page_size: DEFAULT_FGA_PAGE_SIZE,
+                max_page_size: DEFAULT_FGA_MAX_PAGE_SIZE,
+                consistency: ConsistencyDefaults::default(),
+                contextual_tuple_headers: Vec::new(),
+                contextual_tuple_trusted_proxies: Vec::new(),
+            },
+            dex: Vec::new(),
+            auth0: None,
+            problem_json: false,
+            fga_audit: AuditConfig::default(),
+            request_id_header: HeaderName::from_static(
+                crate::request_id::DEFAULT_REQUEST_ID_HEADER,
+            ),
+            store_names: Arc::new(StoreNameCache::new(STORE_NAME_TTL)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+    #[tokio::test]
+    async fn test_retry_with_backoff_succeeds_after_failures() {
+        let attempts = AtomicU32::new(0);
+
+        let result = retry_with_backoff(
+            Duration::from_secs(1),
+            Duration::from_millis(1),
+            Duration::from_millis(2),
+            || {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+                async move {
+                    if attempt <= 2 {
+                        Err(BoxError::from(format!("attempt {} failed", attempt)))
+                    } else {
+                        Ok(attempt)
+                    }
+                }
+            },
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_backoff_gives_up_after_budget() {
+        let result: Result<(), BoxError> = retry_with_backoff(
+            Duration::from_millis(20),
+            Duration::from_millis(5),
+            Duration::from_millis(5),
+            || async { Err("unavailable".into()) },
+        )
+        .await;
+
+        assert_eq!(result.unwrap_err().to_string(), "unavailable");
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_backoff_times_out_hanging_attempt() {
+        let started = Instant::now();
+        let result: Result<(), BoxError> = retry_with_backoff(
+            Duration::from_millis(20),
+            Duration::from_millis(5),
+            Duration::from_millis(5),
+            std::future::pending,
+        )
+        .await;
+
+        assert!(result.unwrap_err().is::<Elapsed>());
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_page_size_is_clamped() {
+        let config = OpenFgaConfig {
+            store_id: "store".to_string(),
+            authorization_model_id: CurrentModelId::new("model"),
+            use_created_models: false,
+            max_body_size: DEFAULT_FGA_MAX_BODY_SIZE,
+            default_page_size: 25,
+            max_page_size: 100,
+            consistency: ConsistencyDefaults::default(),
+            contextual_tuple_headers: Vec::new(),
+            contextual_tuple_trusted_proxies: Vec::new(),
+        };
+
+        assert_eq!(config.page_size(None), 25);
+        assert_eq!(config.page_size(Some(0)), 1);
+        assert_eq!(config.page_size(Some(-10)), 1);
+        assert_eq!(config.page_size(Some(1_000)), 100);
+        assert_eq!(config.page_size(Some(40)), 40);
+        assert_eq!(config.page_size(Some(100)), 100);
+    }
+
+    #[test]
+    fn test_auth0_config_from_env_vars() {
+        let vars = [
+            ("AUTH0_DOMAIN", "tenant.eu.auth0.com"),
+            ("AUTH0_CLIENT_ID", "client-id"),
+            ("AUTH0_CLIENT_SECRET", "0123456789abcdefghij"),
+            (
+                "AUTH0_REDIRECT_URL",
+                "http://127.0.0.1:5001/auth/auth0/callback",
+            ),
+            ("AUTH0_ORGANIZATION", "org_acme"),
+            ("DATABASE_URL", "postgres://localhost/app"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+
+        let config = Auth0Config::from_vars(vars).unwrap().unwrap();
+        assert_eq!(config.domain, "tenant.eu.auth0.com");
+        assert_eq!(config.client_secret, "0123456789abcdefghij");
+        assert_eq!(config.organization.as_deref(), Some("org_acme"));
+
+        let debug = format!("{:?}", config);
+        assert!(debug.contains("0123****"));
+        assert!(!debug.contains("0123456789abcdefghij"));
+
+        assert!(Auth0Config::from_vars(Vec::new()).unwrap().is_none());
+        let partial = [(
+            "AUTH0_DOMAIN".to_string(),
+            "tenant.eu.auth0.com".to_string(),
+        )];
+        assert!(Auth0Config::from_vars(partial).is_err());
+        assert_eq!(mask_secret("short"), "****");
+    }
+
+    #[test]
+    fn test_consistency_defaults_from_env_vars() {
+        let defaults = ConsistencyDefaults::from_vars(Vec::new()).unwrap();
+        assert_eq!(defaults, ConsistencyDefaults::default());
+        assert_eq!(defaults.check, Consistency::Unspecified);
+        assert_eq!(defaults.list_objects, Consistency::Unspecified);
+
+        let vars = [
+            ("OPENFGA_CONSISTENCY_LIST_OBJECTS", "higher_consistency"),
+            ("OPENFGA_CONSISTENCY_CHECK", "minimize_latency"),
+            ("OPENFGA_STORE_ID", "store"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        let defaults = ConsistencyDefaults::from_vars(vars).unwrap();
+        assert_eq!(defaults.list_objects, Consistency::HigherConsistency);
+        assert_eq!(defaults.check, Consistency::MinimizeLatency);
+        assert_eq!(defaults.list_users, Consistency::Unspecified);
+
+        let typo = [(
+            "OPENFGA_CONSISTENCY_CHEK".to_string(),
+            "minimize_latency".to_string(),
+        )];
+        assert!(ConsistencyDefaults::from_vars(typo).is_err());
+        let invalid = [(
+            "OPENFGA_CONSISTENCY_READ".to_string(),
+            "eventual".to_string(),
+        )];
+        assert!(ConsistencyDefaults::from_vars(invalid).is_err());
+    }
+}
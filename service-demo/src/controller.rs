@@ -1,5 +1,6 @@
 use crate::auth_m::AuthUser;
 use crate::context::Ctx;
+use crate::fga_apis::errors::grpc_error_status;
 use axum::{
     Extension,
     extract::{Json, Path, Query, State},
@@ -116,7 +117,7 @@ async fn check_permission(
             relation: tuple_key.relation,
             object: tuple_key.object,
         }),
-        authorization_model_id: ctx.fga_config.authorization_model_id.clone(),
+        authorization_model_id: ctx.fga_config.authorization_model_id.get(),
         ..Default::default()
     });
 
@@ -386,7 +387,7 @@ pub async fn list_objects(
     // Create ListObjects request
     let request = Request::new(ListObjectsRequest {
         store_id: ctx.fga_config.store_id.clone(),
-        authorization_model_id: ctx.fga_config.authorization_model_id.clone(),
+        authorization_model_id: ctx.fga_config.authorization_model_id.get(),
         r#type: object_type.clone(),
         consistency: 8,
         relation: relation.clone(),
@@ -418,7 +419,7 @@ pub async fn list_objects(
         Err(e) => {
             tracing::error!("Error listing objects: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                grpc_error_status(&e),
                 Json(json!({
                     "error": "Failed to list objects",
                     "message": e.to_string()
@@ -449,7 +450,7 @@ pub async fn get_shared_resources(
         for relation in &relations {
             let request = Request::new(ListObjectsRequest {
                 store_id: ctx.fga_config.store_id.clone(),
-                authorization_model_id: ctx.fga_config.authorization_model_id.clone(),
+                authorization_model_id: ctx.fga_config.authorization_model_id.get(),
                 r#type: object_type.to_string(),
                 consistency: 8,
                 relation: relation.to_string(),
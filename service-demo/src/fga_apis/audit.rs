@@ -0,0 +1,505 @@
+//! Audit trail of OpenFGA checks, writes and deletes, recorded to the `fga_audit` table.
+//!
+//! [`Audited`] wraps an [`FgaBackend`] and records the operations sampled by the
+//! [`AuditConfig`] of the context, with the user of the session set by
+//! [`audit_user_middleware`]. Reads and queries other than checks aren't recorded.
+
+use anyhow::Context;
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::{PgPool, Postgres, QueryBuilder};
+use std::future::Future;
+
+use crate::context::Ctx;
+use crate::fga_apis::backend::{BackendError, FgaBackend, StoreRef};
+use crate::fga_apis::dto::{Consistency, ReadFilter, StoredTuple, Tuple, UserFilter};
+use crate::fga_apis::page::Page;
+use crate::fga_apis::store_access::session_claims;
+
+/// SQL schema for fga_audit table
+pub const FGA_AUDIT_TABLE_SCHEMA: &str = include_str!("../../migrations/0005_fga_audit.sql");
+
+tokio::task_local! {
+    static AUDIT_USER: Option<String>;
+}
+
+/// Run `future` with `user_id` recorded as the user of the audited operations
+pub async fn with_audit_user<F: Future>(user_id: Option<String>, future: F) -> F::Output {
+    AUDIT_USER.scope(user_id, future).await
+}
+
+/// User of the current task, `None` outside [`with_audit_user`]
+pub fn current_audit_user() -> Option<String> {
+    AUDIT_USER.try_with(Clone::clone).ok().flatten()
+}
+
+/// Record the user of the request's session as the user of the operations made
+/// while handling it. Does nothing while auditing is disabled.
+pub async fn audit_user_middleware(
+    State(ctx): State<Ctx>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !ctx.fga_audit.enabled {
+        return next.run(request).await;
+    }
+
+    let user_id = match session_claims(&ctx, request.headers()).await {
+        Ok(claims) => claims.map(|c| c.user_id),
+        Err(e) => {
+            tracing::debug!("No session for the audit trail: {:?}", e);
+            None
+        }
+    };
+    with_audit_user(user_id, next.run(request)).await
+}
+
+/// Operation recorded in the audit trail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditOperation {
+    Check,
+    Write,
+    Delete,
+}
+
+impl AuditOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditOperation::Check => "check",
+            AuditOperation::Write => "write",
+            AuditOperation::Delete => "delete",
+        }
+    }
+}
+
+/// Which operations are recorded.
+///
+/// Read from the `OPENFGA_AUDIT_*` environment variables. Sample rates are fractions
+/// from `0.0` (never) to `1.0` (always), e.g. `OPENFGA_AUDIT_CHECK_SAMPLE_RATE=0.01`
+/// records one check in a hundred.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditConfig {
+    /// `OPENFGA_AUDIT_ENABLED`, off by default
+    pub enabled: bool,
+    /// `OPENFGA_AUDIT_CHECK_SAMPLE_RATE`, defaults to `1.0`
+    pub check_sample_rate: f64,
+    /// `OPENFGA_AUDIT_WRITE_SAMPLE_RATE`, defaults to `1.0`
+    pub write_sample_rate: f64,
+    /// `OPENFGA_AUDIT_DELETE_SAMPLE_RATE`, defaults to `1.0`
+    pub delete_sample_rate: f64,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            check_sample_rate: 1.0,
+            write_sample_rate: 1.0,
+            delete_sample_rate: 1.0,
+        }
+    }
+}
+
+impl AuditConfig {
+    /// Build from `OPENFGA_AUDIT_*` variables; unset variables keep their default
+    pub fn from_vars(vars: impl IntoIterator<Item = (String, String)>) -> anyhow::Result<Self> {
+        let mut config = Self::default();
+        for (key, value) in vars {
+            let rate = match key.as_str() {
+                "OPENFGA_AUDIT_ENABLED" => {
+                    config.enabled = value == "true" || value == "1";
+                    continue;
+                }
+                "OPENFGA_AUDIT_CHECK_SAMPLE_RATE" => &mut config.check_sample_rate,
+                "OPENFGA_AUDIT_WRITE_SAMPLE_RATE" => &mut config.write_sample_rate,
+                "OPENFGA_AUDIT_DELETE_SAMPLE_RATE" => &mut config.delete_sample_rate,
+                _ => continue,
+            };
+            *rate = value
+                .parse()
+                .ok()
+                .filter(|rate: &f64| (0.0..=1.0).contains(rate))
+                .ok_or_else(|| {
+                    anyhow::anyhow!("{} must be between 0.0 and 1.0, got {:?}", key, value)
+                })?;
+        }
+        Ok(config)
+    }
+
+    pub fn sample_rate(&self, operation: AuditOperation) -> f64 {
+        match operation {
+            AuditOperation::Check => self.check_sample_rate,
+            AuditOperation::Write => self.write_sample_rate,
+            AuditOperation::Delete => self.delete_sample_rate,
+        }
+    }
+
+    /// Whether to record an operation, given a `roll` drawn uniformly from `0.0..1.0`
+    pub fn should_record(&self, operation: AuditOperation, roll: f64) -> bool {
+        self.enabled && roll < self.sample_rate(operation)
+    }
+
+    /// Randomly decide whether to record an operation, at its sample rate
+    pub fn sample(&self, operation: AuditOperation) -> bool {
+        self.enabled && self.should_record(operation, rand::random())
+    }
+}
+
+/// Row of the `fga_audit` table
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    /// User of the session the operation was made in, `None` without a session
+    pub user_id: Option<String>,
+    pub store_id: String,
+    pub operation: AuditOperation,
+    pub tuple: Tuple,
+    /// Result of a check; `None` for writes and deletes
+    pub decision: Option<bool>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Destination of audit entries
+pub trait AuditSink: Sized + Send + Sync + 'static {
+    /// Get the sink from the application context
+    fn from_ctx(ctx: &Ctx) -> Self;
+
+    fn record(&self, entries: Vec<AuditEntry>) -> impl Future<Output = anyhow::Result<()>> + Send;
+}
+
+/// [`AuditSink`] inserting into the `fga_audit` table
+pub struct PgAuditSink {
+    db: PgPool,
+}
+
+impl PgAuditSink {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+}
+
+impl AuditSink for PgAuditSink {
+    fn from_ctx(ctx: &Ctx) -> Self {
+        Self::new(ctx.db.clone())
+    }
+
+    async fn record(&self, entries: Vec<AuditEntry>) -> anyhow::Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        insert_audit_query(&entries)
+            .build()
+            .execute(&self.db)
+            .await
+            .context("Failed to insert audit entries")?;
+        Ok(())
+    }
+}
+
+/// Insert of all `entries` in one statement
+fn insert_audit_query(entries: &[AuditEntry]) -> QueryBuilder<'_, Postgres> {
+    let mut query = QueryBuilder::new(
+        "INSERT INTO fga_audit (user_id, store_id, operation, tuple_user, tuple_relation, \
+         tuple_object, decision, created_at) ",
+    );
+    query.push_values(entries, |mut row, entry| {
+        row.push_bind(&entry.user_id)
+            .push_bind(&entry.store_id)
+            .push_bind(entry.operation.as_str())
+            .push_bind(&entry.tuple.user)
+            .push_bind(&entry.tuple.relation)
+            .push_bind(&entry.tuple.object)
+            .push_bind(entry.decision)
+            .push_bind(entry.created_at);
+    });
+    query
+}
+
+/// [`FgaBackend`] recording the sampled checks, writes and deletes of `B` to `S`.
+///
+/// Only successful operations are recorded. A failure to record is logged and
+/// doesn't fail the operation.
+pub struct Audited<B, S = PgAuditSink> {
+    inner: B,
+    sink: S,
+    config: AuditConfig,
+}
+
+impl<B, S> Audited<B, S> {
+    pub fn new(inner: B, sink: S, config: AuditConfig) -> Self {
+        Self {
+            inner,
+            sink,
+            config,
+        }
+    }
+}
+
+impl<B: FgaBackend, S: AuditSink> Audited<B, S> {
+    async fn record(
+        &self,
+        store: &StoreRef,
+        operation: AuditOperation,
+        tuples: Vec<Tuple>,
+        decision: Option<bool>,
+    ) {
+        let user_id = current_audit_user();
+        let created_at = Utc::now();
+        let entries = tuples
+            .into_iter()
+            .map(|tuple| AuditEntry {
+                user_id: user_id.clone(),
+                store_id: store.store_id.clone(),
+                operation,
+                tuple,
+                decision,
+                created_at,
+            })
+            .collect();
+
+        if let Err(e) = self.sink.record(entries).await {
+            tracing::warn!("Failed to record {} audit: {:?}", operation.as_str(), e);
+        }
+    }
+}
+
+impl<B: FgaBackend, S: AuditSink> FgaBackend for Audited<B, S> {
+    fn from_ctx(ctx: &Ctx) -> Self {
+        Self::new(B::from_ctx(ctx), S::from_ctx(ctx), ctx.fga_audit.clone())
+    }
+
+    async fn check(
+        &self,
+        store: &StoreRef,
+        tuple: Tuple,
+        context: Option<Value>,
+        consistency: Consistency,
+    ) -> Result<bool, BackendError> {
+        let audited = self
+            .config
+            .sample(AuditOperation::Check)
+            .then(|| tuple.clone());
+        let allowed = self.inner.check(store, tuple, context, consistency).await?;
+
+        if let Some(tuple) = audited {
+            self.record(store, AuditOperation::Check, vec![tuple], Some(allowed))
+                .await;
+        }
+        Ok(allowed)
+    }
+
+    async fn batch_check(
+        &self,
+        store: &StoreRef,
+        tuples: Vec<Tuple>,
+        consistency: Consistency,
+    ) -> Result<Vec<bool>, BackendError> {
+        // Each check of the batch is sampled on its own
+        let audited: Vec<Option<Tuple>> = tuples
+            .iter()
+            .map(|tuple| {
+                self.config
+                    .sample(AuditOperation::Check)
+                    .then(|| tuple.clone())
+            })
+            .collect();
+        let allowed = self.inner.batch_check(store, tuples, consistency).await?;
+
+        for (tuple, allowed) in audited.into_iter().zip(&allowed) {
+            if let Some(tuple) = tuple {
+                self.record(store, AuditOperation::Check, vec![tuple], Some(*allowed))
+                    .await;
+            }
+        }
+        Ok(allowed)
+    }
+
+    async fn write(
+        &self,
+        store: &StoreRef,
+        writes: Vec<Tuple>,
+        deletes: Vec<Tuple>,
+    ) -> Result<(), BackendError> {
+        let audited_writes = (!writes.is_empty() && self.config.sample(AuditOperation::Write))
+            .then(|| writes.clone());
+        let audited_deletes = (!deletes.is_empty() && self.config.sample(AuditOperation::Delete))
+            .then(|| deletes.clone());
+        self.inner.write(store, writes, deletes).await?;
+
+        if let Some(writes) = audited_writes {
+            self.record(store, AuditOperation::Write, writes, None)
+                .await;
+        }
+        if let Some(deletes) = audited_deletes {
+            self.record(store, AuditOperation::Delete, deletes, None)
+                .await;
+        }
+        Ok(())
+    }
+
+    async fn read(
+        &self,
+        store_id: &str,
+        filter: ReadFilter,
+        page_size: i32,
+        continuation_token: Option<String>,
+        consistency: Consistency,
+    ) -> Result<Page<StoredTuple>, BackendError> {
+        self.inner
+            .read(store_id, filter, page_size, continuation_token, consistency)
+            .await
+    }
+
+    async fn expand(
+        &self,
+        store: &StoreRef,
+        object: String,
+        relation: String,
+        contextual_tuples: Vec<Tuple>,
+        consistency: Consistency,
+    ) -> Result<Value, BackendError> {
+        self.inner
+            .expand(store, object, relation, contextual_tuples, consistency)
+            .await
+    }
+
+    async fn list_objects(
+        &self,
+        store: &StoreRef,
+        object_type: String,
+        relation: String,
+        user: String,
+        context: Option<Value>,
+        consistency: Consistency,
+    ) -> Result<Vec<String>, BackendError> {
+        self.inner
+            .list_objects(store, object_type, relation, user, context, consistency)
+            .await
+    }
+
+    async fn list_users(
+        &self,
+        store: &StoreRef,
+        object: String,
+        relation: String,
+        user_filters: Vec<UserFilter>,
+        context: Option<Value>,
+        consistency: Consistency,
+    ) -> Result<Vec<String>, BackendError> {
+        self.inner
+            .list_users(store, object, relation, user_filters, context, consistency)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fga_apis::mock_backend::MockBackend;
+    use std::sync::{Arc, Mutex};
+
+    /// Sink keeping the recorded entries
+    #[derive(Clone, Default)]
+    struct MemorySink(Arc<Mutex<Vec<AuditEntry>>>);
+
+    impl AuditSink for MemorySink {
+        fn from_ctx(_ctx: &Ctx) -> Self {
+            Self::default()
+        }
+
+        async fn record(&self, entries: Vec<AuditEntry>) -> anyhow::Result<()> {
+            self.0.lock().unwrap().extend(entries);
+            Ok(())
+        }
+    }
+
+    fn tuple(user: &str, relation: &str, object: &str) -> Tuple {
+        Tuple {
+            user: user.to_string(),
+            relation: relation.to_string(),
+            object: object.to_string(),
+            condition: None,
+        }
+    }
+
+    #[test]
+    fn test_sampling_decision() {
+        let vars = [
+            ("OPENFGA_AUDIT_ENABLED", "true"),
+            ("OPENFGA_AUDIT_CHECK_SAMPLE_RATE", "0.01"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        let config = AuditConfig::from_vars(vars).unwrap();
+
+        assert!(config.should_record(AuditOperation::Check, 0.005));
+        assert!(!config.should_record(AuditOperation::Check, 0.5));
+        assert!(config.should_record(AuditOperation::Write, 0.999));
+        assert!(config.should_record(AuditOperation::Delete, 0.0));
+
+        let disabled = AuditConfig::default();
+        assert!(!disabled.should_record(AuditOperation::Write, 0.0));
+        assert!(!disabled.sample(AuditOperation::Write));
+
+        let never = AuditConfig {
+            enabled: true,
+            write_sample_rate: 0.0,
+            ..AuditConfig::default()
+        };
+        assert!(!never.should_record(AuditOperation::Write, 0.0));
+
+        let invalid =
+            [("OPENFGA_AUDIT_WRITE_SAMPLE_RATE", "2")].map(|(k, v)| (k.to_string(), v.to_string()));
+        assert!(AuditConfig::from_vars(invalid).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_write_is_recorded_with_session_user() {
+        let sink = MemorySink::default();
+        let config = AuditConfig {
+            enabled: true,
+            check_sample_rate: 0.0,
+            ..AuditConfig::default()
+        };
+        let backend = Audited::new(MockBackend, sink.clone(), config);
+        let store = StoreRef {
+            store_id: "01HSTORE".to_string(),
+            authorization_model_id: None,
+        };
+        let anne = tuple("user:anne", "viewer", "document:readme");
+
+        with_audit_user(Some("usr_anne".to_string()), async {
+            backend
+                .write(&store, vec![anne.clone()], Vec::new())
+                .await
+                .unwrap();
+            // Checks aren't sampled
+            assert!(
+                backend
+                    .check(&store, anne.clone(), None, Consistency::Unspecified)
+                    .await
+                    .unwrap()
+            );
+        })
+        .await;
+
+        let entries = sink.0.lock().unwrap().clone();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].user_id.as_deref(), Some("usr_anne"));
+        assert_eq!(entries[0].store_id, "01HSTORE");
+        assert_eq!(entries[0].operation, AuditOperation::Write);
+        assert_eq!(entries[0].tuple, anne);
+        assert_eq!(entries[0].decision, None);
+
+        let query = insert_audit_query(&entries);
+        assert_eq!(
+            query.sql(),
+            "INSERT INTO fga_audit (user_id, store_id, operation, tuple_user, tuple_relation, \
+             tuple_object, decision, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"
+        );
+    }
+}
@@ -0,0 +1,955 @@
+use axum::{Json, http::StatusCode};
+use chrono::{DateTime, Utc};
+use openfga_grpc_client::{OpenFGAClient, user::User as GrpcUser};
+use openfga_http_client::apis::configuration::Configuration;
+use openfga_http_client::apis::{relationship_queries_api, relationship_tuples_api};
+use openfga_http_client::models as http;
+use serde_json::{Value, json};
+use std::future::Future;
+
+use crate::context::{Ctx, FgaGrpcClient};
+use crate::fga_apis::contextual_tuples::current_contextual_tuples;
+use crate::fga_apis::dto::{Consistency, ReadFilter, StoredTuple, Tuple, UserFilter};
+use crate::fga_apis::errors::{grpc_error_status, http_error_status};
+use crate::fga_apis::http::retry::{HttpCall, HttpRetryConfig};
+use crate::fga_apis::page::Page;
+use crate::time_utils::timestamp_to_chrono;
+
+/// Larger batches are sent in chunks of this many checks
+pub use openfga_grpc_client::MAX_BATCH_CHECK_ITEMS;
+
+/// Store and model a request runs against
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreRef {
+    pub store_id: String,
+    /// Latest model of the store when `None`
+    pub authorization_model_id: Option<String>,
+}
+
+/// Error of a backend call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackendError {
+    /// The request can't be built, e.g. a `context` that isn't a JSON object
+    InvalidRequest(String),
+    /// OpenFGA has no such store or authorization model
+    NotFound(String),
+    /// OpenFGA rejected the service's credentials
+    Unauthenticated(String),
+    /// OpenFGA failed or rejected the request
+    Upstream(String),
+}
+
+impl BackendError {
+    /// Handler error for this backend error
+    pub fn into_response(self) -> (StatusCode, Json<Value>) {
+        let (status, message) = match self {
+            BackendError::InvalidRequest(message) => (StatusCode::BAD_REQUEST, message),
+            BackendError::NotFound(message) => (StatusCode::NOT_FOUND, message),
+            BackendError::Unauthenticated(message) => (StatusCode::UNAUTHORIZED, message),
+            BackendError::Upstream(message) => (StatusCode::INTERNAL_SERVER_ERROR, message),
+        };
+        (status, Json(json!({ "message": message })))
+    }
+
+    /// Backend error for an OpenFGA error answered with `status`, see
+    /// [`grpc_error_status`] and [`http_error_status`]
+    fn from_status(status: StatusCode, message: String) -> Self {
+        match status {
+            StatusCode::BAD_REQUEST => BackendError::InvalidRequest(message),
+            StatusCode::NOT_FOUND => BackendError::NotFound(message),
+            StatusCode::UNAUTHORIZED => BackendError::Unauthenticated(message),
+            _ => BackendError::Upstream(message),
+        }
+    }
+}
+
+impl std::fmt::Display for BackendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackendError::InvalidRequest(message)
+            | BackendError::NotFound(message)
+            | BackendError::Unauthenticated(message)
+            | BackendError::Upstream(message) => {
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BackendError {}
+
+/// OpenFGA operations shared by the gRPC and HTTP routes.
+///
+/// Checks and expands carry the contextual tuples of the current task (see
+/// [`with_contextual_tuples`](crate::fga_apis::contextual_tuples::with_contextual_tuples)).
+pub trait FgaBackend: Sized + Send + Sync + 'static {
+    /// Get the backend from the application context
+    fn from_ctx(ctx: &Ctx) -> Self;
+
+    /// Whether `tuple`'s user has the relation to the object; its condition is ignored
+    fn check(
+        &self,
+        store: &StoreRef,
+        tuple: Tuple,
+        context: Option<Value>,
+        consistency: Consistency,
+    ) -> impl Future<Output = Result<bool, BackendError>> + Send;
+
+    /// Whether each tuple's user has the relation to the object, in the order of
+    /// `tuples`. Fails if any check fails.
+    fn batch_check(
+        &self,
+        store: &StoreRef,
+        tuples: Vec<Tuple>,
+        consistency: Consistency,
+    ) -> impl Future<Output = Result<Vec<bool>, BackendError>> + Send;
+
+    /// Write and delete tuples in one transaction; conditions of deletes are ignored
+    fn write(
+        &self,
+        store: &StoreRef,
+        writes: Vec<Tuple>,
+        deletes: Vec<Tuple>,
+    ) -> impl Future<Output = Result<(), BackendError>> + Send;
+
+    /// Read one page of the tuples matching `filter`
+    fn read(
+        &self,
+        store_id: &str,
+        filter: ReadFilter,
+        page_size: i32,
+        continuation_token: Option<String>,
+        consistency: Consistency,
+    ) -> impl Future<Output = Result<Page<StoredTuple>, BackendError>> + Send;
+
+    /// Userset tree of the relation on the object, as JSON, evaluated with
+    /// `contextual_tuples`; their conditions carry the context expand has none of
+    fn expand(
+        &self,
+        store: &StoreRef,
+        object: String,
+        relation: String,
+        contextual_tuples: Vec<Tuple>,
+        consistency: Consistency,
+    ) -> impl Future<Output = Result<Value, BackendError>> + Send;
+
+    /// Objects of `object_type` the user has the relation to
+    fn list_objects(
+        &self,
+        store: &StoreRef,
+        object_type: String,
+        relation: String,
+        user: String,
+        context: Option<Value>,
+        consistency: Consistency,
+    ) -> impl Future<Output = Result<Vec<String>, BackendError>> + Send;
+
+    /// Users with the relation to the object, as `type:id`, `type:id#relation` or `type:*`
+    fn list_users(
+        &self,
+        store: &StoreRef,
+        object: String,
+        relation: String,
+        user_filters: Vec<UserFilter>,
+        context: Option<Value>,
+        consistency: Consistency,
+    ) -> impl Future<Output = Result<Vec<String>, BackendError>> + Send;
+}
+
+// =============================================================================
+// gRPC
+// =============================================================================
+
+/// [`FgaBackend`] over the OpenFGA gRPC API
+#[derive(Clone)]
+pub struct GrpcBackend {
+    client: FgaGrpcClient,
+}
+
+impl GrpcBackend {
+    pub fn new(client: FgaGrpcClient) -> Self {
+        Self { client }
+    }
+
+    /// Build the check request sent by [`FgaBackend::check`]
+    pub fn check_request(
+        store: &StoreRef,
+        tuple: Tuple,
+        context: Option<Value>,
+        consistency: Consistency,
+    ) -> Result<openfga_grpc_client::CheckRequest, BackendError> {
+        let contextual_tuples = current_contextual_tuples();
+        Ok(openfga_grpc_client::CheckRequest {
+            store_id: store.store_id.clone(),
+            tuple_key: Some(tuple.into()),
+            authorization_model_id: store.authorization_model_id.clone().unwrap_or_default(),
+            consistency: openfga_grpc_client::ConsistencyPreference::from(consistency) as i32,
+            context: grpc_context(context)?,
+            trace: false,
+            contextual_tuples: (!contextual_tuples.is_empty()).then_some(
+                openfga_grpc_client::ContextualTupleKeys {
+                    tuple_keys: contextual_tuples,
+                },
+            ),
+        })
+    }
+
+    /// Build the expand request sent by [`FgaBackend::expand`], with `contextual_tuples`
+    /// followed by those of the current task
+    pub fn expand_request(
+        store: &StoreRef,
+        object: String,
+        relation: String,
+        contextual_tuples: Vec<Tuple>,
+        consistency: Consistency,
+    ) -> Result<openfga_grpc_client::ExpandRequest, BackendError> {
+        let mut tuple_keys = contextual_tuples
+            .into_iter()
+            .map(openfga_grpc_client::TupleKey::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(invalid_context)?;
+        tuple_keys.extend(current_contextual_tuples());
+
+        let mut request = OpenFGAClient::create_expand_request(
+            store.store_id.clone(),
+            object,
+            relation,
+            tuple_keys,
+        );
+        request.authorization_model_id = store.authorization_model_id.clone().unwrap_or_default();
+        request.consistency = openfga_grpc_client::ConsistencyPreference::from(consistency) as i32;
+        Ok(request)
+    }
+}
+
+impl FgaBackend for GrpcBackend {
+    fn from_ctx(ctx: &Ctx) -> Self {
+        Self::new(ctx.fga_client.clone())
+    }
+
+    async fn check(
+        &self,
+        store: &StoreRef,
+        tuple: Tuple,
+        context: Option<Value>,
+        consistency: Consistency,
+    ) -> Result<bool, BackendError> {
+        let request = Self::check_request(store, tuple, context, consistency)?;
+        tracing::info!("Checking if user has relation to object: {:?}", request);
+
+        let response = self
+            .client
+            .clone()
+            .check(request)
+            .await
+            .map_err(grpc_error)?;
+        Ok(response.into_inner().allowed)
+    }
+
+    async fn batch_check(
+        &self,
+        store: &StoreRef,
+        tuples: Vec<Tuple>,
+        consistency: Consistency,
+    ) -> Result<Vec<bool>, BackendError> {
+        use openfga_grpc_client::batch_check_single_result::CheckResult;
+
+        let contextual_tuples = current_contextual_tuples();
+        let mut allowed = Vec::with_capacity(tuples.len());
+        for chunk in tuples.chunks(MAX_BATCH_CHECK_ITEMS) {
+            // Correlation IDs are the positions in the chunk
+            let request = openfga_grpc_client::BatchCheckRequest {
+                store_id: store.store_id.clone(),
+                checks: chunk
+                    .iter()
+                    .enumerate()
+                    .map(|(i, tuple)| openfga_grpc_client::BatchCheckItem {
+                        tuple_key: Some(tuple.clone().into()),
+                        contextual_tuples: (!contextual_tuples.is_empty()).then(|| {
+                            openfga_grpc_client::ContextualTupleKeys {
+                                tuple_keys: contextual_tuples.clone(),
+                            }
+                        }),
+                        context: None,
+                        correlation_id: i.to_string(),
+                    })
+                    .collect(),
+                authorization_model_id: store.authorization_model_id.clone().unwrap_or_default(),
+                consistency: openfga_grpc_client::ConsistencyPreference::from(consistency) as i32,
+            };
+
+            let mut results = self
+                .client
+                .clone()
+                .batch_check(request)
+                .await
+                .map_err(grpc_error)?
+                .into_inner()
+                .result;
+            for i in 0..chunk.len() {
+                let result = results.remove(&i.to_string()).and_then(|r| r.check_result);
+                allowed.push(match result {
+                    Some(CheckResult::Allowed(allowed)) => allowed,
+                    Some(CheckResult::Error(e)) => return Err(upstream(e.message)),
+                    None => return Err(upstream(format!("no result for check {}", i))),
+                });
+            }
+        }
+        Ok(allowed)
+    }
+
+    async fn write(
+        &self,
+        store: &StoreRef,
+        writes: Vec<Tuple>,
+        deletes: Vec<Tuple>,
+    ) -> Result<(), BackendError> {
+        let writes = writes
+            .into_iter()
+            .map(openfga_grpc_client::TupleKey::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(invalid_context)?;
+        let deletes: Vec<openfga_grpc_client::TupleKeyWithoutCondition> =
+            deletes.into_iter().map(Into::into).collect();
+
+        let request = openfga_grpc_client::WriteRequest {
+            store_id: store.store_id.clone(),
+            authorization_model_id: store.authorization_model_id.clone().unwrap_or_default(),
+            writes: (!writes.is_empty()).then(|| openfga_grpc_client::WriteRequestWrites {
+                tuple_keys: writes,
+                on_duplicate: "ignore".to_string(),
+            }),
+            deletes: (!deletes.is_empty()).then(|| openfga_grpc_client::WriteRequestDeletes {
+                tuple_keys: deletes,
+                on_missing: "error".to_string(),
+            }),
+        };
+
+        self.client
+            .clone()
+            .write(request)
+            .await
+            .map_err(grpc_error)?;
+        Ok(())
+    }
+
+    async fn read(
+        &self,
+        store_id: &str,
+        filter: ReadFilter,
+        page_size: i32,
+        continuation_token: Option<String>,
+        consistency: Consistency,
+    ) -> Result<Page<StoredTuple>, BackendError> {
+        let request = openfga_grpc_client::ReadRequest {
+            store_id: store_id.to_string(),
+            tuple_key: Some(filter.into()),
+            page_size: Some(page_size),
+            continuation_token: continuation_token.unwrap_or_default(),
+            consistency: openfga_grpc_client::ConsistencyPreference::from(consistency) as i32,
+        };
+
+        let response = self
+            .client
+            .clone()
+            .read(request)
+            .await
+            .map_err(grpc_error)?
+            .into_inner();
+        let tuples = response
+            .tuples
+            .into_iter()
+            .filter_map(|tuple| {
+                Some(StoredTuple {
+                    tuple: tuple.key?.into(),
+                    timestamp: tuple.timestamp.as_ref().map(timestamp_to_chrono),
+                })
+            })
+            .collect();
+
+        Ok(Page::new(tuples, response.continuation_token))
+    }
+
+    async fn expand(
+        &self,
+        store: &StoreRef,
+        object: String,
+        relation: String,
+        contextual_tuples: Vec<Tuple>,
+        consistency: Consistency,
+    ) -> Result<Value, BackendError> {
+        let request =
+            Self::expand_request(store, object, relation, contextual_tuples, consistency)?;
+
+        let response = self
+            .client
+            .clone()
+            .expand(request)
+            .await
+            .map_err(grpc_error)?;
+        Ok(serde_json::to_value(response.into_inner().tree).unwrap_or_default())
+    }
+
+    async fn list_objects(
+        &self,
+        store: &StoreRef,
+        object_type: String,
+        relation: String,
+        user: String,
+        context: Option<Value>,
+        consistency: Consistency,
+    ) -> Result<Vec<String>, BackendError> {
+        let mut request = OpenFGAClient::create_list_objects_request(
+            store.store_id.clone(),
+            object_type,
+            relation,
+            user,
+            context,
+        )
+        .map_err(invalid_context)?;
+        request.authorization_model_id = store.authorization_model_id.clone().unwrap_or_default();
+        request.consistency = openfga_grpc_client::ConsistencyPreference::from(consistency) as i32;
+
+        let response = self
+            .client
+            .clone()
+            .list_objects(request)
+            .await
+            .map_err(grpc_error)?;
+        Ok(response.into_inner().objects)
+    }
+
+    async fn list_users(
+        &self,
+        store: &StoreRef,
+        object: String,
+        relation: String,
+        user_filters: Vec<UserFilter>,
+        context: Option<Value>,
+        consistency: Consistency,
+    ) -> Result<Vec<String>, BackendError> {
+        let (object_type, object_id) = split_object(&object)?;
+        let mut request = OpenFGAClient::create_list_users_request(
+            store.store_id.clone(),
+            openfga_grpc_client::Object {
+                r#type: object_type.to_string(),
+                id: object_id.to_string(),
+            },
+            relation,
+            user_filters.into_iter().map(Into::into).collect(),
+            context,
+        )
+        .map_err(invalid_context)?;
+        request.authorization_model_id = store.authorization_model_id.clone().unwrap_or_default();
+        request.consistency = openfga_grpc_client::ConsistencyPreference::from(consistency) as i32;
+
+        let response = self
+            .client
+            .clone()
+            .list_users(request)
+            .await
+            .map_err(grpc_error)?;
+        Ok(response
+            .into_inner()
+            .users
+            .into_iter()
+            .filter_map(|user| match user.user? {
+                GrpcUser::Object(o) => Some(format!("{}:{}", o.r#type, o.id)),
+                GrpcUser::Userset(u) => Some(format!("{}:{}#{}", u.r#type, u.id, u.relation)),
+                GrpcUser::Wildcard(w) => Some(format!("{}:*", w.r#type)),
+            })
+            .collect())
+    }
+}
+
+// =============================================================================
+// HTTP
+// =============================================================================
+
+/// [`FgaBackend`] over the OpenFGA HTTP API, retrying calls per its [`HttpRetryConfig`]
+#[derive(Clone)]
+pub struct HttpBackend {
+    config: Configuration,
+    retry: HttpRetryConfig,
+}
+
+impl HttpBackend {
+    pub fn new(config: Configuration, retry: HttpRetryConfig) -> Self {
+        Self { config, retry }
+    }
+
+    /// Build the check request sent by [`FgaBackend::check`]
+    pub fn check_request(
+        store: &StoreRef,
+        tuple: Tuple,
+        context: Option<Value>,
+        consistency: Consistency,
+    ) -> http::CheckRequest {
+        let contextual_tuples: Vec<http::TupleKey> = current_contextual_tuples()
+            .into_iter()
+            .map(|tuple| Tuple::from(tuple).into())
+            .collect();
+
+        let mut request = http::CheckRequest::new(tuple.into());
+        request.authorization_model_id = store.authorization_model_id.clone();
+        request.consistency = Some(consistency.into());
+        request.context = context;
+        request.contextual_tuples = (!contextual_tuples.is_empty())
+            .then(|| Box::new(http::ContextualTupleKeys::new(contextual_tuples)));
+        request
+    }
+
+    /// Build the expand request sent by [`FgaBackend::expand`], with `contextual_tuples`
+    /// followed by those of the current task
+    pub fn expand_request(
+        store: &StoreRef,
+        object: String,
+        relation: String,
+        contextual_tuples: Vec<Tuple>,
+        consistency: Consistency,
+    ) -> http::ExpandRequest {
+        let contextual_tuples: Vec<http::TupleKey> = contextual_tuples
+            .into_iter()
+            .chain(current_contextual_tuples().into_iter().map(Tuple::from))
+            .map(Into::into)
+            .collect();
+
+        let mut request =
+            http::ExpandRequest::new(http::ExpandRequestTupleKey::new(relation, object));
+        request.authorization_model_id = store.authorization_model_id.clone();
+        request.consistency = Some(consistency.into());
+        request.contextual_tuples = (!contextual_tuples.is_empty())
+            .then(|| Box::new(http::ContextualTupleKeys::new(contextual_tuples)));
+        request
+    }
+}
+
+impl FgaBackend for HttpBackend {
+    fn from_ctx(ctx: &Ctx) -> Self {
+        Self::new(ctx.fga_http_config.clone(), ctx.fga_http_retry.clone())
+    }
+
+    async fn check(
+        &self,
+        store: &StoreRef,
+        tuple: Tuple,
+        context: Option<Value>,
+        consistency: Consistency,
+    ) -> Result<bool, BackendError> {
+        check_context(context.as_ref())?;
+        let request = Self::check_request(store, tuple, context, consistency);
+
+        let response = self
+            .retry
+            .call(HttpCall::Read, || {
+                relationship_queries_api::check(&self.config, &store.store_id, request.clone())
+            })
+            .await
+            .map_err(http_error)?;
+        Ok(response.allowed.unwrap_or(false))
+    }
+
+    async fn batch_check(
+        &self,
+        store: &StoreRef,
+        tuples: Vec<Tuple>,
+        consistency: Consistency,
+    ) -> Result<Vec<bool>, BackendError> {
+        let contextual_tuples: Vec<http::TupleKey> = current_contextual_tuples()
+            .into_iter()
+            .map(|tuple| Tuple::from(tuple).into())
+            .collect();
+
+        let mut allowed = Vec::with_capacity(tuples.len());
+        for chunk in tuples.chunks(MAX_BATCH_CHECK_ITEMS) {
+            // Correlation IDs are the positions in the chunk
+            let checks = chunk
+                .iter()
+                .enumerate()
+                .map(|(i, tuple)| {
+                    let mut item = http::BatchCheckItem::new(tuple.clone().into(), i.to_string());
+                    item.contextual_tuples = (!contextual_tuples.is_empty()).then(|| {
+                        Box::new(http::ContextualTupleKeys::new(contextual_tuples.clone()))
+                    });
+                    item
+                })
+                .collect();
+            let mut request = http::BatchCheckRequest::new(checks);
+            request.authorization_model_id = store.authorization_model_id.clone();
+            request.consistency = Some(consistency.into());
+
+            let mut results = self
+                .retry
+                .call(HttpCall::Read, || {
+                    relationship_queries_api::batch_check(
+                        &self.config,
+                        &store.store_id,
+                        request.clone(),
+                    )
+                })
+                .await
+                .map_err(http_error)?
+                .result
+                .unwrap_or_default();
+            for i in 0..chunk.len() {
+                let result = results
+                    .remove(&i.to_string())
+                    .ok_or_else(|| upstream(format!("no result for check {}", i)))?;
+                if let Some(error) = result.error {
+                    return Err(upstream(error.message.unwrap_or_default()));
+                }
+                allowed.push(result.allowed.unwrap_or(false));
+            }
+        }
+        Ok(allowed)
+    }
+
+    async fn write(
+        &self,
+        store: &StoreRef,
+        writes: Vec<Tuple>,
+        deletes: Vec<Tuple>,
+    ) -> Result<(), BackendError> {
+        let writes: Vec<http::TupleKey> = writes.into_iter().map(Into::into).collect();
+        let deletes: Vec<http::TupleKeyWithoutCondition> =
+            deletes.into_iter().map(Into::into).collect();
+
+        let request = http::WriteRequest {
+            writes: (!writes.is_empty()).then(|| Box::new(http::WriteRequestWrites::new(writes))),
+            deletes: (!deletes.is_empty())
+                .then(|| Box::new(http::WriteRequestDeletes::new(deletes))),
+            authorization_model_id: store.authorization_model_id.clone(),
+        };
+
+        self.retry
+            .call(HttpCall::Write, || {
+                relationship_tuples_api::write(&self.config, &store.store_id, request.clone())
+            })
+            .await
+            .map_err(http_error)?;
+        Ok(())
+    }
+
+    async fn read(
+        &self,
+        store_id: &str,
+        filter: ReadFilter,
+        page_size: i32,
+        continuation_token: Option<String>,
+        consistency: Consistency,
+    ) -> Result<Page<StoredTuple>, BackendError> {
+        let request = http::ReadRequest {
+            tuple_key: Some(Box::new(filter.into())),
+            page_size: Some(page_size),
+            continuation_token,
+            consistency: Some(consistency.into()),
+        };
+
+        let response = self
+            .retry
+            .call(HttpCall::Read, || {
+                relationship_tuples_api::read(&self.config, store_id, request.clone())
+            })
+            .await
+            .map_err(http_error)?;
+        let tuples = response
+            .tuples
+            .into_iter()
+            .map(|tuple| StoredTuple {
+                tuple: (*tuple.key).into(),
+                timestamp: DateTime::parse_from_rfc3339(&tuple.timestamp)
+                    .ok()
+                    .map(|t| t.with_timezone(&Utc)),
+            })
+            .collect();
+
+        Ok(Page::new(tuples, response.continuation_token))
+    }
+
+    async fn expand(
+        &self,
+        store: &StoreRef,
+        object: String,
+        relation: String,
+        contextual_tuples: Vec<Tuple>,
+        consistency: Consistency,
+    ) -> Result<Value, BackendError> {
+        let request = Self::expand_request(store, object, relation, contextual_tuples, consistency);
+
+        let response = self
+            .retry
+            .call(HttpCall::Read, || {
+                relationship_queries_api::expand(&self.config, &store.store_id, request.clone())
+            })
+            .await
+            .map_err(http_error)?;
+        Ok(serde_json::to_value(response.tree).unwrap_or_default())
+    }
+
+    async fn list_objects(
+        &self,
+        store: &StoreRef,
+        object_type: String,
+        relation: String,
+        user: String,
+        context: Option<Value>,
+        consistency: Consistency,
+    ) -> Result<Vec<String>, BackendError> {
+        check_context(context.as_ref())?;
+        let mut request = http::ListObjectsRequest::new(object_type, relation, user);
+        request.authorization_model_id = store.authorization_model_id.clone();
+        request.context = context;
+        request.consistency = Some(consistency.into());
+
+        let response = self
+            .retry
+            .call(HttpCall::Read, || {
+                relationship_queries_api::list_objects(
+                    &self.config,
+                    &store.store_id,
+                    request.clone(),
+                )
+            })
+            .await
+            .map_err(http_error)?;
+        Ok(response.objects)
+    }
+
+    async fn list_users(
+        &self,
+        store: &StoreRef,
+        object: String,
+        relation: String,
+        user_filters: Vec<UserFilter>,
+        context: Option<Value>,
+        consistency: Consistency,
+    ) -> Result<Vec<String>, BackendError> {
+        check_context(context.as_ref())?;
+        let (object_type, object_id) = split_object(&object)?;
+        let mut request = http::ListUsersRequest::new(
+            http::Object::new(object_type.to_string(), object_id.to_string()),
+            relation,
+            user_filters.into_iter().map(Into::into).collect(),
+        );
+        request.authorization_model_id = store.authorization_model_id.clone();
+        request.context = context;
+        request.consistency = Some(consistency.into());
+
+        let response = self
+            .retry
+            .call(HttpCall::Read, || {
+                relationship_queries_api::list_users(&self.config, &store.store_id, request.clone())
+            })
+            .await
+            .map_err(http_error)?;
+        Ok(response
+            .users
+            .into_iter()
+            .filter_map(|user| {
+                if let Some(o) = user.object {
+                    Some(format!("{}:{}", o.r#type, o.id))
+                } else if let Some(u) = user.userset {
+                    Some(format!("{}:{}#{}", u.r#type, u.id, u.relation))
+                } else {
+                    user.wildcard.map(|w| format!("{}:*", w.r#type))
+                }
+            })
+            .collect())
+    }
+}
+
+fn upstream(e: impl std::fmt::Display) -> BackendError {
+    tracing::error!("OpenFGA request failed: {}", e);
+    BackendError::Upstream(e.to_string())
+}
+
+fn grpc_error(e: tonic::Status) -> BackendError {
+    tracing::error!("OpenFGA request failed: {}", e);
+    BackendError::from_status(grpc_error_status(&e), e.to_string())
+}
+
+fn http_error<T>(e: openfga_http_client::apis::Error<T>) -> BackendError {
+    tracing::error!("OpenFGA request failed: {}", e);
+    BackendError::from_status(http_error_status(&e), e.to_string())
+}
+
+fn invalid_context(e: serde_json::Error) -> BackendError {
+    BackendError::InvalidRequest(format!("context must be a JSON object: {}", e))
+}
+
+/// Reject a `context` that isn't a JSON object, as the gRPC conversion does
+fn check_context(context: Option<&Value>) -> Result<(), BackendError> {
+    match context {
+        Some(context) if !context.is_object() => Err(BackendError::InvalidRequest(
+            "context must be a JSON object".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn grpc_context(context: Option<Value>) -> Result<Option<prost_wkt_types::Struct>, BackendError> {
+    context
+        .map(OpenFGAClient::context_from_json)
+        .transpose()
+        .map_err(invalid_context)
+}
+
+/// Split `type:id` into its parts
+fn split_object(object: &str) -> Result<(&str, &str), BackendError> {
+    object.split_once(':').ok_or_else(|| {
+        BackendError::InvalidRequest(format!("object {:?} must have the form type:id", object))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fga_apis::contextual_tuples::with_contextual_tuples;
+    use crate::fga_apis::dto::Condition;
+
+    fn assert_backend<B: FgaBackend>() {}
+
+    #[tokio::test]
+    async fn test_both_backends_build_the_same_check() {
+        assert_backend::<GrpcBackend>();
+        assert_backend::<HttpBackend>();
+
+        let store = StoreRef {
+            store_id: "store".to_string(),
+            authorization_model_id: Some("model".to_string()),
+        };
+        let tuple = Tuple {
+            user: "user:alice".to_string(),
+            relation: "viewer".to_string(),
+            object: "document:readme".to_string(),
+            condition: None,
+        };
+        let context = json!({ "ip": "10.0.0.1" });
+        let tenant = openfga_grpc_client::TupleKey {
+            user: "user:alice".to_string(),
+            relation: "member".to_string(),
+            object: "tenant:acme".to_string(),
+            condition: None,
+        };
+
+        let (grpc, http) = with_contextual_tuples(vec![tenant], async {
+            (
+                GrpcBackend::check_request(
+                    &store,
+                    tuple.clone(),
+                    Some(context.clone()),
+                    Consistency::HigherConsistency,
+                )
+                .unwrap(),
+                HttpBackend::check_request(
+                    &store,
+                    tuple.clone(),
+                    Some(context.clone()),
+                    Consistency::HigherConsistency,
+                ),
+            )
+        })
+        .await;
+
+        let grpc_key = grpc.tuple_key.unwrap();
+        assert_eq!(
+            (grpc_key.user, grpc_key.relation, grpc_key.object),
+            (
+                http.tuple_key.user.clone(),
+                http.tuple_key.relation.clone(),
+                http.tuple_key.object.clone()
+            )
+        );
+        assert_eq!(
+            Some(grpc.authorization_model_id),
+            http.authorization_model_id
+        );
+        assert_eq!(
+            grpc.consistency,
+            openfga_grpc_client::ConsistencyPreference::HigherConsistency as i32
+        );
+        assert_eq!(
+            http.consistency,
+            Some(http::ConsistencyPreference::HigherConsistency)
+        );
+        assert_eq!(serde_json::to_value(grpc.context).unwrap(), context);
+        assert_eq!(http.context, Some(context));
+        assert_eq!(
+            grpc.contextual_tuples.unwrap().tuple_keys[0].object,
+            http.contextual_tuples.unwrap().tuple_keys[0].object
+        );
+
+        let error =
+            GrpcBackend::check_request(&store, tuple, Some(json!([1])), Consistency::Unspecified)
+                .unwrap_err();
+        assert!(matches!(error, BackendError::InvalidRequest(_)));
+        assert!(matches!(
+            check_context(Some(&json!("text"))),
+            Err(BackendError::InvalidRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_expand_request_encodes_context() {
+        let store = StoreRef {
+            store_id: "store".to_string(),
+            authorization_model_id: Some("model".to_string()),
+        };
+        let context = json!({ "ip": "10.0.0.1" });
+        let membership = Tuple {
+            user: "user:alice".to_string(),
+            relation: "member".to_string(),
+            object: "group:eng".to_string(),
+            condition: Some(Condition {
+                name: "in_office".to_string(),
+                context: context.as_object().cloned(),
+            }),
+        };
+        let tenant = openfga_grpc_client::TupleKey {
+            user: "user:alice".to_string(),
+            relation: "member".to_string(),
+            object: "tenant:acme".to_string(),
+            condition: None,
+        };
+
+        let (grpc, http) = with_contextual_tuples(vec![tenant], async {
+            let object = "document:readme".to_string();
+            let relation = "viewer".to_string();
+            (
+                GrpcBackend::expand_request(
+                    &store,
+                    object.clone(),
+                    relation.clone(),
+                    vec![membership.clone()],
+                    Consistency::HigherConsistency,
+                )
+                .unwrap(),
+                HttpBackend::expand_request(
+                    &store,
+                    object,
+                    relation,
+                    vec![membership.clone()],
+                    Consistency::HigherConsistency,
+                ),
+            )
+        })
+        .await;
+
+        let grpc_tuples = grpc.contextual_tuples.unwrap().tuple_keys;
+        let http_tuples = http.contextual_tuples.unwrap().tuple_keys;
+        let objects: Vec<&str> = grpc_tuples.iter().map(|t| t.object.as_str()).collect();
+        assert_eq!(objects, vec!["group:eng", "tenant:acme"]);
+        assert_eq!(grpc_tuples.len(), http_tuples.len());
+
+        let grpc_condition = grpc_tuples[0].condition.as_ref().unwrap();
+        assert_eq!(grpc_condition.name, "in_office");
+        assert_eq!(
+            serde_json::to_value(&grpc_condition.context).unwrap(),
+            context
+        );
+        let http_condition = http_tuples[0].condition.as_ref().unwrap();
+        assert_eq!(http_condition.context, Some(context));
+        assert_eq!(grpc.authorization_model_id, "model");
+        assert_eq!(http.authorization_model_id.as_deref(), Some("model"));
+    }
+}
@@ -0,0 +1,483 @@
+//! Request bodies of the shared relationship routes.
+//!
+//! Each route takes the flat request of its handler, e.g. [`CheckReq`], and still takes
+//! the body it did before the gRPC and HTTP routes shared handlers: a single tuple on
+//! gRPC tuple writes and deletes, an `{ "type", "id" }` object on gRPC list users, and
+//! on the HTTP routes the OpenFGA request next to its store, e.g.
+//! `{ "store_id", "check_request": { "tuple_key": { ... } } }`. As in OpenFGA, a
+//! consistency left out of an OpenFGA request is left to the server.
+
+use axum::{Json, http::StatusCode};
+use openfga_http_client::models as http;
+use serde_json::{Value, json};
+
+use crate::fga_apis::dto::{Consistency, ReadFilter, Tuple, UserFilter};
+use crate::fga_apis::relationships::{
+    CheckReq, ExpandReq, ListObjectsReq, ListUsersReq, ReadReq, StoreReq, TuplesReq,
+};
+
+/// Body of the check routes
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+pub enum CheckBody {
+    OpenFga {
+        store_id: String,
+        check_request: http::CheckRequest,
+    },
+    Flat(CheckReq),
+}
+
+impl From<CheckBody> for CheckReq {
+    fn from(body: CheckBody) -> Self {
+        match body {
+            CheckBody::Flat(req) => req,
+            CheckBody::OpenFga {
+                store_id,
+                check_request,
+            } => {
+                let tuple_key = *check_request.tuple_key;
+                CheckReq {
+                    store: store(store_id, check_request.authorization_model_id),
+                    user: tuple_key.user,
+                    relation: tuple_key.relation,
+                    object: tuple_key.object,
+                    context: check_request.context,
+                    contextual_tuples: contextual_tuples(check_request.contextual_tuples),
+                    consistency: openfga_consistency(check_request.consistency),
+                }
+            }
+        }
+    }
+}
+
+/// Body of the tuple write and delete routes
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+pub enum TuplesBody {
+    OpenFga {
+        store_id: String,
+        write_request: http::WriteRequest,
+    },
+    Tuples(TuplesReq),
+    /// A single tuple of the configured store
+    Tuple(Tuple),
+}
+
+/// Tuples written and deleted in one transaction
+#[derive(Debug, PartialEq)]
+pub struct TupleChanges {
+    pub store: StoreReq,
+    pub writes: Vec<Tuple>,
+    pub deletes: Vec<Tuple>,
+}
+
+impl TuplesBody {
+    /// The tuples of the body written, or deleted when `delete` is set. An OpenFGA
+    /// write request is sent as is, with both its writes and its deletes.
+    pub fn into_changes(self, delete: bool) -> TupleChanges {
+        let (store, tuples) = match self {
+            TuplesBody::OpenFga {
+                store_id,
+                write_request,
+            } => {
+                return TupleChanges {
+                    store: store(store_id, write_request.authorization_model_id),
+                    writes: write_request
+                        .writes
+                        .map(|writes| writes.tuple_keys.into_iter().map(Tuple::from).collect())
+                        .unwrap_or_default(),
+                    deletes: write_request
+                        .deletes
+                        .map(|deletes| {
+                            deletes
+                                .tuple_keys
+                                .into_iter()
+                                .map(|key| Tuple {
+                                    user: key.user,
+                                    relation: key.relation,
+                                    object: key.object,
+                                    condition: None,
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                };
+            }
+            TuplesBody::Tuples(req) => (req.store, req.tuples),
+            TuplesBody::Tuple(tuple) => (StoreReq::default(), vec![tuple]),
+        };
+
+        if delete {
+            TupleChanges {
+                store,
+                writes: Vec::new(),
+                deletes: tuples,
+            }
+        } else {
+            TupleChanges {
+                store,
+                writes: tuples,
+                deletes: Vec::new(),
+            }
+        }
+    }
+}
+
+/// Body of the tuple read routes
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+pub enum ReadBody {
+    OpenFga {
+        store_id: String,
+        read_request: http::ReadRequest,
+    },
+    Flat(ReadReq),
+}
+
+impl From<ReadBody> for ReadReq {
+    fn from(body: ReadBody) -> Self {
+        match body {
+            ReadBody::Flat(req) => req,
+            ReadBody::OpenFga {
+                store_id,
+                read_request,
+            } => ReadReq {
+                store_id: Some(store_id),
+                filter: read_request
+                    .tuple_key
+                    .map(|key| ReadFilter {
+                        user: key.user,
+                        relation: key.relation,
+                        object: key.object,
+                    })
+                    .unwrap_or_default(),
+                page_size: read_request.page_size,
+                continuation_token: read_request.continuation_token,
+                consistency: openfga_consistency(read_request.consistency),
+            },
+        }
+    }
+}
+
+/// Body of the expand routes
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+pub enum ExpandBody {
+    OpenFga {
+        store_id: String,
+        expand_request: http::ExpandRequest,
+    },
+    Flat(ExpandReq),
+}
+
+impl From<ExpandBody> for ExpandReq {
+    fn from(body: ExpandBody) -> Self {
+        match body {
+            ExpandBody::Flat(req) => req,
+            ExpandBody::OpenFga {
+                store_id,
+                expand_request,
+            } => ExpandReq {
+                store: store(store_id, expand_request.authorization_model_id),
+                object: expand_request.tuple_key.object,
+                relation: expand_request.tuple_key.relation,
+                contextual_tuples: contextual_tuples(expand_request.contextual_tuples),
+                consistency: openfga_consistency(expand_request.consistency),
+            },
+        }
+    }
+}
+
+/// Body of the list objects routes
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+pub enum ListObjectsBody {
+    OpenFga {
+        store_id: String,
+        list_objects_request: http::ListObjectsRequest,
+    },
+    Flat(ListObjectsReq),
+}
+
+/// Fails for contextual tuples, which list objects isn't evaluated with
+impl TryFrom<ListObjectsBody> for ListObjectsReq {
+    type Error = (StatusCode, Json<Value>);
+
+    fn try_from(body: ListObjectsBody) -> Result<Self, Self::Error> {
+        match body {
+            ListObjectsBody::Flat(req) => Ok(req),
+            ListObjectsBody::OpenFga {
+                store_id,
+                list_objects_request: request,
+            } => {
+                if !contextual_tuples(request.contextual_tuples).is_empty() {
+                    return Err(contextual_tuples_unsupported());
+                }
+                Ok(ListObjectsReq {
+                    store: store(store_id, request.authorization_model_id),
+                    r#type: request.r#type,
+                    relation: request.relation,
+                    user: request.user,
+                    context: request.context,
+                    consistency: openfga_consistency(request.consistency),
+                })
+            }
+        }
+    }
+}
+
+/// Object of a list users request on the gRPC route
+#[derive(Debug, serde::Deserialize)]
+pub struct ObjectParts {
+    pub r#type: String,
+    pub id: String,
+}
+
+/// Body of the list users routes
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+pub enum ListUsersBody {
+    OpenFga {
+        store_id: String,
+        list_users_request: http::ListUsersRequest,
+    },
+    Flat(ListUsersReq),
+    /// The object as its parts, with an empty filter relation for user types
+    Grpc {
+        #[serde(flatten)]
+        store: StoreReq,
+        object: ObjectParts,
+        relation: String,
+        user_filters: Vec<UserFilter>,
+        context: Option<Value>,
+        #[serde(default)]
+        consistency: Option<Consistency>,
+    },
+}
+
+/// Fails for contextual tuples, which list users isn't evaluated with
+impl TryFrom<ListUsersBody> for ListUsersReq {
+    type Error = (StatusCode, Json<Value>);
+
+    fn try_from(body: ListUsersBody) -> Result<Self, Self::Error> {
+        match body {
+            ListUsersBody::Flat(req) => Ok(req),
+            ListUsersBody::OpenFga {
+                store_id,
+                list_users_request: request,
+            } => {
+                if request.contextual_tuples.is_some_and(|t| !t.is_empty()) {
+                    return Err(contextual_tuples_unsupported());
+                }
+                Ok(ListUsersReq {
+                    store: store(store_id, request.authorization_model_id),
+                    object: format!("{}:{}", request.object.r#type, request.object.id),
+                    relation: request.relation,
+                    user_filters: request
+                        .user_filters
+                        .into_iter()
+                        .map(|filter| UserFilter {
+                            r#type: filter.r#type,
+                            relation: filter.relation,
+                        })
+                        .collect(),
+                    context: request.context,
+                    consistency: openfga_consistency(request.consistency),
+                })
+            }
+            ListUsersBody::Grpc {
+                store,
+                object,
+                relation,
+                user_filters,
+                context,
+                consistency,
+            } => Ok(ListUsersReq {
+                store,
+                object: format!("{}:{}", object.r#type, object.id),
+                relation,
+                user_filters: user_filters
+                    .into_iter()
+                    .map(|filter| UserFilter {
+                        r#type: filter.r#type,
+                        relation: filter.relation.filter(|relation| !relation.is_empty()),
+                    })
+                    .collect(),
+                context,
+                consistency,
+            }),
+        }
+    }
+}
+
+fn store(store_id: String, authorization_model_id: Option<String>) -> StoreReq {
+    StoreReq {
+        store_id: Some(store_id),
+        authorization_model_id,
+    }
+}
+
+/// Consistency of an OpenFGA request; the server's default when left out
+fn openfga_consistency(consistency: Option<http::ConsistencyPreference>) -> Option<Consistency> {
+    Some(consistency.map(Consistency::from).unwrap_or_default())
+}
+
+fn contextual_tuples(tuples: Option<Box<http::ContextualTupleKeys>>) -> Vec<Tuple> {
+    tuples
+        .map(|tuples| tuples.tuple_keys.into_iter().map(Tuple::from).collect())
+        .unwrap_or_default()
+}
+
+fn contextual_tuples_unsupported() -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(json!({ "message": "contextual_tuples are not supported on this route" })),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tuple(user: &str) -> Tuple {
+        Tuple {
+            user: user.to_string(),
+            relation: "viewer".to_string(),
+            object: "document:readme".to_string(),
+            condition: None,
+        }
+    }
+
+    #[test]
+    fn test_openfga_check_body() {
+        let body: CheckBody = serde_json::from_value(json!({
+            "store_id": "01HXYZ",
+            "check_request": {
+                "tuple_key": {
+                    "user": "user:anne",
+                    "relation": "viewer",
+                    "object": "document:readme"
+                },
+                "contextual_tuples": {
+                    "tuple_keys": [
+                        { "user": "user:bob", "relation": "viewer", "object": "document:readme" }
+                    ]
+                }
+            }
+        }))
+        .unwrap();
+        let req = CheckReq::from(body);
+        assert_eq!(req.store.store_id.as_deref(), Some("01HXYZ"));
+        assert_eq!(req.user, "user:anne");
+        assert_eq!(req.contextual_tuples, vec![tuple("user:bob")]);
+        // Left to the server, as OpenFGA does
+        assert_eq!(req.consistency, Some(Consistency::Unspecified));
+
+        let body: CheckBody = serde_json::from_value(json!({
+            "user": "user:anne",
+            "relation": "viewer",
+            "object": "document:readme"
+        }))
+        .unwrap();
+        let req = CheckReq::from(body);
+        assert_eq!(req.store.store_id, None);
+        assert_eq!(req.consistency, None);
+    }
+
+    #[test]
+    fn test_tuple_bodies() {
+        // A single tuple, as taken by the gRPC routes
+        let body: TuplesBody = serde_json::from_value(json!({
+            "user": "user:anne",
+            "relation": "viewer",
+            "object": "document:readme"
+        }))
+        .unwrap();
+        assert_eq!(
+            body.into_changes(true),
+            TupleChanges {
+                store: StoreReq::default(),
+                writes: Vec::new(),
+                deletes: vec![tuple("user:anne")],
+            }
+        );
+
+        let body: TuplesBody = serde_json::from_value(json!({
+            "tuples": [
+                { "user": "user:anne", "relation": "viewer", "object": "document:readme" }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(body.into_changes(false).writes, vec![tuple("user:anne")]);
+
+        // An OpenFGA write request keeps its writes and deletes on either route
+        let body: TuplesBody = serde_json::from_value(json!({
+            "store_id": "01HXYZ",
+            "write_request": {
+                "writes": { "tuple_keys": [
+                    { "user": "user:anne", "relation": "viewer", "object": "document:readme" }
+                ] },
+                "deletes": { "tuple_keys": [
+                    { "user": "user:bob", "relation": "viewer", "object": "document:readme" }
+                ] }
+            }
+        }))
+        .unwrap();
+        let changes = body.into_changes(true);
+        assert_eq!(changes.store.store_id.as_deref(), Some("01HXYZ"));
+        assert_eq!(changes.writes, vec![tuple("user:anne")]);
+        assert_eq!(changes.deletes, vec![tuple("user:bob")]);
+    }
+
+    #[test]
+    fn test_openfga_read_body() {
+        let body: ReadBody = serde_json::from_value(json!({
+            "store_id": "01HXYZ",
+            "read_request": {
+                "tuple_key": { "object": "document:" },
+                "page_size": 10,
+                "consistency": "HIGHER_CONSISTENCY"
+            }
+        }))
+        .unwrap();
+        let req = ReadReq::from(body);
+        assert_eq!(req.store_id.as_deref(), Some("01HXYZ"));
+        assert_eq!(req.filter.object.as_deref(), Some("document:"));
+        assert_eq!(req.page_size, Some(10));
+        assert_eq!(req.consistency, Some(Consistency::HigherConsistency));
+    }
+
+    #[test]
+    fn test_list_users_bodies() {
+        let body: ListUsersBody = serde_json::from_value(json!({
+            "object": { "type": "document", "id": "readme" },
+            "relation": "viewer",
+            "user_filters": [{ "type": "user", "relation": "" }]
+        }))
+        .unwrap();
+        let req = ListUsersReq::try_from(body).unwrap();
+        assert_eq!(req.object, "document:readme");
+        assert_eq!(
+            req.user_filters,
+            vec![UserFilter {
+                r#type: "user".to_string(),
+                relation: None
+            }]
+        );
+
+        let body: ListUsersBody = serde_json::from_value(json!({
+            "store_id": "01HXYZ",
+            "list_users_request": {
+                "object": { "type": "document", "id": "readme" },
+                "relation": "viewer",
+                "user_filters": [{ "type": "group", "relation": "member" }],
+                "contextual_tuples": [
+                    { "user": "user:bob", "relation": "viewer", "object": "document:readme" }
+                ]
+            }
+        }))
+        .unwrap();
+        let (status, _) = ListUsersReq::try_from(body).unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
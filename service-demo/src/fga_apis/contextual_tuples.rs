@@ -0,0 +1,297 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+use openfga_grpc_client::{
+    CheckRequest, CheckRequestTupleKey, ConsistencyPreference, ContextualTupleKeys, TupleKey,
+};
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use crate::context::OpenFgaConfig;
+use crate::fga_apis::identifiers::validate_tuple;
+
+tokio::task_local! {
+    static CONTEXTUAL_TUPLES: Vec<TupleKey>;
+}
+
+/// Derives the contextual tuples for a request, e.g. the caller's tenant or the current time
+pub type ContextualTuplesFn = Arc<dyn Fn(&Request) -> Vec<TupleKey> + Send + Sync>;
+
+/// A request header turned into a contextual tuple, e.g. `X-Tenant: acme` into
+/// `user:* member tenant:acme` for `{"header": "x-tenant", "user": "user:*",
+/// "relation": "member", "object_type": "tenant"}`.
+///
+/// Configured as a JSON array in `OPENFGA_CONTEXTUAL_TUPLE_HEADERS`. The user is taken
+/// as is; a type wildcard such as `user:*` makes the attribute hold for whoever is
+/// checked.
+///
+/// Clients could set these headers themselves, so they're only mapped on requests
+/// from the proxies listed in `OPENFGA_CONTEXTUAL_TUPLE_TRUSTED_PROXIES`, which are
+/// expected to set or strip them, see [`from_headers`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HeaderTupleMapping {
+    /// Header name, matched case-insensitively
+    pub header: String,
+    pub user: String,
+    pub relation: String,
+    /// Type of the object; the header value is its ID
+    pub object_type: String,
+}
+
+/// Contextual tuples for the `headers` named by `mappings`, one per header value in
+/// the order of `mappings`. Missing headers, blank or non-UTF-8 values and values that
+/// don't make a valid tuple, e.g. `acme#member`, are skipped.
+pub fn tuples_from_headers(mappings: &[HeaderTupleMapping], headers: &HeaderMap) -> Vec<TupleKey> {
+    mappings
+        .iter()
+        .flat_map(|mapping| {
+            headers
+                .get_all(mapping.header.as_str())
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(move |value| TupleKey {
+                    user: mapping.user.clone(),
+                    relation: mapping.relation.clone(),
+                    object: format!("{}:{}", mapping.object_type, value),
+                    condition: None,
+                })
+                .filter(|tuple| {
+                    let valid = validate_tuple(&tuple.user, &tuple.relation, &tuple.object);
+                    if valid.is_err() {
+                        tracing::warn!(
+                            "Ignoring header {} that doesn't make a valid contextual tuple",
+                            mapping.header
+                        );
+                    }
+                    valid.is_ok()
+                })
+        })
+        .collect()
+}
+
+/// [`ContextualTuplesFn`] deriving the tuples of each request from its headers, see
+/// [`tuples_from_headers`]. Only requests whose peer is one of `trusted_proxies` are
+/// mapped; the peer is the [`ConnectInfo`] of the connection.
+pub fn from_headers(
+    mappings: Vec<HeaderTupleMapping>,
+    trusted_proxies: Vec<IpAddr>,
+) -> ContextualTuplesFn {
+    Arc::new(move |request: &Request| {
+        let peer = request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        if mappings.is_empty() || !peer.is_some_and(|ip| trusted_proxies.contains(&ip)) {
+            return Vec::new();
+        }
+        tuples_from_headers(&mappings, request.headers())
+    })
+}
+
+/// Run `future` with `tuples` attached to every check built through [`check_request`]
+pub async fn with_contextual_tuples<F: Future>(tuples: Vec<TupleKey>, future: F) -> F::Output {
+    CONTEXTUAL_TUPLES.scope(tuples, future).await
+}
+
+/// Contextual tuples of the current task, empty outside [`with_contextual_tuples`]
+pub fn current_contextual_tuples() -> Vec<TupleKey> {
+    CONTEXTUAL_TUPLES
+        .try_with(|tuples| tuples.clone())
+        .unwrap_or_default()
+}
+
+/// Attach the tuples derived from the request to all checks made while handling it.
+///
+/// ```rust,ignore
+/// let tenant: ContextualTuplesFn = Arc::new(|request| tenant_tuples(request));
+/// router.layer(axum::middleware::from_fn_with_state(tenant, contextual_tuples_middleware))
+/// ```
+pub async fn contextual_tuples_middleware(
+    State(contextual_tuples): State<ContextualTuplesFn>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let tuples = contextual_tuples(&request);
+    with_contextual_tuples(tuples, next.run(request)).await
+}
+
+/// Build a check request for the configured store and model, carrying the contextual
+/// tuples of the current task
+pub fn check_request(fga_config: &OpenFgaConfig, tuple_key: CheckRequestTupleKey) -> CheckRequest {
+    let tuples = current_contextual_tuples();
+
+    CheckRequest {
+        store_id: fga_config.store_id.clone(),
+        tuple_key: Some(tuple_key),
+        authorization_model_id: fga_config.authorization_model_id.get(),
+        consistency: ConsistencyPreference::from(fga_config.consistency.check) as i32,
+        context: None,
+        trace: false,
+        contextual_tuples: (!tuples.is_empty())
+            .then_some(ContextualTupleKeys { tuple_keys: tuples }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{
+        CurrentModelId, DEFAULT_FGA_MAX_BODY_SIZE, DEFAULT_FGA_MAX_PAGE_SIZE, DEFAULT_FGA_PAGE_SIZE,
+    };
+    use axum::{Router, body::Body, http::StatusCode, routing::get};
+    use tower::ServiceExt;
+
+    fn fga_config() -> OpenFgaConfig {
+        OpenFgaConfig {
+            store_id: "store".to_string(),
+            authorization_model_id: CurrentModelId::new("model"),
+            use_created_models: false,
+            max_body_size: DEFAULT_FGA_MAX_BODY_SIZE,
+            default_page_size: DEFAULT_FGA_PAGE_SIZE,
+            max_page_size: DEFAULT_FGA_MAX_PAGE_SIZE,
+            consistency: Default::default(),
+            contextual_tuple_headers: Vec::new(),
+            contextual_tuple_trusted_proxies: Vec::new(),
+        }
+    }
+
+    fn tuple_key() -> CheckRequestTupleKey {
+        CheckRequestTupleKey {
+            user: "user:alice".to_string(),
+            relation: "viewer".to_string(),
+            object: "document:readme".to_string(),
+        }
+    }
+
+    fn tenant_tuple(tenant: &str) -> TupleKey {
+        TupleKey {
+            user: "user:alice".to_string(),
+            relation: "member".to_string(),
+            object: format!("tenant:{}", tenant),
+            condition: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_request_picks_up_task_local_tuples() {
+        let request = with_contextual_tuples(vec![tenant_tuple("acme")], async {
+            check_request(&fga_config(), tuple_key())
+        })
+        .await;
+
+        let contextual = request.contextual_tuples.unwrap().tuple_keys;
+        assert_eq!(contextual, vec![tenant_tuple("acme")]);
+        assert_eq!(request.store_id, "store");
+
+        // Outside the scope no contextual tuples are attached
+        let request = check_request(&fga_config(), tuple_key());
+        assert_eq!(request.contextual_tuples, None);
+    }
+
+    #[tokio::test]
+    async fn test_middleware_scopes_tuples_to_request() {
+        async fn handler() -> String {
+            let request = check_request(&fga_config(), tuple_key());
+            request
+                .contextual_tuples
+                .map(|t| t.tuple_keys[0].object.clone())
+                .unwrap_or_default()
+        }
+
+        let from_header: ContextualTuplesFn = Arc::new(|request: &Request| {
+            request
+                .headers()
+                .get("x-tenant")
+                .and_then(|v| v.to_str().ok())
+                .map(|tenant| vec![tenant_tuple(tenant)])
+                .unwrap_or_default()
+        });
+        let app = Router::new().route("/check", get(handler)).layer(
+            axum::middleware::from_fn_with_state(from_header, contextual_tuples_middleware),
+        );
+
+        let request = Request::builder()
+            .uri("/check")
+            .header("x-tenant", "acme")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body, "tenant:acme");
+    }
+
+    #[test]
+    fn test_tuples_from_headers() {
+        let mappings: Vec<HeaderTupleMapping> = serde_json::from_str(
+            r#"[
+                { "header": "X-Tenant", "user": "user:*", "relation": "member", "object_type": "tenant" },
+                { "header": "x-region", "user": "user:*", "relation": "in", "object_type": "region" },
+                { "header": "x-device", "user": "user:*", "relation": "uses", "object_type": "device" }
+            ]"#,
+        )
+        .unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-tenant", "acme".parse().unwrap());
+        // Values that would change the tuple's object aren't mapped
+        headers.append("x-tenant", "acme#member".parse().unwrap());
+        headers.append("x-tenant", "acme:eng".parse().unwrap());
+        headers.append("x-region", "eu".parse().unwrap());
+        headers.append("x-region", " us ".parse().unwrap());
+        headers.insert("x-device", "".parse().unwrap());
+        headers.insert("x-unmapped", "ignored".parse().unwrap());
+
+        let tuple = |relation: &str, object: &str| TupleKey {
+            user: "user:*".to_string(),
+            relation: relation.to_string(),
+            object: object.to_string(),
+            condition: None,
+        };
+        assert_eq!(
+            tuples_from_headers(&mappings, &headers),
+            vec![
+                tuple("member", "tenant:acme"),
+                tuple("in", "region:eu"),
+                tuple("in", "region:us"),
+            ]
+        );
+        assert!(tuples_from_headers(&mappings, &HeaderMap::new()).is_empty());
+    }
+
+    #[test]
+    fn test_headers_mapped_only_from_trusted_proxies() {
+        let mappings: Vec<HeaderTupleMapping> = serde_json::from_str(
+            r#"[{ "header": "x-tenant", "user": "user:*", "relation": "member", "object_type": "tenant" }]"#,
+        )
+        .unwrap();
+        let proxy: IpAddr = "10.0.0.2".parse().unwrap();
+        let contextual_tuples = from_headers(mappings, vec![proxy]);
+
+        let request_from = |peer: Option<IpAddr>| {
+            let mut request = Request::builder()
+                .header("x-tenant", "acme")
+                .body(Body::empty())
+                .unwrap();
+            if let Some(ip) = peer {
+                request
+                    .extensions_mut()
+                    .insert(ConnectInfo(SocketAddr::new(ip, 4000)));
+            }
+            request
+        };
+
+        assert_eq!(contextual_tuples(&request_from(Some(proxy))).len(), 1);
+        assert!(contextual_tuples(&request_from(Some("203.0.113.9".parse().unwrap()))).is_empty());
+        assert!(contextual_tuples(&request_from(None)).is_empty());
+    }
+}
@@ -0,0 +1,447 @@
+//! Transport-independent request and response types, with conversions into the
+//! gRPC and HTTP generated types.
+
+use chrono::{DateTime, Utc};
+use openfga_grpc_client as grpc;
+use openfga_grpc_client::OpenFGAClient;
+use openfga_http_client::models as http;
+use serde_json::{Map, Value};
+
+use crate::time_utils::timestamp_to_chrono;
+
+/// Condition attached to a tuple
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Condition {
+    /// Name of a condition defined in the authorization model
+    pub name: String,
+    /// Values for the condition's parameters
+    pub context: Option<Map<String, Value>>,
+}
+
+/// Relationship tuple, independent of the transport
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Tuple {
+    pub user: String,
+    pub relation: String,
+    pub object: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<Condition>,
+}
+
+/// Tuple returned by a read, with the time it was written
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct StoredTuple {
+    #[serde(flatten)]
+    pub tuple: Tuple,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// Tuples matched by a read; `object` may be a bare `type:` to match all objects of a type
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize)]
+pub struct ReadFilter {
+    pub user: Option<String>,
+    pub relation: Option<String>,
+    pub object: Option<String>,
+}
+
+/// User type, or userset type when `relation` is set, returned by list users
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct UserFilter {
+    pub r#type: String,
+    pub relation: Option<String>,
+}
+
+/// Consistency preference of a query, mapped to each transport's `ConsistencyPreference`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Consistency {
+    /// Leave the choice to the server
+    #[default]
+    Unspecified,
+    MinimizeLatency,
+    HigherConsistency,
+}
+
+impl From<Consistency> for grpc::ConsistencyPreference {
+    fn from(consistency: Consistency) -> Self {
+        match consistency {
+            Consistency::Unspecified => Self::Unspecified,
+            Consistency::MinimizeLatency => Self::MinimizeLatency,
+            Consistency::HigherConsistency => Self::HigherConsistency,
+        }
+    }
+}
+
+impl From<Consistency> for http::ConsistencyPreference {
+    fn from(consistency: Consistency) -> Self {
+        match consistency {
+            Consistency::Unspecified => Self::Unspecified,
+            Consistency::MinimizeLatency => Self::MinimizeLatency,
+            Consistency::HigherConsistency => Self::HigherConsistency,
+        }
+    }
+}
+
+impl From<http::ConsistencyPreference> for Consistency {
+    fn from(consistency: http::ConsistencyPreference) -> Self {
+        match consistency {
+            http::ConsistencyPreference::Unspecified => Self::Unspecified,
+            http::ConsistencyPreference::MinimizeLatency => Self::MinimizeLatency,
+            http::ConsistencyPreference::HigherConsistency => Self::HigherConsistency,
+        }
+    }
+}
+
+// =============================================================================
+// Tuple keys; conversions without a condition drop it
+// =============================================================================
+
+impl From<Tuple> for grpc::CheckRequestTupleKey {
+    fn from(tuple: Tuple) -> Self {
+        Self {
+            user: tuple.user,
+            relation: tuple.relation,
+            object: tuple.object,
+        }
+    }
+}
+
+impl From<Tuple> for http::CheckRequestTupleKey {
+    fn from(tuple: Tuple) -> Self {
+        Self::new(tuple.user, tuple.relation, tuple.object)
+    }
+}
+
+impl From<Tuple> for grpc::TupleKeyWithoutCondition {
+    fn from(tuple: Tuple) -> Self {
+        Self {
+            user: tuple.user,
+            relation: tuple.relation,
+            object: tuple.object,
+        }
+    }
+}
+
+impl From<Tuple> for http::TupleKeyWithoutCondition {
+    fn from(tuple: Tuple) -> Self {
+        Self::new(tuple.user, tuple.relation, tuple.object)
+    }
+}
+
+/// Fails when the condition context can't be converted to a protobuf `Struct`
+impl TryFrom<Tuple> for grpc::TupleKey {
+    type Error = serde_json::Error;
+
+    fn try_from(tuple: Tuple) -> Result<Self, Self::Error> {
+        let condition = tuple
+            .condition
+            .map(|condition| {
+                Ok::<_, serde_json::Error>(grpc::RelationshipCondition {
+                    name: condition.name,
+                    context: condition
+                        .context
+                        .map(|c| OpenFGAClient::context_from_json(Value::Object(c)))
+                        .transpose()?,
+                })
+            })
+            .transpose()?;
+
+        Ok(Self {
+            user: tuple.user,
+            relation: tuple.relation,
+            object: tuple.object,
+            condition,
+        })
+    }
+}
+
+impl From<Tuple> for http::TupleKey {
+    fn from(tuple: Tuple) -> Self {
+        Self {
+            user: tuple.user,
+            relation: tuple.relation,
+            object: tuple.object,
+            condition: tuple.condition.map(|condition| {
+                Box::new(http::RelationshipCondition {
+                    name: condition.name,
+                    context: condition.context.map(Value::Object),
+                })
+            }),
+        }
+    }
+}
+
+impl From<grpc::TupleKey> for Tuple {
+    fn from(key: grpc::TupleKey) -> Self {
+        Self {
+            user: key.user,
+            relation: key.relation,
+            object: key.object,
+            condition: key.condition.map(|condition| Condition {
+                name: condition.name,
+                context: condition
+                    .context
+                    .and_then(|c| serde_json::to_value(c).ok())
+                    .and_then(into_object),
+            }),
+        }
+    }
+}
+
+impl From<http::TupleKey> for Tuple {
+    fn from(key: http::TupleKey) -> Self {
+        Self {
+            user: key.user,
+            relation: key.relation,
+            object: key.object,
+            condition: key.condition.map(|condition| Condition {
+                name: condition.name,
+                context: condition.context.and_then(into_object),
+            }),
+        }
+    }
+}
+
+// =============================================================================
+// Filters
+// =============================================================================
+
+impl From<ReadFilter> for grpc::ReadRequestTupleKey {
+    fn from(filter: ReadFilter) -> Self {
+        Self {
+            user: filter.user.unwrap_or_default(),
+            relation: filter.relation.unwrap_or_default(),
+            object: filter.object.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<ReadFilter> for http::ReadRequestTupleKey {
+    fn from(filter: ReadFilter) -> Self {
+        Self {
+            user: filter.user,
+            relation: filter.relation,
+            object: filter.object,
+        }
+    }
+}
+
+impl From<UserFilter> for grpc::UserTypeFilter {
+    fn from(filter: UserFilter) -> Self {
+        Self {
+            r#type: filter.r#type,
+            relation: filter.relation.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<UserFilter> for http::UserTypeFilter {
+    fn from(filter: UserFilter) -> Self {
+        Self {
+            r#type: filter.r#type,
+            relation: filter.relation,
+        }
+    }
+}
+
+// =============================================================================
+// Stores
+// =============================================================================
+
+/// Store with its timestamps. A timestamp missing from the response, or one the
+/// HTTP API sent that isn't RFC 3339, reads as the Unix epoch.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct StoreInfo {
+    pub id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Set for a deleted store
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl StoreInfo {
+    fn from_grpc(
+        id: String,
+        name: String,
+        created_at: Option<prost_wkt_types::Timestamp>,
+        updated_at: Option<prost_wkt_types::Timestamp>,
+        deleted_at: Option<prost_wkt_types::Timestamp>,
+    ) -> Self {
+        Self {
+            id,
+            name,
+            created_at: created_at
+                .as_ref()
+                .map(timestamp_to_chrono)
+                .unwrap_or_default(),
+            updated_at: updated_at
+                .as_ref()
+                .map(timestamp_to_chrono)
+                .unwrap_or_default(),
+            deleted_at: deleted_at.as_ref().map(timestamp_to_chrono),
+        }
+    }
+
+    fn from_http(
+        id: String,
+        name: String,
+        created_at: &str,
+        updated_at: &str,
+        deleted_at: Option<&str>,
+    ) -> Self {
+        Self {
+            id,
+            name,
+            created_at: parse_rfc3339(created_at).unwrap_or_default(),
+            updated_at: parse_rfc3339(updated_at).unwrap_or_default(),
+            deleted_at: deleted_at.and_then(parse_rfc3339),
+        }
+    }
+}
+
+impl From<grpc::GetStoreResponse> for StoreInfo {
+    fn from(store: grpc::GetStoreResponse) -> Self {
+        Self::from_grpc(
+            store.id,
+            store.name,
+            store.created_at,
+            store.updated_at,
+            store.deleted_at,
+        )
+    }
+}
+
+impl From<grpc::Store> for StoreInfo {
+    fn from(store: grpc::Store) -> Self {
+        Self::from_grpc(
+            store.id,
+            store.name,
+            store.created_at,
+            store.updated_at,
+            store.deleted_at,
+        )
+    }
+}
+
+impl From<http::GetStoreResponse> for StoreInfo {
+    fn from(store: http::GetStoreResponse) -> Self {
+        Self::from_http(
+            store.id,
+            store.name,
+            &store.created_at,
+            &store.updated_at,
+            store.deleted_at.as_deref(),
+        )
+    }
+}
+
+impl From<http::Store> for StoreInfo {
+    fn from(store: http::Store) -> Self {
+        Self::from_http(
+            store.id,
+            store.name,
+            &store.created_at,
+            &store.updated_at,
+            store.deleted_at.as_deref(),
+        )
+    }
+}
+
+fn parse_rfc3339(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+fn into_object(value: Value) -> Option<Map<String, Value>> {
+    match value {
+        Value::Object(map) => Some(map),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_check_tuple_round_trip() {
+        let tuple: Tuple = serde_json::from_value(json!({
+            "user": "user:alice",
+            "relation": "viewer",
+            "object": "document:readme"
+        }))
+        .unwrap();
+
+        let grpc_key = grpc::CheckRequestTupleKey::from(tuple.clone());
+        let http_key = http::CheckRequestTupleKey::from(tuple.clone());
+        assert_eq!(
+            (&grpc_key.user, &grpc_key.relation, &grpc_key.object),
+            (&http_key.user, &http_key.relation, &http_key.object)
+        );
+        assert_eq!(
+            serde_json::to_value(&http_key).unwrap(),
+            serde_json::to_value(&tuple).unwrap()
+        );
+
+        let conditioned = Tuple {
+            condition: Some(Condition {
+                name: "in_region".to_string(),
+                context: Some(into_object(json!({ "region": "eu" })).unwrap()),
+            }),
+            ..tuple
+        };
+        let grpc_key = grpc::TupleKey::try_from(conditioned.clone()).unwrap();
+        assert_eq!(Tuple::from(grpc_key), conditioned);
+        assert_eq!(
+            Tuple::from(http::TupleKey::from(conditioned.clone())),
+            conditioned
+        );
+    }
+
+    #[test]
+    fn test_store_info_from_get_store_response() {
+        let response = grpc::GetStoreResponse {
+            id: "01HSTORE".to_string(),
+            name: "demo".to_string(),
+            created_at: Some(prost_wkt_types::Timestamp {
+                seconds: 1_700_000_000,
+                nanos: 500_000_000,
+            }),
+            updated_at: Some(prost_wkt_types::Timestamp {
+                seconds: 1_700_000_060,
+                nanos: 0,
+            }),
+            deleted_at: None,
+        };
+
+        let store = StoreInfo::from(response);
+        assert_eq!(store.id, "01HSTORE");
+        assert_eq!(store.name, "demo");
+        assert_eq!(
+            store.created_at,
+            DateTime::from_timestamp(1_700_000_000, 500_000_000).unwrap()
+        );
+        assert_eq!(
+            store.updated_at,
+            DateTime::from_timestamp(1_700_000_060, 0).unwrap()
+        );
+        assert_eq!(store.deleted_at, None);
+
+        let mut response = http::GetStoreResponse::new(
+            "01HSTORE".to_string(),
+            "demo".to_string(),
+            "2023-11-14T22:13:20.5Z".to_string(),
+            "2023-11-14T22:14:20Z".to_string(),
+        );
+        assert_eq!(StoreInfo::from(response.clone()), store);
+
+        response.deleted_at = Some("2023-11-15T00:00:00Z".to_string());
+        assert_eq!(
+            StoreInfo::from(response).deleted_at,
+            Some(DateTime::from_timestamp(1_700_006_400, 0).unwrap())
+        );
+    }
+}
@@ -0,0 +1,231 @@
+use axum::http::StatusCode;
+use openfga_grpc_client::{
+    AuthErrorCode, ErrorCode, InternalErrorCode, NotFoundErrorCode, openfga_error_code,
+};
+use openfga_http_client::apis::Error;
+
+use crate::problem::grpc_code_to_http;
+
+/// Error of an OpenFGA client call, with the HTTP status to answer it with
+pub trait OpenFgaError: std::fmt::Display {
+    fn http_status(&self) -> StatusCode;
+}
+
+impl OpenFgaError for tonic::Status {
+    fn http_status(&self) -> StatusCode {
+        grpc_error_status(self)
+    }
+}
+
+impl<T> OpenFgaError for Error<T> {
+    fn http_status(&self) -> StatusCode {
+        http_error_status(self)
+    }
+}
+
+/// HTTP status of an OpenFGA error code, by its name in OpenFGA's error bodies,
+/// e.g. `store_id_not_found`. `None` for names OpenFGA doesn't define.
+pub fn openfga_code_status(code: &str) -> Option<StatusCode> {
+    if let Some(code) = NotFoundErrorCode::from_str_name(code) {
+        return match code {
+            NotFoundErrorCode::NoNotFoundError => None,
+            NotFoundErrorCode::UndefinedEndpoint | NotFoundErrorCode::StoreIdNotFound => {
+                Some(StatusCode::NOT_FOUND)
+            }
+            NotFoundErrorCode::Unimplemented => Some(StatusCode::NOT_IMPLEMENTED),
+        };
+    }
+    if let Some(code) = ErrorCode::from_str_name(code) {
+        return match code {
+            ErrorCode::NoError => None,
+            ErrorCode::AuthorizationModelNotFound
+            | ErrorCode::LatestAuthorizationModelNotFound
+            | ErrorCode::AuthorizationModelAssertionsNotFound => Some(StatusCode::NOT_FOUND),
+            _ => Some(StatusCode::BAD_REQUEST),
+        };
+    }
+    if let Some(code) = AuthErrorCode::from_str_name(code) {
+        return match code {
+            AuthErrorCode::NoAuthError => None,
+            AuthErrorCode::Forbidden => Some(StatusCode::FORBIDDEN),
+            _ => Some(StatusCode::UNAUTHORIZED),
+        };
+    }
+    InternalErrorCode::from_str_name(code)
+        .filter(|code| *code != InternalErrorCode::NoInternalError)
+        .map(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Name of an OpenFGA error code number, e.g. `invalid_tuple` for 2027
+fn openfga_code_name(code: i32) -> Option<&'static str> {
+    ErrorCode::try_from(code)
+        .map(|code| code.as_str_name())
+        .or_else(|_| NotFoundErrorCode::try_from(code).map(|code| code.as_str_name()))
+        .or_else(|_| AuthErrorCode::try_from(code).map(|code| code.as_str_name()))
+        .or_else(|_| InternalErrorCode::try_from(code).map(|code| code.as_str_name()))
+        .ok()
+}
+
+/// HTTP status of a failed OpenFGA gRPC call.
+///
+/// OpenFGA answers with its own codes, e.g. 5002 for `store_id_not_found`, which tonic
+/// reads as `Unknown`. The client's channel keeps the number (see
+/// [`openfga_grpc_client::ErrorCodeService`]), which is mapped like the codes of the
+/// HTTP API. Without it, `Unknown` errors are told apart by their message and the
+/// rest are mapped with [`grpc_code_to_http`].
+pub fn grpc_error_status(status: &tonic::Status) -> StatusCode {
+    let openfga_status = openfga_error_code(status)
+        .and_then(openfga_code_name)
+        .and_then(openfga_code_status);
+    if let Some(openfga_status) = openfga_status {
+        return openfga_status;
+    }
+
+    match status.code() {
+        tonic::Code::Unknown if status.message().to_lowercase().contains("not found") => {
+            StatusCode::NOT_FOUND
+        }
+        code => grpc_code_to_http(code),
+    }
+}
+
+/// HTTP status of a failed OpenFGA HTTP call: the status of the `code` of OpenFGA's
+/// error body, else the response's own status for client errors. Transport and
+/// decoding errors are 500.
+pub fn http_error_status<T>(error: &Error<T>) -> StatusCode {
+    let Error::ResponseError(response) = error else {
+        return StatusCode::INTERNAL_SERVER_ERROR;
+    };
+
+    let code = serde_json::from_str::<serde_json::Value>(&response.content)
+        .ok()
+        .and_then(|body| body["code"].as_str().and_then(openfga_code_status));
+    match code {
+        Some(status) => status,
+        None if response.status.is_client_error() => response.status,
+        None => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openfga_http_client::apis::ResponseContent;
+
+    fn response_error(status: u16, content: &str) -> Error<()> {
+        Error::ResponseError(ResponseContent {
+            status: reqwest::StatusCode::from_u16(status).unwrap(),
+            content: content.to_string(),
+            entity: None,
+        })
+    }
+
+    #[test]
+    fn test_openfga_code_status() {
+        let cases = [
+            ("store_id_not_found", StatusCode::NOT_FOUND),
+            ("authorization_model_not_found", StatusCode::NOT_FOUND),
+            (
+                "latest_authorization_model_not_found",
+                StatusCode::NOT_FOUND,
+            ),
+            ("validation_error", StatusCode::BAD_REQUEST),
+            ("invalid_tuple", StatusCode::BAD_REQUEST),
+            ("unauthenticated", StatusCode::UNAUTHORIZED),
+            ("bearer_token_missing", StatusCode::UNAUTHORIZED),
+            ("forbidden", StatusCode::FORBIDDEN),
+            ("internal_error", StatusCode::INTERNAL_SERVER_ERROR),
+        ];
+        for (code, status) in cases {
+            assert_eq!(openfga_code_status(code), Some(status), "{}", code);
+        }
+        assert_eq!(openfga_code_status("no_error"), None);
+        assert_eq!(openfga_code_status("not_a_code"), None);
+    }
+
+    /// Status of a call answered with OpenFGA's own `code`, as the client's channel
+    /// receives it
+    async fn openfga_status(code: &str, message: &str) -> tonic::Status {
+        use axum::http;
+        use openfga_grpc_client::ErrorCodeService;
+        use tower::{Service, service_fn};
+
+        let (code, message) = (code.to_string(), message.to_string());
+        let mut channel = ErrorCodeService::new(service_fn(move |_: http::Request<()>| {
+            let mut response = http::Response::new(tonic::body::empty_body());
+            let headers = response.headers_mut();
+            headers.insert("grpc-status", code.parse().unwrap());
+            headers.insert("grpc-message", message.parse().unwrap());
+            async move { Ok::<_, std::convert::Infallible>(response) }
+        }));
+        let response = channel.call(http::Request::new(())).await.unwrap();
+        tonic::Status::from_header_map(response.headers()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_grpc_error_status_of_openfga_codes() {
+        let cases = [
+            ("2027", "invalid tuple", StatusCode::BAD_REQUEST),
+            ("2000", "invalid relation", StatusCode::BAD_REQUEST),
+            ("2001", "model 'x' missing", StatusCode::NOT_FOUND),
+            ("5002", "no such store", StatusCode::NOT_FOUND),
+            ("4000", "internal", StatusCode::INTERNAL_SERVER_ERROR),
+        ];
+        for (code, message, expected) in cases {
+            let status = openfga_status(code, message).await;
+            assert_eq!(status.code(), tonic::Code::Unknown);
+            assert_eq!(grpc_error_status(&status), expected, "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_grpc_error_status() {
+        let store_not_found = tonic::Status::new(tonic::Code::Unknown, "store not found");
+        assert_eq!(grpc_error_status(&store_not_found), StatusCode::NOT_FOUND);
+        let model_not_found =
+            tonic::Status::new(tonic::Code::Unknown, "Authorization Model 'x' not found");
+        assert_eq!(grpc_error_status(&model_not_found), StatusCode::NOT_FOUND);
+        assert_eq!(
+            grpc_error_status(&tonic::Status::invalid_argument("invalid tuple")),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            grpc_error_status(&tonic::Status::unauthenticated("unauthenticated")),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            grpc_error_status(&tonic::Status::unknown("boom")),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_http_error_status() {
+        let body = |code: &str| format!(r#"{{"code":"{}","message":"..."}}"#, code);
+        assert_eq!(
+            http_error_status(&response_error(404, &body("store_id_not_found"))),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            http_error_status(&response_error(400, &body("authorization_model_not_found"))),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            http_error_status(&response_error(400, &body("validation_error"))),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            http_error_status(&response_error(401, &body("unauthenticated"))),
+            StatusCode::UNAUTHORIZED
+        );
+        // Without a known code, client errors keep their status
+        assert_eq!(
+            http_error_status(&response_error(401, "")),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            http_error_status(&response_error(503, "")),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
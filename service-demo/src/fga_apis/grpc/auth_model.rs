@@ -1,15 +1,18 @@
 use std::collections::HashMap;
 
 use crate::context::Ctx;
+use crate::fga_apis::errors::grpc_error_status;
+use crate::fga_apis::page::Page;
 use axum::{
     Json,
     extract::{Path, Query, State},
     http::StatusCode,
 };
-use openfga_grpc_client::JsonAuthModel;
 use openfga_grpc_client::{
     Condition, ReadAuthorizationModelRequest, ReadAuthorizationModelsRequest, TypeDefinition,
-    WriteAuthorizationModelRequest,
+};
+use openfga_grpc_client::{
+    JsonAuthModel, ModelValidationError, WriteModelRequestBuilder, model_schema,
 };
 use serde_json::Value;
 
@@ -26,12 +29,15 @@ pub async fn create_auth_model(
     Json(req): Json<CreateAuthModelReq>,
 ) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
     tracing::info!("Creating auth model for store: {}", store_id);
-    let create_request = WriteAuthorizationModelRequest {
-        store_id: store_id.clone(),
-        type_definitions: req.type_definitions,
-        schema_version: req.schema_version.unwrap_or_else(|| "1.1".to_string()),
-        conditions: req.conditions.unwrap_or_else(|| HashMap::new()),
-    };
+    let mut builder =
+        WriteModelRequestBuilder::new(store_id.clone()).type_definitions(req.type_definitions);
+    if let Some(schema_version) = req.schema_version {
+        builder = builder.schema_version(schema_version);
+    }
+    if let Some(conditions) = req.conditions {
+        builder = builder.conditions(conditions);
+    }
+    let create_request = builder.build().map_err(invalid_model)?;
 
     let create_response = match ctx
         .fga_client
@@ -42,22 +48,34 @@ pub async fn create_auth_model(
         Ok(create_response) => create_response,
         Err(e) => {
             return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                grpc_error_status(&e),
                 Json(serde_json::json!({ "message": e.to_string() })),
             ));
         }
     };
 
     tracing::info!("Auth model created for store: {}", store_id);
+    let create_response = create_response.into_inner();
+    ctx.fga_config
+        .model_created(&store_id, &create_response.authorization_model_id);
 
     Ok((
         StatusCode::OK,
         Json(
-            serde_json::json!({ "message": "Auth model created", "create_response": create_response.into_inner() }),
+            serde_json::json!({ "message": "Auth model created", "create_response": create_response }),
         ),
     ))
 }
 
+/// Map local model validation errors to a 400 response listing every issue
+pub fn invalid_model(errors: Vec<ModelValidationError>) -> (StatusCode, Json<Value>) {
+    tracing::warn!("Rejecting invalid auth model: {} issue(s)", errors.len());
+    (
+        StatusCode::BAD_REQUEST,
+        Json(serde_json::json!({ "message": "Invalid authorization model", "issues": errors })),
+    )
+}
+
 // New endpoint that accepts JSON format from OpenFGA playground
 pub async fn create_auth_model_from_json(
     State(ctx): State<Ctx>,
@@ -67,8 +85,8 @@ pub async fn create_auth_model_from_json(
     tracing::info!("Creating auth model from JSON for store: {}", store_id);
 
     // Convert our JSON types to OpenFGA types
-    let (type_definitions, schema_version, conditions) = match json_model.to_openfga_types() {
-        Ok(result) => result,
+    let converted = match json_model.to_openfga_types_with_warnings() {
+        Ok(converted) => converted,
         Err(e) => {
             tracing::error!("Failed to convert JSON to OpenFGA types: {}", e);
             return Err((
@@ -78,6 +96,11 @@ pub async fn create_auth_model_from_json(
         }
     };
 
+    for warning in &converted.warnings {
+        tracing::warn!("Auth model conversion for store {}: {}", store_id, warning);
+    }
+    let type_definitions = converted.type_definitions;
+
     // Debug log the converted type definitions
     for type_def in &type_definitions {
         tracing::info!("Type: {}", type_def.r#type);
@@ -95,12 +118,12 @@ pub async fn create_auth_model_from_json(
         }
     }
 
-    let create_request = WriteAuthorizationModelRequest {
-        store_id: store_id.clone(),
-        type_definitions,
-        schema_version,
-        conditions,
-    };
+    let create_request = WriteModelRequestBuilder::new(store_id.clone())
+        .schema_version(converted.schema_version)
+        .type_definitions(type_definitions)
+        .conditions(converted.conditions)
+        .build()
+        .map_err(invalid_model)?;
 
     let create_response = match ctx
         .fga_client
@@ -112,19 +135,23 @@ pub async fn create_auth_model_from_json(
         Err(e) => {
             tracing::error!("Failed to create auth model: {}", e);
             return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                grpc_error_status(&e),
                 Json(serde_json::json!({ "error": e.to_string() })),
             ));
         }
     };
 
     tracing::info!("Auth model created from JSON for store: {}", store_id);
+    let authorization_model_id = create_response.into_inner().authorization_model_id;
+    ctx.fga_config
+        .model_created(&store_id, &authorization_model_id);
 
     Ok((
         StatusCode::OK,
         Json(serde_json::json!({
             "message": "Auth model created from JSON",
-            "authorization_model_id": create_response.into_inner().authorization_model_id
+            "authorization_model_id": authorization_model_id,
+            "warnings": converted.warnings,
         })),
     ))
 }
@@ -149,7 +176,7 @@ pub async fn get_auth_model(
         Err(e) => {
             tracing::error!("Failed to get auth model: {}", e);
             return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                grpc_error_status(&e),
                 Json(serde_json::json!({ "error": e.to_string() })),
             ));
         }
@@ -177,7 +204,7 @@ pub async fn list_auth_models(
     tracing::info!("Listing auth models for store: {}", store_id);
     let list_request = ReadAuthorizationModelsRequest {
         store_id: store_id.clone(),
-        page_size: query.page_size,
+        page_size: Some(ctx.fga_config.page_size(query.page_size)),
         continuation_token: query.continuation_token.unwrap_or_else(|| String::new()),
     };
 
@@ -191,16 +218,63 @@ pub async fn list_auth_models(
         Err(e) => {
             tracing::error!("Failed to list auth models: {}", e);
             return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                grpc_error_status(&e),
                 Json(serde_json::json!({ "error": e.to_string() })),
             ));
         }
     };
 
-    Ok((
-        StatusCode::OK,
-        Json(
-            serde_json::json!({ "message": "Auth models listed", "list_response": list_response.into_inner() }),
-        ),
-    ))
+    let list_response = list_response.into_inner();
+    let page = Page::new(
+        list_response.authorization_models,
+        list_response.continuation_token,
+    );
+
+    Ok((StatusCode::OK, Json(serde_json::json!(page))))
+}
+
+/// Types of the store's latest model and their relation names, for permission-aware UIs
+pub async fn get_schema_summary(
+    State(ctx): State<Ctx>,
+    Path(store_id): Path<String>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    tracing::info!("Getting schema summary for store: {}", store_id);
+    let list_request = ReadAuthorizationModelsRequest {
+        store_id: store_id.clone(),
+        page_size: Some(1),
+        continuation_token: String::new(),
+    };
+
+    let latest = match ctx
+        .fga_client
+        .clone()
+        .read_authorization_models(list_request)
+        .await
+    {
+        Ok(list_response) => list_response
+            .into_inner()
+            .authorization_models
+            .into_iter()
+            .next(),
+        Err(e) => {
+            tracing::error!("Failed to read latest auth model: {}", e);
+            return Err((
+                grpc_error_status(&e),
+                Json(serde_json::json!({ "error": e.to_string() })),
+            ));
+        }
+    };
+
+    match latest {
+        Some(model) => Ok((
+            StatusCode::OK,
+            Json(serde_json::json!(model_schema(&model))),
+        )),
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": format!("store {} has no authorization model", store_id)
+            })),
+        )),
+    }
 }
@@ -1,91 +1,62 @@
 use axum::{Json, extract::State, http::StatusCode};
 use openfga_grpc_client::{
-    BatchCheckItem, BatchCheckRequest, CheckRequest, CheckRequestTupleKey, ConsistencyPreference,
-    ExpandRequest, ExpandRequestTupleKey, ListObjectsRequest, ListUsersRequest,
+    BatchCheckItem, BatchCheckRequest, ConsistencyPreference, ContextualTupleKeys,
 };
 use serde_json::Value;
 
 use crate::context::Ctx;
-
-#[derive(Debug, serde::Deserialize)]
-pub struct CheckReq {
-    pub user: String,
-    pub object: String,
-    pub relation: String,
-}
-
-pub async fn check(
-    State(ctx): State<Ctx>,
-    Json(req): Json<CheckReq>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    let check_request = CheckRequest {
-        store_id: ctx.fga_config.store_id.clone(),
-        tuple_key: Some(CheckRequestTupleKey {
-            user: req.user,
-            object: req.object,
-            relation: req.relation,
-        }),
-        authorization_model_id: ctx.fga_config.authorization_model_id.clone(),
-        consistency: ConsistencyPreference::HigherConsistency as i32,
-        context: None,
-        trace: false,
-        contextual_tuples: None,
-    };
-
-    tracing::info!(
-        "Checking if user has relation to object: {:?}",
-        check_request
-    );
-    let check_response = match ctx.fga_client.clone().check(check_request).await {
-        Ok(check_response) => check_response,
-        Err(e) => {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({ "error": e.to_string() })),
-            ));
-        }
-    };
-    Ok((
-        StatusCode::OK,
-        Json(serde_json::json!({ "check_response": check_response.into_inner() })),
-    ))
-}
+use crate::fga_apis::contextual_tuples::current_contextual_tuples;
+use crate::fga_apis::dto::{Consistency, Tuple};
+use crate::fga_apis::errors::grpc_error_status;
+use crate::fga_apis::relationships::StoreReq;
 
 #[derive(Debug, serde::Deserialize)]
 pub struct BatchCheckItemReq {
-    tuple: CheckReq,
+    /// Checked without its condition
+    tuple: Tuple,
     id: String,
 }
 
 #[derive(Debug, serde::Deserialize)]
 pub struct BatchCheckReq {
+    /// Store and model to check against, the configured ones by default
+    #[serde(flatten)]
+    pub store: StoreReq,
     pub checks: Vec<BatchCheckItemReq>,
+    /// Overrides the configured consistency of the operation
+    #[serde(default)]
+    pub consistency: Option<Consistency>,
 }
 
-pub async fn batch_check(
-    State(ctx): State<Ctx>,
-    Json(req): Json<BatchCheckReq>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    let batch_check_request = BatchCheckRequest {
-        store_id: ctx.fga_config.store_id.clone(),
-        authorization_model_id: ctx.fga_config.authorization_model_id.clone(),
-        consistency: ConsistencyPreference::HigherConsistency as i32,
+fn batch_check_request(ctx: &Ctx, req: BatchCheckReq) -> BatchCheckRequest {
+    let contextual_tuples = current_contextual_tuples();
+    let store = req.store.resolve(ctx);
+    BatchCheckRequest {
+        store_id: store.store_id,
+        authorization_model_id: store.authorization_model_id.unwrap_or_default(),
+        consistency: ConsistencyPreference::from(
+            req.consistency.unwrap_or(ctx.fga_config.consistency.check),
+        ) as i32,
         checks: req
             .checks
             .into_iter()
             .map(|check| BatchCheckItem {
-                tuple_key: Some(CheckRequestTupleKey {
-                    user: check.tuple.user,
-                    object: check.tuple.object,
-                    relation: check.tuple.relation,
+                tuple_key: Some(check.tuple.into()),
+                contextual_tuples: (!contextual_tuples.is_empty()).then(|| ContextualTupleKeys {
+                    tuple_keys: contextual_tuples.clone(),
                 }),
-                contextual_tuples: None,
                 context: None,
                 correlation_id: check.id,
             })
             .collect(),
-    };
+    }
+}
 
+pub async fn batch_check(
+    State(ctx): State<Ctx>,
+    Json(req): Json<BatchCheckReq>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    let batch_check_request = batch_check_request(&ctx, req);
     let batch_check_response = match ctx
         .fga_client
         .clone()
@@ -95,7 +66,7 @@ pub async fn batch_check(
         Ok(batch_check_response) => batch_check_response,
         Err(e) => {
             return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                grpc_error_status(&e),
                 Json(serde_json::json!({ "error": e.to_string() })),
             ));
         }
@@ -107,145 +78,31 @@ pub async fn batch_check(
     ))
 }
 
-#[derive(Debug, serde::Deserialize)]
-pub struct ExpandReq {
-    pub object: String,
-    pub relation: String,
-}
-
-pub async fn expand(
-    State(ctx): State<Ctx>,
-    Json(req): Json<ExpandReq>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    let expand_request = ExpandRequest {
-        store_id: ctx.fga_config.store_id.clone(),
-        authorization_model_id: ctx.fga_config.authorization_model_id.clone(),
-        consistency: ConsistencyPreference::HigherConsistency as i32,
-        contextual_tuples: None,
-        tuple_key: Some(ExpandRequestTupleKey {
-            object: req.object,
-            relation: req.relation,
-        }),
-    };
-
-    let expand_response = match ctx.fga_client.clone().expand(expand_request).await {
-        Ok(expand_response) => expand_response,
-        Err(e) => {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({ "error": e.to_string() })),
-            ));
-        }
-    };
-
-    Ok((
-        StatusCode::OK,
-        Json(serde_json::json!({ "expand_response": expand_response.into_inner() })),
-    ))
-}
-
-// List Users associated with an object for a type
-
-#[derive(Debug, serde::Deserialize)]
-pub struct UserTypeFilterReq {
-    pub r#type: String,
-    pub relation: String,
-}
-
-#[derive(Debug, serde::Deserialize)]
-pub struct ObjectReq {
-    pub r#type: String,
-    pub id: String,
-}
-
-#[derive(Debug, serde::Deserialize)]
-pub struct ListUsersReq {
-    pub relation: String,
-    pub user_filters: Vec<UserTypeFilterReq>,
-    pub object: ObjectReq,
-}
-
-pub async fn list_users(
-    State(ctx): State<Ctx>,
-    Json(tuple): Json<ListUsersReq>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    let list_request = ListUsersRequest {
-        store_id: ctx.fga_config.store_id.clone(),
-        authorization_model_id: ctx.fga_config.authorization_model_id.clone(),
-        relation: tuple.relation.clone(),
-        object: Some(openfga_grpc_client::Object {
-            r#type: tuple.object.r#type.clone(),
-            id: tuple.object.id.clone(),
-        }),
-        user_filters: tuple
-            .user_filters
-            .into_iter()
-            .map(|f| openfga_grpc_client::UserTypeFilter {
-                r#type: f.r#type.clone(),
-                relation: f.relation.clone(),
-            })
-            .collect(),
-        contextual_tuples: vec![],
-        context: None,
-        consistency: ConsistencyPreference::MinimizeLatency as i32,
-    };
-
-    let list_response = match ctx.fga_client.clone().list_users(list_request).await {
-        Ok(list_response) => list_response,
-        Err(e) => {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({ "message": e.to_string() })),
-            ));
-        }
-    };
-
-    Ok((
-        StatusCode::OK,
-        Json(
-            serde_json::json!({ "message": "Users listed", "list_response": list_response.into_inner() }),
-        ),
-    ))
-}
-
-// List All the Objects for a given type that user has relation with
-
-#[derive(Debug, serde::Deserialize)]
-pub struct ListObjsRequest {
-    pub r#type: String,
-    pub relation: String,
-    pub user: String,
-}
-
-pub async fn list_objects(
-    State(ctx): State<Ctx>,
-    Json(tuple): Json<ListObjsRequest>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    let list_request = ListObjectsRequest {
-        store_id: ctx.fga_config.store_id.clone(),
-        authorization_model_id: ctx.fga_config.authorization_model_id.clone(),
-        r#type: tuple.r#type.clone(),
-        relation: tuple.relation.clone(),
-        user: tuple.user.clone(),
-        contextual_tuples: None,
-        context: None,
-        consistency: ConsistencyPreference::MinimizeLatency as i32,
-    };
-
-    let list_response = match ctx.fga_client.clone().list_objects(list_request).await {
-        Ok(list_response) => list_response,
-        Err(e) => {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({ "message": e.to_string() })),
-            ));
-        }
-    };
-
-    Ok((
-        StatusCode::OK,
-        Json(
-            serde_json::json!({ "message": "Objects listed", "list_response": list_response.into_inner() }),
-        ),
-    ))
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_batch_check_model_override() {
+        let ctx = Ctx::for_test();
+        let checks = json!([{
+            "id": "1",
+            "tuple": { "user": "user:anne", "relation": "viewer", "object": "resource:roadmap" }
+        }]);
+
+        let req = serde_json::from_value(json!({ "checks": checks })).unwrap();
+        let request = batch_check_request(&ctx, req);
+        assert_eq!(request.store_id, "store");
+        assert_eq!(request.authorization_model_id, "model");
+
+        let req = serde_json::from_value(json!({
+            "authorization_model_id": "01HMODELV2",
+            "checks": checks
+        }))
+        .unwrap();
+        let request = batch_check_request(&ctx, req);
+        assert_eq!(request.authorization_model_id, "01HMODELV2");
+        assert_eq!(request.checks[0].correlation_id, "1");
+    }
 }
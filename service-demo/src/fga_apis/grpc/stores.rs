@@ -8,17 +8,26 @@ use openfga_grpc_client::{
 };
 use serde_json::Value;
 
+use crate::auth::db_ops;
 use crate::context::Ctx;
+use crate::fga_apis::dto::StoreInfo;
+use crate::fga_apis::errors::grpc_error_status;
+use crate::fga_apis::page::Page;
+use crate::fga_apis::store_access::StoreAccess;
+use crate::fga_apis::store_deletion::{ConfirmDeleteQuery, delete_if_name_matches};
 
 #[derive(Debug, serde::Deserialize)]
 pub struct CreateStoreReq {
     pub name: String,
 }
 
+/// Create a store and allow the session's organization to operate on it
 pub async fn create_store(
     State(ctx): State<Ctx>,
+    access: StoreAccess,
     Json(tuple): Json<CreateStoreReq>,
 ) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    let org_id = access.org_id(&ctx).await?;
     let create_request = CreateStoreRequest {
         name: tuple.name.clone(),
     };
@@ -27,17 +36,18 @@ pub async fn create_store(
         Ok(create_response) => create_response,
         Err(e) => {
             return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                grpc_error_status(&e),
                 Json(serde_json::json!({ "message": e.to_string() })),
             ));
         }
     };
 
+    let create_response = create_response.into_inner();
+    record_org_store(&ctx, org_id, &create_response.id).await?;
+
     Ok((
         StatusCode::OK,
-        Json(
-            serde_json::json!({ "message": "Store created", "create_response": create_response.into_inner() }),
-        ),
+        Json(serde_json::json!({ "message": "Store created", "create_response": create_response })),
     ))
 }
 
@@ -56,18 +66,14 @@ pub async fn get_store(
         Ok(get_response) => get_response,
         Err(e) => {
             return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                grpc_error_status(&e),
                 Json(serde_json::json!({ "message": e.to_string() })),
             ));
         }
     };
 
-    Ok((
-        StatusCode::OK,
-        Json(
-            serde_json::json!({ "message": "Store fetched", "get_response": get_response.into_inner() }),
-        ),
-    ))
+    let store = StoreInfo::from(get_response.into_inner());
+    Ok((StatusCode::OK, Json(serde_json::json!(store))))
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -77,12 +83,16 @@ pub struct ListStoresQuery {
     pub name: Option<String>,
 }
 
+/// List the stores of the session's organization. Stores of other organizations are
+/// left out, so a page may hold fewer than `page_size` stores.
 pub async fn list_stores(
     State(ctx): State<Ctx>,
+    access: StoreAccess,
     Query(tuple): Query<ListStoresQuery>,
 ) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    let store_ids = access.store_ids(&ctx).await?;
     let list_request = ListStoresRequest {
-        page_size: tuple.page_size,
+        page_size: Some(ctx.fga_config.page_size(tuple.page_size)),
         continuation_token: tuple.continuation_token.unwrap_or_else(|| String::new()),
         name: tuple.name.unwrap_or_else(|| String::new()),
     };
@@ -91,18 +101,22 @@ pub async fn list_stores(
         Ok(list_response) => list_response,
         Err(e) => {
             return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                grpc_error_status(&e),
                 Json(serde_json::json!({ "message": e.to_string() })),
             ));
         }
     };
 
-    Ok((
-        StatusCode::OK,
-        Json(
-            serde_json::json!({ "message": "Stores listed", "list_response": list_response.into_inner() }),
-        ),
-    ))
+    let list_response = list_response.into_inner();
+    let stores: Vec<StoreInfo> = list_response
+        .stores
+        .into_iter()
+        .filter(|store| store_ids.contains(store.id.as_str()))
+        .map(Into::into)
+        .collect();
+    let page = Page::new(stores, list_response.continuation_token);
+
+    Ok((StatusCode::OK, Json(serde_json::json!(page))))
 }
 
 pub async fn delete_store(
@@ -118,7 +132,7 @@ pub async fn delete_store(
         Ok(delete_response) => delete_response,
         Err(e) => {
             return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                grpc_error_status(&e),
                 Json(serde_json::json!({ "message": e.to_string() })),
             ));
         }
@@ -126,6 +140,7 @@ pub async fn delete_store(
 
     tracing::info!("Delete response: {:?}", delete_response);
     tracing::info!("Store deleted: {}", store_id);
+    forget_org_store(&ctx, &store_id).await;
 
     Ok((
         StatusCode::OK,
@@ -134,3 +149,60 @@ pub async fn delete_store(
         ),
     ))
 }
+
+/// Delete a store after checking the `name` query parameter is the store's name
+pub async fn delete_store_confirmed(
+    State(ctx): State<Ctx>,
+    Path(store_id): Path<String>,
+    Query(query): Query<ConfirmDeleteQuery>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    let client = &ctx.fga_client;
+    delete_if_name_matches(
+        &query.name,
+        async || {
+            let request = GetStoreRequest {
+                store_id: store_id.clone(),
+            };
+            Ok::<_, tonic::Status>(client.clone().get_store(request).await?.into_inner().name)
+        },
+        async || {
+            let request = DeleteStoreRequest {
+                store_id: store_id.clone(),
+            };
+            client.clone().delete_store(request).await.map(|_| ())
+        },
+    )
+    .await?;
+
+    tracing::info!("Store deleted: {}", store_id);
+    forget_org_store(&ctx, &store_id).await;
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({ "message": "Store deleted" })),
+    ))
+}
+
+/// Map a created store to the organization that created it
+pub(crate) async fn record_org_store(
+    ctx: &Ctx,
+    org_id: &str,
+    store_id: &str,
+) -> Result<(), (StatusCode, Json<Value>)> {
+    db_ops::insert_org_store(&ctx.db, org_id, store_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to map store {} to {}: {:?}", store_id, org_id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "message": e.to_string() })),
+            )
+        })
+}
+
+/// Drop a deleted store's organization mapping; the store is gone either way, so a
+/// failure is only logged
+pub(crate) async fn forget_org_store(ctx: &Ctx, store_id: &str) {
+    if let Err(e) = db_ops::delete_org_store(&ctx.db, store_id).await {
+        tracing::warn!("Failed to unmap deleted store {}: {:?}", store_id, e);
+    }
+}
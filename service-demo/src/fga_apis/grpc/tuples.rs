@@ -1,114 +1,95 @@
 use axum::{Json, extract::State, http::StatusCode};
-use openfga_grpc_client::{
-    ConsistencyPreference, ReadChangesRequest, ReadRequest, ReadRequestTupleKey, TupleKey,
-    TupleKeyWithoutCondition, WriteRequest, WriteRequestDeletes, WriteRequestWrites,
-};
+use chrono::{DateTime, Utc};
+use openfga_grpc_client::{ReadChangesRequest, TupleChange, TupleKey, TupleOperation};
 use serde_json::{Value, json};
 
 use crate::context::Ctx;
+use crate::fga_apis::errors::grpc_error_status;
+use crate::time_utils::{chrono_to_timestamp, timestamp_to_chrono};
 
-pub async fn write_tuple(
-    State(ctx): State<Ctx>,
-    Json(tuple): Json<TupleKey>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    let write_request = WriteRequest {
-        authorization_model_id: ctx.fga_config.authorization_model_id.clone(),
-        store_id: ctx.fga_config.store_id.clone(),
-        deletes: None,
-        writes: Some(WriteRequestWrites {
-            tuple_keys: vec![tuple],
-            on_duplicate: "ignore".to_string(),
-        }),
-    };
-
-    let write_response = match ctx.fga_client.clone().write(write_request).await {
-        Ok(write_response) => write_response,
-        Err(e) => {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "message": e.to_string() })),
-            ));
-        }
-    };
+#[derive(Debug, serde::Deserialize)]
+pub struct TupleChangesRequest {
+    pub r#type: String,
+    pub page_size: Option<i32>,
+    pub continuation_token: Option<String>,
+    /// RFC 3339 timestamp to read changes from
+    pub start_time: Option<DateTime<Utc>>,
+}
 
-    Ok((
-        StatusCode::OK,
-        Json(json!({ "message": "Tuple created", "write_response": write_response.into_inner() })),
-    ))
+/// Operation recorded in the tuple changelog
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeOp {
+    Write,
+    Delete,
 }
 
-pub async fn read_tuple(
-    State(ctx): State<Ctx>,
-    Json(tuple): Json<ReadRequestTupleKey>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    let read_request = ReadRequest {
-        store_id: ctx.fga_config.store_id.clone(),
-        tuple_key: Some(tuple),
-        page_size: Some(100),
-        continuation_token: String::new(),
-        consistency: ConsistencyPreference::HigherConsistency as i32,
-    };
+impl From<TupleOperation> for ChangeOp {
+    fn from(operation: TupleOperation) -> Self {
+        match operation {
+            TupleOperation::Write => ChangeOp::Write,
+            TupleOperation::Delete => ChangeOp::Delete,
+        }
+    }
+}
 
-    let read_response = match ctx.fga_client.clone().read(read_request).await {
-        Ok(read_response) => read_response,
-        Err(e) => {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "message": e.to_string() })),
-            ));
+impl From<ChangeOp> for TupleOperation {
+    fn from(operation: ChangeOp) -> Self {
+        match operation {
+            ChangeOp::Write => TupleOperation::Write,
+            ChangeOp::Delete => TupleOperation::Delete,
         }
-    };
+    }
+}
 
-    Ok((
-        StatusCode::OK,
-        Json(json!({ "message": "Tuple read", "read_response": read_response.into_inner() })),
-    ))
+/// Typed representation of a single tuple change
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct TupleChangeDto {
+    pub tuple_key: TupleKey,
+    pub operation: ChangeOp,
+    pub timestamp: DateTime<Utc>,
 }
 
-pub async fn delete_tuple(
-    State(ctx): State<Ctx>,
-    Json(tuple): Json<TupleKeyWithoutCondition>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    let delete_request = WriteRequest {
-        authorization_model_id: ctx.fga_config.authorization_model_id.clone(),
-        store_id: ctx.fga_config.store_id.clone(),
-        deletes: Some(WriteRequestDeletes {
-            tuple_keys: vec![tuple],
-            on_missing: "error".to_string(),
-        }),
-        writes: None,
-    };
+impl TryFrom<TupleChange> for TupleChangeDto {
+    type Error = String;
 
-    let delete_response = match ctx.fga_client.clone().write(delete_request).await {
-        Ok(delete_response) => delete_response,
-        Err(e) => {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "message": e.to_string() })),
-            ));
-        }
-    };
+    fn try_from(change: TupleChange) -> Result<Self, Self::Error> {
+        let tuple_key = change
+            .tuple_key
+            .ok_or_else(|| "Tuple change is missing tuple_key".to_string())?;
 
-    Ok((
-        StatusCode::OK,
-        Json(
-            json!({ "message": "Tuple deleted", "delete_response": delete_response.into_inner() }),
-        ),
-    ))
+        let operation = TupleOperation::try_from(change.operation)
+            .map_err(|_| format!("Unknown tuple operation: {}", change.operation))?;
+
+        let timestamp = change
+            .timestamp
+            .as_ref()
+            .map(timestamp_to_chrono)
+            .ok_or_else(|| "Tuple change is missing timestamp".to_string())?;
+
+        Ok(Self {
+            tuple_key,
+            operation: operation.into(),
+            timestamp,
+        })
+    }
 }
 
-#[derive(Debug, serde::Deserialize)]
-pub struct Timestamp {
-    pub seconds: i64,
-    pub nanos: i32,
+impl From<TupleChangeDto> for TupleChange {
+    fn from(change: TupleChangeDto) -> Self {
+        Self {
+            tuple_key: Some(change.tuple_key),
+            operation: TupleOperation::from(change.operation) as i32,
+            timestamp: Some(chrono_to_timestamp(change.timestamp)),
+        }
+    }
 }
 
-#[derive(Debug, serde::Deserialize)]
-pub struct TupleChangesRequest {
-    pub r#type: String,
-    pub page_size: Option<i32>,
-    pub continuation_token: Option<String>,
-    pub start_time: Option<Timestamp>,
+/// Typed tuple changes page returned by the `tuple_changes` handler
+#[derive(Debug, serde::Serialize)]
+pub struct TupleChangesResponse {
+    pub changes: Vec<TupleChangeDto>,
+    pub continuation_token: String,
 }
 
 pub async fn tuple_changes(
@@ -118,14 +99,9 @@ pub async fn tuple_changes(
     let tuple_changes_request = ReadChangesRequest {
         store_id: ctx.fga_config.store_id.clone(),
         r#type: tuple.r#type,
-        page_size: Some(100),
-        continuation_token: String::new(),
-        start_time: tuple
-            .start_time
-            .map(|timestamp| prost_wkt_types::Timestamp {
-                seconds: timestamp.seconds,
-                nanos: timestamp.nanos,
-            }),
+        page_size: Some(ctx.fga_config.page_size(tuple.page_size)),
+        continuation_token: tuple.continuation_token.unwrap_or_default(),
+        start_time: tuple.start_time.map(chrono_to_timestamp),
     };
 
     let tuple_changes_response = match ctx
@@ -137,16 +113,111 @@ pub async fn tuple_changes(
         Ok(tuple_changes_response) => tuple_changes_response,
         Err(e) => {
             return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                grpc_error_status(&e),
                 Json(json!({ "message": e.to_string() })),
             ));
         }
     };
 
+    let tuple_changes_response = tuple_changes_response.into_inner();
+    let changes = match tuple_changes_response
+        .changes
+        .into_iter()
+        .map(TupleChangeDto::try_from)
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(changes) => changes,
+        Err(e) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": e })),
+            ));
+        }
+    };
+
     Ok((
         StatusCode::OK,
-        Json(
-            json!({ "message": "Tuple changes", "tuple_changes_response": tuple_changes_response.into_inner() }),
-        ),
+        Json(json!({
+            "message": "Tuple changes",
+            "tuple_changes_response": TupleChangesResponse {
+                changes,
+                continuation_token: tuple_changes_response.continuation_token,
+            }
+        })),
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fga_apis::bodies::ReadBody;
+    use crate::fga_apis::dto::Consistency;
+    use crate::fga_apis::relationships::ReadReq;
+    use openfga_grpc_client::ConsistencyPreference;
+
+    #[test]
+    fn test_tuple_change_dto_roundtrip() {
+        let change = TupleChange {
+            tuple_key: Some(TupleKey {
+                user: "user:alice".to_string(),
+                relation: "viewer".to_string(),
+                object: "document:readme".to_string(),
+                condition: None,
+            }),
+            operation: TupleOperation::Write as i32,
+            timestamp: Some(prost_wkt_types::Timestamp {
+                seconds: 1_700_000_000,
+                nanos: 123_000_000,
+            }),
+        };
+
+        let dto = TupleChangeDto::try_from(change.clone()).unwrap();
+        assert_eq!(dto.operation, ChangeOp::Write);
+        assert_eq!(dto.tuple_key.user, "user:alice");
+        assert_eq!(dto.timestamp.timestamp(), 1_700_000_000);
+        assert_eq!(dto.timestamp.timestamp_subsec_nanos(), 123_000_000);
+
+        assert_eq!(TupleChange::from(dto), change);
+    }
+
+    #[tokio::test]
+    async fn test_read_tuple_consistency() {
+        let ctx = Ctx::for_test();
+        let consistency = |request: &ReadReq| {
+            request
+                .consistency
+                .unwrap_or(ctx.fga_config.consistency.read)
+        };
+
+        let body: ReadBody = serde_json::from_value(json!({
+            "user": "user:alice",
+            "relation": "viewer",
+            "object": "document:readme"
+        }))
+        .unwrap();
+        let request = ReadReq::from(body);
+        assert_eq!(request.filter.object.as_deref(), Some("document:readme"));
+        assert_eq!(consistency(&request), Consistency::Unspecified);
+        assert_eq!(
+            ConsistencyPreference::from(consistency(&request)),
+            ConsistencyPreference::Unspecified
+        );
+
+        let body: ReadBody = serde_json::from_value(json!({
+            "user": "user:alice",
+            "relation": "viewer",
+            "object": "document:",
+            "consistency": "higher_consistency"
+        }))
+        .unwrap();
+        let request = ReadReq::from(body);
+        assert_eq!(
+            ConsistencyPreference::from(consistency(&request)),
+            ConsistencyPreference::HigherConsistency
+        );
+        assert_eq!(
+            ConsistencyPreference::from(Consistency::MinimizeLatency),
+            ConsistencyPreference::MinimizeLatency
+        );
+    }
+}
@@ -1,33 +1,89 @@
-use axum::{Json, extract::Path, extract::State, http::StatusCode};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use openfga_grpc_client::{Condition, JsonAuthModel, WriteModelRequestBuilder};
 use openfga_http_client::apis::authorization_models_api;
 use openfga_http_client::models::{AuthorizationModel, WriteAuthorizationModelRequest};
 use serde_json::Value;
 
 use crate::context::Ctx;
+use crate::fga_apis::errors::http_error_status;
+use crate::fga_apis::grpc::auth_model::{ReadAuthorizationModelsQuery, invalid_model};
+use crate::fga_apis::http::retry::HttpCall;
+use crate::fga_apis::page::Page;
 
 /// Create a new authorization model using HTTP client
 pub async fn create_auth_model(
     State(ctx): State<Ctx>,
     Path(store_id): Path<String>,
-    Json(req): Json<WriteAuthorizationModelRequest>,
+    Json(mut req): Json<WriteAuthorizationModelRequest>,
 ) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    req.schema_version = validate_model(&store_id, &req)?;
+
     match authorization_models_api::write_authorization_model(&ctx.fga_http_config, &store_id, req)
         .await
     {
-        Ok(response) => Ok((
-            StatusCode::CREATED,
-            Json(serde_json::to_value(response).unwrap_or_default()),
-        )),
+        Ok(response) => {
+            ctx.fga_config
+                .model_created(&store_id, &response.authorization_model_id);
+            Ok((
+                StatusCode::CREATED,
+                Json(serde_json::to_value(response).unwrap_or_default()),
+            ))
+        }
         Err(e) => {
             tracing::error!("Failed to create authorization model via HTTP: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                http_error_status(&e),
                 Json(serde_json::json!({ "error": e.to_string() })),
             ))
         }
     }
 }
 
+/// Validate the model locally, returning the canonical schema version
+fn validate_model(
+    store_id: &str,
+    req: &WriteAuthorizationModelRequest,
+) -> Result<String, (StatusCode, Json<Value>)> {
+    let conversion_failed = |e: String| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": format!("Conversion failed: {}", e) })),
+        )
+    };
+
+    let model: JsonAuthModel = serde_json::to_value(req)
+        .and_then(serde_json::from_value)
+        .map_err(|e| conversion_failed(e.to_string()))?;
+    let (type_definitions, _, _) = model.to_openfga_types().map_err(conversion_failed)?;
+
+    // Only the condition names are needed to validate references to them
+    let conditions = req
+        .conditions
+        .iter()
+        .flatten()
+        .map(|(name, _)| {
+            let condition = Condition {
+                name: name.clone(),
+                ..Default::default()
+            };
+            (name.clone(), condition)
+        })
+        .collect();
+
+    let validated = WriteModelRequestBuilder::new(store_id)
+        .schema_version(req.schema_version.clone())
+        .type_definitions(type_definitions)
+        .conditions(conditions)
+        .build()
+        .map_err(invalid_model)?;
+
+    Ok(validated.schema_version)
+}
+
 /// Create authorization model from JSON (convenience endpoint)
 pub async fn create_auth_model_from_json(
     State(ctx): State<Ctx>,
@@ -49,13 +105,17 @@ pub async fn get_auth_model(
     State(ctx): State<Ctx>,
     Path((store_id, auth_model_id)): Path<(String, String)>,
 ) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    match authorization_models_api::read_authorization_model(
-        &ctx.fga_http_config,
-        &store_id,
-        &auth_model_id,
-    )
-    .await
-    {
+    let result = ctx
+        .fga_http_retry
+        .call(HttpCall::Read, || {
+            authorization_models_api::read_authorization_model(
+                &ctx.fga_http_config,
+                &store_id,
+                &auth_model_id,
+            )
+        })
+        .await;
+    match result {
         Ok(response) => Ok((
             StatusCode::OK,
             Json(serde_json::to_value(response).unwrap_or_default()),
@@ -63,7 +123,7 @@ pub async fn get_auth_model(
         Err(e) => {
             tracing::error!("Failed to get authorization model via HTTP: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                http_error_status(&e),
                 Json(serde_json::json!({ "error": e.to_string() })),
             ))
         }
@@ -74,23 +134,31 @@ pub async fn get_auth_model(
 pub async fn list_auth_models(
     State(ctx): State<Ctx>,
     Path(store_id): Path<String>,
+    Query(query): Query<ReadAuthorizationModelsQuery>,
 ) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    match authorization_models_api::read_authorization_models(
-        &ctx.fga_http_config,
-        &store_id,
-        None,
-        None,
-    )
-    .await
-    {
+    let result = ctx
+        .fga_http_retry
+        .call(HttpCall::Read, || {
+            authorization_models_api::read_authorization_models(
+                &ctx.fga_http_config,
+                &store_id,
+                Some(ctx.fga_config.page_size(query.page_size)),
+                query.continuation_token.as_deref(),
+            )
+        })
+        .await;
+    match result {
         Ok(response) => Ok((
             StatusCode::OK,
-            Json(serde_json::to_value(response).unwrap_or_default()),
+            Json(serde_json::json!(Page::new(
+                response.authorization_models,
+                response.continuation_token
+            ))),
         )),
         Err(e) => {
             tracing::error!("Failed to list authorization models via HTTP: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                http_error_status(&e),
                 Json(serde_json::json!({ "error": e.to_string() })),
             ))
         }
@@ -1,4 +1,5 @@
 pub mod auth_model;
 pub mod query;
+pub mod retry;
 pub mod stores;
 pub mod tuples;
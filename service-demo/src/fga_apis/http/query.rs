@@ -1,17 +1,12 @@
 use axum::{Json, extract::State, http::StatusCode};
 use openfga_http_client::apis::relationship_queries_api;
-use openfga_http_client::models::{
-    BatchCheckRequest, CheckRequest, ExpandRequest, ListObjectsRequest, ListUsersRequest,
-};
+use openfga_http_client::models::BatchCheckRequest;
 use serde_json::Value;
 
 use crate::context::Ctx;
-
-#[derive(Debug, serde::Deserialize)]
-pub struct CheckReq {
-    pub store_id: String,
-    pub check_request: CheckRequest,
-}
+use crate::fga_apis::errors::http_error_status;
+use crate::fga_apis::http::retry::HttpCall;
+use crate::fga_apis::relationships::StoreReq;
 
 #[derive(Debug, serde::Deserialize)]
 pub struct BatchCheckReq {
@@ -19,58 +14,34 @@ pub struct BatchCheckReq {
     pub batch_check_request: BatchCheckRequest,
 }
 
-#[derive(Debug, serde::Deserialize)]
-pub struct ExpandReq {
-    pub store_id: String,
-    pub expand_request: ExpandRequest,
-}
-
-#[derive(Debug, serde::Deserialize)]
-pub struct ListObjectsReq {
-    pub store_id: String,
-    pub list_objects_request: ListObjectsRequest,
-}
-
-#[derive(Debug, serde::Deserialize)]
-pub struct ListUsersReq {
-    pub store_id: String,
-    pub list_users_request: ListUsersRequest,
-}
-
-/// Check authorization using HTTP client
-pub async fn check(
+/// Batch check authorization using HTTP client.
+///
+/// Checks against the configured model when the request targets the configured
+/// store without naming a model; any other store must belong to the caller's
+/// organization.
+pub async fn batch_check(
     State(ctx): State<Ctx>,
-    Json(req): Json<CheckReq>,
+    Json(mut req): Json<BatchCheckReq>,
 ) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    match relationship_queries_api::check(&ctx.fga_http_config, &req.store_id, req.check_request)
-        .await
-    {
-        Ok(response) => Ok((
-            StatusCode::OK,
-            Json(serde_json::to_value(response).unwrap_or_default()),
-        )),
-        Err(e) => {
-            tracing::error!("Failed to check authorization via HTTP: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({ "error": e.to_string() })),
-            ))
-        }
+    let store = StoreReq {
+        store_id: Some(req.store_id),
+        authorization_model_id: req.batch_check_request.authorization_model_id.take(),
     }
-}
+    .resolve(&ctx);
+    req.store_id = store.store_id;
+    req.batch_check_request.authorization_model_id = store.authorization_model_id;
 
-/// Batch check authorization using HTTP client
-pub async fn batch_check(
-    State(ctx): State<Ctx>,
-    Json(req): Json<BatchCheckReq>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    match relationship_queries_api::batch_check(
-        &ctx.fga_http_config,
-        &req.store_id,
-        req.batch_check_request,
-    )
-    .await
-    {
+    let result = ctx
+        .fga_http_retry
+        .call(HttpCall::Read, || {
+            relationship_queries_api::batch_check(
+                &ctx.fga_http_config,
+                &req.store_id,
+                req.batch_check_request.clone(),
+            )
+        })
+        .await;
+    match result {
         Ok(response) => Ok((
             StatusCode::OK,
             Json(serde_json::to_value(response).unwrap_or_default()),
@@ -78,83 +49,43 @@ pub async fn batch_check(
         Err(e) => {
             tracing::error!("Failed to batch check authorization via HTTP: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                http_error_status(&e),
                 Json(serde_json::json!({ "error": e.to_string() })),
             ))
         }
     }
 }
 
-/// Expand relationships using HTTP client
-pub async fn expand(
-    State(ctx): State<Ctx>,
-    Json(req): Json<ExpandReq>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    match relationship_queries_api::expand(&ctx.fga_http_config, &req.store_id, req.expand_request)
-        .await
-    {
-        Ok(response) => Ok((
-            StatusCode::OK,
-            Json(serde_json::to_value(response).unwrap_or_default()),
-        )),
-        Err(e) => {
-            tracing::error!("Failed to expand relationships via HTTP: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({ "error": e.to_string() })),
-            ))
-        }
-    }
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fga_apis::relationships::CheckPath;
+    use axum::{Router, body::Body, extract::Path, http::Request, routing::get};
+    use openfga_http_client::models::CheckRequest;
+    use tower::ServiceExt;
 
-/// List objects using HTTP client
-pub async fn list_objects(
-    State(ctx): State<Ctx>,
-    Json(req): Json<ListObjectsReq>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    match relationship_queries_api::list_objects(
-        &ctx.fga_http_config,
-        &req.store_id,
-        req.list_objects_request,
-    )
-    .await
-    {
-        Ok(response) => Ok((
-            StatusCode::OK,
-            Json(serde_json::to_value(response).unwrap_or_default()),
-        )),
-        Err(e) => {
-            tracing::error!("Failed to list objects via HTTP: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({ "error": e.to_string() })),
-            ))
-        }
-    }
-}
+    #[tokio::test]
+    async fn test_check_path_decodes_segments() {
+        let app = Router::new().route(
+            "/stores/{store_id}/check/{object}/{relation}/{user}",
+            get(|Path(path): Path<CheckPath>| async move {
+                Json(serde_json::to_value(CheckRequest::new(path.tuple().into())).unwrap())
+            }),
+        );
 
-/// List users using HTTP client
-pub async fn list_users(
-    State(ctx): State<Ctx>,
-    Json(req): Json<ListUsersReq>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    match relationship_queries_api::list_users(
-        &ctx.fga_http_config,
-        &req.store_id,
-        req.list_users_request,
-    )
-    .await
-    {
-        Ok(response) => Ok((
-            StatusCode::OK,
-            Json(serde_json::to_value(response).unwrap_or_default()),
-        )),
-        Err(e) => {
-            tracing::error!("Failed to list users via HTTP: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({ "error": e.to_string() })),
-            ))
-        }
+        let request = Request::builder()
+            .uri("/stores/01HXYZ/check/document%3Areadme/viewer/group%3Aeng%23member")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let check: CheckRequest = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(check.tuple_key.object, "document:readme");
+        assert_eq!(check.tuple_key.relation, "viewer");
+        assert_eq!(check.tuple_key.user, "group:eng#member");
     }
 }
@@ -0,0 +1,227 @@
+use openfga_http_client::apis::Error;
+use std::future::Future;
+use std::time::Duration;
+
+/// Whether an OpenFGA HTTP call can be repeated without changing the store
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpCall {
+    /// Check, read, list and expand calls
+    Read,
+    /// Writes, which may have been applied when the response was lost
+    Write,
+}
+
+/// Retry policy of the OpenFGA HTTP calls.
+///
+/// Read from the `OPENFGA_HTTP_RETRY_*` environment variables. Calls are retried on
+/// 5xx responses, timeouts and connection errors, with the delay doubling from
+/// `OPENFGA_HTTP_RETRY_INITIAL_BACKOFF_MS` up to `OPENFGA_HTTP_RETRY_MAX_BACKOFF_MS`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRetryConfig {
+    /// `OPENFGA_HTTP_RETRY_MAX_RETRIES`, retries after the first attempt; `0`
+    /// disables retries. Defaults to `2`.
+    pub max_retries: u32,
+    /// `OPENFGA_HTTP_RETRY_INITIAL_BACKOFF_MS`, defaults to 100ms
+    pub initial_backoff: Duration,
+    /// `OPENFGA_HTTP_RETRY_MAX_BACKOFF_MS`, defaults to 2s
+    pub max_backoff: Duration,
+    /// `OPENFGA_HTTP_RETRY_WRITES`, off by default
+    pub retry_writes: bool,
+}
+
+impl Default for HttpRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(2),
+            retry_writes: false,
+        }
+    }
+}
+
+impl HttpRetryConfig {
+    /// Build from `OPENFGA_HTTP_RETRY_*` variables; unset variables keep their default
+    pub fn from_vars(vars: impl IntoIterator<Item = (String, String)>) -> anyhow::Result<Self> {
+        let invalid = |key: &str, value: &str| anyhow::anyhow!("invalid {}: {:?}", key, value);
+
+        let mut config = Self::default();
+        for (key, value) in vars {
+            match key.as_str() {
+                "OPENFGA_HTTP_RETRY_MAX_RETRIES" => {
+                    config.max_retries = value.parse().map_err(|_| invalid(&key, &value))?;
+                }
+                "OPENFGA_HTTP_RETRY_INITIAL_BACKOFF_MS" => {
+                    let millis = value.parse().map_err(|_| invalid(&key, &value))?;
+                    config.initial_backoff = Duration::from_millis(millis);
+                }
+                "OPENFGA_HTTP_RETRY_MAX_BACKOFF_MS" => {
+                    let millis = value.parse().map_err(|_| invalid(&key, &value))?;
+                    config.max_backoff = Duration::from_millis(millis);
+                }
+                "OPENFGA_HTTP_RETRY_WRITES" => {
+                    config.retry_writes = value == "true" || value == "1";
+                }
+                _ => {}
+            }
+        }
+        Ok(config)
+    }
+
+    /// Run `op`, repeating it on transient errors as the policy allows for `call`.
+    /// Returns the first success or non-transient error, or the last error once the
+    /// retries are used up.
+    pub async fn call<T, E, F, Fut>(&self, call: HttpCall, mut op: F) -> Result<T, Error<E>>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, Error<E>>>,
+    {
+        let max_retries = match call {
+            HttpCall::Write if !self.retry_writes => 0,
+            _ => self.max_retries,
+        };
+        let mut delay = self.initial_backoff;
+        let mut retries = 0;
+
+        loop {
+            match op().await {
+                Err(e) if retries < max_retries && is_transient(&e) => {
+                    retries += 1;
+                    tracing::warn!(
+                        "OpenFGA HTTP call failed: {}. Retry {} of {} in {:?}",
+                        e,
+                        retries,
+                        max_retries,
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(self.max_backoff);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Whether an HTTP call may succeed when repeated: 5xx responses, timeouts and
+/// connection errors
+pub fn is_transient<E>(error: &Error<E>) -> bool {
+    match error {
+        Error::ResponseError(response) => response.status.is_server_error(),
+        Error::Reqwest(e) => e.is_timeout() || e.is_connect(),
+        Error::Serde(_) | Error::Io(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fga_apis::backend::{FgaBackend, HttpBackend, StoreRef};
+    use crate::fga_apis::dto::{Consistency, Tuple};
+    use axum::{Json, Router, http::StatusCode, routing::post};
+    use openfga_http_client::apis::configuration::Configuration;
+    use serde_json::{Value, json};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Serve OpenFGA's check and write routes, failing the first `failures` calls
+    /// with 503. Returns the base URL and the number of calls served.
+    async fn flaky_openfga(failures: u32) -> (String, Arc<AtomicU32>) {
+        let calls = Arc::new(AtomicU32::new(0));
+        let respond = |calls: Arc<AtomicU32>, body: Value| {
+            move || async move {
+                if calls.fetch_add(1, Ordering::SeqCst) < failures {
+                    Err(StatusCode::SERVICE_UNAVAILABLE)
+                } else {
+                    Ok(Json(body))
+                }
+            }
+        };
+        let app = Router::new()
+            .route(
+                "/stores/{store_id}/check",
+                post(respond(calls.clone(), json!({ "allowed": true }))),
+            )
+            .route(
+                "/stores/{store_id}/write",
+                post(respond(calls.clone(), json!({}))),
+            );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (format!("http://{}", address), calls)
+    }
+
+    fn backend(base_path: String) -> HttpBackend {
+        let config = Configuration {
+            base_path,
+            ..Configuration::default()
+        };
+        let retry = HttpRetryConfig {
+            initial_backoff: Duration::from_millis(1),
+            ..HttpRetryConfig::default()
+        };
+        HttpBackend::new(config, retry)
+    }
+
+    fn store() -> StoreRef {
+        StoreRef {
+            store_id: "store".to_string(),
+            authorization_model_id: None,
+        }
+    }
+
+    fn tuple() -> Tuple {
+        Tuple {
+            user: "user:anne".to_string(),
+            relation: "viewer".to_string(),
+            object: "document:readme".to_string(),
+            condition: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_is_retried_after_503() {
+        let (base_path, calls) = flaky_openfga(1).await;
+
+        let allowed = backend(base_path)
+            .check(&store(), tuple(), None, Consistency::Unspecified)
+            .await
+            .unwrap();
+        assert!(allowed);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_write_is_not_retried_by_default() {
+        let (base_path, calls) = flaky_openfga(1).await;
+
+        let result = backend(base_path)
+            .write(&store(), vec![tuple()], Vec::new())
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_retry_config_from_env_vars() {
+        let vars = [
+            ("OPENFGA_HTTP_RETRY_MAX_RETRIES", "5"),
+            ("OPENFGA_HTTP_RETRY_INITIAL_BACKOFF_MS", "50"),
+            ("OPENFGA_HTTP_RETRY_WRITES", "true"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        let config = HttpRetryConfig::from_vars(vars).unwrap();
+        assert_eq!(config.max_retries, 5);
+        assert_eq!(config.initial_backoff, Duration::from_millis(50));
+        assert_eq!(config.max_backoff, Duration::from_secs(2));
+        assert!(config.retry_writes);
+
+        let invalid = [(
+            "OPENFGA_HTTP_RETRY_MAX_RETRIES".to_string(),
+            "-1".to_string(),
+        )];
+        assert!(HttpRetryConfig::from_vars(invalid).is_err());
+    }
+}
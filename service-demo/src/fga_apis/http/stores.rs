@@ -1,24 +1,41 @@
-use axum::{Json, extract::Path, extract::State, http::StatusCode};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
 use openfga_http_client::apis::stores_api;
 use openfga_http_client::models::CreateStoreRequest;
 use serde_json::Value;
 
 use crate::context::Ctx;
+use crate::fga_apis::dto::StoreInfo;
+use crate::fga_apis::errors::http_error_status;
+use crate::fga_apis::grpc::stores::{ListStoresQuery, forget_org_store, record_org_store};
+use crate::fga_apis::http::retry::HttpCall;
+use crate::fga_apis::page::Page;
+use crate::fga_apis::store_access::StoreAccess;
+use crate::fga_apis::store_deletion::{ConfirmDeleteQuery, delete_if_name_matches};
 
-/// Create a new store using HTTP client
+/// Create a new store using HTTP client, allowing the session's organization to
+/// operate on it
 pub async fn create_store(
     State(ctx): State<Ctx>,
+    access: StoreAccess,
     Json(req): Json<CreateStoreRequest>,
 ) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    let org_id = access.org_id(&ctx).await?;
     match stores_api::create_store(&ctx.fga_http_config, req).await {
-        Ok(response) => Ok((
-            StatusCode::CREATED,
-            Json(serde_json::to_value(response).unwrap_or_default()),
-        )),
+        Ok(response) => {
+            record_org_store(&ctx, org_id, &response.id).await?;
+            Ok((
+                StatusCode::CREATED,
+                Json(serde_json::to_value(response).unwrap_or_default()),
+            ))
+        }
         Err(e) => {
             tracing::error!("Failed to create store via HTTP: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                http_error_status(&e),
                 Json(serde_json::json!({ "error": e.to_string() })),
             ))
         }
@@ -30,34 +47,63 @@ pub async fn get_store(
     State(ctx): State<Ctx>,
     Path(store_id): Path<String>,
 ) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    match stores_api::get_store(&ctx.fga_http_config, &store_id).await {
+    let result = ctx
+        .fga_http_retry
+        .call(HttpCall::Read, || {
+            stores_api::get_store(&ctx.fga_http_config, &store_id)
+        })
+        .await;
+    match result {
         Ok(response) => Ok((
             StatusCode::OK,
-            Json(serde_json::to_value(response).unwrap_or_default()),
+            Json(serde_json::json!(StoreInfo::from(response))),
         )),
         Err(e) => {
             tracing::error!("Failed to get store via HTTP: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                http_error_status(&e),
                 Json(serde_json::json!({ "error": e.to_string() })),
             ))
         }
     }
 }
 
-/// List all stores using HTTP client
+/// List the stores of the session's organization using HTTP client. Stores of other
+/// organizations are left out, so a page may hold fewer stores than OpenFGA returned.
 pub async fn list_stores(
     State(ctx): State<Ctx>,
+    access: StoreAccess,
+    Query(query): Query<ListStoresQuery>,
 ) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    match stores_api::list_stores(&ctx.fga_http_config, None, None, None).await {
+    let store_ids = access.store_ids(&ctx).await?;
+    let result = ctx
+        .fga_http_retry
+        .call(HttpCall::Read, || {
+            stores_api::list_stores(
+                &ctx.fga_http_config,
+                Some(ctx.fga_config.page_size(query.page_size)),
+                query.continuation_token.as_deref(),
+                query.name.as_deref(),
+            )
+        })
+        .await;
+    match result {
         Ok(response) => Ok((
             StatusCode::OK,
-            Json(serde_json::to_value(response).unwrap_or_default()),
+            Json(serde_json::json!(Page::new(
+                response
+                    .stores
+                    .into_iter()
+                    .filter(|store| store_ids.contains(store.id.as_str()))
+                    .map(StoreInfo::from)
+                    .collect::<Vec<_>>(),
+                response.continuation_token
+            ))),
         )),
         Err(e) => {
             tracing::error!("Failed to list stores via HTTP: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                http_error_status(&e),
                 Json(serde_json::json!({ "error": e.to_string() })),
             ))
         }
@@ -70,16 +116,44 @@ pub async fn delete_store(
     Path(store_id): Path<String>,
 ) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
     match stores_api::delete_store(&ctx.fga_http_config, &store_id).await {
-        Ok(_) => Ok((
-            StatusCode::NO_CONTENT,
-            Json(serde_json::json!({ "message": "Store deleted successfully" })),
-        )),
+        Ok(_) => {
+            forget_org_store(&ctx, &store_id).await;
+            Ok((
+                StatusCode::NO_CONTENT,
+                Json(serde_json::json!({ "message": "Store deleted successfully" })),
+            ))
+        }
         Err(e) => {
             tracing::error!("Failed to delete store via HTTP: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                http_error_status(&e),
                 Json(serde_json::json!({ "error": e.to_string() })),
             ))
         }
     }
 }
+
+/// Delete a store using HTTP client after checking the `name` query parameter is
+/// the store's name
+pub async fn delete_store_confirmed(
+    State(ctx): State<Ctx>,
+    Path(store_id): Path<String>,
+    Query(query): Query<ConfirmDeleteQuery>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    delete_if_name_matches(
+        &query.name,
+        async || {
+            stores_api::get_store(&ctx.fga_http_config, &store_id)
+                .await
+                .map(|store| store.name)
+        },
+        async || stores_api::delete_store(&ctx.fga_http_config, &store_id).await,
+    )
+    .await?;
+    forget_org_store(&ctx, &store_id).await;
+
+    Ok((
+        StatusCode::NO_CONTENT,
+        Json(serde_json::json!({ "message": "Store deleted successfully" })),
+    ))
+}
@@ -1,26 +1,68 @@
 use axum::{Json, extract::State, http::StatusCode};
+use chrono::{DateTime, Utc};
 use openfga_http_client::apis::relationship_tuples_api;
-use openfga_http_client::models::{ReadRequest, WriteRequest};
-use serde_json::Value;
+use openfga_http_client::models::{
+    RelationshipCondition, TupleKey, WriteRequest, WriteRequestWrites,
+};
+use serde_json::{Map, Value};
 
 use crate::context::Ctx;
+use crate::fga_apis::errors::http_error_status;
+use crate::fga_apis::http::retry::HttpCall;
+use crate::fga_apis::identifiers::validate_tuple;
+use crate::time_utils::chrono_to_rfc3339;
 
-#[derive(Debug, serde::Deserialize)]
-pub struct WriteTupleRequest {
-    pub store_id: String,
-    pub write_request: WriteRequest,
+/// Condition attached to a written tuple
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct TupleCondition {
+    /// Name of a condition defined in the authorization model
+    pub name: String,
+    /// Values for the condition's parameters
+    pub context: Option<Map<String, Value>>,
 }
 
-#[derive(Debug, serde::Deserialize)]
-pub struct ReadTupleRequest {
-    pub store_id: String,
-    pub read_request: ReadRequest,
+/// Tuple with an optional ABAC condition
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct ConditionalTuple {
+    pub user: String,
+    pub relation: String,
+    pub object: String,
+    pub condition: Option<TupleCondition>,
+}
+
+impl From<ConditionalTuple> for TupleKey {
+    fn from(tuple: ConditionalTuple) -> Self {
+        TupleKey {
+            user: tuple.user,
+            relation: tuple.relation,
+            object: tuple.object,
+            condition: tuple.condition.map(|condition| {
+                Box::new(RelationshipCondition {
+                    name: condition.name,
+                    context: condition.context.map(Value::Object),
+                })
+            }),
+        }
+    }
 }
 
 #[derive(Debug, serde::Deserialize)]
-pub struct DeleteTupleRequest {
+pub struct WriteConditionalTuplesRequest {
     pub store_id: String,
-    pub write_request: WriteRequest, // Uses WriteRequest with deletes field
+    pub authorization_model_id: Option<String>,
+    pub tuples: Vec<ConditionalTuple>,
+}
+
+impl WriteConditionalTuplesRequest {
+    /// Build the generated write request for the tuples
+    pub fn to_write_request(&self) -> WriteRequest {
+        let tuple_keys = self.tuples.iter().cloned().map(TupleKey::from).collect();
+        WriteRequest {
+            writes: Some(Box::new(WriteRequestWrites::new(tuple_keys))),
+            deletes: None,
+            authorization_model_id: self.authorization_model_id.clone(),
+        }
+    }
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -29,68 +71,38 @@ pub struct TupleChangesRequest {
     pub r#type: Option<String>,
     pub page_size: Option<i32>,
     pub continuation_token: Option<String>,
-    pub start_time: Option<String>,
-}
-
-/// Write tuples using HTTP client
-pub async fn write_tuple(
-    State(ctx): State<Ctx>,
-    Json(req): Json<WriteTupleRequest>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    match relationship_tuples_api::write(&ctx.fga_http_config, &req.store_id, req.write_request)
-        .await
-    {
-        Ok(response) => Ok((
-            StatusCode::OK,
-            Json(serde_json::to_value(response).unwrap_or_default()),
-        )),
-        Err(e) => {
-            tracing::error!("Failed to write tuple via HTTP: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({ "error": e.to_string() })),
-            ))
-        }
-    }
+    /// RFC 3339 timestamp to read changes from
+    pub start_time: Option<DateTime<Utc>>,
 }
 
-/// Read tuples using HTTP client
-pub async fn read_tuple(
+/// Write tuples with optional conditions using HTTP client
+pub async fn write_conditional_tuples(
     State(ctx): State<Ctx>,
-    Json(req): Json<ReadTupleRequest>,
+    Json(req): Json<WriteConditionalTuplesRequest>,
 ) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    match relationship_tuples_api::read(&ctx.fga_http_config, &req.store_id, req.read_request).await
-    {
-        Ok(response) => Ok((
-            StatusCode::OK,
-            Json(serde_json::to_value(response).unwrap_or_default()),
-        )),
-        Err(e) => {
-            tracing::error!("Failed to read tuple via HTTP: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({ "error": e.to_string() })),
-            ))
-        }
+    for tuple in &req.tuples {
+        validate_tuple(&tuple.user, &tuple.relation, &tuple.object)?;
     }
-}
 
-/// Delete tuples using HTTP client
-pub async fn delete_tuple(
-    State(ctx): State<Ctx>,
-    Json(req): Json<DeleteTupleRequest>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    match relationship_tuples_api::write(&ctx.fga_http_config, &req.store_id, req.write_request)
-        .await
-    {
+    let result = ctx
+        .fga_http_retry
+        .call(HttpCall::Write, || {
+            relationship_tuples_api::write(
+                &ctx.fga_http_config,
+                &req.store_id,
+                req.to_write_request(),
+            )
+        })
+        .await;
+    match result {
         Ok(response) => Ok((
             StatusCode::OK,
             Json(serde_json::to_value(response).unwrap_or_default()),
         )),
         Err(e) => {
-            tracing::error!("Failed to delete tuple via HTTP: {}", e);
+            tracing::error!("Failed to write conditional tuples via HTTP: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                http_error_status(&e),
                 Json(serde_json::json!({ "error": e.to_string() })),
             ))
         }
@@ -102,16 +114,20 @@ pub async fn tuple_changes(
     State(ctx): State<Ctx>,
     Json(req): Json<TupleChangesRequest>,
 ) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    match relationship_tuples_api::read_changes(
-        &ctx.fga_http_config,
-        &req.store_id,
-        req.r#type.clone().as_deref(),
-        req.page_size,
-        req.continuation_token.as_deref(),
-        req.start_time,
-    )
-    .await
-    {
+    let result = ctx
+        .fga_http_retry
+        .call(HttpCall::Read, || {
+            relationship_tuples_api::read_changes(
+                &ctx.fga_http_config,
+                &req.store_id,
+                req.r#type.as_deref(),
+                Some(ctx.fga_config.page_size(req.page_size)),
+                req.continuation_token.as_deref(),
+                req.start_time.map(chrono_to_rfc3339),
+            )
+        })
+        .await;
+    match result {
         Ok(response) => Ok((
             StatusCode::OK,
             Json(serde_json::to_value(response).unwrap_or_default()),
@@ -119,9 +135,47 @@ pub async fn tuple_changes(
         Err(e) => {
             tracing::error!("Failed to get tuple changes via HTTP: {}", e);
             Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
+                http_error_status(&e),
                 Json(serde_json::json!({ "error": e.to_string() })),
             ))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_conditional_tuple_keeps_condition_context() {
+        let body = json!({
+            "store_id": "01HXYZ",
+            "tuples": [
+                {
+                    "user": "user:alice",
+                    "relation": "viewer",
+                    "object": "document:readme",
+                    "condition": {
+                        "name": "in_office_hours",
+                        "context": { "start": "09:00", "timezone": "UTC" }
+                    }
+                },
+                { "user": "user:bob", "relation": "viewer", "object": "document:readme" }
+            ]
+        });
+        let req: WriteConditionalTuplesRequest = serde_json::from_value(body).unwrap();
+
+        let write_request = req.to_write_request();
+        let tuple_keys = &write_request.writes.unwrap().tuple_keys;
+
+        let condition = tuple_keys[0].condition.as_deref().unwrap();
+        assert_eq!(condition.name, "in_office_hours");
+        assert_eq!(
+            condition.context,
+            Some(json!({ "start": "09:00", "timezone": "UTC" }))
+        );
+        assert!(tuple_keys[1].condition.is_none());
+        assert!(write_request.deletes.is_none());
+    }
+}
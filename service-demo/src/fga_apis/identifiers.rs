@@ -0,0 +1,186 @@
+use axum::{Json, http::StatusCode};
+use serde_json::{Value, json};
+
+const MAX_TYPE_LEN: usize = 254;
+const MAX_ID_LEN: usize = 256;
+const MAX_RELATION_LEN: usize = 50;
+
+/// Position an identifier takes in a tuple key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdentifierKind {
+    /// `type:id`
+    Object,
+    /// `type:id`, `type:id#relation` or `type:*`
+    User,
+}
+
+/// Validate an object or user identifier before it is used in a tuple key.
+///
+/// Accepted grammar:
+///
+/// ```text
+/// object    = type ":" id
+/// user      = type ":" id [ "#" relation ]   ; userset, e.g. group:eng#member
+///           | type ":*"                      ; type-bound wildcard
+/// type      = 1*254 name-char
+/// relation  = 1*50 name-char
+/// name-char = ALPHA / DIGIT / "_" / "-"
+/// id        = 1*256 id-char
+/// id-char   = any character except ":", "#", "*", whitespace and control characters
+/// ```
+///
+/// Values outside the grammar are rejected rather than rewritten.
+pub fn sanitize_identifier(value: &str, kind: IdentifierKind) -> Result<String, String> {
+    if let Some(c) = value.chars().find(|c| c.is_control() || c.is_whitespace()) {
+        return Err(format!(
+            "identifier {:?} contains invalid character {:?}",
+            value, c
+        ));
+    }
+    if value.matches('#').count() > 1 {
+        return Err(format!("identifier {:?} contains more than one '#'", value));
+    }
+
+    let (object, relation) = match value.split_once('#') {
+        Some(_) if kind == IdentifierKind::Object => {
+            return Err(format!("object {:?} must not contain '#'", value));
+        }
+        Some((object, relation)) => (object, Some(relation)),
+        None => (value, None),
+    };
+
+    let Some((object_type, id)) = object.split_once(':') else {
+        return Err(format!("identifier {:?} must have the form type:id", value));
+    };
+    validate_name(object_type, MAX_TYPE_LEN)
+        .map_err(|e| format!("invalid type in {:?}: {}", value, e))?;
+
+    if id == "*" {
+        if kind == IdentifierKind::Object || relation.is_some() {
+            return Err(format!(
+                "wildcard is only allowed as a plain user: {:?}",
+                value
+            ));
+        }
+        return Ok(value.to_string());
+    }
+    validate_id(id).map_err(|e| format!("invalid id in {:?}: {}", value, e))?;
+
+    if let Some(relation) = relation {
+        validate_name(relation, MAX_RELATION_LEN)
+            .map_err(|e| format!("invalid relation in {:?}: {}", value, e))?;
+    }
+
+    Ok(value.to_string())
+}
+
+/// Validate a relation name
+pub fn sanitize_relation(value: &str) -> Result<String, String> {
+    validate_name(value, MAX_RELATION_LEN)
+        .map_err(|e| format!("invalid relation {:?}: {}", value, e))?;
+    Ok(value.to_string())
+}
+
+/// Validate the parts of a tuple key, producing a `400 Bad Request` handler error
+pub fn validate_tuple(
+    user: &str,
+    relation: &str,
+    object: &str,
+) -> Result<(), (StatusCode, Json<Value>)> {
+    sanitize_identifier(user, IdentifierKind::User)
+        .and_then(|_| sanitize_relation(relation))
+        .and_then(|_| sanitize_identifier(object, IdentifierKind::Object))
+        .map(|_| ())
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(json!({ "message": e }))))
+}
+
+fn validate_name(value: &str, max_len: usize) -> Result<(), String> {
+    if value.is_empty() || value.len() > max_len {
+        return Err(format!("must be 1 to {} characters", max_len));
+    }
+    if !value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err("only letters, digits, '_' and '-' are allowed".to_string());
+    }
+    Ok(())
+}
+
+fn validate_id(value: &str) -> Result<(), String> {
+    if value.is_empty() || value.len() > MAX_ID_LEN {
+        return Err(format!("must be 1 to {} characters", MAX_ID_LEN));
+    }
+    if let Some(c) = value.chars().find(|c| matches!(c, ':' | '#' | '*')) {
+        return Err(format!("{:?} is not allowed", c));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_identifiers() {
+        for object in [
+            "document:readme",
+            "folder:a1b2-c3_d4",
+            "repo:org/project.rs",
+        ] {
+            assert_eq!(
+                sanitize_identifier(object, IdentifierKind::Object).as_deref(),
+                Ok(object)
+            );
+        }
+        for user in [
+            "user:alice",
+            "user:*",
+            "group:eng#member",
+            "user:alice@example.com",
+        ] {
+            assert_eq!(
+                sanitize_identifier(user, IdentifierKind::User).as_deref(),
+                Ok(user)
+            );
+        }
+        assert!(sanitize_relation("can_view").is_ok());
+    }
+
+    #[test]
+    fn test_malicious_identifiers_are_rejected() {
+        let users = [
+            "",
+            "alice",
+            "user:",
+            ":alice",
+            "user:alice bob",
+            "user:alice\n",
+            "user:al\u{0}ice",
+            "group:eng#member#owner",
+            "group:eng#",
+            "user:*#member",
+            "user:alice:admin",
+            "user:a*",
+            "us er:alice",
+        ];
+        for user in users {
+            assert!(
+                sanitize_identifier(user, IdentifierKind::User).is_err(),
+                "{:?} should be rejected",
+                user
+            );
+        }
+
+        for object in ["document:readme#owner", "document:*", "document:a:b"] {
+            assert!(
+                sanitize_identifier(object, IdentifierKind::Object).is_err(),
+                "{:?} should be rejected",
+                object
+            );
+        }
+
+        assert!(sanitize_relation("viewer or owner").is_err());
+        assert!(sanitize_relation("viewer#owner").is_err());
+    }
+}
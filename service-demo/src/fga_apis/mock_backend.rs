@@ -0,0 +1,103 @@
+//! [`FgaBackend`] for handler tests, in place of OpenFGA.
+
+use serde_json::Value;
+use std::sync::Mutex;
+
+use crate::context::Ctx;
+use crate::fga_apis::backend::{BackendError, FgaBackend, StoreRef};
+use crate::fga_apis::dto::{Consistency, ReadFilter, StoredTuple, Tuple, UserFilter};
+use crate::fga_apis::page::Page;
+
+/// Stores and tuples checked through [`MockBackend::check`], by every test
+pub(crate) static CHECKED: Mutex<Vec<(StoreRef, Tuple)>> = Mutex::new(Vec::new());
+
+/// Backend allowing only `user:anne`, recording single checks in [`CHECKED`],
+/// accepting every write and listing the consistency of object queries as the only
+/// object. Reads, expands and user lists fail.
+pub(crate) struct MockBackend;
+
+fn unsupported(operation: &str) -> BackendError {
+    BackendError::Upstream(format!("MockBackend doesn't support {}", operation))
+}
+
+impl FgaBackend for MockBackend {
+    fn from_ctx(_ctx: &Ctx) -> Self {
+        Self
+    }
+
+    async fn check(
+        &self,
+        store: &StoreRef,
+        tuple: Tuple,
+        _context: Option<Value>,
+        _consistency: Consistency,
+    ) -> Result<bool, BackendError> {
+        let allowed = tuple.user == "user:anne";
+        CHECKED.lock().unwrap().push((store.clone(), tuple));
+        Ok(allowed)
+    }
+
+    async fn batch_check(
+        &self,
+        _store: &StoreRef,
+        tuples: Vec<Tuple>,
+        _consistency: Consistency,
+    ) -> Result<Vec<bool>, BackendError> {
+        Ok(tuples.iter().map(|t| t.user == "user:anne").collect())
+    }
+
+    async fn write(
+        &self,
+        _store: &StoreRef,
+        _writes: Vec<Tuple>,
+        _deletes: Vec<Tuple>,
+    ) -> Result<(), BackendError> {
+        Ok(())
+    }
+
+    async fn read(
+        &self,
+        _store_id: &str,
+        _filter: ReadFilter,
+        _page_size: i32,
+        _continuation_token: Option<String>,
+        _consistency: Consistency,
+    ) -> Result<Page<StoredTuple>, BackendError> {
+        Err(unsupported("read"))
+    }
+
+    async fn expand(
+        &self,
+        _store: &StoreRef,
+        _object: String,
+        _relation: String,
+        _contextual_tuples: Vec<Tuple>,
+        _consistency: Consistency,
+    ) -> Result<Value, BackendError> {
+        Err(unsupported("expand"))
+    }
+
+    async fn list_objects(
+        &self,
+        _store: &StoreRef,
+        _object_type: String,
+        _relation: String,
+        _user: String,
+        _context: Option<Value>,
+        consistency: Consistency,
+    ) -> Result<Vec<String>, BackendError> {
+        Ok(vec![format!("consistency:{:?}", consistency)])
+    }
+
+    async fn list_users(
+        &self,
+        _store: &StoreRef,
+        _object: String,
+        _relation: String,
+        _user_filters: Vec<UserFilter>,
+        _context: Option<Value>,
+        _consistency: Consistency,
+    ) -> Result<Vec<String>, BackendError> {
+        Err(unsupported("list_users"))
+    }
+}
@@ -1,2 +1,16 @@
+pub mod audit;
+pub mod backend;
+pub mod bodies;
+pub mod contextual_tuples;
+pub mod dto;
+pub mod errors;
 pub mod grpc;
 pub mod http;
+pub mod identifiers;
+#[cfg(test)]
+pub(crate) mod mock_backend;
+pub mod page;
+pub mod relationships;
+pub mod store_access;
+pub mod store_deletion;
+pub mod store_names;
@@ -0,0 +1,49 @@
+use serde::Serialize;
+
+/// Pagination envelope returned by the list handlers of both transports
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub continuation_token: Option<String>,
+    pub has_more: bool,
+}
+
+impl<T> Page<T> {
+    /// Build a page from OpenFGA's continuation token, where an empty token means
+    /// there are no further pages
+    pub fn new(items: Vec<T>, continuation_token: impl Into<Option<String>>) -> Self {
+        let continuation_token = continuation_token.into().filter(|t| !t.is_empty());
+        Self {
+            items,
+            has_more: continuation_token.is_some(),
+            continuation_token,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_token_has_no_more_pages() {
+        let page = Page::new(vec!["store-1"], String::new());
+        assert!(!page.has_more);
+        assert_eq!(page.continuation_token, None);
+
+        let page = Page::<&str>::new(vec![], None);
+        assert!(!page.has_more);
+
+        let page = Page::new(vec!["store-1"], "NHw=".to_string());
+        assert!(page.has_more);
+        assert_eq!(page.continuation_token.as_deref(), Some("NHw="));
+        assert_eq!(
+            serde_json::to_value(&page).unwrap(),
+            serde_json::json!({
+                "items": ["store-1"],
+                "continuation_token": "NHw=",
+                "has_more": true
+            })
+        );
+    }
+}
@@ -0,0 +1,753 @@
+//! Relationship handlers shared by the gRPC and HTTP routes, parameterized over the
+//! [`FgaBackend`] that talks to OpenFGA.
+
+use axum::{
+    Json,
+    body::{Body, Bytes},
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use futures_util::{Stream, stream};
+use openfga_grpc_client::TupleKey;
+use serde_json::{Value, json};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::Arc;
+
+use crate::context::Ctx;
+use crate::fga_apis::backend::{BackendError, FgaBackend, StoreRef};
+use crate::fga_apis::bodies::{
+    CheckBody, ExpandBody, ListObjectsBody, ListUsersBody, ReadBody, TuplesBody,
+};
+use crate::fga_apis::contextual_tuples::{current_contextual_tuples, with_contextual_tuples};
+use crate::fga_apis::dto::{Consistency, ReadFilter, StoredTuple, Tuple, UserFilter};
+use crate::fga_apis::identifiers::validate_tuple;
+use crate::fga_apis::page::Page;
+
+/// Store and model of a request; the configured ones when `store_id` is absent
+#[derive(Debug, Default, PartialEq, serde::Deserialize)]
+pub struct StoreReq {
+    pub store_id: Option<String>,
+    /// The latest model of the store when absent
+    pub authorization_model_id: Option<String>,
+}
+
+impl StoreReq {
+    /// The store to operate on. A store named in the request was checked against the
+    /// caller's organization by
+    /// [`store_access_middleware`](crate::fga_apis::store_access::store_access_middleware)
+    /// before the handler ran.
+    pub(crate) fn resolve(self, ctx: &Ctx) -> StoreRef {
+        match self.store_id {
+            Some(store_id) if store_id != ctx.fga_config.store_id => StoreRef {
+                store_id,
+                authorization_model_id: self.authorization_model_id,
+            },
+            _ => StoreRef {
+                store_id: ctx.fga_config.store_id.clone(),
+                authorization_model_id: self
+                    .authorization_model_id
+                    .or_else(|| Some(ctx.fga_config.authorization_model_id.get())),
+            },
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct CheckReq {
+    #[serde(flatten)]
+    pub store: StoreReq,
+    pub user: String,
+    pub relation: String,
+    pub object: String,
+    /// Values for the parameters of conditioned relations
+    pub context: Option<Value>,
+    /// Tuples the check is evaluated with besides those of mapped headers
+    #[serde(default)]
+    pub contextual_tuples: Vec<Tuple>,
+    /// Overrides the configured consistency of the operation
+    #[serde(default)]
+    pub consistency: Option<Consistency>,
+}
+
+pub async fn check<B: FgaBackend>(
+    State(ctx): State<Ctx>,
+    Json(body): Json<CheckBody>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    let req = CheckReq::from(body);
+    validate_tuple(&req.user, &req.relation, &req.object)?;
+    let mut contextual_tuples = current_contextual_tuples();
+    for tuple in req.contextual_tuples {
+        validate_tuple(&tuple.user, &tuple.relation, &tuple.object)?;
+        contextual_tuples.push(TupleKey::try_from(tuple).map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "message": format!("Invalid contextual tuple condition: {}", e) })),
+            )
+        })?);
+    }
+
+    let store = req.store.resolve(&ctx);
+    let tuple = Tuple {
+        user: req.user,
+        relation: req.relation,
+        object: req.object,
+        condition: None,
+    };
+    let backend = B::from_ctx(&ctx);
+    let allowed = with_contextual_tuples(
+        contextual_tuples,
+        backend.check(
+            &store,
+            tuple,
+            req.context,
+            req.consistency.unwrap_or(ctx.fga_config.consistency.check),
+        ),
+    )
+    .await
+    .map_err(BackendError::into_response)?;
+
+    Ok((StatusCode::OK, Json(json!({ "allowed": allowed }))))
+}
+
+/// Path segments of `GET /stores/{store_id}/check/{object}/{relation}/{user}`.
+///
+/// Segments are percent-decoded by the extractor, so `document%3Areadme` becomes
+/// `document:readme` and `group%3Aeng%23member` becomes `group:eng#member`.
+/// A literal `#` can't be sent unencoded since it starts the URL fragment.
+#[derive(Debug, serde::Deserialize)]
+pub struct CheckPath {
+    pub store_id: String,
+    pub object: String,
+    pub relation: String,
+    pub user: String,
+}
+
+impl CheckPath {
+    /// Tuple checked for the decoded path
+    pub fn tuple(&self) -> Tuple {
+        Tuple {
+            user: self.user.clone(),
+            relation: self.relation.clone(),
+            object: self.object.clone(),
+            condition: None,
+        }
+    }
+}
+
+/// Check from path segments against the latest model of the store
+pub async fn check_by_path<B: FgaBackend>(
+    State(ctx): State<Ctx>,
+    Path(path): Path<CheckPath>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    validate_tuple(&path.user, &path.relation, &path.object)?;
+
+    let tuple = path.tuple();
+    let store = StoreRef {
+        store_id: path.store_id,
+        authorization_model_id: None,
+    };
+    let allowed = B::from_ctx(&ctx)
+        .check(&store, tuple, None, ctx.fga_config.consistency.check)
+        .await
+        .map_err(BackendError::into_response)?;
+
+    Ok((StatusCode::OK, Json(json!({ "allowed": allowed }))))
+}
+
+/// A check of [`CheckManyReq`], echoed in its result
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CheckItem {
+    pub user: String,
+    pub relation: String,
+    pub object: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct CheckManyReq {
+    #[serde(flatten)]
+    pub store: StoreReq,
+    pub checks: Vec<CheckItem>,
+    /// Overrides the configured consistency of the operation
+    #[serde(default)]
+    pub consistency: Option<Consistency>,
+}
+
+/// Run several checks through batch check, without the correlation IDs of the OpenFGA
+/// request shape. Each result is the check with its `allowed` result, in request order.
+pub async fn check_many<B: FgaBackend>(
+    State(ctx): State<Ctx>,
+    Json(req): Json<CheckManyReq>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    for check in &req.checks {
+        validate_tuple(&check.user, &check.relation, &check.object)?;
+    }
+
+    let store = req.store.resolve(&ctx);
+    let tuples = req
+        .checks
+        .iter()
+        .map(|check| Tuple {
+            user: check.user.clone(),
+            relation: check.relation.clone(),
+            object: check.object.clone(),
+            condition: None,
+        })
+        .collect();
+    let allowed = if req.checks.is_empty() {
+        Vec::new()
+    } else {
+        B::from_ctx(&ctx)
+            .batch_check(
+                &store,
+                tuples,
+                req.consistency.unwrap_or(ctx.fga_config.consistency.check),
+            )
+            .await
+            .map_err(BackendError::into_response)?
+    };
+
+    let results: Vec<Value> = req
+        .checks
+        .into_iter()
+        .zip(allowed)
+        .map(|(check, allowed)| {
+            json!({
+                "user": check.user,
+                "relation": check.relation,
+                "object": check.object,
+                "allowed": allowed,
+            })
+        })
+        .collect();
+    Ok((StatusCode::OK, Json(json!({ "results": results }))))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct TuplesReq {
+    #[serde(flatten)]
+    pub store: StoreReq,
+    pub tuples: Vec<Tuple>,
+}
+
+pub async fn write_tuples<B: FgaBackend>(
+    State(ctx): State<Ctx>,
+    Json(body): Json<TuplesBody>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    let changes = body.into_changes(false);
+    for tuple in changes.writes.iter().chain(&changes.deletes) {
+        validate_tuple(&tuple.user, &tuple.relation, &tuple.object)?;
+    }
+
+    let store = changes.store.resolve(&ctx);
+    B::from_ctx(&ctx)
+        .write(&store, changes.writes, changes.deletes)
+        .await
+        .map_err(BackendError::into_response)?;
+
+    Ok((StatusCode::OK, Json(json!({ "message": "Tuples written" }))))
+}
+
+pub async fn delete_tuples<B: FgaBackend>(
+    State(ctx): State<Ctx>,
+    Json(body): Json<TuplesBody>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    let changes = body.into_changes(true);
+    for tuple in changes.writes.iter().chain(&changes.deletes) {
+        validate_tuple(&tuple.user, &tuple.relation, &tuple.object)?;
+    }
+
+    let store = changes.store.resolve(&ctx);
+    B::from_ctx(&ctx)
+        .write(&store, changes.writes, changes.deletes)
+        .await
+        .map_err(BackendError::into_response)?;
+
+    Ok((StatusCode::OK, Json(json!({ "message": "Tuples deleted" }))))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ReadReq {
+    pub store_id: Option<String>,
+    #[serde(flatten)]
+    pub filter: ReadFilter,
+    pub page_size: Option<i32>,
+    pub continuation_token: Option<String>,
+    /// Overrides the configured consistency of the operation
+    #[serde(default)]
+    pub consistency: Option<Consistency>,
+}
+
+pub async fn read_tuples<B: FgaBackend>(
+    State(ctx): State<Ctx>,
+    Json(body): Json<ReadBody>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    let req = ReadReq::from(body);
+    let store = StoreReq {
+        store_id: req.store_id,
+        authorization_model_id: None,
+    }
+    .resolve(&ctx);
+    let page = B::from_ctx(&ctx)
+        .read(
+            &store.store_id,
+            req.filter,
+            ctx.fga_config.page_size(req.page_size),
+            req.continuation_token,
+            req.consistency.unwrap_or(ctx.fga_config.consistency.read),
+        )
+        .await
+        .map_err(BackendError::into_response)?;
+
+    Ok((StatusCode::OK, Json(json!(page))))
+}
+
+/// Stream all tuples of a store as newline-delimited JSON, one tuple per line.
+///
+/// Pages are read from OpenFGA only as the client consumes the body, so memory stays
+/// bounded by the page size. A failed read ends the body early.
+pub async fn export_tuples<B: FgaBackend>(
+    State(ctx): State<Ctx>,
+    Path(store_id): Path<String>,
+) -> Response {
+    let backend = Arc::new(B::from_ctx(&ctx));
+    let page_size = ctx.fga_config.page_size(None);
+    let consistency = ctx.fga_config.consistency.read;
+    let lines = ndjson_tuples(move |continuation_token| {
+        let backend = backend.clone();
+        let store_id = store_id.clone();
+        async move {
+            backend
+                .read(
+                    &store_id,
+                    ReadFilter::default(),
+                    page_size,
+                    continuation_token,
+                    consistency,
+                )
+                .await
+        }
+    });
+
+    (
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(lines),
+    )
+        .into_response()
+}
+
+/// NDJSON lines of the tuples of the pages returned by `fetch_page`, which is called
+/// with the continuation token of the previous page. The next page is fetched once
+/// the tuples of the current one are consumed; the stream ends after an error.
+pub fn ndjson_tuples<F, Fut>(fetch_page: F) -> impl Stream<Item = Result<Bytes, BackendError>>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<Page<StoredTuple>, BackendError>>,
+{
+    // The token of the next page to read, or `None` once the last page was read
+    let next_page: Option<Option<String>> = Some(None);
+    stream::unfold(
+        (fetch_page, VecDeque::new(), next_page),
+        |(mut fetch_page, mut buffered, mut next_page)| async move {
+            loop {
+                if let Some(tuple) = buffered.pop_front() {
+                    let mut line = serde_json::to_vec(&tuple).expect("tuples serialize to JSON");
+                    line.push(b'\n');
+                    return Some((Ok(Bytes::from(line)), (fetch_page, buffered, next_page)));
+                }
+
+                let continuation_token = next_page.take()?;
+                match fetch_page(continuation_token).await {
+                    Ok(page) => {
+                        buffered.extend(page.items);
+                        next_page = page.continuation_token.map(Some);
+                    }
+                    Err(e) => return Some((Err(e), (fetch_page, buffered, None))),
+                }
+            }
+        },
+    )
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ExpandReq {
+    #[serde(flatten)]
+    pub store: StoreReq,
+    pub object: String,
+    pub relation: String,
+    /// Tuples the tree is evaluated with, e.g. conditioned ones carrying ABAC context
+    #[serde(default)]
+    pub contextual_tuples: Vec<Tuple>,
+    /// Overrides the configured consistency of the operation
+    #[serde(default)]
+    pub consistency: Option<Consistency>,
+}
+
+pub async fn expand<B: FgaBackend>(
+    State(ctx): State<Ctx>,
+    Json(body): Json<ExpandBody>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    let req = ExpandReq::from(body);
+    for tuple in &req.contextual_tuples {
+        validate_tuple(&tuple.user, &tuple.relation, &tuple.object)?;
+    }
+
+    let store = req.store.resolve(&ctx);
+    let tree = B::from_ctx(&ctx)
+        .expand(
+            &store,
+            req.object,
+            req.relation,
+            req.contextual_tuples,
+            req.consistency.unwrap_or(ctx.fga_config.consistency.expand),
+        )
+        .await
+        .map_err(BackendError::into_response)?;
+
+    Ok((StatusCode::OK, Json(json!({ "tree": tree }))))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ListObjectsReq {
+    #[serde(flatten)]
+    pub store: StoreReq,
+    pub r#type: String,
+    pub relation: String,
+    pub user: String,
+    /// Values for the parameters of conditioned relations
+    pub context: Option<Value>,
+    /// Overrides the configured consistency of the operation
+    #[serde(default)]
+    pub consistency: Option<Consistency>,
+}
+
+pub async fn list_objects<B: FgaBackend>(
+    State(ctx): State<Ctx>,
+    Json(body): Json<ListObjectsBody>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    let req = ListObjectsReq::try_from(body)?;
+    let store = req.store.resolve(&ctx);
+    let objects = B::from_ctx(&ctx)
+        .list_objects(
+            &store,
+            req.r#type,
+            req.relation,
+            req.user,
+            req.context,
+            req.consistency
+                .unwrap_or(ctx.fga_config.consistency.list_objects),
+        )
+        .await
+        .map_err(BackendError::into_response)?;
+
+    Ok((StatusCode::OK, Json(json!({ "objects": objects }))))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ListUsersReq {
+    #[serde(flatten)]
+    pub store: StoreReq,
+    /// `type:id`
+    pub object: String,
+    pub relation: String,
+    pub user_filters: Vec<UserFilter>,
+    /// Values for the parameters of conditioned relations
+    pub context: Option<Value>,
+    /// Overrides the configured consistency of the operation
+    #[serde(default)]
+    pub consistency: Option<Consistency>,
+}
+
+pub async fn list_users<B: FgaBackend>(
+    State(ctx): State<Ctx>,
+    Json(body): Json<ListUsersBody>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    let req = ListUsersReq::try_from(body)?;
+    let store = req.store.resolve(&ctx);
+    let users = B::from_ctx(&ctx)
+        .list_users(
+            &store,
+            req.object,
+            req.relation,
+            req.user_filters,
+            req.context,
+            req.consistency
+                .unwrap_or(ctx.fga_config.consistency.list_users),
+        )
+        .await
+        .map_err(BackendError::into_response)?;
+
+    Ok((StatusCode::OK, Json(json!({ "users": users }))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fga_apis::backend::{GrpcBackend, HttpBackend};
+    use crate::fga_apis::mock_backend::{CHECKED, MockBackend};
+    use axum::{Router, body::Body, http::Request, routing::get};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_check_path_decodes_segments() {
+        let app: Router = Router::new()
+            .route(
+                "/stores/{store_id}/check/{object}/{relation}/{user}",
+                get(check_by_path::<MockBackend>),
+            )
+            .with_state(Ctx::for_test());
+
+        let request = Request::builder()
+            .uri("/stores/01HXYZ/check/document%3Areadme/viewer/group%3Aeng%23member")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body, json!({ "allowed": false }));
+
+        // Other tests check through the mock backend too
+        let checked = CHECKED.lock().unwrap().clone();
+        assert!(checked.contains(&(
+            StoreRef {
+                store_id: "01HXYZ".to_string(),
+                authorization_model_id: None,
+            },
+            Tuple {
+                user: "group:eng#member".to_string(),
+                relation: "viewer".to_string(),
+                object: "document:readme".to_string(),
+                condition: None,
+            }
+        )));
+    }
+
+    #[test]
+    fn test_read_consistency_is_optional() {
+        let req: ReadReq = serde_json::from_value(json!({
+            "user": "user:alice",
+            "object": "document:"
+        }))
+        .unwrap();
+        assert_eq!(req.consistency, None);
+        assert_eq!(req.filter.user.as_deref(), Some("user:alice"));
+        assert_eq!(req.filter.relation, None);
+
+        let req: ReadReq = serde_json::from_value(json!({
+            "object": "document:readme",
+            "consistency": "higher_consistency"
+        }))
+        .unwrap();
+        assert_eq!(req.consistency, Some(Consistency::HigherConsistency));
+    }
+
+    #[tokio::test]
+    async fn test_store_defaults_to_configured_store() {
+        let ctx = Ctx::for_test();
+
+        let store = StoreReq::default().resolve(&ctx);
+        assert_eq!(store.store_id, "store");
+        assert_eq!(store.authorization_model_id.as_deref(), Some("model"));
+
+        // A model given in the request overrides the configured one
+        let store = StoreReq {
+            store_id: None,
+            authorization_model_id: Some("01HMODELV2".to_string()),
+        }
+        .resolve(&ctx);
+        assert_eq!(store.store_id, "store");
+        assert_eq!(store.authorization_model_id.as_deref(), Some("01HMODELV2"));
+
+        // Another store's model can't default to the configured one
+        let store = StoreReq {
+            store_id: Some("01HXYZ".to_string()),
+            authorization_model_id: None,
+        }
+        .resolve(&ctx);
+        assert_eq!(store.store_id, "01HXYZ");
+        assert_eq!(store.authorization_model_id, None);
+
+        // Naming the configured store is the same as leaving it out
+        let store = StoreReq {
+            store_id: Some("store".to_string()),
+            authorization_model_id: None,
+        }
+        .resolve(&ctx);
+        assert_eq!(store.authorization_model_id.as_deref(), Some("model"));
+    }
+
+    #[tokio::test]
+    async fn test_created_model_becomes_the_default() {
+        let mut ctx = Ctx::for_test();
+        ctx.fga_config.use_created_models = true;
+        let running = ctx.clone();
+
+        // Models of other stores don't change the default
+        assert!(!ctx.fga_config.model_created("01HXYZ", "01HOTHER"));
+        assert!(ctx.fga_config.model_created("store", "01HMODELV2"));
+
+        // Every clone of the context, and so every later check, uses the new model
+        let store = StoreReq::default().resolve(&running);
+        assert_eq!(store.authorization_model_id.as_deref(), Some("01HMODELV2"));
+        let request = crate::fga_apis::contextual_tuples::check_request(
+            &running.fga_config,
+            openfga_grpc_client::CheckRequestTupleKey {
+                user: "user:anne".to_string(),
+                relation: "viewer".to_string(),
+                object: "document:readme".to_string(),
+            },
+        );
+        assert_eq!(request.authorization_model_id, "01HMODELV2");
+
+        // Pinned models stay in place
+        ctx.fga_config.use_created_models = false;
+        assert!(!ctx.fga_config.model_created("store", "01HMODELV3"));
+        let store = StoreReq::default().resolve(&running);
+        assert_eq!(store.authorization_model_id.as_deref(), Some("01HMODELV2"));
+    }
+
+    #[tokio::test]
+    async fn test_ndjson_export_streams_all_pages() {
+        use futures_util::StreamExt;
+
+        let stored = |user: &str| StoredTuple {
+            tuple: Tuple {
+                user: user.to_string(),
+                relation: "viewer".to_string(),
+                object: "document:readme".to_string(),
+                condition: None,
+            },
+            timestamp: None,
+        };
+        let tokens = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = tokens.clone();
+        let lines = ndjson_tuples(move |token: Option<String>| {
+            recorded.lock().unwrap().push(token.clone());
+            let page = match token.as_deref() {
+                None => Page::new(
+                    vec![stored("user:anne"), stored("user:bob")],
+                    "page-2".to_string(),
+                ),
+                Some(_) => Page::new(vec![stored("user:carl")], String::new()),
+            };
+            async move { Ok(page) }
+        });
+        let mut lines = Box::pin(lines);
+
+        // The second page isn't read before the first is consumed
+        lines.next().await.unwrap().unwrap();
+        assert_eq!(*tokens.lock().unwrap(), vec![None]);
+
+        let body = Body::from_stream(lines);
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        let users: Vec<String> = std::str::from_utf8(&bytes)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap()["user"].to_string())
+            .collect();
+        assert_eq!(users, vec!["\"user:bob\"", "\"user:carl\""]);
+        assert_eq!(
+            *tokens.lock().unwrap(),
+            vec![None, Some("page-2".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_rejects_invalid_tuple_on_both_backends() {
+        let app: Router = Router::new()
+            .route("/grpc/check", axum::routing::post(check::<GrpcBackend>))
+            .route("/http/check", axum::routing::post(check::<HttpBackend>))
+            .with_state(Ctx::for_test());
+
+        for uri in ["/grpc/check", "/http/check"] {
+            let request = Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    r#"{"user":"alice","relation":"viewer","object":"document:readme"}"#,
+                ))
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_many_returns_results_in_order() {
+        let app: Router = Router::new()
+            .route(
+                "/check-many",
+                axum::routing::post(check_many::<MockBackend>),
+            )
+            .with_state(Ctx::for_test());
+
+        let checks = json!([
+            { "user": "user:anne", "relation": "viewer", "object": "document:readme" },
+            { "user": "user:bob", "relation": "viewer", "object": "document:readme" },
+            { "user": "user:anne", "relation": "editor", "object": "document:roadmap" },
+        ]);
+        let request = Request::builder()
+            .method("POST")
+            .uri("/check-many")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({ "checks": checks }).to_string()))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        let results = body["results"].as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        for (result, check) in results.iter().zip(checks.as_array().unwrap()) {
+            for field in ["user", "relation", "object"] {
+                assert_eq!(result[field], check[field]);
+            }
+        }
+        let allowed: Vec<_> = results.iter().map(|r| r["allowed"].clone()).collect();
+        assert_eq!(allowed, vec![json!(true), json!(false), json!(true)]);
+    }
+
+    #[tokio::test]
+    async fn test_list_objects_uses_configured_consistency() {
+        let mut ctx = Ctx::for_test();
+        ctx.fga_config.consistency.list_objects = Consistency::HigherConsistency;
+        let app: Router = Router::new()
+            .route(
+                "/list-objects",
+                axum::routing::post(list_objects::<MockBackend>),
+            )
+            .with_state(ctx);
+
+        let query = json!({ "type": "document", "relation": "viewer", "user": "user:anne" });
+        let mut overridden = query.clone();
+        overridden["consistency"] = json!("minimize_latency");
+        for (body, expected) in [
+            (query, "consistency:HigherConsistency"),
+            (overridden, "consistency:MinimizeLatency"),
+        ] {
+            let request = Request::builder()
+                .method("POST")
+                .uri("/list-objects")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let body: Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["objects"], json!([expected]));
+        }
+    }
+}
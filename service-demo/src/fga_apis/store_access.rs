@@ -0,0 +1,353 @@
+use axum::{
+    Json, RequestExt,
+    body::{Body, HttpBody},
+    extract::{FromRequestParts, RawPathParams, Request, State},
+    http::{HeaderMap, StatusCode, header, request::Parts},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http_body_util::LengthLimitError;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::convert::Infallible;
+use tokio::sync::OnceCell;
+use tower_cookies::Cookie;
+
+use crate::auth::authn_controller::{extract_subdomain_from_host, get_org_config_by_subdomain};
+use crate::auth::callback::resolve_session;
+use crate::auth::db_ops;
+use crate::auth::models::{OrgStore, SessionClaims};
+use crate::context::Ctx;
+
+/// Decide whether an organization may operate on a store.
+///
+/// `org_id` is the organization of the caller's session, `None` when there is no
+/// valid session.
+pub fn check_store_access(
+    org_id: Option<&str>,
+    store_id: &str,
+    org_stores: &[OrgStore],
+) -> Result<(), (StatusCode, Json<Value>)> {
+    let Some(org_id) = org_id else {
+        return Err(session_required());
+    };
+
+    if org_stores
+        .iter()
+        .any(|s| s.org_id == org_id && s.store_id == store_id)
+    {
+        Ok(())
+    } else {
+        Err((
+            StatusCode::FORBIDDEN,
+            Json(
+                json!({ "message": format!("Store {} is not allowed for this organization", store_id) }),
+            ),
+        ))
+    }
+}
+
+/// Stores the caller may operate on: those mapped to the organization of its session
+/// in `org_stores`.
+///
+/// [`store_access_middleware`] checks the store a request names before its handler
+/// runs; handlers creating or listing stores use the organization and its stores. The
+/// session and its organization's stores are looked up once, on first use.
+pub struct StoreAccess {
+    headers: HeaderMap,
+    org: OnceCell<Option<SessionOrg>>,
+}
+
+/// Organization of a session and the stores mapped to it
+struct SessionOrg {
+    org_id: String,
+    org_stores: Vec<OrgStore>,
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for StoreAccess {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_headers(parts.headers.clone()))
+    }
+}
+
+impl StoreAccess {
+    /// Access of the session whose cookie is in `headers`
+    pub fn from_headers(headers: HeaderMap) -> Self {
+        Self {
+            headers,
+            org: OnceCell::new(),
+        }
+    }
+
+    async fn org(&self, ctx: &Ctx) -> Result<Option<&SessionOrg>, (StatusCode, Json<Value>)> {
+        let org = self
+            .org
+            .get_or_try_init(|| async {
+                let claims = session_claims(ctx, &self.headers).await.map_err(|e| {
+                    tracing::error!("Failed to resolve session for store access: {:?}", e);
+                    session_required()
+                })?;
+                let Some(claims) = claims else {
+                    return Ok(None);
+                };
+
+                let org_stores = db_ops::find_org_stores(&ctx.db, &claims.org_id)
+                    .await
+                    .map_err(|e| {
+                        tracing::error!("Failed to load organization stores: {:?}", e);
+                        (
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            Json(json!({ "message": e.to_string() })),
+                        )
+                    })?;
+                Ok(Some(SessionOrg {
+                    org_id: claims.org_id,
+                    org_stores,
+                }))
+            })
+            .await?;
+        Ok(org.as_ref())
+    }
+
+    /// Reject `store_id` unless it's mapped to the session's organization, see
+    /// [`check_store_access`]
+    pub async fn check(&self, ctx: &Ctx, store_id: &str) -> Result<(), (StatusCode, Json<Value>)> {
+        match self.org(ctx).await? {
+            Some(org) => check_store_access(Some(&org.org_id), store_id, &org.org_stores),
+            None => check_store_access(None, store_id, &[]),
+        }
+    }
+
+    /// Organization of the session, which stores created by the request are mapped to
+    pub async fn org_id(&self, ctx: &Ctx) -> Result<&str, (StatusCode, Json<Value>)> {
+        self.org(ctx)
+            .await?
+            .map(|org| org.org_id.as_str())
+            .ok_or_else(session_required)
+    }
+
+    /// IDs of the stores mapped to the session's organization
+    pub async fn store_ids(&self, ctx: &Ctx) -> Result<HashSet<&str>, (StatusCode, Json<Value>)> {
+        let org = self.org(ctx).await?.ok_or_else(session_required)?;
+        Ok(org.org_stores.iter().map(|s| s.store_id.as_str()).collect())
+    }
+}
+
+/// Reject requests naming a store that isn't mapped to the session's organization, see
+/// [`StoreAccess::check`].
+///
+/// The store is the `store_id` path parameter, or else the `store_id` of the JSON body,
+/// which is buffered up to the configured body size for the handler. A body naming the
+/// configured store, or none, operates on the service's own store and isn't checked.
+pub async fn store_access_middleware(
+    State(ctx): State<Ctx>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    // Bodies known to be too large are rejected before the session is looked up
+    if request.body().size_hint().lower() > ctx.fga_config.max_body_size as u64 {
+        return body_too_large().into_response();
+    }
+
+    let path_store_id = request
+        .extract_parts::<RawPathParams>()
+        .await
+        .ok()
+        .and_then(|params| {
+            params
+                .iter()
+                .find(|(name, _)| *name == "store_id")
+                .map(|(_, store_id)| store_id.to_string())
+        });
+
+    let store_id = match path_store_id {
+        Some(store_id) => Some(store_id),
+        None => {
+            let (parts, body) = request.into_parts();
+            let bytes = match axum::body::to_bytes(body, ctx.fga_config.max_body_size).await {
+                Ok(bytes) => bytes,
+                Err(e) => return body_error(e).into_response(),
+            };
+            let store_id = body_store_id(&bytes).filter(|id| *id != ctx.fga_config.store_id);
+            request = Request::from_parts(parts, Body::from(bytes));
+            store_id
+        }
+    };
+
+    if let Some(store_id) = store_id {
+        let access = StoreAccess::from_headers(request.headers().clone());
+        if let Err(rejection) = access.check(&ctx, &store_id).await {
+            return rejection.into_response();
+        }
+    }
+    next.run(request).await
+}
+
+/// `store_id` of a JSON body; `None` for other bodies, which their handler rejects
+fn body_store_id(body: &[u8]) -> Option<String> {
+    let body: Value = serde_json::from_slice(body).ok()?;
+    body.get("store_id")?.as_str().map(str::to_string)
+}
+
+fn body_error(e: axum::Error) -> (StatusCode, Json<Value>) {
+    if e.into_inner().is::<LengthLimitError>() {
+        body_too_large()
+    } else {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "Failed to read the request body" })),
+        )
+    }
+}
+
+fn body_too_large() -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::PAYLOAD_TOO_LARGE,
+        Json(json!({ "message": "Request body is too large" })),
+    )
+}
+
+fn session_required() -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(json!({ "message": "A valid session is required to access stores" })),
+    )
+}
+
+/// Claims of the session cookie sent to an organization's subdomain; `None` without
+/// a session or for a session issued for another organization
+pub(crate) async fn session_claims(
+    ctx: &Ctx,
+    headers: &HeaderMap,
+) -> anyhow::Result<Option<SessionClaims>> {
+    let Some(subdomain) = headers
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok())
+        .and_then(extract_subdomain_from_host)
+    else {
+        return Ok(None);
+    };
+
+    let org_config = get_org_config_by_subdomain(&ctx.db, &subdomain).await?;
+    let cookie_name = org_config.session_config.session_cookie_name();
+    let Some(cookie_value) = headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(Cookie::split_parse)
+        .filter_map(Result::ok)
+        .find(|c| c.name() == cookie_name)
+        .map(|c| c.value().to_string())
+    else {
+        return Ok(None);
+    };
+
+    let claims = resolve_session(&ctx.db, &cookie_value, &org_config.session_config).await?;
+
+    // A session issued for another organization doesn't grant access here
+    Ok(claims.filter(|c| c.org_id == org_config.org_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Router, middleware,
+        routing::{get, post},
+    };
+    use chrono::Utc;
+    use tower::ServiceExt;
+
+    fn org_store(org_id: &str, store_id: &str) -> OrgStore {
+        OrgStore {
+            org_id: org_id.to_string(),
+            store_id: store_id.to_string(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_store_access_decision() {
+        let org_stores = [
+            org_store("acme", "01HACME"),
+            org_store("globex", "01HGLOBEX"),
+        ];
+
+        assert!(check_store_access(Some("acme"), "01HACME", &org_stores).is_ok());
+
+        let (status, _) = check_store_access(Some("acme"), "01HGLOBEX", &org_stores).unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, _) = check_store_access(Some("initech"), "01HACME", &org_stores).unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, _) = check_store_access(None, "01HACME", &org_stores).unwrap_err();
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    /// Routes behind the store access check, answering with the body they got
+    fn checked_routes(ctx: Ctx) -> Router {
+        Router::new()
+            .route("/stores/{store_id}", get(|| async { StatusCode::OK }))
+            .route("/write", post(|body: String| async { body }))
+            .route_layer(middleware::from_fn_with_state(
+                ctx.clone(),
+                store_access_middleware,
+            ))
+            .with_state(ctx)
+    }
+
+    fn post_body(body: &str) -> Request {
+        Request::builder()
+            .method("POST")
+            .uri("/write")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_store_access_middleware() {
+        let app = checked_routes(Ctx::for_test());
+
+        let request = Request::builder()
+            .uri("/stores/01HXYZ")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app
+            .clone()
+            .oneshot(post_body(r#"{"store_id":"01HXYZ","user":"user:anne"}"#))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // The configured store, or none, isn't checked, and the handler still gets the body
+        for body in [
+            r#"{"store_id":"store"}"#,
+            r#"{"user":"user:anne"}"#,
+            "not json",
+        ] {
+            let response = app.clone().oneshot(post_body(body)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{}", body);
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            assert_eq!(bytes, body.as_bytes());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_store_access_middleware_limits_body() {
+        let mut ctx = Ctx::for_test();
+        ctx.fga_config.max_body_size = 1024;
+        let app = checked_routes(ctx);
+
+        let body = format!(r#"{{"store_id":"{}"}}"#, "x".repeat(2048));
+        let response = app.oneshot(post_body(&body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
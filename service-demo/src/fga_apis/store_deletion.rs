@@ -0,0 +1,83 @@
+//! Store deletion guarded by the store's name, like deleting a GitHub repository.
+
+use axum::{Json, http::StatusCode};
+use serde_json::{Value, json};
+
+use crate::fga_apis::errors::OpenFgaError;
+
+/// Query of the confirmed delete routes
+#[derive(Debug, serde::Deserialize)]
+pub struct ConfirmDeleteQuery {
+    /// Current name of the store
+    pub name: String,
+}
+
+/// Delete a store only if `confirm_name` is its current name.
+///
+/// A mismatch is rejected with 400 before `delete` is called.
+pub async fn delete_if_name_matches<E1: OpenFgaError, E2: OpenFgaError>(
+    confirm_name: &str,
+    fetch_name: impl AsyncFnOnce() -> Result<String, E1>,
+    delete: impl AsyncFnOnce() -> Result<(), E2>,
+) -> Result<(), (StatusCode, Json<Value>)> {
+    let name = fetch_name().await.map_err(|e| {
+        tracing::error!("Failed to fetch store before deleting it: {}", e);
+        (e.http_status(), Json(json!({ "message": e.to_string() })))
+    })?;
+
+    if name != confirm_name {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "Confirmation doesn't match the store name" })),
+        ));
+    }
+
+    delete().await.map_err(|e| {
+        tracing::error!("Failed to delete store: {}", e);
+        (e.http_status(), Json(json!({ "message": e.to_string() })))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_delete_requires_matching_name() {
+        let mut deleted = false;
+        let result = delete_if_name_matches(
+            "demo",
+            async || Ok::<_, tonic::Status>("demo".to_string()),
+            async || {
+                deleted = true;
+                Ok::<_, tonic::Status>(())
+            },
+        )
+        .await;
+        assert!(result.is_ok());
+        assert!(deleted);
+
+        let mut deleted = false;
+        let (status, _) = delete_if_name_matches(
+            "prod",
+            async || Ok::<_, tonic::Status>("demo".to_string()),
+            async || {
+                deleted = true;
+                Ok::<_, tonic::Status>(())
+            },
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(!deleted);
+
+        let (status, _) = delete_if_name_matches(
+            "demo",
+            async || Err::<String, _>(tonic::Status::new(tonic::Code::Unknown, "store not found")),
+            async || Ok::<_, tonic::Status>(()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
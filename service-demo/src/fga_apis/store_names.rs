@@ -0,0 +1,305 @@
+//! `name:<store name>` in place of a store ID in the HTTP store routes, e.g.
+//! `GET /api/ofga/http/stores/name:my-store/authorization-models`.
+//!
+//! A name is resolved only among the stores of the session's organization, so it
+//! requires a session and never reveals another organization's stores: a name they
+//! use reads as unknown. The name is looked up with a `list_stores` name filter and the
+//! request is rewritten to the store's ID before it's routed, so handlers and the store
+//! access check only see IDs. Resolutions are cached per organization for
+//! [`STORE_NAME_TTL`] in [`Ctx::store_names`].
+
+use axum::{
+    Json, Router,
+    extract::{Request, State},
+    http::{StatusCode, Uri},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+};
+use openfga_http_client::apis::stores_api;
+use percent_encoding::{NON_ALPHANUMERIC, percent_decode_str, utf8_percent_encode};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::context::Ctx;
+use crate::fga_apis::store_access::StoreAccess;
+
+/// Prefix of a `store_id` path segment holding a store name
+pub const STORE_NAME_PREFIX: &str = "name:";
+
+/// How long a resolved name is reused
+pub const STORE_NAME_TTL: Duration = Duration::from_secs(30);
+
+/// Path of the HTTP store routes, followed by the store ID
+const HTTP_STORES_PATH: &str = "/api/ofga/http/stores/";
+
+/// Error resolving a store name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreNameError {
+    /// No store of the organization has the name
+    NotFound(String),
+    /// Several stores of the organization have the name
+    Ambiguous(String),
+    /// Listing the stores failed
+    Lookup(String),
+}
+
+impl IntoResponse for StoreNameError {
+    fn into_response(self) -> Response {
+        let (status, body) = match self {
+            StoreNameError::NotFound(name) => (
+                StatusCode::NOT_FOUND,
+                json!({ "message": format!("No store is named {:?}", name) }),
+            ),
+            StoreNameError::Ambiguous(name) => (
+                StatusCode::CONFLICT,
+                json!({ "message": format!("Several stores are named {:?}, use a store ID", name) }),
+            ),
+            StoreNameError::Lookup(message) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                json!({ "message": message }),
+            ),
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+/// Store IDs by organization and name
+pub struct StoreNameCache {
+    entries: Mutex<HashMap<(String, String), (String, Instant)>>,
+    ttl: Duration,
+}
+
+impl StoreNameCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+        }
+    }
+
+    /// ID of the only store of `org_id` named `name`, from the cache or the IDs of the
+    /// organization's stores with that name returned by `lookup`
+    pub async fn resolve<F>(
+        &self,
+        org_id: &str,
+        name: &str,
+        lookup: F,
+    ) -> Result<String, StoreNameError>
+    where
+        F: AsyncFnOnce(&str) -> Result<Vec<String>, StoreNameError>,
+    {
+        let key = (org_id.to_string(), name.to_string());
+        if let Some((store_id, resolved_at)) = self.entries.lock().unwrap().get(&key)
+            && resolved_at.elapsed() < self.ttl
+        {
+            return Ok(store_id.clone());
+        }
+
+        let mut store_ids = lookup(name).await?;
+        let store_id = match store_ids.len() {
+            0 => return Err(StoreNameError::NotFound(name.to_string())),
+            1 => store_ids.remove(0),
+            _ => return Err(StoreNameError::Ambiguous(name.to_string())),
+        };
+
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(key, (store_id.clone(), Instant::now()));
+        Ok(store_id)
+    }
+}
+
+/// IDs of the stores named `name` among `org_store_ids`, through the OpenFGA HTTP API
+async fn list_store_ids(
+    ctx: &Ctx,
+    org_store_ids: &HashSet<&str>,
+    name: &str,
+) -> Result<Vec<String>, StoreNameError> {
+    let mut store_ids = Vec::new();
+    let mut continuation_token: Option<String> = None;
+    loop {
+        let response = stores_api::list_stores(
+            &ctx.fga_http_config,
+            None,
+            continuation_token.as_deref(),
+            Some(name),
+        )
+        .await
+        .map_err(|e| StoreNameError::Lookup(format!("Failed to list stores: {}", e)))?;
+
+        // Older servers ignore the name filter
+        store_ids.extend(
+            response
+                .stores
+                .into_iter()
+                .filter(|store| store.name == name && org_store_ids.contains(store.id.as_str()))
+                .map(|store| store.id),
+        );
+        if response.continuation_token.is_empty() {
+            return Ok(store_ids);
+        }
+        continuation_token = Some(response.continuation_token);
+    }
+}
+
+/// Store name of an HTTP store route path, with the byte range of its segment
+fn store_name_segment(path: &str) -> Option<(String, std::ops::Range<usize>)> {
+    if !path.starts_with(HTTP_STORES_PATH) {
+        return None;
+    }
+    let start = HTTP_STORES_PATH.len();
+    let end = path[start..].find('/').map_or(path.len(), |i| start + i);
+    let segment = percent_decode_str(&path[start..end]).decode_utf8().ok()?;
+    let name = segment.strip_prefix(STORE_NAME_PREFIX)?;
+    Some((name.to_string(), start..end))
+}
+
+/// Rewrite `name:<store name>` in HTTP store route paths to the ID of the session
+/// organization's store with that name
+pub async fn resolve_store_name_middleware(
+    State(ctx): State<Ctx>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some((name, segment)) = store_name_segment(request.uri().path()) else {
+        return next.run(request).await;
+    };
+
+    let access = StoreAccess::from_headers(request.headers().clone());
+    let (org_id, org_store_ids) = match (access.org_id(&ctx).await, access.store_ids(&ctx).await) {
+        (Ok(org_id), Ok(store_ids)) => (org_id, store_ids),
+        (Err(rejection), _) | (_, Err(rejection)) => return rejection.into_response(),
+    };
+    let store_id = match ctx
+        .store_names
+        .resolve(org_id, &name, async |name| {
+            list_store_ids(&ctx, &org_store_ids, name).await
+        })
+        .await
+    {
+        Ok(store_id) => store_id,
+        Err(e) => return e.into_response(),
+    };
+
+    let uri = request.uri();
+    let mut path_and_query = format!(
+        "{}{}{}",
+        &uri.path()[..segment.start],
+        utf8_percent_encode(&store_id, NON_ALPHANUMERIC),
+        &uri.path()[segment.end..]
+    );
+    if let Some(query) = uri.query() {
+        path_and_query.push('?');
+        path_and_query.push_str(query);
+    }
+    match path_and_query.parse::<Uri>() {
+        Ok(uri) => *request.uri_mut() = uri,
+        Err(e) => {
+            tracing::error!("Failed to rewrite store name path: {}", e);
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+    }
+
+    next.run(request).await
+}
+
+/// Resolve store names before `app` routes the request.
+///
+/// Middleware added with [`Router::layer`] runs after routing, when the `store_id`
+/// path parameter is already extracted, so `app` is nested as the fallback of a router
+/// running the rewrite.
+pub fn with_store_names(ctx: Ctx, app: Router) -> Router {
+    Router::new()
+        .fallback_service(app)
+        .layer(middleware::from_fn_with_state(
+            ctx,
+            resolve_store_name_middleware,
+        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_resolve_store_name() {
+        let cache = StoreNameCache::new(STORE_NAME_TTL);
+        let lookups = AtomicUsize::new(0);
+        let lookup = async |name: &str| {
+            lookups.fetch_add(1, Ordering::SeqCst);
+            Ok(match name {
+                "demo" => vec!["01HDEMO".to_string()],
+                "shared" => vec!["01HSHARED1".to_string(), "01HSHARED2".to_string()],
+                _ => Vec::new(),
+            })
+        };
+
+        assert_eq!(
+            cache.resolve("acme", "demo", lookup).await.unwrap(),
+            "01HDEMO"
+        );
+        // Resolved again from the cache
+        assert_eq!(
+            cache.resolve("acme", "demo", lookup).await.unwrap(),
+            "01HDEMO"
+        );
+        assert_eq!(lookups.load(Ordering::SeqCst), 1);
+        // but not for another organization
+        cache.resolve("globex", "demo", lookup).await.unwrap();
+        assert_eq!(lookups.load(Ordering::SeqCst), 2);
+
+        assert_eq!(
+            cache.resolve("acme", "shared", lookup).await.unwrap_err(),
+            StoreNameError::Ambiguous("shared".to_string())
+        );
+        assert_eq!(
+            cache.resolve("acme", "missing", lookup).await.unwrap_err(),
+            StoreNameError::NotFound("missing".to_string())
+        );
+        // Errors aren't cached
+        cache.resolve("acme", "shared", lookup).await.unwrap_err();
+        assert_eq!(lookups.load(Ordering::SeqCst), 5);
+
+        // The conflicting stores aren't revealed
+        let response = StoreNameError::Ambiguous("shared".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(!String::from_utf8_lossy(&bytes).contains("01HSHARED"));
+    }
+
+    #[tokio::test]
+    async fn test_store_name_requires_session() {
+        let app = with_store_names(
+            Ctx::for_test(),
+            Router::new().fallback(|| async { StatusCode::OK }),
+        );
+
+        let request = Request::builder()
+            .uri("/api/ofga/http/stores/name:demo/authorization-models")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_store_name_segment() {
+        let path = "/api/ofga/http/stores/name%3Amy%20store/authorization-models";
+        let (name, segment) = store_name_segment(path).unwrap();
+        assert_eq!(name, "my store");
+        assert_eq!(&path[segment], "name%3Amy%20store");
+
+        let (name, _) = store_name_segment("/api/ofga/http/stores/name:demo").unwrap();
+        assert_eq!(name, "demo");
+
+        assert!(store_name_segment("/api/ofga/http/stores/01HDEMO/check").is_none());
+        assert!(store_name_segment("/api/ofga/grpc/store/name:demo").is_none());
+    }
+}
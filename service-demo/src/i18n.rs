@@ -0,0 +1,225 @@
+//! Message catalog for the auth error pages, selected from the request's
+//! `Accept-Language` header. Messages missing from a catalog fall back to English.
+
+use axum::http::{HeaderMap, header};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// Languages with a message catalog
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lang {
+    En,
+    Es,
+    Fr,
+}
+
+impl Lang {
+    /// Language of a primary language subtag, e.g. `en`
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.to_ascii_lowercase().as_str() {
+            "en" => Some(Lang::En),
+            "es" => Some(Lang::Es),
+            "fr" => Some(Lang::Fr),
+            _ => None,
+        }
+    }
+}
+
+/// Message keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    AuthError,
+    AuthFailed,
+    ErrorCode,
+    ErrorDescription,
+    ErrorDetails,
+    CommonCauses,
+    CauseAccessDenied,
+    CauseUnauthorized,
+    CauseInvalidRequest,
+    CauseServerError,
+    CauseOrganizationRequired,
+    TryAgain,
+    ReturnToLogin,
+}
+
+static CATALOG: LazyLock<HashMap<Lang, HashMap<Key, String>>> = LazyLock::new(|| {
+    let catalog = |messages: &[(Key, &str)]| {
+        messages
+            .iter()
+            .map(|(key, message)| (*key, message.to_string()))
+            .collect::<HashMap<_, _>>()
+    };
+
+    HashMap::from([
+        (
+            Lang::En,
+            catalog(&[
+                (Key::AuthError, "Authentication Error"),
+                (Key::AuthFailed, "Authentication Failed"),
+                (Key::ErrorCode, "Error Code:"),
+                (Key::ErrorDescription, "Error Description:"),
+                (Key::ErrorDetails, "Error Details:"),
+                (Key::CommonCauses, "Common Causes:"),
+                (
+                    Key::CauseAccessDenied,
+                    "User cancelled the login or doesn't have access",
+                ),
+                (
+                    Key::CauseUnauthorized,
+                    "Invalid organization or missing permissions",
+                ),
+                (Key::CauseInvalidRequest, "Malformed request parameters"),
+                (Key::CauseServerError, "Issue with the identity provider"),
+                (
+                    Key::CauseOrganizationRequired,
+                    "Organization parameter is required but not provided",
+                ),
+                (Key::TryAgain, "Try Again"),
+                (Key::ReturnToLogin, "Return to Login"),
+            ]),
+        ),
+        (
+            Lang::Es,
+            catalog(&[
+                (Key::AuthError, "Error de autenticación"),
+                (Key::AuthFailed, "La autenticación falló"),
+                (Key::ErrorCode, "Código de error:"),
+                (Key::ErrorDescription, "Descripción del error:"),
+                (Key::ErrorDetails, "Detalles del error:"),
+                (Key::CommonCauses, "Causas comunes:"),
+                (
+                    Key::CauseAccessDenied,
+                    "El usuario canceló el inicio de sesión o no tiene acceso",
+                ),
+                (
+                    Key::CauseUnauthorized,
+                    "Organización no válida o permisos insuficientes",
+                ),
+                (
+                    Key::CauseInvalidRequest,
+                    "Parámetros de la solicitud mal formados",
+                ),
+                (
+                    Key::CauseServerError,
+                    "Problema con el proveedor de identidad",
+                ),
+                (
+                    Key::CauseOrganizationRequired,
+                    "El parámetro de organización es obligatorio",
+                ),
+                (Key::TryAgain, "Intentar de nuevo"),
+                (Key::ReturnToLogin, "Volver al inicio de sesión"),
+            ]),
+        ),
+        (
+            Lang::Fr,
+            catalog(&[
+                (Key::AuthError, "Erreur d'authentification"),
+                (Key::AuthFailed, "Échec de l'authentification"),
+                (Key::ErrorCode, "Code d'erreur :"),
+                (Key::ErrorDescription, "Description de l'erreur :"),
+                (Key::ErrorDetails, "Détails de l'erreur :"),
+                (Key::CommonCauses, "Causes fréquentes :"),
+                (
+                    Key::CauseAccessDenied,
+                    "L'utilisateur a annulé la connexion ou n'a pas accès",
+                ),
+                (
+                    Key::CauseUnauthorized,
+                    "Organisation invalide ou permissions manquantes",
+                ),
+                (Key::CauseInvalidRequest, "Paramètres de requête invalides"),
+                (
+                    Key::CauseServerError,
+                    "Problème avec le fournisseur d'identité",
+                ),
+                (
+                    Key::CauseOrganizationRequired,
+                    "Le paramètre d'organisation est obligatoire",
+                ),
+                (Key::TryAgain, "Réessayer"),
+                (Key::ReturnToLogin, "Retour à la connexion"),
+            ]),
+        ),
+    ])
+});
+
+/// Message for `key` in `lang`, or in English when the catalog lacks it
+pub fn translate(lang: Lang, key: Key) -> &'static str {
+    CATALOG
+        .get(&lang)
+        .and_then(|messages| messages.get(&key))
+        .or_else(|| CATALOG[&Lang::En].get(&key))
+        .map(String::as_str)
+        .unwrap_or_default()
+}
+
+/// Preferred language with a catalog in an `Accept-Language` value, e.g.
+/// `fr-CH, fr;q=0.9, en;q=0.8`. A tag matches its language exactly or by its
+/// primary subtag (`en-US` selects `en`); without a match the language is English.
+pub fn negotiate(accept_language: &str) -> Lang {
+    let mut ranges: Vec<(&str, f32)> = accept_language
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let tag = parts.next()?.trim();
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse().ok())?;
+            (!tag.is_empty() && quality > 0.0).then_some((tag, quality))
+        })
+        .collect();
+    // Stable, so tags of equal quality keep their order
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    ranges
+        .into_iter()
+        .find_map(|(tag, _)| {
+            Lang::from_code(tag).or_else(|| Lang::from_code(tag.split('-').next()?))
+        })
+        .unwrap_or(Lang::En)
+}
+
+/// Language of a request, from its `Accept-Language` header
+pub fn request_lang(headers: &HeaderMap) -> Lang {
+    headers
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map_or(Lang::En, negotiate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_language() {
+        // Exact match
+        assert_eq!(negotiate("fr"), Lang::Fr);
+        // Primary subtag match
+        assert_eq!(negotiate("en-US"), Lang::En);
+        assert_eq!(negotiate("es-MX,es;q=0.9"), Lang::Es);
+        // Quality order, skipping languages without a catalog
+        assert_eq!(negotiate("de;q=1.0, fr;q=0.5, es;q=0.8"), Lang::Es);
+        // Fallback
+        assert_eq!(negotiate("de-DE, ja;q=0.7"), Lang::En);
+        assert_eq!(negotiate(""), Lang::En);
+        assert_eq!(negotiate("fr;q=0"), Lang::En);
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(request_lang(&headers), Lang::En);
+        headers.insert(header::ACCEPT_LANGUAGE, "fr-CA".parse().unwrap());
+        assert_eq!(request_lang(&headers), Lang::Fr);
+    }
+
+    #[test]
+    fn test_translate() {
+        assert_eq!(translate(Lang::Es, Key::TryAgain), "Intentar de nuevo");
+        assert_eq!(translate(Lang::En, Key::TryAgain), "Try Again");
+        // Every key has an English message
+        for key in CATALOG[&Lang::Fr].keys() {
+            assert!(!translate(Lang::En, *key).is_empty());
+        }
+    }
+}
@@ -3,8 +3,16 @@ pub mod auth_m;
 pub mod context;
 pub mod controller;
 pub mod fga_apis;
+pub mod i18n;
 pub mod listener;
+pub mod logging;
+pub mod migrations;
+pub mod problem;
+pub mod request_id;
 pub mod routes;
+pub mod seed;
+pub mod telemetry;
+pub mod time_utils;
 
 // Re-export json types from openfga-client for convenience
 pub use openfga_grpc_client::{
@@ -2,11 +2,49 @@ use axum::Router;
 use std::net::SocketAddr;
 use tokio::net::TcpListener;
 
-/// Starts the HTTP server with the given router
+/// Starts the HTTP server with the given router. Returns once a shutdown signal
+/// (Ctrl-C or SIGTERM) is received and the in-flight requests have completed.
 pub async fn serve(app: Router, addr: SocketAddr) -> Result<(), std::io::Error> {
     tracing::info!("Listener starting on {}", addr);
 
     let listener = TcpListener::bind(addr).await.unwrap();
     tracing::info!("Server started successfully");
-    axum::serve(listener, app.into_make_service()).await
+    // The peer address tells requests from trusted proxies apart
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await
+}
+
+/// Resolves on Ctrl-C, or SIGTERM on Unix
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    tracing::info!("Shutdown signal received, draining requests");
 }
@@ -0,0 +1,70 @@
+//! Tracing setup with per-module log levels.
+//!
+//! `RUST_LOG` takes `EnvFilter` directives, e.g.
+//! `info,openfga_grpc_client::json_types=debug,service_demo::auth=debug` to see the
+//! model conversion and auth debug logs. Without it, [`DEFAULT_LOG_FILTER`] applies.
+
+use tracing_subscriber::filter::ParseError;
+use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
+
+/// Variable holding the log directives
+pub const LOG_ENV: &str = "RUST_LOG";
+
+/// Directives used when `RUST_LOG` is unset: info overall, with the per-query logs
+/// of sqlx quieted
+pub const DEFAULT_LOG_FILTER: &str =
+    "info,openfga_grpc_client=info,openfga_http_client=info,service_demo=info,sqlx=warn";
+
+/// Filter of `directives`, or of [`DEFAULT_LOG_FILTER`] when there are none
+pub fn log_filter(directives: Option<&str>) -> Result<EnvFilter, ParseError> {
+    match directives.map(str::trim) {
+        Some(directives) if !directives.is_empty() => EnvFilter::try_new(directives),
+        _ => EnvFilter::try_new(DEFAULT_LOG_FILTER),
+    }
+}
+
+/// Install the global subscriber, filtered by `RUST_LOG`. Invalid directives fall back
+/// to [`DEFAULT_LOG_FILTER`] with a warning.
+pub fn init_tracing() {
+    let directives = std::env::var(LOG_ENV).ok();
+    let (filter, error) = match log_filter(directives.as_deref()) {
+        Ok(filter) => (filter, None),
+        Err(e) => (
+            log_filter(None).expect("the default log filter is valid"),
+            Some(e),
+        ),
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    if let Some(e) = error {
+        tracing::warn!("Ignoring invalid {}: {}", LOG_ENV, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::filter::LevelFilter;
+
+    #[test]
+    fn test_log_filter_parses_module_directives() {
+        let filter = log_filter(Some(
+            "openfga_grpc_client=info,openfga_grpc_client::json_types=debug,service_demo::auth=debug",
+        ))
+        .unwrap();
+        let directives = filter.to_string();
+        assert!(directives.contains("openfga_grpc_client::json_types=debug"));
+        assert!(directives.contains("service_demo::auth=debug"));
+        assert_eq!(filter.max_level_hint(), Some(LevelFilter::DEBUG));
+
+        let default = log_filter(Some("  ")).unwrap();
+        assert_eq!(default.max_level_hint(), Some(LevelFilter::INFO));
+        assert!(log_filter(None).is_ok());
+
+        assert!(log_filter(Some("service_demo=loud")).is_err());
+    }
+}
@@ -1,19 +1,17 @@
 use service_demo::context::Ctx;
+use service_demo::fga_apis::store_names;
 use service_demo::listener;
+use service_demo::logging;
+use service_demo::request_id;
 use service_demo::routes;
+use service_demo::telemetry::TelemetryShutdown;
 use std::net::SocketAddr;
 use tower_http::trace::TraceLayer;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[tokio::main]
 async fn main() {
     // Initialize tracing
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(
-            std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
-        ))
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    logging::init_tracing();
 
     // Initialize the application context
     let ctx = match Ctx::new().await {
@@ -25,11 +23,23 @@ async fn main() {
     };
 
     // Initialize the application
-    let app = routes::create_routes(ctx).layer(TraceLayer::new_for_http());
+    // Store names in HTTP store routes are resolved to IDs before routing; the request
+    // ID is set before the trace span is created so the span carries it
+    let request_id_header = ctx.request_id_header.clone();
+    let app = store_names::with_store_names(ctx.clone(), routes::create_routes(ctx.clone()))
+        .layer(TraceLayer::new_for_http().make_span_with(request_id::make_span(request_id_header)))
+        .layer(axum::middleware::from_fn_with_state(
+            ctx,
+            request_id::request_id_middleware,
+        ));
 
     // Start the server
     let addr = SocketAddr::from(([127, 0, 0, 1], 5001));
     tracing::info!("Server listening on {}", addr);
 
+    // Exporters that buffer spans or metrics register their flush here
+    let telemetry = TelemetryShutdown::from_env();
+
     listener::serve(app, addr).await.unwrap();
+    telemetry.shutdown().await;
 }
@@ -0,0 +1,103 @@
+//! Database schema, applied in order from the `migrations/` directory.
+//!
+//! Migrations are embedded at compile time and recorded in `_sqlx_migrations`, so
+//! each runs once per database. They use `IF NOT EXISTS`, so databases whose tables
+//! were created by hand before migrations existed are adopted as they are.
+
+use anyhow::{Context, Result};
+use sqlx::PgPool;
+use sqlx::migrate::Migrator;
+
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Apply the migrations the database hasn't run yet
+pub async fn run_migrations(db: &PgPool) -> Result<()> {
+    MIGRATOR
+        .run(db)
+        .await
+        .context("Failed to run database migrations")?;
+    tracing::info!("Database schema is up to date");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+
+    #[test]
+    fn test_migrations_are_ordered() {
+        let migrations: Vec<(i64, &str)> = MIGRATOR
+            .iter()
+            .map(|m| (m.version, m.description.as_ref()))
+            .collect();
+        assert_eq!(
+            migrations,
+            vec![
+                (1, "users"),
+                (2, "user sessions"),
+                (3, "organizations"),
+                (4, "org stores"),
+                (5, "fga audit"),
+                (6, "auth events"),
+                (7, "organization previous secrets"),
+            ]
+        );
+    }
+
+    /// Applies the migrations to a fresh schema of the database at `TEST_DATABASE_URL`;
+    /// skipped when it isn't set
+    #[tokio::test]
+    async fn test_migrations_apply_to_empty_database() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("TEST_DATABASE_URL not set, skipping");
+            return;
+        };
+
+        let schema = format!("migrations_{}", uuid::Uuid::now_v7().simple());
+        let admin = PgPoolOptions::new().connect(&url).await.unwrap();
+        sqlx::query(&format!("CREATE SCHEMA {}", schema))
+            .execute(&admin)
+            .await
+            .unwrap();
+
+        let options: PgConnectOptions = url.parse().unwrap();
+        let db = PgPoolOptions::new()
+            .max_connections(1)
+            .connect_with(options.options([("search_path", schema.as_str())]))
+            .await
+            .unwrap();
+
+        run_migrations(&db).await.unwrap();
+        // Running again is a no-op
+        run_migrations(&db).await.unwrap();
+
+        let tables: Vec<String> = sqlx::query_scalar(
+            "SELECT table_name::TEXT FROM information_schema.tables \
+             WHERE table_schema = $1 ORDER BY table_name",
+        )
+        .bind(&schema)
+        .fetch_all(&db)
+        .await
+        .unwrap();
+
+        db.close().await;
+        sqlx::query(&format!("DROP SCHEMA {} CASCADE", schema))
+            .execute(&admin)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            tables,
+            vec![
+                "_sqlx_migrations",
+                "auth_events",
+                "fga_audit",
+                "org_stores",
+                "organizations",
+                "user_sessions",
+                "users",
+            ]
+        );
+    }
+}
@@ -0,0 +1,265 @@
+use axum::{
+    Json,
+    body::HttpBody,
+    extract::{Request, State},
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::auth::authn_controller::AppError;
+use crate::context::Ctx;
+
+/// Media type of RFC 7807 problem details
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Largest error body the middleware will buffer for conversion
+const MAX_ERROR_BODY_SIZE: usize = 64 * 1024;
+
+/// RFC 7807 problem details
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+}
+
+impl ProblemDetails {
+    /// Create problem details with the generic `about:blank` type
+    pub fn new(status: StatusCode, detail: impl Into<String>) -> Self {
+        Self {
+            problem_type: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or("Error").to_string(),
+            status: status.as_u16(),
+            detail: detail.into(),
+            instance: None,
+        }
+    }
+
+    /// Set the URI reference identifying this occurrence of the problem
+    pub fn with_instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+}
+
+impl From<&tonic::Status> for ProblemDetails {
+    fn from(status: &tonic::Status) -> Self {
+        ProblemDetails::new(grpc_code_to_http(status.code()), status.message())
+    }
+}
+
+impl From<AppError> for ProblemDetails {
+    fn from(error: AppError) -> Self {
+        match error {
+            AppError::NotFound(msg) => ProblemDetails::new(StatusCode::NOT_FOUND, msg),
+            AppError::BadRequest(msg) => ProblemDetails::new(StatusCode::BAD_REQUEST, msg),
+            AppError::InternalError(msg) => {
+                ProblemDetails::new(StatusCode::INTERNAL_SERVER_ERROR, msg)
+            }
+        }
+    }
+}
+
+impl IntoResponse for ProblemDetails {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let mut response = (status, Json(self)).into_response();
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+        response
+    }
+}
+
+/// Map a gRPC status code to the closest HTTP status
+pub fn grpc_code_to_http(code: tonic::Code) -> StatusCode {
+    use tonic::Code;
+
+    match code {
+        Code::Ok => StatusCode::OK,
+        Code::InvalidArgument | Code::OutOfRange | Code::FailedPrecondition => {
+            StatusCode::BAD_REQUEST
+        }
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        Code::Cancelled => StatusCode::REQUEST_TIMEOUT,
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::Unknown | Code::Internal | Code::DataLoss => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Rewrite JSON error responses as problem+json.
+///
+/// Applies when the client sends `Accept: application/problem+json` or when
+/// `Ctx::problem_json` is enabled. The `detail` is taken from the `message` or
+/// `error` field of the original body. Bodies over `MAX_ERROR_BODY_SIZE` are passed
+/// through unchanged.
+pub async fn problem_json_middleware(
+    State(ctx): State<Ctx>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let wants_problem = ctx.problem_json || accepts_problem_json(&request);
+    let instance = request.uri().path().to_string();
+
+    let response = next.run(request).await;
+    if !wants_problem || !is_json_error(&response) {
+        return response;
+    }
+    if response
+        .body()
+        .size_hint()
+        .upper()
+        .is_none_or(|size| size > MAX_ERROR_BODY_SIZE as u64)
+    {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, MAX_ERROR_BODY_SIZE).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            // The body is partly read, so only the status is left to report
+            tracing::warn!("Failed to buffer error body for problem+json: {}", e);
+            return ProblemDetails::new(parts.status, "")
+                .with_instance(instance)
+                .into_response();
+        }
+    };
+
+    let detail = serde_json::from_slice::<Value>(&bytes)
+        .ok()
+        .and_then(|body| {
+            ["message", "error"]
+                .iter()
+                .find_map(|key| body.get(key).and_then(Value::as_str).map(str::to_string))
+        })
+        .unwrap_or_default();
+
+    ProblemDetails::new(parts.status, detail)
+        .with_instance(instance)
+        .into_response()
+}
+
+fn accepts_problem_json(request: &Request) -> bool {
+    request
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains(PROBLEM_JSON))
+}
+
+fn is_json_error(response: &Response) -> bool {
+    let status = response.status();
+    (status.is_client_error() || status.is_server_error())
+        && response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|ct| ct.starts_with("application/json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, routing::get};
+    use tower::ServiceExt;
+
+    async fn problem_fields(response: Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_not_found_status_as_problem_json() {
+        let status = tonic::Status::not_found("store 01HXYZ not found");
+        let response = ProblemDetails::from(&status)
+            .with_instance("/api/ofga/grpc/store/01HXYZ")
+            .into_response();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_JSON);
+
+        let body = problem_fields(response).await;
+        assert_eq!(body["type"], "about:blank");
+        assert_eq!(body["title"], "Not Found");
+        assert_eq!(body["status"], 404);
+        assert_eq!(body["detail"], "store 01HXYZ not found");
+        assert_eq!(body["instance"], "/api/ofga/grpc/store/01HXYZ");
+    }
+
+    #[tokio::test]
+    async fn test_middleware_converts_app_error_when_requested() {
+        async fn handler() -> Result<(), AppError> {
+            Err(AppError::NotFound("Organization not found".to_string()))
+        }
+
+        let app =
+            Router::new()
+                .route("/org", get(handler))
+                .layer(axum::middleware::from_fn_with_state(
+                    Ctx::for_test(),
+                    problem_json_middleware,
+                ));
+
+        let request = Request::builder()
+            .uri("/org")
+            .header(header::ACCEPT, PROBLEM_JSON)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_JSON);
+        let body = problem_fields(response).await;
+        assert_eq!(body["detail"], "Organization not found");
+        assert_eq!(body["instance"], "/org");
+
+        // Without the Accept header the original body is kept
+        let request = Request::builder().uri("/org").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+    }
+
+    #[tokio::test]
+    async fn test_middleware_keeps_large_error_body() {
+        let message = "x".repeat(MAX_ERROR_BODY_SIZE);
+        let expected = serde_json::json!({ "message": message });
+        let body = expected.clone();
+
+        let app = Router::new()
+            .route(
+                "/export",
+                get(move || async move { (StatusCode::BAD_GATEWAY, Json(body)) }),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                Ctx::for_test(),
+                problem_json_middleware,
+            ));
+
+        let request = Request::builder()
+            .uri("/export")
+            .header(header::ACCEPT, PROBLEM_JSON)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(problem_fields(response).await, expected);
+    }
+}
@@ -0,0 +1,126 @@
+//! Request IDs for correlating the logs of this service and OpenFGA.
+//!
+//! [`request_id_middleware`] takes the ID from the request's `X-Request-Id` header (the
+//! name is configurable with `REQUEST_ID_HEADER`) or generates one, echoes it on the
+//! response and runs the handler with it as the task's request ID, which the OpenFGA
+//! clients send as gRPC metadata. [`make_span`] adds it to the HTTP trace spans.
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use openfga_grpc_client::with_request_id;
+
+use crate::context::Ctx;
+
+/// Header carrying the request ID unless `REQUEST_ID_HEADER` names another one
+pub const DEFAULT_REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest incoming request ID that is kept
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Request ID sent by the caller, if it's short printable ASCII
+fn incoming_request_id(request: &Request, header: &HeaderName) -> Option<String> {
+    let id = request.headers().get(header)?.to_str().ok()?;
+    (!id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.bytes().all(|b| b.is_ascii_graphic()))
+        .then(|| id.to_string())
+}
+
+/// Use the caller's request ID, or a new one, for the request and its response
+pub async fn request_id_middleware(
+    State(ctx): State<Ctx>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let header = &ctx.request_id_header;
+    let request_id =
+        incoming_request_id(&request, header).unwrap_or_else(|| uuid::Uuid::now_v7().to_string());
+    let value = HeaderValue::from_str(&request_id).expect("request IDs are printable ASCII");
+
+    // Later layers, such as the trace span, read the ID from the request
+    request.headers_mut().insert(header.clone(), value.clone());
+    let mut response = with_request_id(request_id, next.run(request)).await;
+    response.headers_mut().insert(header.clone(), value);
+    response
+}
+
+/// Span of an HTTP request carrying its request ID, for
+/// `TraceLayer::new_for_http().make_span_with(...)` inside [`request_id_middleware`]
+pub fn make_span(header: HeaderName) -> impl Fn(&Request) -> tracing::Span + Clone {
+    move |request: &Request| {
+        let request_id = request
+            .headers()
+            .get(&header)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        tracing::info_span!(
+            "request",
+            method = %request.method(),
+            uri = %request.uri(),
+            request_id = %request_id,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, routing::get};
+    use openfga_grpc_client::request_id::current_request_id;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_request_id_is_echoed() {
+        // The handler sees the request ID the OpenFGA calls are made with
+        async fn handler() -> String {
+            current_request_id().unwrap_or_default()
+        }
+
+        let ctx = Ctx::for_test();
+        let app = Router::new().route("/health", get(handler)).layer(
+            axum::middleware::from_fn_with_state(ctx.clone(), request_id_middleware),
+        );
+
+        let request = Request::builder()
+            .uri("/health")
+            .header("X-Request-Id", "req-42")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[DEFAULT_REQUEST_ID_HEADER], "req-42");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body, "req-42");
+
+        // Without one, an ID is generated
+        let request = Request::builder()
+            .uri("/health")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let generated = response.headers()[DEFAULT_REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(uuid::Uuid::parse_str(&generated).is_ok());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body, generated.as_str());
+
+        // IDs that can't be forwarded are replaced
+        let request = Request::builder()
+            .uri("/health")
+            .header("X-Request-Id", "a".repeat(MAX_REQUEST_ID_LEN + 1))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(
+            response.headers()[DEFAULT_REQUEST_ID_HEADER].len(),
+            generated.len()
+        );
+    }
+}
@@ -1,16 +1,18 @@
 /// Authentication Routes
 ///
 /// This module contains route definitions for the multi-tenant authentication flow
+use crate::auth::authn::{StateCache, state_cache_health};
 use crate::auth::authn_controller::{
     AppState, LoginRequest, extract_subdomain_from_host, get_authorize_url_handler, login_handler,
 };
 use axum::{
     Json, Router,
     extract::{Query, State},
-    http::HeaderMap,
+    http::{HeaderMap, StatusCode},
     response::Response,
     routing::{get, post},
 };
+use serde_json::{Value, json};
 
 // ============================================================================
 // Route Handlers
@@ -70,15 +72,19 @@ async fn callback_handler(
     })?;
 
     // Handle callback
+    let requested_scopes = org_config.effective_scopes(&state.dex_config);
     let result = crate::auth::callback::handle_callback(
         &state.db,
-        &state.dex_config,
+        &crate::auth::callback::DexTokenExchanger::new(&state.dex_config),
         &org_config,
         &auth_builder,
-        &query,
-        &cookies,
-        &client_ip,
-        &client_user_agent,
+        crate::auth::callback::CallbackRequest {
+            query: &query,
+            cookies: &cookies,
+            client_ip: &client_ip,
+            client_user_agent: &client_user_agent,
+            requested_scopes: &requested_scopes,
+        },
     )
     .await
     .map_err(|e| {
@@ -89,7 +95,7 @@ async fn callback_handler(
     tracing::info!(
         "User {} logged in successfully with session {}",
         result.user_id,
-        result.session_id
+        result.session_id.as_deref().unwrap_or("stateless")
     );
 
     // Redirect to return URL
@@ -191,6 +197,42 @@ async fn api_login_handler(
     Ok(Json(serde_json::to_value(response.0).unwrap()))
 }
 
+/// Health of Redis and the auth state cache
+///
+/// Only whether the cache is up is public; the Redis error, the auth state count and
+/// the latency metrics are logged.
+///
+/// # Response
+/// 200 `{ "status": "up" }` when Redis is reachable, 503 `{ "status": "down" }` otherwise
+async fn health_handler(State(state): State<AppState>) -> (StatusCode, Json<Value>) {
+    let state_cache = match StateCache::new_with_pool(state.redis_pool.clone()).await {
+        Ok(state_cache) => state_cache,
+        Err(e) => {
+            tracing::error!("Failed to create state cache: {:?}", e);
+            return health_response(false);
+        }
+    };
+    let health = state_cache_health(&state_cache).await;
+
+    if health.redis_reachable {
+        tracing::debug!("Auth state cache health: {:?}", health);
+    } else {
+        tracing::error!("Auth state cache is unhealthy: {:?}", health);
+    }
+    health_response(health.redis_reachable)
+}
+
+fn health_response(up: bool) -> (StatusCode, Json<Value>) {
+    if up {
+        (StatusCode::OK, Json(json!({ "status": "up" })))
+    } else {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "status": "down" })),
+        )
+    }
+}
+
 // ============================================================================
 // Route Definitions
 // ============================================================================
@@ -205,6 +247,8 @@ pub fn auth_routes(state: AppState) -> Router {
         .route("/api/v2/login-with", post(api_login_handler))
         // OAuth callback (handles token exchange and session creation)
         .route("/auth/callback", get(callback_handler))
+        // Redis and auth state cache health
+        .route("/auth/health", get(health_handler))
         .layer(tower_cookies::CookieManagerLayer::new()) // Add cookie middleware
         .with_state(state)
 }
@@ -9,6 +9,10 @@ pub fn routes<S: Send + Sync>(ctx: crate::context::Ctx) -> Router<S> {
             "/auth/callback",
             get(crate::auth::openid::handle_openid_callback),
         )
+        .route(
+            "/auth/session",
+            get(crate::auth::session::get_session::<crate::context::Ctx>),
+        )
         .with_state(ctx)
 }
 
@@ -1,5 +1,12 @@
 use crate::context::Ctx;
 use crate::fga_apis;
+use crate::fga_apis::audit::Audited;
+use crate::fga_apis::backend::{GrpcBackend, HttpBackend};
+use crate::fga_apis::contextual_tuples;
+use crate::fga_apis::relationships;
+use crate::fga_apis::store_access;
+use axum::extract::DefaultBodyLimit;
+use axum::middleware;
 use axum::routing::delete;
 use axum::{
     Router,
@@ -7,34 +14,44 @@ use axum::{
 };
 
 pub fn create_fga_routes<S: Send + Sync>(ctx: Ctx) -> Router<S> {
+    // Authorization models and bulk writes can be large; cap them at the configured size
+    let body_limit = DefaultBodyLimit::max(ctx.fga_config.max_body_size);
+
+    // Creating and listing stores work on the session's organization, not on a store
+    let org_routes = Router::new()
+        .route(
+            "/api/ofga/grpc/store",
+            post(fga_apis::grpc::stores::create_store).get(fga_apis::grpc::stores::list_stores),
+        )
+        .route(
+            "/api/ofga/http/stores",
+            post(fga_apis::http::stores::create_store).get(fga_apis::http::stores::list_stores),
+        );
+
     Router::new() // =============================================================================
         // gRPC-based APIs (existing)
         // =============================================================================
         // store APIs (gRPC)
-        .route(
-            "/api/ofga/grpc/store",
-            post(fga_apis::grpc::stores::create_store),
-        )
         .route(
             "/api/ofga/grpc/store/{store_id}",
             get(fga_apis::grpc::stores::get_store),
         )
-        .route(
-            "/api/ofga/grpc/store",
-            get(fga_apis::grpc::stores::list_stores),
-        )
         .route(
             "/api/ofga/grpc/store/{store_id}",
             delete(fga_apis::grpc::stores::delete_store),
         )
+        .route(
+            "/api/ofga/grpc/store/{store_id}/confirmed",
+            delete(fga_apis::grpc::stores::delete_store_confirmed),
+        )
         // model APIs (gRPC)
         .route(
             "/api/ofga/grpc/model/{store_id}",
-            post(fga_apis::grpc::auth_model::create_auth_model),
+            post(fga_apis::grpc::auth_model::create_auth_model).layer(body_limit),
         )
         .route(
             "/api/ofga/grpc/model-json/{store_id}",
-            post(fga_apis::grpc::auth_model::create_auth_model_from_json),
+            post(fga_apis::grpc::auth_model::create_auth_model_from_json).layer(body_limit),
         )
         .route(
             "/api/ofga/grpc/model/{store_id}/{auth_model_id}",
@@ -44,18 +61,22 @@ pub fn create_fga_routes<S: Send + Sync>(ctx: Ctx) -> Router<S> {
             "/api/ofga/grpc/model/{store_id}",
             get(fga_apis::grpc::auth_model::list_auth_models),
         )
+        .route(
+            "/api/ofga/grpc/schema/{store_id}",
+            get(fga_apis::grpc::auth_model::get_schema_summary),
+        )
         // tuple APIs (gRPC)
         .route(
             "/api/ofga/grpc/tuple-write",
-            post(fga_apis::grpc::tuples::write_tuple),
+            post(relationships::write_tuples::<Audited<GrpcBackend>>).layer(body_limit),
         )
         .route(
             "/api/ofga/grpc/tuple-read",
-            post(fga_apis::grpc::tuples::read_tuple),
+            post(relationships::read_tuples::<GrpcBackend>),
         )
         .route(
             "/api/ofga/grpc/tuple-delete",
-            post(fga_apis::grpc::tuples::delete_tuple),
+            post(relationships::delete_tuples::<Audited<GrpcBackend>>).layer(body_limit),
         )
         .route(
             "/api/ofga/grpc/tuple-changes",
@@ -64,30 +85,28 @@ pub fn create_fga_routes<S: Send + Sync>(ctx: Ctx) -> Router<S> {
         // tuple query APIs (gRPC)
         .route(
             "/api/ofga/grpc/list-objs",
-            get(fga_apis::grpc::query::list_objects),
+            get(relationships::list_objects::<GrpcBackend>),
         )
         .route(
             "/api/ofga/grpc/list-users",
-            get(fga_apis::grpc::query::list_users),
+            get(relationships::list_users::<GrpcBackend>),
+        )
+        .route(
+            "/api/ofga/grpc/check",
+            post(relationships::check::<Audited<GrpcBackend>>),
         )
-        .route("/api/ofga/grpc/check", post(fga_apis::grpc::query::check))
         .route(
             "/api/ofga/grpc/batch-check",
             post(fga_apis::grpc::query::batch_check),
         )
-        .route("/api/ofga/grpc/expand", post(fga_apis::grpc::query::expand))
+        .route(
+            "/api/ofga/grpc/expand",
+            post(relationships::expand::<GrpcBackend>),
+        )
         // =============================================================================
         // HTTP-based APIs (new - following OpenFGA REST API standards)
         // =============================================================================
         // store APIs (HTTP) - following OpenFGA REST API paths
-        .route(
-            "/api/ofga/http/stores",
-            post(fga_apis::http::stores::create_store),
-        )
-        .route(
-            "/api/ofga/http/stores",
-            get(fga_apis::http::stores::list_stores),
-        )
         .route(
             "/api/ofga/http/stores/{store_id}",
             get(fga_apis::http::stores::get_store),
@@ -96,10 +115,18 @@ pub fn create_fga_routes<S: Send + Sync>(ctx: Ctx) -> Router<S> {
             "/api/ofga/http/stores/{store_id}",
             delete(fga_apis::http::stores::delete_store),
         )
+        .route(
+            "/api/ofga/http/stores/{store_id}/confirmed",
+            delete(fga_apis::http::stores::delete_store_confirmed),
+        )
+        .route(
+            "/api/ofga/http/stores/{store_id}/tuples/export",
+            get(relationships::export_tuples::<HttpBackend>),
+        )
         // authorization model APIs (HTTP)
         .route(
             "/api/ofga/http/stores/{store_id}/authorization-models",
-            post(fga_apis::http::auth_model::create_auth_model),
+            post(fga_apis::http::auth_model::create_auth_model).layer(body_limit),
         )
         .route(
             "/api/ofga/http/stores/{store_id}/authorization-models",
@@ -111,39 +138,216 @@ pub fn create_fga_routes<S: Send + Sync>(ctx: Ctx) -> Router<S> {
         )
         .route(
             "/api/ofga/http/stores/{store_id}/authorization-models/json",
-            post(fga_apis::http::auth_model::create_auth_model_from_json),
+            post(fga_apis::http::auth_model::create_auth_model_from_json).layer(body_limit),
         )
         // tuple APIs (HTTP)
         .route(
             "/api/ofga/http/write",
-            post(fga_apis::http::tuples::write_tuple),
+            post(relationships::write_tuples::<Audited<HttpBackend>>).layer(body_limit),
+        )
+        .route(
+            "/api/ofga/http/write-conditional",
+            post(fga_apis::http::tuples::write_conditional_tuples).layer(body_limit),
         )
         .route(
             "/api/ofga/http/read",
-            post(fga_apis::http::tuples::read_tuple),
+            post(relationships::read_tuples::<HttpBackend>),
         )
         .route(
             "/api/ofga/http/delete",
-            post(fga_apis::http::tuples::delete_tuple),
+            post(relationships::delete_tuples::<Audited<HttpBackend>>).layer(body_limit),
         )
         .route(
             "/api/ofga/http/changes",
             post(fga_apis::http::tuples::tuple_changes),
         )
         // relationship query APIs (HTTP)
-        .route("/api/ofga/http/check", post(fga_apis::http::query::check))
+        .route(
+            "/api/ofga/http/check",
+            post(relationships::check::<Audited<HttpBackend>>),
+        )
+        .route(
+            "/api/ofga/http/stores/{store_id}/check/{object}/{relation}/{user}",
+            get(relationships::check_by_path::<Audited<HttpBackend>>),
+        )
         .route(
             "/api/ofga/http/batch-check",
             post(fga_apis::http::query::batch_check),
         )
-        .route("/api/ofga/http/expand", post(fga_apis::http::query::expand))
+        .route(
+            "/api/ofga/http/check-many",
+            post(relationships::check_many::<Audited<HttpBackend>>),
+        )
+        .route(
+            "/api/ofga/http/expand",
+            post(relationships::expand::<HttpBackend>),
+        )
         .route(
             "/api/ofga/http/list-objects",
-            post(fga_apis::http::query::list_objects),
+            post(relationships::list_objects::<HttpBackend>),
         )
         .route(
             "/api/ofga/http/list-users",
-            post(fga_apis::http::query::list_users),
+            post(relationships::list_users::<HttpBackend>),
         )
+        // A store named in the path or the body must be mapped to the session's organization
+        .route_layer(middleware::from_fn_with_state(
+            ctx.clone(),
+            store_access::store_access_middleware,
+        ))
+        .merge(org_routes)
+        // Checks, writes and deletes are recorded with the session's user when auditing is on
+        .route_layer(middleware::from_fn_with_state(
+            ctx.clone(),
+            fga_apis::audit::audit_user_middleware,
+        ))
+        // Mapped request headers are attached to checks and queries as contextual tuples
+        .route_layer(middleware::from_fn_with_state(
+            contextual_tuples::from_headers(
+                ctx.fga_config.contextual_tuple_headers.clone(),
+                ctx.fga_config.contextual_tuple_trusted_proxies.clone(),
+            ),
+            contextual_tuples::contextual_tuples_middleware,
+        ))
         .with_state(ctx)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode, header};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_model_route_rejects_body_over_limit() {
+        let mut ctx = Ctx::for_test();
+        ctx.fga_config.max_body_size = 1024;
+        let app: Router = create_fga_routes(ctx);
+
+        let body = format!(r#"{{"schema_version":"{}"}}"#, "x".repeat(2048));
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/ofga/grpc/model-json/store")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_write_routes_reject_body_over_limit() {
+        let mut ctx = Ctx::for_test();
+        ctx.fga_config.max_body_size = 1024;
+        let app: Router = create_fga_routes(ctx);
+
+        let body = format!(r#"{{"user":"{}"}}"#, "x".repeat(2048));
+        for uri in [
+            "/api/ofga/grpc/tuple-write",
+            "/api/ofga/grpc/tuple-delete",
+            "/api/ofga/http/write",
+            "/api/ofga/http/delete",
+        ] {
+            let request = Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.clone()))
+                .unwrap();
+
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE, "{}", uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_routes_accept_bodies_up_to_limit() {
+        // Above axum's 2 MB default, which applies to routes without the limit layer
+        let mut ctx = Ctx::for_test();
+        ctx.fga_config.max_body_size = 4 * 1024 * 1024;
+        let app: Router = create_fga_routes(ctx);
+
+        let body = format!(r#"{{"user":"{}"}}"#, "x".repeat(3 * 1024 * 1024));
+        for uri in [
+            "/api/ofga/grpc/tuple-write",
+            "/api/ofga/grpc/tuple-delete",
+            "/api/ofga/http/write",
+            "/api/ofga/http/delete",
+        ] {
+            let request = Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.clone()))
+                .unwrap();
+
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_ne!(response.status(), StatusCode::PAYLOAD_TOO_LARGE, "{}", uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_store_route_requires_session() {
+        let app: Router = create_fga_routes(Ctx::for_test());
+
+        for uri in [
+            "/api/ofga/grpc/model/01HXYZ",
+            "/api/ofga/grpc/store/01HXYZ",
+            "/api/ofga/grpc/schema/01HXYZ",
+            "/api/ofga/http/stores/01HXYZ/tuples/export",
+            "/api/ofga/http/stores/01HXYZ/check/document:readme/viewer/user:anne",
+        ] {
+            let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{}", uri);
+        }
+
+        // Stores named in the body are checked the same way
+        for uri in [
+            "/api/ofga/http/read",
+            "/api/ofga/http/changes",
+            "/api/ofga/http/write-conditional",
+            "/api/ofga/grpc/tuple-read",
+        ] {
+            let request = Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(r#"{"store_id":"01HXYZ"}"#))
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{}", uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_checks_require_session_for_other_stores() {
+        let app: Router = create_fga_routes(Ctx::for_test());
+
+        for (uri, body) in [
+            (
+                "/api/ofga/grpc/batch-check",
+                r#"{"store_id":"01HXYZ","checks":[]}"#,
+            ),
+            (
+                "/api/ofga/http/batch-check",
+                r#"{"store_id":"01HXYZ","batch_check_request":{"checks":[]}}"#,
+            ),
+            (
+                "/api/ofga/http/check-many",
+                r#"{"store_id":"01HXYZ","checks":[]}"#,
+            ),
+        ] {
+            let request = Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body))
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{}", uri);
+        }
+    }
+}
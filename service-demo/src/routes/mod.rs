@@ -5,6 +5,7 @@ pub mod fga;
 use crate::auth_m;
 use crate::context::Ctx;
 use crate::controller;
+use crate::problem;
 use axum::{
     Json, Router,
     http::StatusCode,
@@ -46,8 +47,14 @@ pub fn create_routes<S: Send + Sync>(ctx: Ctx) -> Router<S> {
         .merge(dex::routes(ctx.clone()))
         .merge(dex::routes_auth0(ctx.clone()));
 
-    // Merge all routes
-    public_routes.merge(protected_routes).with_state(ctx)
+    // Merge all routes; error responses are rendered as problem+json when requested
+    public_routes
+        .merge(protected_routes)
+        .layer(middleware::from_fn_with_state(
+            ctx.clone(),
+            problem::problem_json_middleware,
+        ))
+        .with_state(ctx)
 }
 
 /// Health check endpoint
@@ -0,0 +1,247 @@
+use anyhow::{Context, Result, anyhow, bail};
+use openfga_grpc_client::bundle::{STORE_BUNDLE_VERSION, StoreBundle, import_store};
+use openfga_grpc_client::{AuthorizationModel, CreateStoreRequest, OpenFGAClient, TupleKey};
+use std::path::PathBuf;
+
+use crate::fga_apis::dto::Tuple;
+
+/// Model written when `--model` isn't given, relative to the repository root
+pub const DEFAULT_SEED_MODEL_PATH: &str = "etc/fga/auth-model-example.json";
+
+/// Name of the store created when `--store-name` isn't given
+pub const DEFAULT_SEED_STORE_NAME: &str = "openfga-demo";
+
+const USAGE: &str =
+    "usage: seed [--endpoint URL] [--store-name NAME] [--model PATH] [--tuples PATH]";
+
+/// Options of the `seed` binary
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeedConfig {
+    /// OpenFGA gRPC endpoint
+    pub endpoint: String,
+    pub store_name: String,
+    /// JSON authorization model
+    pub model_path: PathBuf,
+    /// JSON array of `{ "user", "relation", "object" }` tuples, each with an optional
+    /// `"condition": { "name", "context" }`; [`sample_tuples`] when `None`
+    pub tuples_path: Option<PathBuf>,
+}
+
+impl SeedConfig {
+    /// Parse command line arguments, without the program name
+    pub fn from_args(
+        args: impl IntoIterator<Item = String>,
+        default_endpoint: String,
+    ) -> Result<Self> {
+        let mut config = SeedConfig {
+            endpoint: default_endpoint,
+            store_name: DEFAULT_SEED_STORE_NAME.to_string(),
+            model_path: PathBuf::from(DEFAULT_SEED_MODEL_PATH),
+            tuples_path: None,
+        };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| anyhow!("missing value for {}\n{}", arg, USAGE))
+            };
+            match arg.as_str() {
+                "--endpoint" => config.endpoint = value()?,
+                "--store-name" => config.store_name = value()?,
+                "--model" => config.model_path = PathBuf::from(value()?),
+                "--tuples" => config.tuples_path = Some(PathBuf::from(value()?)),
+                _ => bail!("unknown argument {}\n{}", arg, USAGE),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// Tuples written when no tuple file is given, matching the example model
+pub fn sample_tuples() -> Vec<TupleKey> {
+    [
+        ("user:anne", "member", "organisation:system"),
+        ("user:bob", "admin", "organisation:system"),
+        ("user:carl", "member", "organisation:partner"),
+        ("organisation:partner", "child", "organisation:system"),
+        ("user:diana", "member", "group:engineering"),
+        ("user:anne", "owner", "resource:roadmap"),
+        ("organisation:system", "parent_org", "resource:roadmap"),
+        ("group:engineering#member", "viewer", "resource:roadmap"),
+    ]
+    .into_iter()
+    .map(|(user, relation, object)| tuple_key(user, relation, object))
+    .collect()
+}
+
+/// Parse a JSON array of tuples. Tuples with a condition carry its name and context;
+/// the others import as plain tuples.
+pub fn parse_tuples(json: &str) -> Result<Vec<TupleKey>> {
+    let tuples: Vec<Tuple> = serde_json::from_str(json)?;
+    tuples
+        .into_iter()
+        .enumerate()
+        .map(|(i, tuple)| {
+            TupleKey::try_from(tuple)
+                .with_context(|| format!("invalid condition context of tuple {}", i))
+        })
+        .collect()
+}
+
+/// Convert a JSON model and tuples into a bundle ready for import
+pub fn seed_bundle(model_json: &str, tuples: Vec<TupleKey>) -> Result<StoreBundle> {
+    let (type_definitions, schema_version, conditions) =
+        OpenFGAClient::parse_authorization_model_from_json(model_json)
+            .map_err(|e| anyhow!("invalid model JSON: {}", e))?
+            .to_openfga_types()
+            .map_err(|e| anyhow!("failed to convert model: {}", e))?;
+
+    Ok(StoreBundle {
+        version: STORE_BUNDLE_VERSION,
+        authorization_model: AuthorizationModel {
+            id: String::new(),
+            schema_version,
+            type_definitions,
+            conditions,
+        },
+        tuples,
+    })
+}
+
+/// IDs created by [`seed`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeedOutput {
+    pub store_id: String,
+    pub authorization_model_id: String,
+    pub tuples_written: usize,
+}
+
+/// Create a store, write the model and write the tuples
+pub async fn seed(config: &SeedConfig) -> Result<SeedOutput> {
+    let model_json = std::fs::read_to_string(&config.model_path)
+        .with_context(|| format!("failed to read model {}", config.model_path.display()))?;
+    let tuples = match &config.tuples_path {
+        Some(path) => std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read tuples {}", path.display()))
+            .and_then(|json| {
+                parse_tuples(&json)
+                    .with_context(|| format!("invalid tuples file {}", path.display()))
+            })?,
+        None => sample_tuples(),
+    };
+    let bundle = seed_bundle(&model_json, tuples)
+        .with_context(|| format!("invalid model {}", config.model_path.display()))?;
+
+    let mut client = OpenFGAClient::builder(config.endpoint.clone())
+        .connect()
+        .await
+        .map_err(|e| anyhow!("failed to connect to OpenFGA at {}: {}", config.endpoint, e))?;
+
+    let store_id = client
+        .create_store(CreateStoreRequest {
+            name: config.store_name.clone(),
+        })
+        .await
+        .context("failed to create store")?
+        .into_inner()
+        .id;
+
+    let report = import_store(&mut client, &store_id, bundle)
+        .await
+        .with_context(|| format!("failed to write model and tuples to store {}", store_id))?;
+
+    Ok(SeedOutput {
+        store_id,
+        authorization_model_id: report.authorization_model_id,
+        tuples_written: report.tuples_written,
+    })
+}
+
+fn tuple_key(user: &str, relation: &str, object: &str) -> TupleKey {
+    TupleKey {
+        user: user.to_string(),
+        relation: relation.to_string(),
+        object: object.to_string(),
+        condition: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seed_args() {
+        let args = ["--model", "model.json", "--tuples", "tuples.json"].map(String::from);
+        let config = SeedConfig::from_args(args, "http://localhost:8081".to_string()).unwrap();
+        assert_eq!(config.model_path, PathBuf::from("model.json"));
+        assert_eq!(config.tuples_path, Some(PathBuf::from("tuples.json")));
+        assert_eq!(config.store_name, DEFAULT_SEED_STORE_NAME);
+        assert_eq!(config.endpoint, "http://localhost:8081");
+
+        assert!(SeedConfig::from_args(["--model".to_string()], String::new()).is_err());
+        assert!(SeedConfig::from_args(["--force".to_string()], String::new()).is_err());
+    }
+
+    #[test]
+    fn test_parse_conditioned_tuples() {
+        let tuples = parse_tuples(
+            r#"[
+                { "user": "user:anne", "relation": "member", "object": "organisation:system" },
+                {
+                    "user": "user:carl",
+                    "relation": "viewer",
+                    "object": "resource:roadmap",
+                    "condition": {
+                        "name": "in_region",
+                        "context": { "region": "eu", "max_level": 3 }
+                    }
+                }
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            tuples[0],
+            tuple_key("user:anne", "member", "organisation:system")
+        );
+
+        let condition = tuples[1].condition.as_ref().unwrap();
+        assert_eq!(condition.name, "in_region");
+        let context = condition.context.as_ref().unwrap();
+        assert_eq!(context.fields.len(), 2);
+        assert_eq!(
+            context.fields["region"],
+            prost_wkt_types::Value::from("eu".to_string())
+        );
+        assert_eq!(
+            context.fields["max_level"],
+            prost_wkt_types::Value::from(3.0)
+        );
+    }
+
+    #[test]
+    fn test_seed_bundle_from_example_model() {
+        let model_json =
+            std::fs::read_to_string(format!("../{}", DEFAULT_SEED_MODEL_PATH)).unwrap();
+        let tuples = parse_tuples(
+            r#"[{ "user": "user:anne", "relation": "member", "object": "organisation:system" }]"#,
+        )
+        .unwrap();
+
+        let bundle = seed_bundle(&model_json, tuples).unwrap();
+        let types: Vec<_> = bundle
+            .authorization_model
+            .type_definitions
+            .iter()
+            .map(|t| t.r#type.as_str())
+            .collect();
+        assert!(types.contains(&"organisation"));
+        assert!(types.contains(&"resource"));
+        assert_eq!(bundle.tuples[0].object, "organisation:system");
+
+        assert!(seed_bundle("{", Vec::new()).is_err());
+    }
+}
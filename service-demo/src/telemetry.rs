@@ -0,0 +1,126 @@
+//! Flushing buffered telemetry on shutdown.
+//!
+//! Exporters such as an OpenTelemetry tracer or meter provider batch their data, so
+//! the last spans and metrics of a process are lost unless they're flushed before it
+//! exits. Each exporter registers its flush-and-shutdown with [`TelemetryShutdown`],
+//! which runs them once the server has stopped, bounded by
+//! `TELEMETRY_SHUTDOWN_TIMEOUT_SECS` (default: 5 seconds).
+//!
+//! ```rust,ignore
+//! let mut telemetry = TelemetryShutdown::from_env();
+//! telemetry.register("tracer", move || tracer_provider.shutdown().map_err(|e| e.to_string()));
+//! listener::serve(app, addr).await?;
+//! telemetry.shutdown().await;
+//! ```
+
+use std::time::Duration;
+
+/// Default bound on flushing the exporters, in seconds
+pub const DEFAULT_TELEMETRY_SHUTDOWN_TIMEOUT_SECS: u64 = 5;
+
+/// Flushes and shuts down an exporter, blocking until it's done
+type ShutdownFn = Box<dyn FnOnce() -> Result<(), String> + Send>;
+
+/// Exporters to flush and shut down when the process exits
+pub struct TelemetryShutdown {
+    exporters: Vec<(String, ShutdownFn)>,
+    timeout: Duration,
+}
+
+impl TelemetryShutdown {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            exporters: Vec::new(),
+            timeout,
+        }
+    }
+
+    /// Bounded by `TELEMETRY_SHUTDOWN_TIMEOUT_SECS`
+    pub fn from_env() -> Self {
+        let timeout = std::env::var("TELEMETRY_SHUTDOWN_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(Duration::from_secs(DEFAULT_TELEMETRY_SHUTDOWN_TIMEOUT_SECS));
+        Self::new(timeout)
+    }
+
+    /// Register an exporter's flush and shutdown, e.g. the provider's blocking
+    /// `shutdown()`
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        shutdown: impl FnOnce() -> Result<(), String> + Send + 'static,
+    ) {
+        self.exporters.push((name.into(), Box::new(shutdown)));
+    }
+
+    /// Flush and shut down the exporters concurrently. Returns once all are done, or
+    /// with `false` once the timeout elapses; exporters still flushing are abandoned.
+    pub async fn shutdown(self) -> bool {
+        let tasks: Vec<_> = self
+            .exporters
+            .into_iter()
+            .map(|(name, shutdown)| (name, tokio::task::spawn_blocking(shutdown)))
+            .collect();
+
+        let flush = async {
+            for (name, task) in tasks {
+                match task.await {
+                    Ok(Ok(())) => tracing::info!("Flushed telemetry exporter {}", name),
+                    Ok(Err(e)) => {
+                        tracing::warn!("Failed to flush telemetry exporter {}: {}", name, e)
+                    }
+                    Err(e) => tracing::warn!("Telemetry exporter {} panicked: {}", name, e),
+                }
+            }
+        };
+
+        match tokio::time::timeout(self.timeout, flush).await {
+            Ok(()) => true,
+            Err(_) => {
+                tracing::warn!(
+                    "Telemetry exporters didn't flush within {:?}, exiting anyway",
+                    self.timeout
+                );
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[tokio::test]
+    async fn test_shutdown_waits_for_flush_within_timeout() {
+        let flushed = Arc::new(AtomicBool::new(false));
+        let mut telemetry = TelemetryShutdown::new(Duration::from_secs(5));
+        let done = flushed.clone();
+        telemetry.register("tracer", move || {
+            std::thread::sleep(Duration::from_millis(20));
+            done.store(true, Ordering::SeqCst);
+            Ok(())
+        });
+        telemetry.register("meter", || Err("exporter unreachable".to_string()));
+
+        assert!(telemetry.shutdown().await);
+        assert!(flushed.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_gives_up_after_timeout() {
+        let (release, stuck) = std::sync::mpsc::channel::<()>();
+        let mut telemetry = TelemetryShutdown::new(Duration::from_millis(20));
+        telemetry.register("tracer", move || {
+            stuck.recv().ok();
+            Ok(())
+        });
+
+        assert!(!telemetry.shutdown().await);
+        release.send(()).unwrap();
+    }
+}
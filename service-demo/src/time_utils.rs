@@ -0,0 +1,98 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+
+/// Convert a protobuf timestamp to a UTC date time
+///
+/// Non-normalized timestamps (nanos outside `0..1_000_000_000`, including negative
+/// nanos) are normalized first. Values outside chrono's range saturate to
+/// `DateTime::<Utc>::MIN_UTC`/`MAX_UTC`.
+pub fn timestamp_to_chrono(timestamp: &prost_wkt_types::Timestamp) -> DateTime<Utc> {
+    let nanos = i64::from(timestamp.nanos);
+    let seconds = timestamp
+        .seconds
+        .saturating_add(nanos.div_euclid(NANOS_PER_SECOND));
+    let nanos = nanos.rem_euclid(NANOS_PER_SECOND) as u32;
+
+    DateTime::from_timestamp(seconds, nanos).unwrap_or(if seconds < 0 {
+        DateTime::<Utc>::MIN_UTC
+    } else {
+        DateTime::<Utc>::MAX_UTC
+    })
+}
+
+/// Convert a UTC date time to a normalized protobuf timestamp
+///
+/// Pre-epoch instants have negative `seconds` and non-negative `nanos`, as protobuf
+/// requires. A leap second is folded into the last nanosecond of its second.
+pub fn chrono_to_timestamp(date_time: DateTime<Utc>) -> prost_wkt_types::Timestamp {
+    let nanos = date_time
+        .timestamp_subsec_nanos()
+        .min(NANOS_PER_SECOND as u32 - 1);
+
+    prost_wkt_types::Timestamp {
+        seconds: date_time.timestamp(),
+        nanos: nanos as i32,
+    }
+}
+
+/// Format a date time as RFC 3339, as expected by the OpenFGA HTTP API
+pub fn chrono_to_rfc3339(date_time: DateTime<Utc>) -> String {
+    date_time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timestamp(seconds: i64, nanos: i32) -> prost_wkt_types::Timestamp {
+        prost_wkt_types::Timestamp { seconds, nanos }
+    }
+
+    #[test]
+    fn test_epoch() {
+        let date_time = timestamp_to_chrono(&timestamp(0, 0));
+
+        assert_eq!(date_time, DateTime::UNIX_EPOCH);
+        assert_eq!(chrono_to_timestamp(date_time), timestamp(0, 0));
+        assert_eq!(chrono_to_rfc3339(date_time), "1970-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn test_fractional_seconds() {
+        let date_time = timestamp_to_chrono(&timestamp(1_700_000_000, 123_456_789));
+
+        assert_eq!(
+            chrono_to_rfc3339(date_time),
+            "2023-11-14T22:13:20.123456789Z"
+        );
+        assert_eq!(
+            chrono_to_timestamp(date_time),
+            timestamp(1_700_000_000, 123_456_789)
+        );
+    }
+
+    #[test]
+    fn test_pre_epoch() {
+        // 1969-12-31T23:59:58.750Z
+        let date_time = timestamp_to_chrono(&timestamp(-2, 750_000_000));
+
+        assert_eq!(chrono_to_rfc3339(date_time), "1969-12-31T23:59:58.750Z");
+        assert_eq!(chrono_to_timestamp(date_time), timestamp(-2, 750_000_000));
+
+        // The same instant with non-normalized negative nanos
+        assert_eq!(timestamp_to_chrono(&timestamp(-1, -250_000_000)), date_time);
+    }
+
+    #[test]
+    fn test_out_of_range_saturates() {
+        assert_eq!(
+            timestamp_to_chrono(&timestamp(i64::MAX, 0)),
+            DateTime::<Utc>::MAX_UTC
+        );
+        assert_eq!(
+            timestamp_to_chrono(&timestamp(i64::MIN, 0)),
+            DateTime::<Utc>::MIN_UTC
+        );
+    }
+}